@@ -7,7 +7,9 @@ export function mapGameAccountToCurrentVersion(account: {[key: string]: unknown}
         id: getAttribute(account, "id"),
         creator: getAttribute(account, "creator"),
         config: mapGameConfigToCurrentVersion(account.config as {[key: string]: unknown}),
-        state: getAttribute(account, "state")
+        state: getAttribute(account, "state"),
+        creatorFeesDecimalTokens: getAttribute(account, "creatorFeesDecimalTokens", new anchor.BN(0)),
+        dustDecimalTokens: getAttribute(account, "dustDecimalTokens", new anchor.BN(0)),
     };
 }
 
@@ -20,6 +22,23 @@ function mapGameConfigToCurrentVersion(config: {[key: string]: unknown}): GameCo
         nBuckets: getAttribute(config, "nBuckets"),
         maxPlayers: getAttribute(config, "maxPlayers"),
         burnRateDecimalTokensPerMove: getAttribute(config, "burnRateDecimalTokensPerMove", new anchor.BN(0)),
+        endEpochSeconds: getAttribute(config, "endEpochSeconds", null),
+        spillCurve: getAttribute(config, "spillCurve", { linear: {} }),
+        allowlistRoot: getAttribute(config, "allowlistRoot", null),
+        creatorFeeBps: getAttribute(config, "creatorFeeBps", 0),
+        // everyone in older games staked the entry fee
+        maxStakeDecimalTokens: getAttribute(
+            config,
+            "maxStakeDecimalTokens",
+            getAttribute<anchor.BN>(config, "entryFeeDecimalTokens")
+        ),
+        topUpDestination: getAttribute(config, "topUpDestination", { holdingBucket: {} }),
+        minSecondsBetweenMoves: getAttribute(config, "minSecondsBetweenMoves", 0),
+        burnSchedule: getAttribute(config, "burnSchedule", { flat: {} }),
+        burnPenaltyHalfLifeSeconds: getAttribute(config, "burnPenaltyHalfLifeSeconds", null),
+        penaltyDestination: getAttribute(config, "penaltyDestination", { burn: {} }),
+        // the last player to leave older games took their rounding remainders
+        dustDestination: getAttribute(config, "dustDestination", { lastLeaver: {} }),
     };
 }
 
@@ -29,6 +48,11 @@ export function mapPlayerStateToCurrentVersion(account: {[key: string]: unknown}
         version: getAttribute(account, "version", 0),
         bucket: getAttribute(account, "bucket"),
         burnPenaltyDecimalTokens: getAttribute(account, "burnPenaltyDecimalTokens", new anchor.BN(0)),
+        stakeDecimalTokens: getAttribute(account, "stakeDecimalTokens", new anchor.BN(0)),
+        addedDecimalTokens: getAttribute(account, "addedDecimalTokens", new anchor.BN(0)),
+        lastMoveEpochSeconds: getAttribute(account, "lastMoveEpochSeconds", new anchor.BN(0)),
+        moves: getAttribute(account, "moves", 0),
+        hasSession: getAttribute(account, "hasSession", false),
     };
 }
 
@@ -37,6 +61,7 @@ export function mapPoolManagerToCurrentVersion(account: {[key: string]: unknown}
     return {
         version: getAttribute(account, "version", 0),
         bump: getAttribute(account, "bump"),
+        liabilitiesDecimalTokens: getAttribute(account, "liabilitiesDecimalTokens", new anchor.BN(0)),
     };
 }
 
@@ -5,11 +5,14 @@ export const GAME_SEED: string = "equilibrate-game";
 export const PLAYER_SEED: string = "equilibrate-player";
 export const POOL_SEED: string = "equilibrate-pool";
 export const POOL_MANAGER_SEED: string = "equilibrate-pool-manager";
+export const PROGRAM_CONFIG_SEED: string = "equilibrate-program-config";
+export const REFERRAL_SEED: string = "equilibrate-referral";
+export const SESSION_SEED: string = "equilibrate-session";
+export const LAMPORT_VAULT_SEED: string = "equilibrate-lamport-vault";
 export const TOKEN_PROGRAM_ID: PublicKey = anchor.utils.token.TOKEN_PROGRAM_ID;
 export const ASSOCIATED_TOKEN_PROGRAM_ID: PublicKey = anchor.utils.token.ASSOCIATED_PROGRAM_ID;
 export const SYSTEM_PROGRAM_ID: PublicKey = anchor.web3.SystemProgram.programId;
 export const RENT_SYSVAR: PublicKey = anchor.web3.SYSVAR_RENT_PUBKEY;
-export const PROGRAM_FEE_DESTINATION: PublicKey = new PublicKey("EQui1fSdC2HetpETDAoaLaPYvYR7xVuXmLyUiiEvfA2h");
 export const PROGRAM_ID: PublicKey = new PublicKey("Equi1uCecmsviNx18BnTiDDFrGoDFzoUsKHVu9btACaM");
 export const GAME_BUCKETS_MIN: number = 2;
 export const GAME_BUCKETS_MAX: number = 64;
@@ -20,4 +23,9 @@ export const GAME_MAX_PLAYERS_MAX: number = 10000;
 export const GAME_MAX_PLAYERS_MIN: number = 2;
 export const PLAYER_BUCKET_INDEX_MIN: number = 1;
 export const PROGRAM_ERROR_ABORT_LEAVE_ON_LOSS: string = "AbortLeaveOnLoss";
-export const ACCOUNTS_VERSION: number = 1; // needs to match what's in the program constants
+// games played for lamports instead of tokens have this as their mint
+export const LAMPORT_GAME_MINT: PublicKey = PublicKey.default;
+export const LAMPORT_DECIMALS: number = 9;
+// need to match what's in the program constants
+export const GAME_VERSION: number = 2;
+export const PLAYER_STATE_VERSION: number = 2;
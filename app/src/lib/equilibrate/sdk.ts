@@ -1,5 +1,6 @@
 import * as anchor from "@project-serum/anchor";
 import {
+    AccountMeta,
     Connection,
     PublicKey,
     SendTransactionError,
@@ -17,7 +18,6 @@ import {
     GAME_MAX_PLAYERS_MIN,
     PLAYER_BUCKET_INDEX_MIN,
     PROGRAM_ERROR_ABORT_LEAVE_ON_LOSS,
-    PROGRAM_FEE_DESTINATION,
     RENT_SYSVAR,
     SPILL_RATE_MIN_EXCLUSIVE,
     SYSTEM_PROGRAM_ID,
@@ -26,28 +26,39 @@ import {
 import { EventCallback, EventEmitter } from "./events";
 import {
     Bucket,
+    BurnSchedule,
+    DustDestination,
     Game,
     GameConfig,
     GameConfigEnriched,
     GameEnriched,
     GameWithEnrichedConfig,
     PlayerState,
-    PlayerStateEnriched
+    PenaltyDestination,
+    PlayerStateEnriched,
+    SpillCurve,
+    TopUpDestination
 } from "./types";
 import {
     accountExists,
     getAssociatedTokenAddress,
     getGameAddress,
+    getLamportVaultAddress,
     getMintDecimals,
     getPlayerStateAddress,
     getPoolManagerAddress,
-    getTokenPoolAddress
+    getProgramConfigAddress,
+    getReferralStatsAddress,
+    getSessionAddress,
+    getTokenPoolAddress,
+    getTokenProgramId,
+    isLamportGameMint
 } from "./utils";
 import { NATIVE_MINT } from "@solana/spl-token";
 import { AnchorError } from "@project-serum/anchor";
 import { SimpleCache } from "./cache";
 import { Duration } from "../shared/duration";
-import { mapGameAccountToCurrentVersion } from "./accounts";
+import { mapGameAccountToCurrentVersion, mapPlayerStateToCurrentVersion } from "./accounts";
 
 export interface SubmitTransactionFunction {
   (transaction: Transaction, connection: Connection): Promise<string>;
@@ -885,10 +896,35 @@ export class EquilibrateRequest {
         nBuckets?: number;
         maxPlayers?: number;
         burnRateTokensPerMove?: number;
+        endEpochSeconds?: number;
+        spillCurve?: SpillCurve;
+        allowlistRoot?: number[];
+        creatorFeeBps?: number;
+        maxStakeDecimalTokens?: number;
+        topUpDestination?: TopUpDestination;
+        minSecondsBetweenMoves?: number;
+        burnSchedule?: BurnSchedule;
+        burnPenaltyHalfLifeSeconds?: number;
+        penaltyDestination?: PenaltyDestination;
+        dustDestination?: DustDestination;
     } = {};
+    private stakeDecimalTokens: number | undefined;
+    private depositDecimalTokens: number | undefined;
+    private withdrawFractionBps: number | undefined;
+    private allowlistProof: number[][] = [];
+    private referrer: PublicKey | undefined;
     private bucketIndex: number | undefined;
     private gameId: number | undefined;
     private cancelOnLoss: boolean | undefined;
+    private minWinningsDecimalTokens: number | undefined;
+    private maxBurnDecimalTokens: number | undefined;
+    private expectedBucketPlayers: number | undefined;
+    private session: {
+        key?: PublicKey;
+        expiresEpochSeconds?: number;
+        canLeave?: boolean;
+        owner?: PublicKey;
+    } = {};
     private neededToCreatePlayerTokenAccount: boolean = false;
 
     private constructor(sdk: EquilibrateSDK, program: anchor.Program<Equilibrate>, playerAddress: PublicKey) {
@@ -988,6 +1024,225 @@ export class EquilibrateRequest {
     }
 
 
+    /**
+     * Sets the time a new game ends, after which nobody can enter or move and
+     * any remaining player can be settled by anyone. Games without an end time
+     * run until the last player leaves.
+     *
+     * @param endEpochSeconds unix timestamp in seconds
+     * @returns this request
+     * @throws if the end time isn't in the future
+     */
+    public setEndTime(endEpochSeconds: number): EquilibrateRequest {
+        Assert.greaterThan(
+            endEpochSeconds,
+            Date.now() / 1000,
+            "endEpochSeconds"
+        );
+        this.config.endEpochSeconds = endEpochSeconds;
+        return this;
+    }
+
+
+    /**
+     * Sets how fast tokens spill out of buckets in a new game. Defaults to linear.
+     *
+     * @param spillCurve spill curve, whose rates are in whole-number decimal units
+     * @returns this request
+     */
+    public setSpillCurve(spillCurve: SpillCurve): EquilibrateRequest {
+        this.config.spillCurve = spillCurve;
+        return this;
+    }
+
+
+    /**
+     * Makes a new game private: only wallets in the allowlist can enter it.
+     *
+     * @param allowlistRoot Merkle root of the allowlisted wallets
+     * @returns this request
+     */
+    public setAllowlistRoot(allowlistRoot: number[]): EquilibrateRequest {
+        this.config.allowlistRoot = allowlistRoot;
+        return this;
+    }
+
+
+    /**
+     * Sets the share of each entry deposit in a new game paid to its creator.
+     * Defaults to no fee.
+     *
+     * @param creatorFeeBps creator fee in basis points, at most the program config's maximum
+     * @returns this request
+     */
+    public setCreatorFeeBps(creatorFeeBps: number): EquilibrateRequest {
+        this.config.creatorFeeBps = creatorFeeBps;
+        return this;
+    }
+
+
+    /**
+     * Sets the most a player can stake when entering a new game. Defaults to
+     * the entry fee, so every player stakes the same.
+     *
+     * @param maxStakeDecimalTokens maximum stake in decimal tokens, at least the entry fee
+     * @returns this request
+     */
+    public setMaxStakeDecimalTokens(maxStakeDecimalTokens: number): EquilibrateRequest {
+        Assert.isInteger(maxStakeDecimalTokens, "maxStakeDecimalTokens");
+        this.config.maxStakeDecimalTokens = maxStakeDecimalTokens;
+        return this;
+    }
+
+
+    /**
+     * Sets which bucket tokens that players add after entering a new game go
+     * to. Defaults to the holding bucket.
+     *
+     * @param topUpDestination the holding bucket or the bucket of the player adding tokens
+     * @returns this request
+     */
+    public setTopUpDestination(topUpDestination: TopUpDestination): EquilibrateRequest {
+        this.config.topUpDestination = topUpDestination;
+        return this;
+    }
+
+
+    /**
+     * Sets how long players of a new game have to wait after moving before
+     * they can move again. Defaults to no wait.
+     *
+     * @param minSecondsBetweenMoves the cooldown in seconds
+     * @returns this request
+     */
+    public setMinSecondsBetweenMoves(minSecondsBetweenMoves: number): EquilibrateRequest {
+        Assert.isInteger(minSecondsBetweenMoves, "minSecondsBetweenMoves");
+        this.config.minSecondsBetweenMoves = minSecondsBetweenMoves;
+        return this;
+    }
+
+
+    /**
+     * Sets how much each move in a new game adds to the moving player's burn
+     * penalty. Defaults to the burn rate for every move.
+     *
+     * @param burnSchedule burn schedule, whose amounts are in whole-number decimal units
+     * @returns this request
+     */
+    public setBurnSchedule(burnSchedule: BurnSchedule): EquilibrateRequest {
+        this.config.burnSchedule = burnSchedule;
+        return this;
+    }
+
+
+    /**
+     * Sets how long it takes for a player's burn penalty in a new game to
+     * halve while they stay in their bucket. Defaults to never.
+     *
+     * @param burnPenaltyHalfLifeSeconds half-life in seconds
+     * @returns this request
+     */
+    public setBurnPenaltyHalfLifeSeconds(burnPenaltyHalfLifeSeconds: number): EquilibrateRequest {
+        Assert.isInteger(burnPenaltyHalfLifeSeconds, "burnPenaltyHalfLifeSeconds");
+        this.config.burnPenaltyHalfLifeSeconds = burnPenaltyHalfLifeSeconds;
+        return this;
+    }
+
+
+    /**
+     * Sets where burn penalties in a new game go when players leave or
+     * withdraw. Defaults to burning them, which native-mint games cant do.
+     *
+     * @param penaltyDestination a burn, back into the pot, or a token account of an owner
+     * @returns this request
+     */
+    public setPenaltyDestination(penaltyDestination: PenaltyDestination): EquilibrateRequest {
+        this.config.penaltyDestination = penaltyDestination;
+        return this;
+    }
+
+
+    /**
+     * Sets where the rounding remainders of a new game go when it ends.
+     * Defaults to the last player to leave, and native-mint games cant burn them.
+     *
+     * @param dustDestination the last leaver, the game creator, or a burn
+     * @returns this request
+     */
+    public setDustDestination(dustDestination: DustDestination): EquilibrateRequest {
+        this.config.dustDestination = dustDestination;
+        return this;
+    }
+
+
+    /**
+     * Sets how much the player adds to their position in a game they are in.
+     *
+     * @param depositDecimalTokens tokens to add in decimal tokens, more than zero
+     * @returns this request
+     */
+    public setDepositDecimalTokens(depositDecimalTokens: number): EquilibrateRequest {
+        Assert.isInteger(depositDecimalTokens, "depositDecimalTokens");
+        Assert.greaterThan(depositDecimalTokens, 0, "depositDecimalTokens");
+        this.depositDecimalTokens = depositDecimalTokens;
+        return this;
+    }
+
+
+    /**
+     * Sets how much of their position a player withdraws from a game they
+     * stay in.
+     *
+     * @param withdrawFractionBps fraction of the position in basis points, between 1 and 9999
+     * @returns this request
+     */
+    public setWithdrawFractionBps(withdrawFractionBps: number): EquilibrateRequest {
+        Assert.isInteger(withdrawFractionBps, "withdrawFractionBps");
+        Assert.greaterThan(withdrawFractionBps, 0, "withdrawFractionBps");
+        Assert.lessThan(withdrawFractionBps, 10_000, "withdrawFractionBps");
+        this.withdrawFractionBps = withdrawFractionBps;
+        return this;
+    }
+
+
+    /**
+     * Sets what the player deposits to enter a game, which weighs their share
+     * of the buckets they are in. Defaults to the game's entry fee.
+     *
+     * @param stakeDecimalTokens stake in decimal tokens, between the game's entry fee and maximum stake
+     * @returns this request
+     */
+    public setStakeDecimalTokens(stakeDecimalTokens: number): EquilibrateRequest {
+        Assert.isInteger(stakeDecimalTokens, "stakeDecimalTokens");
+        this.stakeDecimalTokens = stakeDecimalTokens;
+        return this;
+    }
+
+
+    /**
+     * Sets the proof that the player is on a private game's allowlist.
+     *
+     * @param allowlistProof sibling hashes from the player's leaf up to the root
+     * @returns this request
+     */
+    public setAllowlistProof(allowlistProof: number[][]): EquilibrateRequest {
+        this.allowlistProof = allowlistProof;
+        return this;
+    }
+
+
+    /**
+     * Sets the wallet that referred the player, which receives a share of the program fee.
+     *
+     * @param referrer referrer's wallet
+     * @returns this request
+     */
+    public setReferrer(referrer: PublicKey): EquilibrateRequest {
+        this.referrer = referrer;
+        return this;
+    }
+
+
     /**
      * Sets the number of buckets for a new game.
      *
@@ -1063,9 +1318,87 @@ export class EquilibrateRequest {
     }
 
 
+    /**
+     * Sets the least the player accepts to win when leaving a game. Leaving is
+     * aborted if the game changes before the transaction lands such that the
+     * player would win less.
+     *
+     * @param minWinningsDecimalTokens minimum winnings in decimal tokens
+     * @returns this request
+     */
+    public setMinWinningsDecimalTokens(minWinningsDecimalTokens: number): EquilibrateRequest {
+        Assert.isInteger(minWinningsDecimalTokens, "minWinningsDecimalTokens");
+        Assert.greaterThanOrEqualTo(minWinningsDecimalTokens, 0, "minWinningsDecimalTokens");
+        this.minWinningsDecimalTokens = minWinningsDecimalTokens;
+        return this;
+    }
+
+
+    /**
+     * Sets the most the player accepts as a burn penalty when leaving a game,
+     * wherever the game sends it.
+     *
+     * @param maxBurnDecimalTokens maximum burn in decimal tokens
+     * @returns this request
+     */
+    public setMaxBurnDecimalTokens(maxBurnDecimalTokens: number): EquilibrateRequest {
+        Assert.isInteger(maxBurnDecimalTokens, "maxBurnDecimalTokens");
+        Assert.greaterThanOrEqualTo(maxBurnDecimalTokens, 0, "maxBurnDecimalTokens");
+        this.maxBurnDecimalTokens = maxBurnDecimalTokens;
+        return this;
+    }
+
+
+    /**
+     * Sets how many players the player expects in the bucket they move into.
+     * Moving is aborted if the bucket has a different number of players by the
+     * time the transaction lands.
+     *
+     * @param expectedBucketPlayers players in the bucket before the move
+     * @returns this request
+     */
+    public setExpectedBucketPlayers(expectedBucketPlayers: number): EquilibrateRequest {
+        Assert.isInteger(expectedBucketPlayers, "expectedBucketPlayers");
+        Assert.greaterThanOrEqualTo(expectedBucketPlayers, 0, "expectedBucketPlayers");
+        this.expectedBucketPlayers = expectedBucketPlayers;
+        return this;
+    }
+
+
+    /**
+     * Sets the session key a player lets act for them in a game.
+     *
+     * @param sessionKey address of the session key
+     * @param expiresEpochSeconds when the session key stops working, at most a week from now
+     * @param canLeave set to true to also let the session key leave the game for the player
+     * @returns this request
+     */
+    public setSession(sessionKey: PublicKey, expiresEpochSeconds: number, canLeave: boolean): EquilibrateRequest {
+        Assert.isInteger(expiresEpochSeconds, "expiresEpochSeconds");
+        this.session.key = sessionKey;
+        this.session.expiresEpochSeconds = expiresEpochSeconds;
+        this.session.canLeave = canLeave;
+        return this;
+    }
+
+
+    /**
+     * Sets the player a session key acts for. The request's player is then
+     * the session key, which signs instead of the player.
+     *
+     * @param sessionOwner wallet of the player who created the session
+     * @returns this request
+     */
+    public setSessionOwner(sessionOwner: PublicKey): EquilibrateRequest {
+        this.session.owner = sessionOwner;
+        return this;
+    }
+
+
     /**
      * Adds instruction to create a new game. Will also add an instruction to create
-     * the token pool/manager if one doesnt already exist.
+     * the token pool/manager if one doesnt already exist. Games whose mint is
+     * `LAMPORT_GAME_MINT` are played for lamports and need no token pool.
      *
      * @param finalizedCallback callback to call when the game config has been finalized
      * @returns this request
@@ -1074,11 +1407,14 @@ export class EquilibrateRequest {
      */
     public withCreateNewGame(finalizedCallback?: (gameAddress: PublicKey) => void): EquilibrateRequest {
         this.validateConfig();
+        Assert.notNullish(this.config.mint, "mint");
+        if (isLamportGameMint(this.config.mint)) return this.withCreateNewLamportGame(finalizedCallback);
 
         this.withWrapSolInstructionsIfNeeded("create game: wrap SOL");
 
         this.addStep("create game", async () => {
             const config: GameConfig = await this.finalizeConfig();
+            const tokenProgram: PublicKey = await getTokenProgramId(config.mint, this.connection);
             // first determine if we need to create the token pool/manager
             // before making the game
             const [poolManagerAddress, poolManagerBump] = await getPoolManagerAddress(
@@ -1106,7 +1442,7 @@ export class EquilibrateRequest {
                         tokenPool: tokenPoolAddress,
                         payer: this.playerAddress,
                         gameMint: config.mint,
-                        tokenProgram: TOKEN_PROGRAM_ID,
+                        tokenProgram: tokenProgram,
                         rent: RENT_SYSVAR,
                         systemProgram: SYSTEM_PROGRAM_ID
                     })
@@ -1125,23 +1461,30 @@ export class EquilibrateRequest {
                 this.playerAddress,
                 this.program.programId
             );
-            const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(config.mint, this.playerAddress);
+            const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(
+                config.mint,
+                this.playerAddress,
+                tokenProgram
+            );
+            const [programConfigAddress, programFeeDestination] = await this.getProgramFeeAccounts();
             const newGameInstruction: TransactionInstruction = await this.program
                 .methods
                 .newGame(
                     config,
-                    new anchor.BN(gameId),
-                    poolManagerAddress
+                    new anchor.BN(gameId)
                 )
                 .accountsStrict({
+                    poolManager: poolManagerAddress,
                     tokenPool: tokenPoolAddress,
                     payer: this.playerAddress,
-                    tokenProgram: TOKEN_PROGRAM_ID,
+                    tokenProgram: tokenProgram,
                     rent: RENT_SYSVAR,
                     systemProgram: SYSTEM_PROGRAM_ID,
                     game: gameAddress,
                     firstPlayer: playerStateAddress,
-                    programFeeDestination: PROGRAM_FEE_DESTINATION,
+                    programConfig: programConfigAddress,
+                    programFeeDestination: programFeeDestination,
+                    gameMint: config.mint,
                     depositSourceAccount: playerTokenAccount,
                     associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID
                 })
@@ -1201,7 +1544,22 @@ export class EquilibrateRequest {
             spillRateDecimalTokensPerSecondPerPlayer: spillRateWithDecimals,
             nBuckets: this.config.nBuckets,
             maxPlayers: this.config.maxPlayers,
-            burnRateDecimalTokensPerMove: burnRateDecimalTokensPerMove
+            burnRateDecimalTokensPerMove: burnRateDecimalTokensPerMove,
+            endEpochSeconds: this.config.endEpochSeconds != null
+                ? new anchor.BN(this.config.endEpochSeconds)
+                : null,
+            spillCurve: this.config.spillCurve ?? { linear: {} },
+            allowlistRoot: this.config.allowlistRoot ?? null,
+            creatorFeeBps: this.config.creatorFeeBps ?? 0,
+            maxStakeDecimalTokens: this.config.maxStakeDecimalTokens != null
+                ? new anchor.BN(this.config.maxStakeDecimalTokens)
+                : entryFeeWithDecimals,
+            topUpDestination: this.config.topUpDestination ?? { holdingBucket: {} },
+            minSecondsBetweenMoves: this.config.minSecondsBetweenMoves ?? 0,
+            burnSchedule: this.config.burnSchedule ?? { flat: {} },
+            burnPenaltyHalfLifeSeconds: this.config.burnPenaltyHalfLifeSeconds ?? null,
+            penaltyDestination: this.config.penaltyDestination ?? { burn: {} },
+            dustDestination: this.config.dustDestination ?? { lastLeaver: {} }
         };
     }
 
@@ -1252,7 +1610,9 @@ export class EquilibrateRequest {
 
             this.addStep(stepName, async () => {
                 const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(mint, this.playerAddress);
-                const entryFeeWithDecimals: anchor.BN = await this.resolveEntryFeeDecimalTokens();
+                const entryFeeWithDecimals: anchor.BN = this.stakeDecimalTokens != null
+                    ? new anchor.BN(this.stakeDecimalTokens)
+                    : await this.resolveEntryFeeDecimalTokens();
                 const nativeTokenAccountExists: boolean = await accountExists(
                     playerTokenAccount,
                     this.program.provider.connection
@@ -1310,10 +1670,12 @@ export class EquilibrateRequest {
         const bucketIndex: number = this.bucketIndex;
         const mint: PublicKey = this.config.mint;
         const gameId: number = this.gameId;
+        if (isLamportGameMint(mint)) return this.withEnterLamportGame(bucketIndex, gameId);
 
         this.withWrapSolInstructionsIfNeeded("enter game: wrap SOL");
 
         this.addStep("enter game", async () => {
+            const tokenProgram: PublicKey = await getTokenProgramId(mint, this.connection);
             const poolManagerAddress: PublicKey = (await getPoolManagerAddress(mint, this.program.programId))[0];
             const tokenPoolAddress: PublicKey = await getTokenPoolAddress(
                 mint,
@@ -1325,24 +1687,51 @@ export class EquilibrateRequest {
                 this.playerAddress,
                 this.program.programId
             );
-            const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(mint, this.playerAddress);
+            const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(
+                mint,
+                this.playerAddress,
+                tokenProgram
+            );
+            const [programConfigAddress, programFeeDestination] = await this.getProgramFeeAccounts();
+            const game: Game = await this.sdk.getGame(gameAddress);
+            const creatorTokenAccount: PublicKey = await getAssociatedTokenAddress(
+                mint,
+                game.creator,
+                tokenProgram
+            );
+            const referralAccounts: AccountMeta[] = this.referrer === undefined ? [] : [
+                { pubkey: this.referrer, isWritable: true, isSigner: false },
+                {
+                    pubkey: await getReferralStatsAddress(this.referrer, this.program.programId),
+                    isWritable: true,
+                    isSigner: false,
+                },
+            ];
             const instruction: TransactionInstruction = await this.program
                 .methods
                 .enterGame(
                     bucketIndex,
-                    poolManagerAddress
+                    this.stakeDecimalTokens != null
+                        ? new anchor.BN(this.stakeDecimalTokens)
+                        : game.config.entryFeeDecimalTokens,
+                    this.allowlistProof
                 )
                 .accountsStrict({
                     game: gameAddress,
-                    programFeeDestination: PROGRAM_FEE_DESTINATION,
+                    programConfig: programConfigAddress,
+                    programFeeDestination: programFeeDestination,
+                    gameMint: mint,
                     depositSourceAccount: playerTokenAccount,
+                    creatorFeeDestination: creatorTokenAccount,
+                    poolManager: poolManagerAddress,
                     tokenPool: tokenPoolAddress,
                     payer: this.playerAddress,
-                    tokenProgram: TOKEN_PROGRAM_ID,
+                    tokenProgram: tokenProgram,
                     systemProgram: SYSTEM_PROGRAM_ID,
                     rent: RENT_SYSVAR,
                     player: playerStateAddress
                 })
+                .remainingAccounts(referralAccounts)
                 .instruction();
 
             return [instruction];
@@ -1354,6 +1743,60 @@ export class EquilibrateRequest {
     }
 
 
+    /**
+     * Adds instruction to add tokens to the player's position in a game they
+     * are in. The tokens are taken from the player's associated token account.
+     *
+     * @returns this request
+     * @throws if any of the following have not been set: `mint`, `gameId`, `depositDecimalTokens`
+     */
+    public withAddDeposit(): EquilibrateRequest {
+        Assert.notNullish(this.config.mint, "mint");
+        Assert.notNullish(this.gameId, "gameId");
+        Assert.notNullish(this.depositDecimalTokens, "depositDecimalTokens");
+        const mint: PublicKey = this.config.mint;
+        const gameId: number = this.gameId;
+        const depositDecimalTokens: number = this.depositDecimalTokens;
+        this.addStep("add deposit", async () => {
+            const tokenProgram: PublicKey = await getTokenProgramId(mint, this.connection);
+            const poolManagerAddress: PublicKey = (await getPoolManagerAddress(mint, this.program.programId))[0];
+            const tokenPoolAddress: PublicKey = await getTokenPoolAddress(
+                mint,
+                this.program.programId
+            );
+            const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+            const playerStateAddress: PublicKey = await getPlayerStateAddress(
+                gameAddress,
+                this.playerAddress,
+                this.program.programId
+            );
+            const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(
+                mint,
+                this.playerAddress,
+                tokenProgram
+            );
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .addDeposit(new anchor.BN(depositDecimalTokens))
+                .accountsStrict({
+                    game: gameAddress,
+                    player: playerStateAddress,
+                    gameMint: mint,
+                    depositSourceAccount: playerTokenAccount,
+                    poolManager: poolManagerAddress,
+                    tokenPool: tokenPoolAddress,
+                    payer: this.playerAddress,
+                    tokenProgram: tokenProgram
+                })
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
     /**
      * Adds instruction to move to a new bucket within a game.
      *
@@ -1365,6 +1808,7 @@ export class EquilibrateRequest {
         Assert.notNullish(this.gameId, "gameId");
         const bucketIndex: number = this.bucketIndex;
         const gameId: number = this.gameId;
+        const expectedBucketPlayers: number | null = this.expectedBucketPlayers ?? null;
         this.addStep("move bucket", async () => {
             const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
             const playerStateAddress: PublicKey = await getPlayerStateAddress(
@@ -1374,7 +1818,7 @@ export class EquilibrateRequest {
             );
             const instruction: TransactionInstruction = await this.program
                 .methods
-                .moveBuckets(bucketIndex)
+                .moveBuckets(bucketIndex, expectedBucketPlayers)
                 .accountsStrict({
                     game: gameAddress,
                     payer: this.playerAddress,
@@ -1389,6 +1833,221 @@ export class EquilibrateRequest {
     }
 
 
+    /**
+     * Adds instruction to withdraw part of the player's position in a game
+     * they stay in. The withdrawal is paid to the player's associated token account.
+     *
+     * @returns this request
+     * @throws if any of the following have not been set: `mint`, `gameId`, `withdrawFractionBps`
+     */
+    public withWithdraw(): EquilibrateRequest {
+        Assert.notNullish(this.config.mint, "mint");
+        Assert.notNullish(this.gameId, "gameId");
+        Assert.notNullish(this.withdrawFractionBps, "withdrawFractionBps");
+        const mint: PublicKey = this.config.mint;
+        const gameId: number = this.gameId;
+        const withdrawFractionBps: number = this.withdrawFractionBps;
+        this.addStep("withdraw", async () => {
+            const tokenProgram: PublicKey = await getTokenProgramId(mint, this.connection);
+            const poolManagerAddress: PublicKey = (await getPoolManagerAddress(mint, this.program.programId))[0];
+            const tokenPoolAddress: PublicKey = await getTokenPoolAddress(
+                mint,
+                this.program.programId
+            );
+            const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+            const playerStateAddress: PublicKey = await getPlayerStateAddress(
+                gameAddress,
+                this.playerAddress,
+                this.program.programId
+            );
+            const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(
+                mint,
+                this.playerAddress,
+                tokenProgram
+            );
+            const game: Game = await this.sdk.getGame(gameAddress);
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .withdraw(withdrawFractionBps)
+                .accountsStrict({
+                    game: gameAddress,
+                    gameMint: mint,
+                    player: playerStateAddress,
+                    winningsDestinationAccount: playerTokenAccount,
+                    poolManager: poolManagerAddress,
+                    tokenPool: tokenPoolAddress,
+                    payer: this.playerAddress,
+                    tokenProgram: tokenProgram
+                })
+                .remainingAccounts(await this.getPenaltyDestinationAccounts(game, mint, tokenProgram))
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
+    /**
+     * Adds instruction to let a session key act for the player in a game they
+     * are in. Replaces any session the player already has in the game.
+     *
+     * @returns this request
+     * @throws if any of the following have not been set: `gameId`, `session`
+     */
+    public withCreateSession(): EquilibrateRequest {
+        Assert.notNullish(this.gameId, "gameId");
+        Assert.notNullish(this.session.key, "session.key");
+        Assert.notNullish(this.session.expiresEpochSeconds, "session.expiresEpochSeconds");
+        const gameId: number = this.gameId;
+        const sessionKey: PublicKey = this.session.key;
+        const expiresEpochSeconds: number = this.session.expiresEpochSeconds;
+        const canLeave: boolean = this.session.canLeave === true;
+        this.addStep("create session", async () => {
+            const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .createSession(sessionKey, new anchor.BN(expiresEpochSeconds), canLeave)
+                .accountsStrict({
+                    game: gameAddress,
+                    player: await getPlayerStateAddress(
+                        gameAddress,
+                        this.playerAddress,
+                        this.program.programId
+                    ),
+                    session: await getSessionAddress(gameId, this.playerAddress, this.program.programId),
+                    payer: this.playerAddress,
+                    systemProgram: SYSTEM_PROGRAM_ID
+                })
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
+    /**
+     * Adds instruction for the session key to move the session owner to a new
+     * bucket within a game.
+     *
+     * @returns this request
+     * @throws if any of the following have not been set: `bucketIndex`, `gameId`, `sessionOwner`
+     */
+    public withSessionMoveBucket(): EquilibrateRequest {
+        Assert.notNullish(this.bucketIndex, "bucketIndex");
+        Assert.notNullish(this.gameId, "gameId");
+        Assert.notNullish(this.session.owner, "sessionOwner");
+        const bucketIndex: number = this.bucketIndex;
+        const gameId: number = this.gameId;
+        const owner: PublicKey = this.session.owner;
+        const expectedBucketPlayers: number | null = this.expectedBucketPlayers ?? null;
+        this.addStep("session move bucket", async () => {
+            const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .sessionMoveBuckets(bucketIndex, expectedBucketPlayers)
+                .accountsStrict({
+                    game: gameAddress,
+                    playerWallet: owner,
+                    player: await getPlayerStateAddress(gameAddress, owner, this.program.programId),
+                    session: await getSessionAddress(gameId, owner, this.program.programId),
+                    payer: this.playerAddress
+                })
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
+    /**
+     * Adds instruction for the session key to leave a game for the session
+     * owner. Winnings are paid to the owner's associated token account, which
+     * must already exist.
+     *
+     * @returns this request
+     * @throws if any of the following have not been set: `mint`, `gameId`, `sessionOwner`
+     */
+    public withSessionLeaveGame(): EquilibrateRequest {
+        Assert.notNullish(this.config.mint, "mint");
+        Assert.notNullish(this.gameId, "gameId");
+        Assert.notNullish(this.session.owner, "sessionOwner");
+        const mint: PublicKey = this.config.mint;
+        const gameId: number = this.gameId;
+        const owner: PublicKey = this.session.owner;
+        const cancelOnLoss: boolean = this.cancelOnLoss === true;
+        const minWinningsDecimalTokens: anchor.BN | null = this.minWinningsDecimalTokens === undefined
+            ? null
+            : new anchor.BN(this.minWinningsDecimalTokens);
+        const maxBurnDecimalTokens: anchor.BN | null = this.maxBurnDecimalTokens === undefined
+            ? null
+            : new anchor.BN(this.maxBurnDecimalTokens);
+        this.addStep("session leave game", async () => {
+            const tokenProgram: PublicKey = await getTokenProgramId(mint, this.connection);
+            const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+            const game: Game = await this.sdk.getGame(gameAddress);
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .sessionLeaveGame(cancelOnLoss, minWinningsDecimalTokens, maxBurnDecimalTokens)
+                .accountsStrict({
+                    game: gameAddress,
+                    gameMint: mint,
+                    gameCreator: game.creator,
+                    playerWallet: owner,
+                    player: await getPlayerStateAddress(gameAddress, owner, this.program.programId),
+                    session: await getSessionAddress(gameId, owner, this.program.programId),
+                    winningsDestinationAccount: await getAssociatedTokenAddress(mint, owner, tokenProgram),
+                    poolManager: (await getPoolManagerAddress(mint, this.program.programId))[0],
+                    tokenPool: await getTokenPoolAddress(mint, this.program.programId),
+                    payer: this.playerAddress,
+                    tokenProgram: tokenProgram,
+                    systemProgram: SYSTEM_PROGRAM_ID
+                })
+                .remainingAccounts([
+                    ...await this.getPenaltyDestinationAccounts(game, mint, tokenProgram),
+                    ...await this.getDustDestinationAccounts(game, mint, tokenProgram),
+                ])
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
+    /**
+     * Adds instruction to revoke the player's session key in a game,
+     * returning the session account's rent.
+     *
+     * @returns this request
+     * @throws if any of the following have not been set: `gameId`
+     */
+    public withRevokeSession(): EquilibrateRequest {
+        Assert.notNullish(this.gameId, "gameId");
+        const gameId: number = this.gameId;
+        this.addStep("revoke session", async () => {
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .revokeSession()
+                .accountsStrict({
+                    session: await getSessionAddress(gameId, this.playerAddress, this.program.programId),
+                    payer: this.playerAddress
+                })
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
     /**
      * Adds instruction to leave an existing game.
      *
@@ -1402,10 +2061,24 @@ export class EquilibrateRequest {
         const mint: PublicKey = this.config.mint;
         const gameId: number = this.gameId;
         const cancelOnLoss: boolean = this.cancelOnLoss;
+        const minWinningsDecimalTokens: anchor.BN | null = this.minWinningsDecimalTokens === undefined
+            ? null
+            : new anchor.BN(this.minWinningsDecimalTokens);
+        const maxBurnDecimalTokens: anchor.BN | null = this.maxBurnDecimalTokens === undefined
+            ? null
+            : new anchor.BN(this.maxBurnDecimalTokens);
+        if (isLamportGameMint(mint)) {
+            return this.withLeaveLamportGame(gameId, cancelOnLoss, minWinningsDecimalTokens, maxBurnDecimalTokens);
+        }
         this.addStep("leave game", async () => {
             const instructions: TransactionInstruction[] = [];
 
-            const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(mint, this.playerAddress);
+            const tokenProgram: PublicKey = await getTokenProgramId(mint, this.connection);
+            const playerTokenAccount: PublicKey = await getAssociatedTokenAddress(
+                mint,
+                this.playerAddress,
+                tokenProgram
+            );
             const shouldCreateAndCloseTokenAccount = !await accountExists(
                 playerTokenAccount,
                 this.program.provider.connection
@@ -1417,7 +2090,8 @@ export class EquilibrateRequest {
                 instructions.push(await this.makeCreateTokenAccountInstruction(
                     mint,
                     this.playerAddress,
-                    playerTokenAccount
+                    playerTokenAccount,
+                    tokenProgram
                 ));
                 this.neededToCreatePlayerTokenAccount = true;
             }
@@ -1436,11 +2110,11 @@ export class EquilibrateRequest {
             const game: Game = await this.sdk.getGame(gameAddress);
             const leaveInstruction: TransactionInstruction = await this.program
                 .methods
-                .leaveGame(cancelOnLoss)
+                .leaveGame(cancelOnLoss, minWinningsDecimalTokens, maxBurnDecimalTokens)
                 .accountsStrict({
                     game: gameAddress,
                     payer: this.playerAddress,
-                    tokenProgram: TOKEN_PROGRAM_ID,
+                    tokenProgram: tokenProgram,
                     systemProgram: SYSTEM_PROGRAM_ID,
                     player: playerStateAddress,
                     poolManager: poolManagerAddress,
@@ -1449,6 +2123,11 @@ export class EquilibrateRequest {
                     winningsDestinationAccount: playerTokenAccount,
                     gameMint: mint
                 })
+                .remainingAccounts([
+                    ...await this.getPenaltyDestinationAccounts(game, mint, tokenProgram),
+                    ...await this.getSessionAccounts(gameId, this.playerAddress),
+                    ...await this.getDustDestinationAccounts(game, mint, tokenProgram),
+                ])
                 .instruction();
 
             instructions.push(leaveInstruction);
@@ -1462,10 +2141,223 @@ export class EquilibrateRequest {
     }
 
 
+    private withCreateNewLamportGame(finalizedCallback?: (gameAddress: PublicKey) => void): EquilibrateRequest {
+        this.addStep("create game", async () => {
+            const config: GameConfig = await this.finalizeConfig();
+            const gameId: number = this.gameId ?? this.generateGameId();
+            const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+            if (finalizedCallback !== undefined) finalizedCallback(gameAddress);
+            const playerStateAddress: PublicKey = await getPlayerStateAddress(
+                gameAddress,
+                this.playerAddress,
+                this.program.programId
+            );
+            const [programConfigAddress, programFeeDestination] = await this.getProgramFeeAccounts();
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .newLamportGame(config, new anchor.BN(gameId))
+                .accountsStrict({
+                    game: gameAddress,
+                    firstPlayer: playerStateAddress,
+                    lamportVault: await getLamportVaultAddress(gameId, this.program.programId),
+                    programConfig: programConfigAddress,
+                    programFeeDestination: programFeeDestination,
+                    payer: this.playerAddress,
+                    systemProgram: SYSTEM_PROGRAM_ID,
+                    rent: RENT_SYSVAR
+                })
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
+    private withEnterLamportGame(bucketIndex: number, gameId: number): EquilibrateRequest {
+        this.addStep("enter game", async () => {
+            const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+            const playerStateAddress: PublicKey = await getPlayerStateAddress(
+                gameAddress,
+                this.playerAddress,
+                this.program.programId
+            );
+            const [programConfigAddress, programFeeDestination] = await this.getProgramFeeAccounts();
+            const game: Game = await this.sdk.getGame(gameAddress);
+            const referralAccounts: AccountMeta[] = this.referrer === undefined ? [] : [
+                { pubkey: this.referrer, isWritable: true, isSigner: false },
+                {
+                    pubkey: await getReferralStatsAddress(this.referrer, this.program.programId),
+                    isWritable: true,
+                    isSigner: false,
+                },
+            ];
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .enterLamportGame(
+                    bucketIndex,
+                    this.stakeDecimalTokens != null
+                        ? new anchor.BN(this.stakeDecimalTokens)
+                        : game.config.entryFeeDecimalTokens,
+                    this.allowlistProof
+                )
+                .accountsStrict({
+                    game: gameAddress,
+                    player: playerStateAddress,
+                    programConfig: programConfigAddress,
+                    programFeeDestination: programFeeDestination,
+                    lamportVault: await getLamportVaultAddress(gameId, this.program.programId),
+                    gameCreator: game.creator,
+                    payer: this.playerAddress,
+                    rent: RENT_SYSVAR,
+                    systemProgram: SYSTEM_PROGRAM_ID
+                })
+                .remainingAccounts(referralAccounts)
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
+    private withLeaveLamportGame(
+        gameId: number,
+        cancelOnLoss: boolean,
+        minWinningsDecimalTokens: anchor.BN | null,
+        maxBurnDecimalTokens: anchor.BN | null
+    ): EquilibrateRequest {
+        this.addStep("leave game", async () => {
+            const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+            const playerStateAddress: PublicKey = await getPlayerStateAddress(
+                gameAddress,
+                this.playerAddress,
+                this.program.programId
+            );
+            const game: Game = await this.sdk.getGame(gameAddress);
+            const penaltyDestination: PenaltyDestination = game.config.penaltyDestination;
+            // lamport games pay penalties straight to the recipient's wallet
+            const penaltyDestinationAccounts: AccountMeta[] = !("account" in penaltyDestination) ? [] : [{
+                pubkey: penaltyDestination.account.owner,
+                isWritable: true,
+                isSigner: false,
+            }];
+            const instruction: TransactionInstruction = await this.program
+                .methods
+                .leaveLamportGame(cancelOnLoss, minWinningsDecimalTokens, maxBurnDecimalTokens)
+                .accountsStrict({
+                    game: gameAddress,
+                    gameCreator: game.creator,
+                    player: playerStateAddress,
+                    lamportVault: await getLamportVaultAddress(gameId, this.program.programId),
+                    payer: this.playerAddress,
+                    systemProgram: SYSTEM_PROGRAM_ID
+                })
+                .remainingAccounts([
+                    ...penaltyDestinationAccounts,
+                    ...await this.getSessionAccounts(gameId, this.playerAddress),
+                ])
+                .instruction();
+
+            return [instruction];
+        });
+
+        return this;
+    }
+
+
+    /**
+     * The program fee destination can be changed by the program config admin,
+     * so look it up rather than assuming it.
+     *
+     * @returns the program config address and the current program fee destination
+     */
+    private async getProgramFeeAccounts(): Promise<[PublicKey, PublicKey]> {
+        const programConfigAddress: PublicKey = await getProgramConfigAddress(this.program.programId);
+        const programConfig = await this.program.account.programConfig.fetch(programConfigAddress);
+        return [programConfigAddress, programConfig.settings.programFeeDestination];
+    }
+
+
+    /**
+     * Games that pay penalties to an account need the recipient's token account
+     * to charge a penalty, which must already exist.
+     *
+     * @returns the remaining accounts for leaving or withdrawing from `game`
+     */
+    private async getPenaltyDestinationAccounts(
+        game: Game,
+        mint: PublicKey,
+        tokenProgram: PublicKey
+    ): Promise<AccountMeta[]> {
+        const penaltyDestination: PenaltyDestination = game.config.penaltyDestination;
+        if (!("account" in penaltyDestination)) {
+            return [];
+        }
+        return [{
+            pubkey: await getAssociatedTokenAddress(mint, penaltyDestination.account.owner, tokenProgram),
+            isWritable: true,
+            isSigner: false,
+        }];
+    }
+
+
+    /**
+     * A player who has made a session for the game must close it as they
+     * leave, so it can't act for them if they enter again. It goes between
+     * any penalty and dust accounts.
+     *
+     * @returns the remaining accounts for closing the session `player` has made in the game
+     */
+    private async getSessionAccounts(gameId: number, player: PublicKey): Promise<AccountMeta[]> {
+        const gameAddress: PublicKey = await getGameAddress(gameId, this.program.programId);
+        const playerStateAddress: PublicKey = await getPlayerStateAddress(
+            gameAddress,
+            player,
+            this.program.programId
+        );
+        const playerState = await this.program.account.playerState.fetchNullable(playerStateAddress);
+        if ((playerState == null) || !mapPlayerStateToCurrentVersion(playerState).hasSession) {
+            return [];
+        }
+        const sessionAddress: PublicKey = await getSessionAddress(gameId, player, this.program.programId);
+        return [{
+            pubkey: sessionAddress,
+            isWritable: true,
+            isSigner: false,
+        }];
+    }
+
+
+    /**
+     * Games that pay their dust to their creator need the creator's token
+     * account when the last player leaves. It goes after any penalty account.
+     *
+     * @returns the remaining accounts for paying the dust of `game`
+     */
+    private async getDustDestinationAccounts(
+        game: Game,
+        mint: PublicKey,
+        tokenProgram: PublicKey
+    ): Promise<AccountMeta[]> {
+        if (!("creator" in game.config.dustDestination)) {
+            return [];
+        }
+        return [{
+            pubkey: await getAssociatedTokenAddress(mint, game.creator, tokenProgram),
+            isWritable: true,
+            isSigner: false,
+        }];
+    }
+
+
     private async makeCreateTokenAccountInstruction(
         mint: PublicKey,
         owner: PublicKey,
-        tokenAccount: PublicKey
+        tokenAccount: PublicKey,
+        tokenProgram: PublicKey = TOKEN_PROGRAM_ID
     ): Promise<TransactionInstruction> {
         return await anchor.Spl
             .associatedToken(this.program.provider)
@@ -1476,7 +2368,7 @@ export class EquilibrateRequest {
                 owner: owner,
                 authority: owner,
                 associatedAccount: tokenAccount,
-                tokenProgram: TOKEN_PROGRAM_ID,
+                tokenProgram: tokenProgram,
                 rent: RENT_SYSVAR,
                 systemProgram: SYSTEM_PROGRAM_ID
             })
@@ -20,6 +20,9 @@ export interface Game {
     state: GameState;
     // since v0.2.0
     version: number;
+    // since game version 2
+    creatorFeesDecimalTokens: anchor.BN;
+    dustDecimalTokens: anchor.BN;
 }
 
 
@@ -36,6 +39,56 @@ export interface GameConfig {
     maxPlayers: number;
     // since v0.2.0
     burnRateDecimalTokensPerMove: anchor.BN;
+    // since game version 2
+    endEpochSeconds: anchor.BN | null;
+    spillCurve: SpillCurve;
+    allowlistRoot: number[] | null;
+    creatorFeeBps: number;
+    maxStakeDecimalTokens: anchor.BN;
+    topUpDestination: TopUpDestination;
+    minSecondsBetweenMoves: number;
+    burnSchedule: BurnSchedule;
+    burnPenaltyHalfLifeSeconds: number | null;
+    penaltyDestination: PenaltyDestination;
+    dustDestination: DustDestination;
+}
+
+
+export type DustDestination =
+    | { lastLeaver: Record<string, never> }
+    | { creator: Record<string, never> }
+    | { burn: Record<string, never> };
+
+
+export type PenaltyDestination =
+    | { burn: Record<string, never> }
+    | { holdingBucket: Record<string, never> }
+    | { otherBuckets: Record<string, never> }
+    | { account: { owner: PublicKey } };
+
+
+export type BurnSchedule =
+    | { flat: Record<string, never> }
+    | { linear: { incrementDecimalTokensPerMove: anchor.BN } }
+    | { geometric: { growthBps: number } }
+    | { shareOfBucket: { shareBps: number } };
+
+
+export type TopUpDestination =
+    | { holdingBucket: Record<string, never> }
+    | { playerBucket: Record<string, never> };
+
+
+export type SpillCurve =
+    | { linear: Record<string, never> }
+    | { proportionalToBalance: { decayBpsPerSecond: number } }
+    | { sqrtOfPlayers: Record<string, never> }
+    | { piecewise: { steps: SpillStep[] } };
+
+
+export interface SpillStep {
+    startEpochSeconds: anchor.BN;
+    spillRateDecimalTokensPerSecondPerPlayer: anchor.BN;
 }
 
 
@@ -58,6 +111,8 @@ export type BucketEnriched = Bucket
 export interface Bucket {
     decimalTokens: anchor.BN;
     players: number;
+    // since game version 2
+    stakeDecimalTokens: anchor.BN;
 }
 
 
@@ -73,6 +128,12 @@ export interface PlayerState {
     burnPenaltyDecimalTokens: anchor.BN;
     // since v0.2.0
     version: number;
+    // since player state version 2
+    stakeDecimalTokens: anchor.BN;
+    addedDecimalTokens: anchor.BN;
+    lastMoveEpochSeconds: anchor.BN;
+    moves: number;
+    hasSession: boolean;
 }
 
 
@@ -80,4 +141,6 @@ export interface PoolManager {
     bump: number;
     // since v0.2.0
     version: number;
+    // what the token pool owes the live games of its mint
+    liabilitiesDecimalTokens: anchor.BN;
 }
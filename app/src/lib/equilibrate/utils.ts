@@ -1,7 +1,18 @@
 import { Connection, PublicKey } from "@solana/web3.js";
 import * as anchor from "@project-serum/anchor";
 import * as spl from "@solana/spl-token";
-import { GAME_SEED, PLAYER_SEED, POOL_SEED, POOL_MANAGER_SEED } from "./constants";
+import {
+    GAME_SEED,
+    LAMPORT_DECIMALS,
+    LAMPORT_GAME_MINT,
+    LAMPORT_VAULT_SEED,
+    PLAYER_SEED,
+    POOL_SEED,
+    POOL_MANAGER_SEED,
+    PROGRAM_CONFIG_SEED,
+    REFERRAL_SEED,
+    SESSION_SEED
+} from "./constants";
 
 export async function getGameAddress(
     gameId: number,
@@ -62,12 +73,89 @@ export async function getPoolManagerAddress(
 }
 
 
-export async function getAssociatedTokenAddress(mint: PublicKey, owner: PublicKey): Promise<PublicKey> {
-    return await spl.getAssociatedTokenAddress(mint, owner);
+export async function getProgramConfigAddress(
+    programId: PublicKey
+): Promise<PublicKey> {
+    return (await PublicKey.findProgramAddress(
+        [
+            anchor.utils.bytes.utf8.encode(PROGRAM_CONFIG_SEED),
+        ],
+        programId
+    ))[0];
+}
+
+
+export async function getReferralStatsAddress(
+    referrer: PublicKey,
+    programId: PublicKey
+): Promise<PublicKey> {
+    return (await PublicKey.findProgramAddress(
+        [
+            anchor.utils.bytes.utf8.encode(REFERRAL_SEED),
+            referrer.toBuffer(),
+        ],
+        programId
+    ))[0];
+}
+
+
+export async function getSessionAddress(
+    gameId: number,
+    player: PublicKey,
+    programId: PublicKey
+): Promise<PublicKey> {
+    return (await PublicKey.findProgramAddress(
+        [
+            anchor.utils.bytes.utf8.encode(SESSION_SEED),
+            new anchor.BN(gameId).toArrayLike(Buffer, "le", 8),
+            player.toBuffer(),
+        ],
+        programId
+    ))[0];
+}
+
+
+export async function getLamportVaultAddress(
+    gameId: number,
+    programId: PublicKey
+): Promise<PublicKey> {
+    return (await PublicKey.findProgramAddress(
+        [
+            anchor.utils.bytes.utf8.encode(LAMPORT_VAULT_SEED),
+            new anchor.BN(gameId).toArrayLike(Buffer, "le", 8),
+        ],
+        programId
+    ))[0];
+}
+
+
+export function isLamportGameMint(mint: PublicKey): boolean {
+    return mint.equals(LAMPORT_GAME_MINT);
+}
+
+
+export async function getAssociatedTokenAddress(
+    mint: PublicKey,
+    owner: PublicKey,
+    tokenProgramId: PublicKey = spl.TOKEN_PROGRAM_ID
+): Promise<PublicKey> {
+    return await spl.getAssociatedTokenAddress(mint, owner, false, tokenProgramId);
+}
+
+
+/**
+ * Games can be played with SPL Token or Token-2022 mints; the token program
+ * passed to the equilibrate program must be the one that owns the mint.
+ */
+export async function getTokenProgramId(mint: PublicKey, connection: Connection): Promise<PublicKey> {
+    const account: anchor.web3.AccountInfo<Buffer> | null = await connection.getAccountInfo(mint);
+    if (account === null) throw new Error(`Mint ${mint.toBase58()} doesnt exist`);
+    return account.owner;
 }
 
 
 export async function getMintDecimals(mint: PublicKey, connection: Connection): Promise<number> {
+    if (isLamportGameMint(mint)) return LAMPORT_DECIMALS;
     return (await connection.getTokenSupply(mint)).value.decimals;
 }
 
@@ -0,0 +1,170 @@
+use std::fmt;
+
+use anchor_lang::{prelude::Pubkey, AccountDeserialize};
+use equilibrate::state::{
+    Game, PlayerState, PoolManager, ProgramConfig, ReferralStats, SessionAuthority,
+};
+
+use crate::pda::{
+    game_address, player_state_address, pool_manager_address, program_config_address,
+    referral_stats_address, session_address,
+};
+
+/// Source of raw account data, e.g. an RPC client or a test bank.
+///
+/// Any `Fn(&Pubkey) -> Result<Option<Vec<u8>>, E>` is a fetcher, so an RPC client
+/// can be adapted with a closure like
+/// `|address| rpc.get_account_data(address).map(Some)`.
+pub trait AccountFetcher {
+    type Error;
+
+    /// Returns the account's data, or `None` if the account doesn't exist.
+    fn fetch_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, Self::Error>;
+}
+
+impl<F, E> AccountFetcher for F
+where
+    F: Fn(&Pubkey) -> Result<Option<Vec<u8>>, E>,
+{
+    type Error = E;
+
+    fn fetch_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, E> {
+        self(address)
+    }
+}
+
+#[derive(Debug)]
+pub enum FetchError<E> {
+    /// the fetcher failed to get the account
+    Fetch(E),
+    /// the account exists but isnt the expected type
+    Deserialize(anchor_lang::error::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for FetchError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Fetch(e) => write!(f, "failed to fetch account: {}", e),
+            FetchError::Deserialize(e) => write!(f, "failed to deserialize account: {}", e),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for FetchError<E> {}
+
+/// Deserializes a game account, including checking its discriminator.
+pub fn deserialize_game(data: &[u8]) -> anchor_lang::Result<Game> {
+    Game::try_deserialize(&mut &data[..])
+}
+
+/// Deserializes a player state account, including checking its discriminator.
+pub fn deserialize_player_state(data: &[u8]) -> anchor_lang::Result<PlayerState> {
+    PlayerState::try_deserialize(&mut &data[..])
+}
+
+/// Deserializes a pool manager account, including checking its discriminator.
+pub fn deserialize_pool_manager(data: &[u8]) -> anchor_lang::Result<PoolManager> {
+    PoolManager::try_deserialize(&mut &data[..])
+}
+
+/// Deserializes the program config account, including checking its discriminator.
+pub fn deserialize_program_config(data: &[u8]) -> anchor_lang::Result<ProgramConfig> {
+    ProgramConfig::try_deserialize(&mut &data[..])
+}
+
+/// Deserializes a referral stats account, including checking its discriminator.
+pub fn deserialize_referral_stats(data: &[u8]) -> anchor_lang::Result<ReferralStats> {
+    ReferralStats::try_deserialize(&mut &data[..])
+}
+
+/// Deserializes a session account, including checking its discriminator.
+pub fn deserialize_session_authority(data: &[u8]) -> anchor_lang::Result<SessionAuthority> {
+    SessionAuthority::try_deserialize(&mut &data[..])
+}
+
+/// Fetches the game with the given ID, or `None` if it doesn't exist (or has ended).
+pub fn fetch_game<F: AccountFetcher>(
+    fetcher: &F,
+    game_id: u64,
+) -> Result<Option<Game>, FetchError<F::Error>> {
+    fetch(fetcher, &game_address(game_id).0, deserialize_game)
+}
+
+/// Fetches a player's state in the given game, or `None` if they aren't in it.
+pub fn fetch_player_state<F: AccountFetcher>(
+    fetcher: &F,
+    game_id: u64,
+    player: &Pubkey,
+) -> Result<Option<PlayerState>, FetchError<F::Error>> {
+    let (game, _) = game_address(game_id);
+    fetch(
+        fetcher,
+        &player_state_address(&game, player).0,
+        deserialize_player_state,
+    )
+}
+
+/// Fetches the pool manager of `mint`, or `None` if the pool hasn't been created.
+pub fn fetch_pool_manager<F: AccountFetcher>(
+    fetcher: &F,
+    mint: &Pubkey,
+) -> Result<Option<PoolManager>, FetchError<F::Error>> {
+    fetch(
+        fetcher,
+        &pool_manager_address(mint).0,
+        deserialize_pool_manager,
+    )
+}
+
+/// Fetches the program config, or `None` if it hasn't been initialized.
+pub fn fetch_program_config<F: AccountFetcher>(
+    fetcher: &F,
+) -> Result<Option<ProgramConfig>, FetchError<F::Error>> {
+    fetch(
+        fetcher,
+        &program_config_address().0,
+        deserialize_program_config,
+    )
+}
+
+/// Fetches the referral stats of `referrer`, or `None` if they haven't referred anyone.
+pub fn fetch_referral_stats<F: AccountFetcher>(
+    fetcher: &F,
+    referrer: &Pubkey,
+) -> Result<Option<ReferralStats>, FetchError<F::Error>> {
+    fetch(
+        fetcher,
+        &referral_stats_address(referrer).0,
+        deserialize_referral_stats,
+    )
+}
+
+/// Fetches a player's session in the given game, or `None` if they don't have one.
+pub fn fetch_session_authority<F: AccountFetcher>(
+    fetcher: &F,
+    game_id: u64,
+    player: &Pubkey,
+) -> Result<Option<SessionAuthority>, FetchError<F::Error>> {
+    fetch(
+        fetcher,
+        &session_address(game_id, player).0,
+        deserialize_session_authority,
+    )
+}
+
+fn fetch<F: AccountFetcher, T>(
+    fetcher: &F,
+    address: &Pubkey,
+    deserialize: fn(&[u8]) -> anchor_lang::Result<T>,
+) -> Result<Option<T>, FetchError<F::Error>> {
+    match fetcher
+        .fetch_account_data(address)
+        .map_err(FetchError::Fetch)?
+    {
+        // closed accounts may linger with no data until garbage collected
+        Some(data) if !data.is_empty() => deserialize(&data)
+            .map(Some)
+            .map_err(FetchError::Deserialize),
+        _ => Ok(None),
+    }
+}
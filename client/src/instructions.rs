@@ -0,0 +1,1486 @@
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program, sysvar},
+    InstructionData, ToAccountMetas,
+};
+use anchor_spl::token;
+use equilibrate::state::{GameConfig, ProgramSettings};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::pda::{
+    game_address, lamport_vault_address, player_state_address, pool_manager_address,
+    program_config_address, program_data_address, referral_stats_address, session_address,
+    token_pool_address,
+};
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: equilibrate::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Builds `initialize_config`. The payer must be the program's upgrade authority.
+pub struct InitializeConfigBuilder {
+    payer: Pubkey,
+    admin: Pubkey,
+    settings: ProgramSettings,
+}
+
+impl InitializeConfigBuilder {
+    pub fn new(payer: Pubkey, settings: ProgramSettings) -> Self {
+        Self {
+            payer,
+            admin: payer,
+            settings,
+        }
+    }
+
+    /// Admin of the new program config. Defaults to the payer.
+    pub fn admin(mut self, admin: Pubkey) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        instruction(
+            equilibrate::accounts::InitializeConfig {
+                program_config: program_config_address().0,
+                program: equilibrate::ID,
+                program_data: program_data_address(),
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::InitializeConfig {
+                admin: self.admin,
+                settings: self.settings.clone(),
+            },
+        )
+    }
+}
+
+/// Builds `update_config`, signed by the current admin.
+pub struct UpdateConfigBuilder {
+    admin: Pubkey,
+    new_admin: Pubkey,
+    settings: ProgramSettings,
+}
+
+impl UpdateConfigBuilder {
+    pub fn new(admin: Pubkey, settings: ProgramSettings) -> Self {
+        Self {
+            admin,
+            new_admin: admin,
+            settings,
+        }
+    }
+
+    /// Hands the program config over to a new admin. Defaults to keeping the current one.
+    pub fn new_admin(mut self, new_admin: Pubkey) -> Self {
+        self.new_admin = new_admin;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        instruction(
+            equilibrate::accounts::UpdateConfig {
+                program_config: program_config_address().0,
+                admin: self.admin,
+            },
+            equilibrate::instruction::UpdateConfig {
+                new_admin: self.new_admin,
+                settings: self.settings.clone(),
+            },
+        )
+    }
+}
+
+/// Builds `create_pool` for a mint that doesn't have a pool yet.
+pub struct CreatePoolBuilder {
+    payer: Pubkey,
+    game_mint: Pubkey,
+    token_program: Pubkey,
+}
+
+impl CreatePoolBuilder {
+    pub fn new(payer: Pubkey, game_mint: Pubkey) -> Self {
+        Self {
+            payer,
+            game_mint,
+            token_program: token::ID,
+        }
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (pool_manager, pool_manager_bump) = pool_manager_address(&self.game_mint);
+        instruction(
+            equilibrate::accounts::CreatePool {
+                pool_manager,
+                token_pool: token_pool_address(&self.game_mint).0,
+                payer: self.payer,
+                game_mint: self.game_mint,
+                token_program: self.token_program,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::CreatePool { pool_manager_bump },
+        )
+    }
+}
+
+/// Builds `audit_pool`, checking the token pool of a mint against what it owes
+/// its games. Anyone can audit any pool; the transaction fee payer needn't be
+/// an account here.
+pub struct AuditPoolBuilder {
+    game_mint: Pubkey,
+    token_program: Pubkey,
+}
+
+impl AuditPoolBuilder {
+    pub fn new(game_mint: Pubkey) -> Self {
+        Self {
+            game_mint,
+            token_program: token::ID,
+        }
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        instruction(
+            equilibrate::accounts::AuditPool {
+                pool_manager: pool_manager_address(&self.game_mint).0,
+                token_pool: token_pool_address(&self.game_mint).0,
+                game_mint: self.game_mint,
+                token_program: self.token_program,
+            },
+            equilibrate::instruction::AuditPool {},
+        )
+    }
+}
+
+/// Builds `new_game`, entering the payer into bucket 1 of the new game.
+pub struct NewGameBuilder {
+    payer: Pubkey,
+    config: GameConfig,
+    game_id: u64,
+    program_fee_destination: Pubkey,
+    deposit_source_account: Option<Pubkey>,
+    token_program: Pubkey,
+}
+
+impl NewGameBuilder {
+    /// `program_fee_destination` must match the one in the program config.
+    pub fn new(
+        payer: Pubkey,
+        config: GameConfig,
+        game_id: u64,
+        program_fee_destination: Pubkey,
+    ) -> Self {
+        Self {
+            payer,
+            config,
+            game_id,
+            program_fee_destination,
+            deposit_source_account: None,
+            token_program: token::ID,
+        }
+    }
+
+    /// Token account the entry deposit is taken from. Defaults to the payer's
+    /// associated token account for the game mint.
+    pub fn deposit_source_account(mut self, account: Pubkey) -> Self {
+        self.deposit_source_account = Some(account);
+        self
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let mint = self.config.mint;
+        let (game, _) = game_address(self.game_id);
+        let (pool_manager, _) = pool_manager_address(&mint);
+        instruction(
+            equilibrate::accounts::NewGame {
+                game,
+                first_player: player_state_address(&game, &self.payer).0,
+                program_config: program_config_address().0,
+                program_fee_destination: self.program_fee_destination,
+                game_mint: mint,
+                deposit_source_account: self.deposit_source_account.unwrap_or_else(|| {
+                    get_associated_token_address_with_program_id(
+                        &self.payer,
+                        &mint,
+                        &self.token_program,
+                    )
+                }),
+                pool_manager,
+                token_pool: token_pool_address(&mint).0,
+                payer: self.payer,
+                associated_token_program: anchor_spl::associated_token::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            equilibrate::instruction::NewGame {
+                config: self.config.clone(),
+                game_id: self.game_id,
+            },
+        )
+    }
+}
+
+/// Builds `enter_game` for a new player.
+pub struct EnterGameBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    game_mint: Pubkey,
+    bucket: u8,
+    stake_decimal_tokens: u64,
+    program_fee_destination: Pubkey,
+    deposit_source_account: Option<Pubkey>,
+    creator_fee_destination: Option<Pubkey>,
+    token_program: Pubkey,
+    allowlist_proof: Vec<[u8; 32]>,
+    referrer: Option<Pubkey>,
+}
+
+impl EnterGameBuilder {
+    /// `stake_decimal_tokens` must be between the game's entry fee and maximum
+    /// stake, and `program_fee_destination` must match the one in the program config.
+    pub fn new(
+        payer: Pubkey,
+        game_id: u64,
+        game_mint: Pubkey,
+        bucket: u8,
+        stake_decimal_tokens: u64,
+        program_fee_destination: Pubkey,
+    ) -> Self {
+        Self {
+            payer,
+            game_id,
+            game_mint,
+            bucket,
+            stake_decimal_tokens,
+            program_fee_destination,
+            deposit_source_account: None,
+            creator_fee_destination: None,
+            token_program: token::ID,
+            allowlist_proof: Vec::new(),
+            referrer: None,
+        }
+    }
+
+    /// Proof that the payer is on the game's allowlist, from
+    /// [`Allowlist::proof`](crate::Allowlist::proof). Public games don't need one.
+    pub fn allowlist_proof(mut self, proof: Vec<[u8; 32]>) -> Self {
+        self.allowlist_proof = proof;
+        self
+    }
+
+    /// Token account the entry deposit is taken from. Defaults to the payer's
+    /// associated token account for the game mint.
+    pub fn deposit_source_account(mut self, account: Pubkey) -> Self {
+        self.deposit_source_account = Some(account);
+        self
+    }
+
+    /// Game creator's token account for the game mint, which the creator fee is
+    /// paid into. Needed for games that charge one, and ignored otherwise.
+    pub fn creator_fee_destination(mut self, account: Pubkey) -> Self {
+        self.creator_fee_destination = Some(account);
+        self
+    }
+
+    /// Wallet that referred the player, which gets a share of the program fee.
+    /// Can't be the payer.
+    pub fn referrer(mut self, referrer: Pubkey) -> Self {
+        self.referrer = Some(referrer);
+        self
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let deposit_source_account = self.deposit_source_account.unwrap_or_else(|| {
+            get_associated_token_address_with_program_id(
+                &self.payer,
+                &self.game_mint,
+                &self.token_program,
+            )
+        });
+        let (pool_manager, _) = pool_manager_address(&self.game_mint);
+        let mut instruction = instruction(
+            equilibrate::accounts::EnterGame {
+                game,
+                player: player_state_address(&game, &self.payer).0,
+                program_config: program_config_address().0,
+                program_fee_destination: self.program_fee_destination,
+                game_mint: self.game_mint,
+                deposit_source_account,
+                // games without a creator fee never read this account
+                creator_fee_destination: self
+                    .creator_fee_destination
+                    .unwrap_or(deposit_source_account),
+                pool_manager,
+                token_pool: token_pool_address(&self.game_mint).0,
+                payer: self.payer,
+                token_program: self.token_program,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::EnterGame {
+                bucket: self.bucket,
+                stake_decimal_tokens: self.stake_decimal_tokens,
+                allowlist_proof: self.allowlist_proof.clone(),
+            },
+        );
+        if let Some(referrer) = self.referrer {
+            instruction.accounts.extend([
+                AccountMeta::new(referrer, false),
+                AccountMeta::new(referral_stats_address(&referrer).0, false),
+            ]);
+        }
+        instruction
+    }
+}
+
+/// Builds `add_deposit` for a player already in the game.
+pub struct AddDepositBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    game_mint: Pubkey,
+    deposit_decimal_tokens: u64,
+    deposit_source_account: Option<Pubkey>,
+    token_program: Pubkey,
+}
+
+impl AddDepositBuilder {
+    pub fn new(
+        payer: Pubkey,
+        game_id: u64,
+        game_mint: Pubkey,
+        deposit_decimal_tokens: u64,
+    ) -> Self {
+        Self {
+            payer,
+            game_id,
+            game_mint,
+            deposit_decimal_tokens,
+            deposit_source_account: None,
+            token_program: token::ID,
+        }
+    }
+
+    /// Token account the deposit is taken from. Defaults to the payer's
+    /// associated token account for the game mint.
+    pub fn deposit_source_account(mut self, account: Pubkey) -> Self {
+        self.deposit_source_account = Some(account);
+        self
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let deposit_source_account = self.deposit_source_account.unwrap_or_else(|| {
+            get_associated_token_address_with_program_id(
+                &self.payer,
+                &self.game_mint,
+                &self.token_program,
+            )
+        });
+        instruction(
+            equilibrate::accounts::AddDeposit {
+                game,
+                player: player_state_address(&game, &self.payer).0,
+                game_mint: self.game_mint,
+                deposit_source_account,
+                pool_manager: pool_manager_address(&self.game_mint).0,
+                token_pool: token_pool_address(&self.game_mint).0,
+                payer: self.payer,
+                token_program: self.token_program,
+            },
+            equilibrate::instruction::AddDeposit {
+                deposit_decimal_tokens: self.deposit_decimal_tokens,
+            },
+        )
+    }
+}
+
+/// Builds `move_buckets` for a player already in the game.
+pub struct MoveBucketsBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    bucket: u8,
+    expected_bucket_players: Option<u16>,
+}
+
+impl MoveBucketsBuilder {
+    pub fn new(payer: Pubkey, game_id: u64, bucket: u8) -> Self {
+        Self {
+            payer,
+            game_id,
+            bucket,
+            expected_bucket_players: None,
+        }
+    }
+
+    /// Abort moving if the bucket doesnt have this many players before the move.
+    pub fn expected_bucket_players(mut self, players: u16) -> Self {
+        self.expected_bucket_players = Some(players);
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        instruction(
+            equilibrate::accounts::MoveBuckets {
+                game,
+                player: player_state_address(&game, &self.payer).0,
+                payer: self.payer,
+            },
+            equilibrate::instruction::MoveBuckets {
+                bucket: self.bucket,
+                expected_bucket_players: self.expected_bucket_players,
+            },
+        )
+    }
+}
+
+/// Builds `withdraw` for a player in the game.
+pub struct WithdrawBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    game_mint: Pubkey,
+    fraction_bps: u16,
+    winnings_destination_account: Option<Pubkey>,
+    penalty_destination_account: Option<Pubkey>,
+    token_program: Pubkey,
+}
+
+impl WithdrawBuilder {
+    /// `fraction_bps` is the part of the player's position to withdraw, in basis
+    /// points, and must be less than all of it.
+    pub fn new(payer: Pubkey, game_id: u64, game_mint: Pubkey, fraction_bps: u16) -> Self {
+        Self {
+            payer,
+            game_id,
+            game_mint,
+            fraction_bps,
+            winnings_destination_account: None,
+            penalty_destination_account: None,
+            token_program: token::ID,
+        }
+    }
+
+    /// Token account the withdrawal is paid into. Defaults to the payer's
+    /// associated token account for the game mint.
+    pub fn winnings_destination_account(mut self, account: Pubkey) -> Self {
+        self.winnings_destination_account = Some(account);
+        self
+    }
+
+    /// Token account of the penalty recipient that the player's penalty is paid
+    /// into. Needed for games whose penalty destination is an account, when
+    /// the player has a penalty to pay.
+    pub fn penalty_destination_account(mut self, account: Pubkey) -> Self {
+        self.penalty_destination_account = Some(account);
+        self
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let mut instruction = instruction(
+            equilibrate::accounts::Withdraw {
+                game,
+                game_mint: self.game_mint,
+                player: player_state_address(&game, &self.payer).0,
+                winnings_destination_account: self.winnings_destination_account.unwrap_or_else(
+                    || {
+                        get_associated_token_address_with_program_id(
+                            &self.payer,
+                            &self.game_mint,
+                            &self.token_program,
+                        )
+                    },
+                ),
+                pool_manager: pool_manager_address(&self.game_mint).0,
+                token_pool: token_pool_address(&self.game_mint).0,
+                payer: self.payer,
+                token_program: self.token_program,
+            },
+            equilibrate::instruction::Withdraw {
+                fraction_bps: self.fraction_bps,
+            },
+        );
+        if let Some(account) = self.penalty_destination_account {
+            instruction.accounts.push(AccountMeta::new(account, false));
+        }
+        instruction
+    }
+}
+
+/// Builds `leave_game` for a player in the game.
+pub struct LeaveGameBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    game_mint: Pubkey,
+    game_creator: Pubkey,
+    cancel_on_loss: bool,
+    min_winnings_decimal_tokens: Option<u64>,
+    max_burn_decimal_tokens: Option<u64>,
+    winnings_destination_account: Option<Pubkey>,
+    penalty_destination_account: Option<Pubkey>,
+    close_session: bool,
+    dust_destination_account: Option<Pubkey>,
+    token_program: Pubkey,
+}
+
+impl LeaveGameBuilder {
+    /// `game_creator` must be the wallet that created the game, since it
+    /// receives the game account's rent if this is the last player.
+    pub fn new(payer: Pubkey, game_id: u64, game_mint: Pubkey, game_creator: Pubkey) -> Self {
+        Self {
+            payer,
+            game_id,
+            game_mint,
+            game_creator,
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+            winnings_destination_account: None,
+            penalty_destination_account: None,
+            close_session: false,
+            dust_destination_account: None,
+            token_program: token::ID,
+        }
+    }
+
+    /// Abort leaving if the player would get back less than they put in.
+    pub fn cancel_on_loss(mut self, cancel_on_loss: bool) -> Self {
+        self.cancel_on_loss = cancel_on_loss;
+        self
+    }
+
+    /// Abort leaving if the player would win less than this.
+    pub fn min_winnings_decimal_tokens(mut self, decimal_tokens: u64) -> Self {
+        self.min_winnings_decimal_tokens = Some(decimal_tokens);
+        self
+    }
+
+    /// Abort leaving if more than this of the player's share would be burned.
+    pub fn max_burn_decimal_tokens(mut self, decimal_tokens: u64) -> Self {
+        self.max_burn_decimal_tokens = Some(decimal_tokens);
+        self
+    }
+
+    /// Token account winnings are paid into. Defaults to the payer's associated
+    /// token account for the game mint.
+    pub fn winnings_destination_account(mut self, account: Pubkey) -> Self {
+        self.winnings_destination_account = Some(account);
+        self
+    }
+
+    /// Token account of the penalty recipient that the player's penalty is paid
+    /// into. Needed for games whose penalty destination is an account, when
+    /// the player has a penalty to pay.
+    pub fn penalty_destination_account(mut self, account: Pubkey) -> Self {
+        self.penalty_destination_account = Some(account);
+        self
+    }
+
+    /// Closes the player's session for the game along with their player state.
+    /// This must be set when the player has made a session (their player state's
+    /// `has_session`), so it can't act for them if they enter the game again.
+    pub fn close_session(mut self, close_session: bool) -> Self {
+        self.close_session = close_session;
+        self
+    }
+
+    /// Token account of the game creator that the game's dust is paid into.
+    /// Needed for games whose dust goes to their creator, when this is the
+    /// last player and the game has dust.
+    pub fn dust_destination_account(mut self, account: Pubkey) -> Self {
+        self.dust_destination_account = Some(account);
+        self
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let mut instruction = instruction(
+            equilibrate::accounts::LeaveGame {
+                game,
+                game_mint: self.game_mint,
+                game_creator: self.game_creator,
+                player: player_state_address(&game, &self.payer).0,
+                winnings_destination_account: self.winnings_destination_account.unwrap_or_else(
+                    || {
+                        get_associated_token_address_with_program_id(
+                            &self.payer,
+                            &self.game_mint,
+                            &self.token_program,
+                        )
+                    },
+                ),
+                pool_manager: pool_manager_address(&self.game_mint).0,
+                token_pool: token_pool_address(&self.game_mint).0,
+                payer: self.payer,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::LeaveGame {
+                cancel_on_loss: self.cancel_on_loss,
+                min_winnings_decimal_tokens: self.min_winnings_decimal_tokens,
+                max_burn_decimal_tokens: self.max_burn_decimal_tokens,
+            },
+        );
+        if let Some(account) = self.penalty_destination_account {
+            instruction.accounts.push(AccountMeta::new(account, false));
+        }
+        if self.close_session {
+            let session = session_address(self.game_id, &self.payer).0;
+            instruction.accounts.push(AccountMeta::new(session, false));
+        }
+        if let Some(account) = self.dust_destination_account {
+            instruction.accounts.push(AccountMeta::new(account, false));
+        }
+        instruction
+    }
+}
+
+/// Builds `update_game`, bringing a game's bucket balances up to date. Anyone
+/// can update any game; the transaction fee payer needn't be an account here.
+pub struct UpdateGameBuilder {
+    game_id: u64,
+}
+
+impl UpdateGameBuilder {
+    pub fn new(game_id: u64) -> Self {
+        Self { game_id }
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        instruction(
+            equilibrate::accounts::UpdateGame {
+                game: game_address(self.game_id).0,
+            },
+            equilibrate::instruction::UpdateGame {},
+        )
+    }
+}
+
+/// Builds `settle_player`, paying out a player of a game that is past its
+/// end time. Anyone can pay to settle any player.
+pub struct SettlePlayerBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    game_mint: Pubkey,
+    game_creator: Pubkey,
+    player: Pubkey,
+    winnings_destination_account: Option<Pubkey>,
+    penalty_destination_account: Option<Pubkey>,
+    close_session: bool,
+    dust_destination_account: Option<Pubkey>,
+    token_program: Pubkey,
+}
+
+impl SettlePlayerBuilder {
+    /// `player` is the wallet of the player being settled. `game_creator` must
+    /// be the wallet that created the game, since it receives the game
+    /// account's rent if this is the last player.
+    pub fn new(
+        payer: Pubkey,
+        game_id: u64,
+        game_mint: Pubkey,
+        game_creator: Pubkey,
+        player: Pubkey,
+    ) -> Self {
+        Self {
+            payer,
+            game_id,
+            game_mint,
+            game_creator,
+            player,
+            winnings_destination_account: None,
+            penalty_destination_account: None,
+            close_session: false,
+            dust_destination_account: None,
+            token_program: token::ID,
+        }
+    }
+
+    /// Token account winnings are paid into, which must be owned by the player.
+    /// Defaults to the player's associated token account for the game mint.
+    pub fn winnings_destination_account(mut self, account: Pubkey) -> Self {
+        self.winnings_destination_account = Some(account);
+        self
+    }
+
+    /// Token account of the penalty recipient that the player's penalty is paid
+    /// into. Needed for games whose penalty destination is an account, when
+    /// the player has a penalty to pay.
+    pub fn penalty_destination_account(mut self, account: Pubkey) -> Self {
+        self.penalty_destination_account = Some(account);
+        self
+    }
+
+    /// Closes the player's session for the game along with their player state.
+    /// This must be set when the player has made a session (their player state's
+    /// `has_session`), so it can't act for them if they enter the game again.
+    pub fn close_session(mut self, close_session: bool) -> Self {
+        self.close_session = close_session;
+        self
+    }
+
+    /// Token account of the game creator that the game's dust is paid into.
+    /// Needed for games whose dust goes to their creator, when this is the
+    /// last player and the game has dust.
+    pub fn dust_destination_account(mut self, account: Pubkey) -> Self {
+        self.dust_destination_account = Some(account);
+        self
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let mut instruction = instruction(
+            equilibrate::accounts::SettlePlayer {
+                game,
+                game_mint: self.game_mint,
+                game_creator: self.game_creator,
+                player_wallet: self.player,
+                player: player_state_address(&game, &self.player).0,
+                winnings_destination_account: self.winnings_destination_account.unwrap_or_else(
+                    || {
+                        get_associated_token_address_with_program_id(
+                            &self.player,
+                            &self.game_mint,
+                            &self.token_program,
+                        )
+                    },
+                ),
+                pool_manager: pool_manager_address(&self.game_mint).0,
+                token_pool: token_pool_address(&self.game_mint).0,
+                payer: self.payer,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::SettlePlayer {},
+        );
+        if let Some(account) = self.penalty_destination_account {
+            instruction.accounts.push(AccountMeta::new(account, false));
+        }
+        if self.close_session {
+            let session = session_address(self.game_id, &self.player).0;
+            instruction.accounts.push(AccountMeta::new(session, false));
+        }
+        if let Some(account) = self.dust_destination_account {
+            instruction.accounts.push(AccountMeta::new(account, false));
+        }
+        instruction
+    }
+}
+
+/// Builds `new_lamport_game`, entering the payer into bucket 1 of a new game
+/// played for lamports. The config's mint must be the default pubkey.
+pub struct NewLamportGameBuilder {
+    payer: Pubkey,
+    config: GameConfig,
+    game_id: u64,
+    program_fee_destination: Pubkey,
+}
+
+impl NewLamportGameBuilder {
+    /// `program_fee_destination` must match the one in the program config.
+    pub fn new(
+        payer: Pubkey,
+        config: GameConfig,
+        game_id: u64,
+        program_fee_destination: Pubkey,
+    ) -> Self {
+        Self {
+            payer,
+            config,
+            game_id,
+            program_fee_destination,
+        }
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        instruction(
+            equilibrate::accounts::NewLamportGame {
+                game,
+                first_player: player_state_address(&game, &self.payer).0,
+                lamport_vault: lamport_vault_address(self.game_id).0,
+                program_config: program_config_address().0,
+                program_fee_destination: self.program_fee_destination,
+                payer: self.payer,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            equilibrate::instruction::NewLamportGame {
+                config: self.config.clone(),
+                game_id: self.game_id,
+            },
+        )
+    }
+}
+
+/// Builds `enter_lamport_game` for a new player of a game played for lamports.
+pub struct EnterLamportGameBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    game_creator: Pubkey,
+    bucket: u8,
+    stake_lamports: u64,
+    program_fee_destination: Pubkey,
+    allowlist_proof: Vec<[u8; 32]>,
+    referrer: Option<Pubkey>,
+}
+
+impl EnterLamportGameBuilder {
+    /// `game_creator` must be the wallet that created the game, since it
+    /// receives any creator fee. `stake_lamports` must be between the game's
+    /// entry fee and maximum stake, and `program_fee_destination` must match
+    /// the one in the program config.
+    pub fn new(
+        payer: Pubkey,
+        game_id: u64,
+        game_creator: Pubkey,
+        bucket: u8,
+        stake_lamports: u64,
+        program_fee_destination: Pubkey,
+    ) -> Self {
+        Self {
+            payer,
+            game_id,
+            game_creator,
+            bucket,
+            stake_lamports,
+            program_fee_destination,
+            allowlist_proof: Vec::new(),
+            referrer: None,
+        }
+    }
+
+    /// Proof that the payer is on the game's allowlist, from
+    /// [`Allowlist::proof`](crate::Allowlist::proof). Public games don't need one.
+    pub fn allowlist_proof(mut self, proof: Vec<[u8; 32]>) -> Self {
+        self.allowlist_proof = proof;
+        self
+    }
+
+    /// Wallet that referred the player, which gets a share of the program fee.
+    /// Can't be the payer.
+    pub fn referrer(mut self, referrer: Pubkey) -> Self {
+        self.referrer = Some(referrer);
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let mut instruction = instruction(
+            equilibrate::accounts::EnterLamportGame {
+                game,
+                player: player_state_address(&game, &self.payer).0,
+                program_config: program_config_address().0,
+                program_fee_destination: self.program_fee_destination,
+                lamport_vault: lamport_vault_address(self.game_id).0,
+                game_creator: self.game_creator,
+                payer: self.payer,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::EnterLamportGame {
+                bucket: self.bucket,
+                stake_decimal_tokens: self.stake_lamports,
+                allowlist_proof: self.allowlist_proof.clone(),
+            },
+        );
+        if let Some(referrer) = self.referrer {
+            instruction.accounts.extend([
+                AccountMeta::new(referrer, false),
+                AccountMeta::new(referral_stats_address(&referrer).0, false),
+            ]);
+        }
+        instruction
+    }
+}
+
+/// Builds `leave_lamport_game` for a player in a game played for lamports.
+pub struct LeaveLamportGameBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    game_creator: Pubkey,
+    cancel_on_loss: bool,
+    min_winnings_lamports: Option<u64>,
+    max_burn_lamports: Option<u64>,
+    penalty_recipient: Option<Pubkey>,
+    close_session: bool,
+}
+
+impl LeaveLamportGameBuilder {
+    /// `game_creator` must be the wallet that created the game, since it
+    /// receives the rent of the game and vault accounts if this is the last player.
+    pub fn new(payer: Pubkey, game_id: u64, game_creator: Pubkey) -> Self {
+        Self {
+            payer,
+            game_id,
+            game_creator,
+            cancel_on_loss: false,
+            min_winnings_lamports: None,
+            max_burn_lamports: None,
+            penalty_recipient: None,
+            close_session: false,
+        }
+    }
+
+    /// Abort leaving if the player would get back less than they put in.
+    pub fn cancel_on_loss(mut self, cancel_on_loss: bool) -> Self {
+        self.cancel_on_loss = cancel_on_loss;
+        self
+    }
+
+    /// Abort leaving if the player would win less than this.
+    pub fn min_winnings_lamports(mut self, lamports: u64) -> Self {
+        self.min_winnings_lamports = Some(lamports);
+        self
+    }
+
+    /// Abort leaving if the player's burn penalty is more than this.
+    pub fn max_burn_lamports(mut self, lamports: u64) -> Self {
+        self.max_burn_lamports = Some(lamports);
+        self
+    }
+
+    /// Wallet the game pays penalties to. Needed for games whose penalty
+    /// destination is an account, when the player has a penalty to pay.
+    pub fn penalty_recipient(mut self, recipient: Pubkey) -> Self {
+        self.penalty_recipient = Some(recipient);
+        self
+    }
+
+    /// Closes the player's session for the game along with their player state.
+    /// This must be set when the player has made a session (their player state's
+    /// `has_session`), so it can't act for them if they enter the game again.
+    pub fn close_session(mut self, close_session: bool) -> Self {
+        self.close_session = close_session;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let mut instruction = instruction(
+            equilibrate::accounts::LeaveLamportGame {
+                game,
+                game_creator: self.game_creator,
+                player: player_state_address(&game, &self.payer).0,
+                lamport_vault: lamport_vault_address(self.game_id).0,
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::LeaveLamportGame {
+                cancel_on_loss: self.cancel_on_loss,
+                min_winnings_decimal_tokens: self.min_winnings_lamports,
+                max_burn_decimal_tokens: self.max_burn_lamports,
+            },
+        );
+        if let Some(recipient) = self.penalty_recipient {
+            instruction
+                .accounts
+                .push(AccountMeta::new(recipient, false));
+        }
+        if self.close_session {
+            let session = session_address(self.game_id, &self.payer).0;
+            instruction.accounts.push(AccountMeta::new(session, false));
+        }
+        instruction
+    }
+}
+
+/// Builds `settle_lamport_player`, paying out a player of a game played for
+/// lamports that is past its end time. Anyone can pay to settle any player.
+pub struct SettleLamportPlayerBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    game_creator: Pubkey,
+    player: Pubkey,
+    penalty_recipient: Option<Pubkey>,
+    close_session: bool,
+}
+
+impl SettleLamportPlayerBuilder {
+    /// `player` is the wallet of the player being settled. `game_creator` must
+    /// be the wallet that created the game, since it receives the rent of the
+    /// game and vault accounts if this is the last player.
+    pub fn new(payer: Pubkey, game_id: u64, game_creator: Pubkey, player: Pubkey) -> Self {
+        Self {
+            payer,
+            game_id,
+            game_creator,
+            player,
+            penalty_recipient: None,
+            close_session: false,
+        }
+    }
+
+    /// Wallet the game pays penalties to. Needed for games whose penalty
+    /// destination is an account, when the player has a penalty to pay.
+    pub fn penalty_recipient(mut self, recipient: Pubkey) -> Self {
+        self.penalty_recipient = Some(recipient);
+        self
+    }
+
+    /// Closes the player's session for the game along with their player state.
+    /// This must be set when the player has made a session (their player state's
+    /// `has_session`), so it can't act for them if they enter the game again.
+    pub fn close_session(mut self, close_session: bool) -> Self {
+        self.close_session = close_session;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let mut instruction = instruction(
+            equilibrate::accounts::SettleLamportPlayer {
+                game,
+                game_creator: self.game_creator,
+                player_wallet: self.player,
+                player: player_state_address(&game, &self.player).0,
+                lamport_vault: lamport_vault_address(self.game_id).0,
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::SettleLamportPlayer {},
+        );
+        if let Some(recipient) = self.penalty_recipient {
+            instruction
+                .accounts
+                .push(AccountMeta::new(recipient, false));
+        }
+        if self.close_session {
+            let session = session_address(self.game_id, &self.player).0;
+            instruction.accounts.push(AccountMeta::new(session, false));
+        }
+        instruction
+    }
+}
+
+/// Builds `create_session` for a player in the game, letting `session_key`
+/// act for them until `expires_epoch_seconds`.
+pub struct CreateSessionBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    session_key: Pubkey,
+    expires_epoch_seconds: i64,
+    can_leave: bool,
+}
+
+impl CreateSessionBuilder {
+    pub fn new(
+        payer: Pubkey,
+        game_id: u64,
+        session_key: Pubkey,
+        expires_epoch_seconds: i64,
+    ) -> Self {
+        Self {
+            payer,
+            game_id,
+            session_key,
+            expires_epoch_seconds,
+            can_leave: false,
+        }
+    }
+
+    /// Let the session key leave the game for the player. Defaults to moving only.
+    pub fn can_leave(mut self, can_leave: bool) -> Self {
+        self.can_leave = can_leave;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        instruction(
+            equilibrate::accounts::CreateSession {
+                game,
+                player: player_state_address(&game, &self.payer).0,
+                session: session_address(self.game_id, &self.payer).0,
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::CreateSession {
+                session_key: self.session_key,
+                expires_epoch_seconds: self.expires_epoch_seconds,
+                can_leave: self.can_leave,
+            },
+        )
+    }
+}
+
+/// Builds `session_move_buckets`, signed by the session key of `player`.
+pub struct SessionMoveBucketsBuilder {
+    session_key: Pubkey,
+    player: Pubkey,
+    game_id: u64,
+    bucket: u8,
+    expected_bucket_players: Option<u16>,
+}
+
+impl SessionMoveBucketsBuilder {
+    pub fn new(session_key: Pubkey, player: Pubkey, game_id: u64, bucket: u8) -> Self {
+        Self {
+            session_key,
+            player,
+            game_id,
+            bucket,
+            expected_bucket_players: None,
+        }
+    }
+
+    /// Abort moving if the bucket doesnt have this many players before the move.
+    pub fn expected_bucket_players(mut self, players: u16) -> Self {
+        self.expected_bucket_players = Some(players);
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        instruction(
+            equilibrate::accounts::SessionMoveBuckets {
+                game,
+                player_wallet: self.player,
+                player: player_state_address(&game, &self.player).0,
+                session: session_address(self.game_id, &self.player).0,
+                payer: self.session_key,
+            },
+            equilibrate::instruction::SessionMoveBuckets {
+                bucket: self.bucket,
+                expected_bucket_players: self.expected_bucket_players,
+            },
+        )
+    }
+}
+
+/// Builds `session_leave_game`, signed by the session key of `player`.
+/// Winnings are paid to the player.
+pub struct SessionLeaveGameBuilder {
+    session_key: Pubkey,
+    player: Pubkey,
+    game_id: u64,
+    game_mint: Pubkey,
+    game_creator: Pubkey,
+    cancel_on_loss: bool,
+    min_winnings_decimal_tokens: Option<u64>,
+    max_burn_decimal_tokens: Option<u64>,
+    winnings_destination_account: Option<Pubkey>,
+    penalty_destination_account: Option<Pubkey>,
+    dust_destination_account: Option<Pubkey>,
+    token_program: Pubkey,
+}
+
+impl SessionLeaveGameBuilder {
+    /// `game_creator` must be the wallet that created the game, since it
+    /// receives the game account's rent if this is the last player.
+    pub fn new(
+        session_key: Pubkey,
+        player: Pubkey,
+        game_id: u64,
+        game_mint: Pubkey,
+        game_creator: Pubkey,
+    ) -> Self {
+        Self {
+            session_key,
+            player,
+            game_id,
+            game_mint,
+            game_creator,
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+            winnings_destination_account: None,
+            penalty_destination_account: None,
+            dust_destination_account: None,
+            token_program: token::ID,
+        }
+    }
+
+    /// Abort leaving if the player would get back less than they put in.
+    pub fn cancel_on_loss(mut self, cancel_on_loss: bool) -> Self {
+        self.cancel_on_loss = cancel_on_loss;
+        self
+    }
+
+    /// Abort leaving if the player would win less than this.
+    pub fn min_winnings_decimal_tokens(mut self, decimal_tokens: u64) -> Self {
+        self.min_winnings_decimal_tokens = Some(decimal_tokens);
+        self
+    }
+
+    /// Abort leaving if more than this of the player's share would be burned.
+    pub fn max_burn_decimal_tokens(mut self, decimal_tokens: u64) -> Self {
+        self.max_burn_decimal_tokens = Some(decimal_tokens);
+        self
+    }
+
+    /// Token account winnings are paid into, which must be owned by the player.
+    /// Defaults to the player's associated token account for the game mint.
+    pub fn winnings_destination_account(mut self, account: Pubkey) -> Self {
+        self.winnings_destination_account = Some(account);
+        self
+    }
+
+    /// Token account of the penalty recipient that the player's penalty is paid
+    /// into. Needed for games whose penalty destination is an account, when
+    /// the player has a penalty to pay.
+    pub fn penalty_destination_account(mut self, account: Pubkey) -> Self {
+        self.penalty_destination_account = Some(account);
+        self
+    }
+
+    /// Token account of the game creator that the game's dust is paid into.
+    /// Needed for games whose dust goes to their creator, when this is the
+    /// last player and the game has dust.
+    pub fn dust_destination_account(mut self, account: Pubkey) -> Self {
+        self.dust_destination_account = Some(account);
+        self
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        let mut instruction = instruction(
+            equilibrate::accounts::SessionLeaveGame {
+                game,
+                game_mint: self.game_mint,
+                game_creator: self.game_creator,
+                player_wallet: self.player,
+                player: player_state_address(&game, &self.player).0,
+                session: session_address(self.game_id, &self.player).0,
+                winnings_destination_account: self.winnings_destination_account.unwrap_or_else(
+                    || {
+                        get_associated_token_address_with_program_id(
+                            &self.player,
+                            &self.game_mint,
+                            &self.token_program,
+                        )
+                    },
+                ),
+                pool_manager: pool_manager_address(&self.game_mint).0,
+                token_pool: token_pool_address(&self.game_mint).0,
+                payer: self.session_key,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::SessionLeaveGame {
+                cancel_on_loss: self.cancel_on_loss,
+                min_winnings_decimal_tokens: self.min_winnings_decimal_tokens,
+                max_burn_decimal_tokens: self.max_burn_decimal_tokens,
+            },
+        );
+        if let Some(account) = self.penalty_destination_account {
+            instruction.accounts.push(AccountMeta::new(account, false));
+        }
+        if let Some(account) = self.dust_destination_account {
+            instruction.accounts.push(AccountMeta::new(account, false));
+        }
+        instruction
+    }
+}
+
+/// Builds `revoke_session`, signed by the player who created the session.
+pub struct RevokeSessionBuilder {
+    payer: Pubkey,
+    game_id: u64,
+}
+
+impl RevokeSessionBuilder {
+    pub fn new(payer: Pubkey, game_id: u64) -> Self {
+        Self { payer, game_id }
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        instruction(
+            equilibrate::accounts::RevokeSession {
+                session: session_address(self.game_id, &self.payer).0,
+                payer: self.payer,
+            },
+            equilibrate::instruction::RevokeSession {},
+        )
+    }
+}
+
+/// Builds `migrate_game`, upgrading a game account by one version. Send it once
+/// per version the game is behind. Anyone can pay to migrate any game.
+pub struct MigrateGameBuilder {
+    payer: Pubkey,
+    game_id: u64,
+}
+
+impl MigrateGameBuilder {
+    pub fn new(payer: Pubkey, game_id: u64) -> Self {
+        Self { payer, game_id }
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        instruction(
+            equilibrate::accounts::MigrateGame {
+                game: game_address(self.game_id).0,
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::MigrateGame {},
+        )
+    }
+}
+
+/// Builds `migrate_player`, upgrading a player state account by one version.
+/// The player's game must be fully migrated first. Anyone can pay to migrate any player.
+pub struct MigratePlayerBuilder {
+    payer: Pubkey,
+    game_id: u64,
+    player: Pubkey,
+}
+
+impl MigratePlayerBuilder {
+    /// `player` is the wallet of the player whose state is migrated
+    pub fn new(payer: Pubkey, game_id: u64, player: Pubkey) -> Self {
+        Self {
+            payer,
+            game_id,
+            player,
+        }
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        let (game, _) = game_address(self.game_id);
+        instruction(
+            equilibrate::accounts::MigratePlayer {
+                game,
+                player_wallet: self.player,
+                player: player_state_address(&game, &self.player).0,
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::MigratePlayer {},
+        )
+    }
+}
+
+/// Builds `migrate_program_config`, upgrading the program config by one
+/// version. Anyone can pay to migrate it.
+pub struct MigrateProgramConfigBuilder {
+    payer: Pubkey,
+}
+
+impl MigrateProgramConfigBuilder {
+    pub fn new(payer: Pubkey) -> Self {
+        Self { payer }
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        instruction(
+            equilibrate::accounts::MigrateProgramConfig {
+                program_config: program_config_address().0,
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::MigrateProgramConfig {},
+        )
+    }
+}
+
+/// Builds `migrate_pool_manager`, upgrading a pool manager made before it
+/// recorded the pool's liabilities. Anyone can pay to migrate any pool manager.
+pub struct MigratePoolManagerBuilder {
+    payer: Pubkey,
+    game_mint: Pubkey,
+    token_program: Pubkey,
+}
+
+impl MigratePoolManagerBuilder {
+    pub fn new(payer: Pubkey, game_mint: Pubkey) -> Self {
+        Self {
+            payer,
+            game_mint,
+            token_program: token::ID,
+        }
+    }
+
+    /// Token program that owns the game mint: SPL token or Token-2022.
+    /// Defaults to SPL token.
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        instruction(
+            equilibrate::accounts::MigratePoolManager {
+                pool_manager: pool_manager_address(&self.game_mint).0,
+                token_pool: token_pool_address(&self.game_mint).0,
+                game_mint: self.game_mint,
+                token_program: self.token_program,
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::MigratePoolManager {},
+        )
+    }
+}
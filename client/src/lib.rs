@@ -0,0 +1,15 @@
+//! Client-side helpers for the equilibrate program: PDA derivation,
+//! instruction builders, typed account deserialization, and building
+//! allowlists for private games.
+
+pub mod accounts;
+pub use accounts::*;
+
+pub mod instructions;
+pub use instructions::*;
+
+pub mod pda;
+pub use pda::*;
+
+pub use equilibrate::state::allowlist::Allowlist;
+pub use equilibrate::ID as PROGRAM_ID;
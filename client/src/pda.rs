@@ -0,0 +1,77 @@
+use anchor_lang::{prelude::Pubkey, solana_program::bpf_loader_upgradeable};
+use equilibrate::constants::{
+    GAME_SEED, LAMPORT_VAULT_SEED, PLAYER_SEED, POOL_MANAGER_SEED, POOL_SEED, PROGRAM_CONFIG_SEED,
+    REFERRAL_SEED, SESSION_SEED,
+};
+
+/// Address and bump of the game account with the given ID.
+pub fn game_address(game_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[GAME_SEED.as_ref(), &game_id.to_le_bytes()],
+        &equilibrate::ID,
+    )
+}
+
+/// Address and bump of a player's state account in the given game.
+pub fn player_state_address(game: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PLAYER_SEED.as_ref(), game.as_ref(), player.as_ref()],
+        &equilibrate::ID,
+    )
+}
+
+/// Address and bump of the pool manager that signs payouts for games of `mint`.
+pub fn pool_manager_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[POOL_MANAGER_SEED.as_ref(), mint.as_ref()],
+        &equilibrate::ID,
+    )
+}
+
+/// Address and bump of the token pool holding deposits for all games of `mint`.
+pub fn token_pool_address(mint: &Pubkey) -> (Pubkey, u8) {
+    let (pool_manager, _) = pool_manager_address(mint);
+    Pubkey::find_program_address(
+        &[POOL_SEED.as_ref(), mint.as_ref(), pool_manager.as_ref()],
+        &equilibrate::ID,
+    )
+}
+
+/// Address and bump of the singleton program config.
+pub fn program_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_CONFIG_SEED.as_ref()], &equilibrate::ID)
+}
+
+/// Address and bump of the referral stats of `referrer`.
+pub fn referral_stats_address(referrer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[REFERRAL_SEED.as_ref(), referrer.as_ref()],
+        &equilibrate::ID,
+    )
+}
+
+/// Address and bump of a player's session in the game with the given ID.
+pub fn session_address(game_id: u64, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SESSION_SEED.as_ref(),
+            &game_id.to_le_bytes(),
+            player.as_ref(),
+        ],
+        &equilibrate::ID,
+    )
+}
+
+/// Address and bump of the vault holding the deposits of the lamport game with
+/// the given ID.
+pub fn lamport_vault_address(game_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LAMPORT_VAULT_SEED.as_ref(), &game_id.to_le_bytes()],
+        &equilibrate::ID,
+    )
+}
+
+/// Address of the upgradeable loader's program data account for this program.
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[equilibrate::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
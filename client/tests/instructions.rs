@@ -0,0 +1,523 @@
+//! Sends each builder's instruction to the program in the in-process bank
+//! from the program's tests, checking the accounts it lists are the ones the
+//! program expects.
+
+#[path = "../../programs/equilibrate/tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::{prelude::Pubkey, AnchorSerialize, Discriminator};
+use common::*;
+use equilibrate::{
+    constants::{GAME_VERSION, PLAYER_STATE_VERSION, PROGRAM_CONFIG_VERSION},
+    state::{
+        migration::{
+            BucketV1, GameConfigV1, GameStateV1, GameV1, PlayerStateV1, PoolManagerV1,
+            ProgramConfigV1, ProgramSettingsV1,
+        },
+        Game, GameConfig, PlayerState, PoolManager, ProgramConfig,
+    },
+};
+use equilibrate_client::{
+    AddDepositBuilder, AuditPoolBuilder, CreatePoolBuilder, CreateSessionBuilder, EnterGameBuilder,
+    EnterLamportGameBuilder, InitializeConfigBuilder, LeaveGameBuilder, LeaveLamportGameBuilder,
+    MigrateGameBuilder, MigratePlayerBuilder, MigratePoolManagerBuilder,
+    MigrateProgramConfigBuilder, MoveBucketsBuilder, NewGameBuilder, NewLamportGameBuilder,
+    RevokeSessionBuilder, SessionLeaveGameBuilder, SessionMoveBucketsBuilder,
+    SettleLamportPlayerBuilder, SettlePlayerBuilder, UpdateConfigBuilder, UpdateGameBuilder,
+    WithdrawBuilder,
+};
+
+const SESSION_SECONDS: i64 = 60 * 60;
+
+fn ending_game_config(context: &TestContext, mint: Pubkey) -> GameConfig {
+    GameConfig {
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + 100),
+        ..default_game_config(mint)
+    }
+}
+
+/// Writes `account` with the layout of an older version
+fn set_old_account<T: Discriminator>(
+    context: &mut TestContext,
+    address: Pubkey,
+    account: &impl AnchorSerialize,
+) {
+    let mut data = T::discriminator().to_vec();
+    account.serialize(&mut data).unwrap();
+    context.bank.set_account(
+        address,
+        StoredAccount {
+            lamports: context.bank.minimum_balance(data.len()),
+            data,
+            owner: equilibrate::ID,
+            executable: false,
+        },
+    );
+}
+
+#[test]
+fn initialize_config_builder_all_good() {
+    let mut context = TestContext::without_config();
+    let settings = default_program_settings(context.program_fee_destination);
+
+    let ix = InitializeConfigBuilder::new(context.upgrade_authority, settings)
+        .admin(context.admin)
+        .instruction();
+    context
+        .bank
+        .process(&ix, &[context.upgrade_authority])
+        .unwrap();
+
+    assert_eq!(context.program_config().unwrap().admin, context.admin);
+}
+
+#[test]
+fn update_config_builder_all_good() {
+    let mut context = TestContext::new();
+    let new_admin = context.new_wallet();
+    let settings = default_program_settings(context.program_fee_destination);
+
+    let ix = UpdateConfigBuilder::new(context.admin, settings)
+        .new_admin(new_admin)
+        .instruction();
+    context.bank.process(&ix, &[context.admin]).unwrap();
+
+    assert_eq!(context.program_config().unwrap().admin, new_admin);
+}
+
+#[test]
+fn create_pool_builder_all_good() {
+    let mut context = TestContext::new();
+    let payer = context.new_wallet();
+    let mint = context.create_mint(Pubkey::new_unique());
+
+    let ix = CreatePoolBuilder::new(payer, mint).instruction();
+    context.bank.process(&ix, &[payer]).unwrap();
+
+    assert!(context.pool_manager(&mint).is_some());
+}
+
+#[test]
+fn audit_pool_builder_all_good() {
+    let mut context = TestContext::new();
+
+    let ix = AuditPoolBuilder::new(context.mint).instruction();
+    context.bank.process(&ix, &[]).unwrap();
+}
+
+#[test]
+fn new_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+
+    let ix = NewGameBuilder::new(
+        creator.wallet,
+        default_game_config(context.mint),
+        game_id,
+        context.program_fee_destination,
+    )
+    .instruction();
+    context.bank.process(&ix, &[creator.wallet]).unwrap();
+
+    assert_eq!(context.player_state(game_id, &creator).unwrap().bucket, 1);
+}
+
+#[test]
+fn enter_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+
+    let ix = EnterGameBuilder::new(
+        player.wallet,
+        game_id,
+        context.mint,
+        2,
+        ENTRY_FEE_DECIMAL_TOKENS,
+        context.program_fee_destination,
+    )
+    .instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert_eq!(context.player_state(game_id, &player).unwrap().bucket, 2);
+}
+
+#[test]
+fn add_deposit_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+
+    let ix = AddDepositBuilder::new(
+        player.wallet,
+        game_id,
+        context.mint,
+        ENTRY_FEE_DECIMAL_TOKENS,
+    )
+    .instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert_eq!(
+        context
+            .player_state(game_id, &player)
+            .unwrap()
+            .added_decimal_tokens,
+        ENTRY_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn move_buckets_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+
+    let ix = MoveBucketsBuilder::new(player.wallet, game_id, 3)
+        .expected_bucket_players(0)
+        .instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert_eq!(context.player_state(game_id, &player).unwrap().bucket, 3);
+}
+
+#[test]
+fn withdraw_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+
+    let ix = WithdrawBuilder::new(player.wallet, game_id, context.mint, 5_000).instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert_eq!(
+        context
+            .player_state(game_id, &player)
+            .unwrap()
+            .stake_decimal_tokens,
+        ENTRY_FEE_DECIMAL_TOKENS / 2
+    );
+}
+
+#[test]
+fn leave_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+
+    let ix =
+        LeaveGameBuilder::new(player.wallet, game_id, context.mint, creator.wallet).instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert!(context.player_state(game_id, &player).is_none());
+}
+
+#[test]
+fn update_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+
+    let ix = UpdateGameBuilder::new(game_id).instruction();
+    context.bank.process(&ix, &[]).unwrap();
+
+    assert_eq!(
+        context
+            .game(game_id)
+            .unwrap()
+            .state
+            .last_update_epoch_seconds,
+        context.bank.now_epoch_seconds()
+    );
+}
+
+#[test]
+fn settle_player_builder_all_good() {
+    let mut context = TestContext::new();
+    let config = ending_game_config(&context, context.mint);
+    let (creator, player, game_id) = context.two_player_game_with(config);
+    context.bank.warp_seconds(100);
+    let settler = context.new_wallet();
+
+    let ix = SettlePlayerBuilder::new(
+        settler,
+        game_id,
+        context.mint,
+        creator.wallet,
+        player.wallet,
+    )
+    .instruction();
+    context.bank.process(&ix, &[settler]).unwrap();
+
+    assert!(context.player_state(game_id, &player).is_none());
+}
+
+#[test]
+fn new_lamport_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+
+    let ix = NewLamportGameBuilder::new(
+        creator.wallet,
+        default_game_config(Pubkey::default()),
+        game_id,
+        context.program_fee_destination,
+    )
+    .instruction();
+    context.bank.process(&ix, &[creator.wallet]).unwrap();
+
+    assert!(context.lamport_vault(game_id).is_some());
+}
+
+#[test]
+fn enter_lamport_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_lamport_game(&creator);
+    let player = context.new_player();
+
+    let ix = EnterLamportGameBuilder::new(
+        player.wallet,
+        game_id,
+        creator.wallet,
+        2,
+        ENTRY_FEE_DECIMAL_TOKENS,
+        context.program_fee_destination,
+    )
+    .instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert_eq!(context.player_state(game_id, &player).unwrap().bucket, 2);
+}
+
+#[test]
+fn leave_lamport_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) =
+        context.two_player_lamport_game_with(default_game_config(Pubkey::default()));
+
+    let ix = LeaveLamportGameBuilder::new(player.wallet, game_id, creator.wallet).instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert!(context.player_state(game_id, &player).is_none());
+}
+
+#[test]
+fn settle_lamport_player_builder_all_good() {
+    let mut context = TestContext::new();
+    let config = ending_game_config(&context, Pubkey::default());
+    let (creator, player, game_id) = context.two_player_lamport_game_with(config);
+    context.bank.warp_seconds(100);
+    let settler = context.new_wallet();
+
+    let ix = SettleLamportPlayerBuilder::new(settler, game_id, creator.wallet, player.wallet)
+        .instruction();
+    context.bank.process(&ix, &[settler]).unwrap();
+
+    assert!(context.player_state(game_id, &player).is_none());
+}
+
+#[test]
+fn create_session_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let session_key = context.new_wallet();
+
+    let ix = CreateSessionBuilder::new(
+        player.wallet,
+        game_id,
+        session_key,
+        context.bank.now_epoch_seconds() + SESSION_SECONDS,
+    )
+    .can_leave(true)
+    .instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert_eq!(
+        context.session(game_id, &player).unwrap().session_key,
+        session_key
+    );
+}
+
+#[test]
+fn session_move_buckets_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let session_key = context.new_wallet();
+    context
+        .create_session(&player, game_id, session_key, SESSION_SECONDS, false)
+        .unwrap();
+
+    let ix = SessionMoveBucketsBuilder::new(session_key, player.wallet, game_id, 3).instruction();
+    context.bank.process(&ix, &[session_key]).unwrap();
+
+    assert_eq!(context.player_state(game_id, &player).unwrap().bucket, 3);
+}
+
+#[test]
+fn session_leave_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    let session_key = context.new_wallet();
+    context
+        .create_session(&player, game_id, session_key, SESSION_SECONDS, true)
+        .unwrap();
+
+    let ix = SessionLeaveGameBuilder::new(
+        session_key,
+        player.wallet,
+        game_id,
+        context.mint,
+        creator.wallet,
+    )
+    .instruction();
+    context.bank.process(&ix, &[session_key]).unwrap();
+
+    assert!(context.player_state(game_id, &player).is_none());
+    assert!(context.session(game_id, &player).is_none());
+}
+
+#[test]
+fn revoke_session_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let session_key = context.new_wallet();
+    context
+        .create_session(&player, game_id, session_key, SESSION_SECONDS, false)
+        .unwrap();
+
+    let ix = RevokeSessionBuilder::new(player.wallet, game_id).instruction();
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert!(context.session(game_id, &player).is_none());
+}
+
+#[test]
+fn migrate_game_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    let game = context.game(game_id).unwrap();
+    set_old_account::<Game>(
+        &mut context,
+        game_address(game_id),
+        &GameV1 {
+            version: 1,
+            id: game.id,
+            creator: game.creator,
+            config: GameConfigV1 {
+                mint: game.config.mint,
+                entry_fee_decimal_tokens: game.config.entry_fee_decimal_tokens,
+                spill_rate_decimal_tokens_per_second_per_player: game
+                    .config
+                    .spill_rate_decimal_tokens_per_second_per_player,
+                n_buckets: game.config.n_buckets,
+                max_players: game.config.max_players,
+                burn_rate_decimal_tokens_per_move: game.config.burn_rate_decimal_tokens_per_move,
+            },
+            state: GameStateV1 {
+                buckets: game
+                    .state
+                    .buckets
+                    .iter()
+                    .map(|bucket| BucketV1 {
+                        decimal_tokens: bucket.decimal_tokens,
+                        players: bucket.players,
+                    })
+                    .collect(),
+                last_update_epoch_seconds: game.state.last_update_epoch_seconds,
+            },
+        },
+    );
+    let payer = context.new_wallet();
+
+    // one instruction per version the game is behind
+    let ix = MigrateGameBuilder::new(payer, game_id).instruction();
+    for _ in 1..GAME_VERSION {
+        context.bank.process(&ix, &[payer]).unwrap();
+    }
+
+    assert_eq!(context.game(game_id).unwrap().version, GAME_VERSION);
+}
+
+#[test]
+fn migrate_player_builder_all_good() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let state = context.player_state(game_id, &player).unwrap();
+    set_old_account::<PlayerState>(
+        &mut context,
+        player_state_address(&game_address(game_id), &player.wallet),
+        &PlayerStateV1 {
+            version: 1,
+            bucket: state.bucket,
+            burn_penalty_decimal_tokens: state.burn_penalty_decimal_tokens,
+        },
+    );
+    let payer = context.new_wallet();
+
+    let ix = MigratePlayerBuilder::new(payer, game_id, player.wallet).instruction();
+    for _ in 1..PLAYER_STATE_VERSION {
+        context.bank.process(&ix, &[payer]).unwrap();
+    }
+
+    assert_eq!(
+        context.player_state(game_id, &player).unwrap().version,
+        PLAYER_STATE_VERSION
+    );
+}
+
+#[test]
+fn migrate_program_config_builder_all_good() {
+    let mut context = TestContext::new();
+    let program_config = context.program_config().unwrap();
+    let settings = &program_config.settings;
+    set_old_account::<ProgramConfig>(
+        &mut context,
+        program_config_address(),
+        &ProgramConfigV1 {
+            version: 1,
+            admin: program_config.admin,
+            settings: ProgramSettingsV1 {
+                program_fee_destination: settings.program_fee_destination,
+                program_fee_lamports: settings.program_fee_lamports,
+                game_max_buckets: settings.game_max_buckets,
+                game_max_players: settings.game_max_players,
+            },
+        },
+    );
+    let payer = context.new_wallet();
+
+    let ix = MigrateProgramConfigBuilder::new(payer).instruction();
+    for _ in 1..PROGRAM_CONFIG_VERSION {
+        context.bank.process(&ix, &[payer]).unwrap();
+    }
+
+    assert_eq!(
+        context.program_config().unwrap().version,
+        PROGRAM_CONFIG_VERSION
+    );
+}
+
+#[test]
+fn migrate_pool_manager_builder_all_good() {
+    let mut context = TestContext::new();
+    let mint = context.mint;
+    set_old_account::<PoolManager>(
+        &mut context,
+        pool_manager_address(&mint),
+        &PoolManagerV1 {
+            bump: PoolManager::get_address(mint).1,
+        },
+    );
+    let payer = context.new_wallet();
+
+    let ix = MigratePoolManagerBuilder::new(payer, mint).instruction();
+    context.bank.process(&ix, &[payer]).unwrap();
+
+    assert_eq!(
+        context
+            .pool_manager(&mint)
+            .unwrap()
+            .liabilities_decimal_tokens,
+        0
+    );
+}
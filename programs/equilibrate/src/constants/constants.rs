@@ -1,2 +1,7 @@
 pub const NATIVE_MINT: &str = "So11111111111111111111111111111111111111112";
-pub const ACCOUNTS_VERSION: u8 = 1;
+// layout version of each account type, bumped only when that type's layout changes
+pub const GAME_VERSION: u8 = 2;
+pub const PLAYER_STATE_VERSION: u8 = 2;
+pub const PROGRAM_CONFIG_VERSION: u8 = 2;
+pub const SESSION_VERSION: u8 = 1;
+pub const REFERRAL_STATS_VERSION: u8 = 1;
@@ -1,9 +1,8 @@
+// hard ceilings on the limits the program config admin can set
 pub const GAME_MAX_BUCKETS: u8 = 64;
 pub const GAME_MAX_PLAYERS: u16 = 10000;
-pub const PROGRAM_FEE_LAMPORTS: u64 = 25000000;
-// PubKey("EQui1fSdC2HetpETDAoaLaPYvYR7xVuXmLyUiiEvfA2h").as_ref()
-// or (in ts) new PublicKey("EQui1fSdC2HetpETDAoaLaPYvYR7xVuXmLyUiiEvfA2h").toBuffer()
-pub const PROGRAM_FEE_DESTINATION: &[u8] = &[
-    199, 71, 226, 127, 27, 142, 50, 250, 123, 57, 93, 205, 218, 14, 3, 202, 40, 26, 200, 127, 61,
-    237, 60, 136, 154, 161, 13, 86, 32, 199, 43, 198,
-];
+pub const GAME_MAX_CREATOR_FEE_BPS: u16 = 5000;
+// most steps a piecewise spill curve can have, which sizes every game account
+pub const SPILL_CURVE_MAX_STEPS: u8 = 8;
+// longest a session key can act for a player before it has to be renewed
+pub const SESSION_MAX_SECONDS: i64 = 7 * 24 * 60 * 60;
@@ -2,3 +2,7 @@ pub const GAME_SEED: [u8; 16] = *b"equilibrate-game";
 pub const PLAYER_SEED: [u8; 18] = *b"equilibrate-player";
 pub const POOL_SEED: [u8; 16] = *b"equilibrate-pool";
 pub const POOL_MANAGER_SEED: [u8; 24] = *b"equilibrate-pool-manager";
+pub const PROGRAM_CONFIG_SEED: [u8; 26] = *b"equilibrate-program-config";
+pub const REFERRAL_SEED: [u8; 20] = *b"equilibrate-referral";
+pub const SESSION_SEED: [u8; 19] = *b"equilibrate-session";
+pub const LAMPORT_VAULT_SEED: [u8; 25] = *b"equilibrate-lamport-vault";
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION},
+    id,
+    model::{DepositAdded, EquilibrateError},
+    state::{game::Game, token_interface, PlayerState, PoolManager},
+};
+
+#[derive(Accounts)]
+pub struct AddDeposit<'info> {
+    /// game account of the game being played
+    #[account(
+        mut,
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub game: Account<'info, Game>,
+
+    /// player state account of the player topping up
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), payer.key().as_ref()],
+        bump,
+        owner = id(),
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub player: Account<'info, PlayerState>,
+
+    /// CHECK: mint of this game, owned by `token_program`
+    #[account(
+        constraint = game_mint.key() == game.config.mint
+        @EquilibrateError::InvalidGameMint,
+        constraint = *game_mint.owner == token_program.key()
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub game_mint: AccountInfo<'info>,
+
+    /// CHECK: player's token acount from which the deposit is taken,
+    /// checked in the handler
+    #[account(mut)]
+    pub deposit_source_account: AccountInfo<'info>,
+
+    /// token pool manager, which records what the pool owes the game
+    #[account(mut)]
+    pub pool_manager: Account<'info, PoolManager>,
+
+    /// CHECK: token pool of the mint/game, checked in the handler
+    #[account(mut)]
+    pub token_pool: AccountInfo<'info>,
+
+    /// the player adding the deposit
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: SPL token or Token-2022 program, whichever owns the game mint,
+    /// for transferring the deposit
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
+}
+
+pub fn add_deposit(ctx: Context<AddDeposit>, deposit_decimal_tokens: u64) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    // check constraints
+    require_gt!(deposit_decimal_tokens, 0, EquilibrateError::EmptyTopUp);
+
+    let game = &ctx.accounts.game;
+    require_gt!(game.get_player_count(), 0, EquilibrateError::GameIsOver);
+    require!(
+        !game.is_past_end(now_epoch_seconds),
+        EquilibrateError::GameHasEnded
+    );
+
+    let mint = token_interface::unpack_game_mint(&ctx.accounts.game_mint)?;
+    let token_program = &ctx.accounts.token_program;
+    let deposit_source = token_interface::unpack_token_account(
+        &ctx.accounts.deposit_source_account,
+        token_program.key,
+    )?;
+    require_keys_eq!(
+        deposit_source.mint,
+        game.config.mint,
+        EquilibrateError::InvalidTokenSourceMint
+    );
+
+    PoolManager::validate_token_pool(
+        &ctx.accounts.token_pool,
+        token_program.key,
+        ctx.accounts.pool_manager.key(),
+        game.config.mint,
+    )?;
+
+    // transfer the deposit to the pool account
+    let received_decimal_tokens = token_interface::deposit(
+        token_program,
+        &ctx.accounts.deposit_source_account,
+        &ctx.accounts.game_mint,
+        &ctx.accounts.token_pool,
+        &ctx.accounts.payer.to_account_info(),
+        deposit_decimal_tokens,
+        mint.decimals,
+    )?;
+
+    // update bucket balances and credit only what the pool received after
+    // any transfer fee
+    ctx.accounts
+        .pool_manager
+        .add_liabilities(received_decimal_tokens);
+    let player = &mut ctx.accounts.player;
+    let game = &mut ctx.accounts.game;
+    game.top_up(
+        player.bucket as usize,
+        received_decimal_tokens,
+        now_epoch_seconds,
+    );
+
+    player.added_decimal_tokens = player
+        .added_decimal_tokens
+        .checked_add(received_decimal_tokens)
+        .unwrap();
+    player.log_top_up(received_decimal_tokens);
+
+    emit!(DepositAdded {
+        game_id: game.id,
+        player: ctx.accounts.payer.key(),
+        bucket: player.bucket,
+        deposit_decimal_tokens: received_decimal_tokens,
+        added_decimal_tokens: player.added_decimal_tokens,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
+
+    Ok(())
+}
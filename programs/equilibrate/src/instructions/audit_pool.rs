@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    model::{EquilibrateError, PoolAudited},
+    state::{token_interface, PoolManager},
+};
+
+#[derive(Accounts)]
+pub struct AuditPool<'info> {
+    /// token pool manager, which records what the pool owes its games
+    pub pool_manager: Account<'info, PoolManager>,
+
+    /// CHECK: token pool of the mint, checked in the handler
+    pub token_pool: AccountInfo<'info>,
+
+    /// CHECK: mint of the token pool, checked against it in the handler
+    pub game_mint: AccountInfo<'info>,
+
+    /// CHECK: SPL token or Token-2022 program, whichever owns the token pool
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
+}
+
+pub fn audit_pool(ctx: Context<AuditPool>) -> Result<()> {
+    let token_program = &ctx.accounts.token_program;
+    PoolManager::validate_token_pool(
+        &ctx.accounts.token_pool,
+        token_program.key,
+        ctx.accounts.pool_manager.key(),
+        ctx.accounts.game_mint.key(),
+    )?;
+
+    let pool_decimal_tokens =
+        token_interface::unpack_token_account(&ctx.accounts.token_pool, token_program.key)?.amount;
+    let pool_manager = &ctx.accounts.pool_manager;
+    pool_manager.log_audit(pool_decimal_tokens);
+    require_gte!(
+        pool_decimal_tokens,
+        pool_manager.liabilities_decimal_tokens,
+        EquilibrateError::PoolUnderfunded
+    );
+
+    emit!(PoolAudited {
+        mint: ctx.accounts.game_mint.key(),
+        pool_decimal_tokens,
+        liabilities_decimal_tokens: pool_manager.liabilities_decimal_tokens,
+        surplus_decimal_tokens: pool_decimal_tokens - pool_manager.liabilities_decimal_tokens,
+    });
+
+    Ok(())
+}
@@ -1,11 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
 
 use crate::{
     constants::{POOL_MANAGER_SEED, POOL_SEED},
     id,
     model::EquilibrateError,
-    state::PoolManager,
+    state::{token_interface, PoolManager},
 };
 
 #[derive(Accounts)]
@@ -25,30 +24,37 @@ pub struct CreatePool<'info> {
     )]
     pub pool_manager: Account<'info, PoolManager>,
 
-    /// token pool for all games played with `game_mint`
+    /// CHECK: token pool for all games played with `game_mint`; created in the
+    /// handler, since its size depends on the mint's extensions
     #[account(
-        init,
+        mut,
         seeds = [
             POOL_SEED.as_ref(),
             game_mint.key().as_ref(),
             pool_manager.key().as_ref(),
         ],
         bump,
-        payer = payer,
-        token::mint = game_mint,
-        token::authority = pool_manager,
     )]
-    pub token_pool: Account<'info, TokenAccount>,
+    pub token_pool: AccountInfo<'info>,
 
     /// payer to create the token pool and pool manager accounts
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    /// mint of the token pool for all games of this mint
-    pub game_mint: Account<'info, Mint>,
+    /// CHECK: mint of the token pool for all games of this mint; its
+    /// extensions are checked in the handler
+    #[account(
+        constraint = *game_mint.owner == token_program.key()
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub game_mint: AccountInfo<'info>,
 
-    /// standard SPL token program, for creating the token pool
-    pub token_program: Program<'info, Token>,
+    /// CHECK: SPL token or Token-2022 program, whichever owns `game_mint`
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
 
     /// standard rent sysvar, for determining rent for created accounts
     pub rent: Sysvar<'info, Rent>,
@@ -58,7 +64,28 @@ pub struct CreatePool<'info> {
 }
 
 pub fn create_pool(ctx: Context<CreatePool>, pool_manager_bump: u8) -> Result<()> {
+    token_interface::unpack_game_mint(&ctx.accounts.game_mint)?;
+
     ctx.accounts.pool_manager.bump = pool_manager_bump;
 
+    let mint = ctx.accounts.game_mint.key();
+    let pool_manager = ctx.accounts.pool_manager.key();
+    let pool_bump = *ctx.bumps.get("token_pool").unwrap();
+    let pool_seeds = &[
+        POOL_SEED.as_ref(),
+        mint.as_ref(),
+        pool_manager.as_ref(),
+        &[pool_bump],
+    ];
+    token_interface::create_token_account(
+        &ctx.accounts.token_pool,
+        pool_seeds,
+        &ctx.accounts.game_mint,
+        &pool_manager,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
     Ok(())
 }
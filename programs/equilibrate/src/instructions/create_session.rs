@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{
+        GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION, SESSION_MAX_SECONDS,
+        SESSION_SEED, SESSION_VERSION,
+    },
+    id,
+    model::{EquilibrateError, SessionCreated},
+    state::{game::Game, PlayerState, SessionAuthority},
+};
+
+#[derive(Accounts)]
+pub struct CreateSession<'info> {
+    /// game account of the game the session is for
+    #[account(
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub game: Account<'info, Game>,
+
+    /// player state account of the player, who must be in the game; it
+    /// records that they made a session, so leaving closes it
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), payer.key().as_ref()],
+        bump,
+        owner = id(),
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub player: Account<'info, PlayerState>,
+
+    /// session of the player in this game; creating it again replaces
+    /// the session key and expiry
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = SessionAuthority::get_space(),
+        seeds = [SESSION_SEED.as_ref(), &game.id.to_le_bytes(), payer.key().as_ref()],
+        bump,
+    )]
+    pub session: Account<'info, SessionAuthority>,
+
+    /// the player, who pays the session account's rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard system program, for creating the session account
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_session(
+    ctx: Context<CreateSession>,
+    session_key: Pubkey,
+    expires_epoch_seconds: i64,
+    can_leave: bool,
+) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    // check constraints
+    require!(
+        expires_epoch_seconds > now_epoch_seconds
+            && expires_epoch_seconds <= now_epoch_seconds + SESSION_MAX_SECONDS,
+        EquilibrateError::InvalidSessionExpiry
+    );
+
+    let game_id = ctx.accounts.game.id;
+    let session = &mut ctx.accounts.session;
+    session.set_inner(SessionAuthority {
+        version: SESSION_VERSION,
+        owner: ctx.accounts.payer.key(),
+        game_id,
+        session_key,
+        expires_epoch_seconds,
+        can_leave,
+    });
+    session.log_create();
+    ctx.accounts.player.has_session = true;
+
+    emit!(SessionCreated {
+        game_id,
+        player: ctx.accounts.payer.key(),
+        session_key,
+        expires_epoch_seconds,
+        can_leave,
+    });
+
+    Ok(())
+}
@@ -1,23 +1,24 @@
 use anchor_lang::{prelude::*, system_program};
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 use crate::{
-    constants::{
-        ACCOUNTS_VERSION, GAME_SEED, PLAYER_SEED, PROGRAM_FEE_DESTINATION, PROGRAM_FEE_LAMPORTS,
-    },
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION, PROGRAM_CONFIG_SEED},
     id,
-    model::EquilibrateError,
-    state::{game::Game, PlayerState, PoolManager},
+    model::{EquilibrateError, PlayerEntered, PlayerReferred},
+    state::{
+        allowlist, game::Game, token_interface, PlayerState, PoolManager, ProgramConfig,
+        ProgramSettings, ReferralStats,
+    },
 };
 
 #[derive(Accounts)]
-#[instruction(pool_manager: Pubkey)]
 pub struct EnterGame<'info> {
     /// game account of the game being played
     #[account(
         mut,
         seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
-        bump
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
     )]
     pub game: Account<'info, Game>,
 
@@ -32,34 +33,59 @@ pub struct EnterGame<'info> {
     )]
     pub player: Account<'info, PlayerState>,
 
+    /// program-wide settings, including where the program fee goes
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
     /// CHECK: wallet where the program fee should be deposited
     #[account(
         mut,
-        constraint = program_fee_destination.key().as_ref() == PROGRAM_FEE_DESTINATION
+        constraint = program_fee_destination.key() == program_config.settings.program_fee_destination
         @EquilibrateError::InvalidProgramFeeDestination
     )]
     pub program_fee_destination: AccountInfo<'info>,
 
-    /// player's token acount from which their entry deposit is taken
+    /// CHECK: mint of this game, owned by `token_program`
     #[account(
-        mut,
-        token::mint = game.config.mint
+        constraint = game_mint.key() == game.config.mint
+        @EquilibrateError::InvalidGameMint,
+        constraint = *game_mint.owner == token_program.key()
+        @EquilibrateError::InvalidTokenProgram
     )]
-    pub deposit_source_account: Account<'info, TokenAccount>,
+    pub game_mint: AccountInfo<'info>,
 
-    /// token pool of the mint/game
-    #[account(
-        mut,
-        token::mint = game.config.mint,
-    )]
-    pub token_pool: Account<'info, TokenAccount>,
+    /// CHECK: player's token acount from which their entry deposit is taken,
+    /// checked in the handler
+    #[account(mut)]
+    pub deposit_source_account: AccountInfo<'info>,
+
+    /// CHECK: game creator's token account that the creator fee is paid into,
+    /// checked in the handler and only used if the game charges a creator fee
+    #[account(mut)]
+    pub creator_fee_destination: AccountInfo<'info>,
+
+    /// token pool manager, which records what the pool owes the game
+    #[account(mut)]
+    pub pool_manager: Account<'info, PoolManager>,
+
+    /// CHECK: token pool of the mint/game, checked in the handler
+    #[account(mut)]
+    pub token_pool: AccountInfo<'info>,
 
     /// payer for creating player state, must be the player
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    /// standard SPL token program, for transferring token deposit
-    pub token_program: Program<'info, Token>,
+    /// CHECK: SPL token or Token-2022 program, whichever owns the game mint,
+    /// for transferring token deposit
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
 
     /// standard rent sysvar, for determining rent for created accounts
     pub rent: Sysvar<'info, Rent>,
@@ -68,12 +94,146 @@ pub struct EnterGame<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn enter_game(ctx: Context<EnterGame>, i_bucket: u8, pool_manager: Pubkey) -> Result<()> {
+/// A player who was referred also passes, as remaining accounts, the
+/// referrer's wallet and their `ReferralStats` PDA, both writable
+pub fn enter_game<'info>(
+    ctx: Context<'_, '_, '_, 'info, EnterGame<'info>>,
+    i_bucket: u8,
+    stake_decimal_tokens: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
     let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
 
     let config = &ctx.accounts.game.config.clone();
 
     // check constraints
+    check_entry(
+        &ctx.accounts.game,
+        i_bucket,
+        stake_decimal_tokens,
+        &allowlist_proof,
+        ctx.accounts.payer.key,
+        now_epoch_seconds,
+    )?;
+
+    let mint = token_interface::unpack_game_mint(&ctx.accounts.game_mint)?;
+    let token_program = &ctx.accounts.token_program;
+    let deposit_source = token_interface::unpack_token_account(
+        &ctx.accounts.deposit_source_account,
+        token_program.key,
+    )?;
+    require_keys_eq!(
+        deposit_source.mint,
+        config.mint,
+        EquilibrateError::InvalidTokenSourceMint
+    );
+
+    PoolManager::validate_token_pool(
+        &ctx.accounts.token_pool,
+        token_program.key,
+        ctx.accounts.pool_manager.key(),
+        config.mint,
+    )?;
+
+    take_program_fee(
+        ctx.accounts.game.id,
+        ctx.remaining_accounts,
+        &ctx.accounts.program_config.settings,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.program_fee_destination,
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // pay the creator their share of the entry deposit
+    let creator_fee_decimal_tokens = config.creator_fee_decimal_tokens(stake_decimal_tokens);
+    if creator_fee_decimal_tokens > 0 {
+        let creator_fee_destination = token_interface::unpack_token_account(
+            &ctx.accounts.creator_fee_destination,
+            token_program.key,
+        )?;
+        require!(
+            creator_fee_destination.mint == config.mint
+                && creator_fee_destination.owner == ctx.accounts.game.creator,
+            EquilibrateError::InvalidCreatorFeeDestination
+        );
+        token_interface::transfer(
+            token_program,
+            &ctx.accounts.deposit_source_account,
+            &ctx.accounts.game_mint,
+            &ctx.accounts.creator_fee_destination,
+            &ctx.accounts.payer.to_account_info(),
+            creator_fee_decimal_tokens,
+            mint.decimals,
+            &[],
+        )?;
+    }
+
+    // transfer the rest of the game tokens to pool account
+    let received_decimal_tokens = token_interface::deposit(
+        token_program,
+        &ctx.accounts.deposit_source_account,
+        &ctx.accounts.game_mint,
+        &ctx.accounts.token_pool,
+        &ctx.accounts.payer.to_account_info(),
+        stake_decimal_tokens - creator_fee_decimal_tokens,
+        mint.decimals,
+    )?;
+    ctx.accounts
+        .pool_manager
+        .add_liabilities(received_decimal_tokens);
+
+    // update bucket balances and insert player into desired bucket, crediting
+    // only what the pool received after any transfer fee
+    let game = &mut ctx.accounts.game;
+    game.creator_fees_decimal_tokens = game
+        .creator_fees_decimal_tokens
+        .checked_add(creator_fee_decimal_tokens)
+        .unwrap();
+    game.add_player(
+        i_bucket as usize,
+        stake_decimal_tokens,
+        received_decimal_tokens,
+        now_epoch_seconds,
+    );
+
+    // create player state account
+    let player = &mut ctx.accounts.player;
+    player.set_inner(PlayerState {
+        version: PLAYER_STATE_VERSION,
+        bucket: i_bucket,
+        burn_penalty_decimal_tokens: 0,
+        stake_decimal_tokens,
+        added_decimal_tokens: 0,
+        last_move_epoch_seconds: 0,
+        moves: 0,
+        has_session: false,
+    });
+    player.log_make();
+
+    emit!(PlayerEntered {
+        game_id: game.id,
+        player: ctx.accounts.payer.key(),
+        bucket: i_bucket,
+        stake_decimal_tokens,
+        creator_fee_decimal_tokens,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
+
+    Ok(())
+}
+
+/// Checks that the player of `payer` can enter `game` in bucket `i_bucket` with
+/// a stake of `stake_decimal_tokens`
+pub(crate) fn check_entry(
+    game: &Game,
+    i_bucket: u8,
+    stake_decimal_tokens: u64,
+    allowlist_proof: &[[u8; 32]],
+    payer: &Pubkey,
+    now_epoch_seconds: i64,
+) -> Result<()> {
+    let config = &game.config;
     require_gt!(
         // there is one more bucket than the creator configures: the holding bucket
         (config.n_buckets + 1),
@@ -83,14 +243,28 @@ pub fn enter_game(ctx: Context<EnterGame>, i_bucket: u8, pool_manager: Pubkey) -
 
     require_gt!(i_bucket, 0u8, EquilibrateError::CannotEnterHoldingBucket);
 
-    PoolManager::validate_token_pool(&ctx.accounts.token_pool, pool_manager, config.mint)?;
+    require!(
+        config.is_valid_stake(stake_decimal_tokens),
+        EquilibrateError::InvalidStake
+    );
+
+    if let Some(allowlist_root) = config.allowlist_root {
+        require!(
+            allowlist::verify(&allowlist_root, allowlist_proof, payer),
+            EquilibrateError::NotAllowlisted
+        );
+    }
 
     // This is untestable since the last person leaving the game
     // also results in the game account being deleted. However, we'll
     // leave it in for completeness.
-    let game_player_count = ctx.accounts.game.get_player_count();
+    let game_player_count = game.get_player_count();
 
     require_gt!(game_player_count, 0, EquilibrateError::GameIsOver);
+    require!(
+        !game.is_past_end(now_epoch_seconds),
+        EquilibrateError::GameHasEnded
+    );
 
     require_gt!(
         config.max_players,
@@ -98,48 +272,69 @@ pub fn enter_game(ctx: Context<EnterGame>, i_bucket: u8, pool_manager: Pubkey) -
         EquilibrateError::GameAtCapacity
     );
 
-    // take program fee
+    Ok(())
+}
+
+/// Takes the program fee from `payer`, paying the referrer's share of it to
+/// them if the player was referred
+pub(crate) fn take_program_fee<'info>(
+    game_id: u64,
+    remaining_accounts: &[AccountInfo<'info>],
+    settings: &ProgramSettings,
+    payer: &AccountInfo<'info>,
+    program_fee_destination: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let referral = match remaining_accounts {
+        [] => None,
+        [referrer, referral_stats, ..] => {
+            require_keys_neq!(referrer.key(), payer.key(), EquilibrateError::SelfReferral);
+            Some((referrer, referral_stats, settings.referral_fee_lamports()))
+        }
+        _ => return err!(EquilibrateError::MissingReferralAccounts),
+    };
+    let referral_fee_lamports = referral.map_or(0, |(_, _, lamports)| lamports);
+
     let program_fee_transfer_context = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
+        system_program.clone(),
         system_program::Transfer {
-            from: ctx.accounts.payer.to_account_info(),
-            to: ctx.accounts.program_fee_destination.clone(),
+            from: payer.clone(),
+            to: program_fee_destination.clone(),
         },
     );
-    system_program::transfer(program_fee_transfer_context, PROGRAM_FEE_LAMPORTS)?;
+    system_program::transfer(
+        program_fee_transfer_context,
+        settings.program_fee_lamports - referral_fee_lamports,
+    )?;
 
-    // transfer game tokens to pool account
-    let pool_transfer_accounts = Transfer {
-        from: ctx.accounts.deposit_source_account.to_account_info(),
-        to: ctx.accounts.token_pool.to_account_info(),
-        authority: ctx.accounts.payer.to_account_info(),
-    };
-    let token_program = ctx.accounts.token_program.to_account_info();
-    let pool_transfer_context = CpiContext::new(token_program, pool_transfer_accounts);
-    token::transfer(pool_transfer_context, config.entry_fee_decimal_tokens)?;
-
-    // update bucket balances and insert player into desired bucket
-    let game = &mut ctx.accounts.game;
-    game.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
-    game.state.buckets[0].players = game.state.buckets[0].players.checked_add(1).unwrap();
-    game.state.buckets[0].decimal_tokens = game.state.buckets[0]
-        .decimal_tokens
-        .checked_add(config.entry_fee_decimal_tokens)
-        .unwrap();
-    game.state.buckets[i_bucket as usize].players = game.state.buckets[i_bucket as usize]
-        .players
-        .checked_add(1)
-        .unwrap();
-    game.state.last_update_epoch_seconds = now_epoch_seconds;
+    if let Some((referrer, referral_stats, referral_fee_lamports)) = referral {
+        if referral_fee_lamports > 0 {
+            let referral_fee_transfer_context = CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: referrer.clone(),
+                },
+            );
+            system_program::transfer(referral_fee_transfer_context, referral_fee_lamports)?;
+        }
 
-    // create player state account
-    let player = &mut ctx.accounts.player;
-    player.set_inner(PlayerState {
-        version: ACCOUNTS_VERSION,
-        bucket: i_bucket,
-        burn_penalty_decimal_tokens: 0,
-    });
-    player.log_make();
+        let stats = ReferralStats::record(
+            referral_stats,
+            referrer.key,
+            settings.program_fee_lamports,
+            referral_fee_lamports,
+            payer,
+            system_program,
+        )?;
+        emit!(PlayerReferred {
+            game_id,
+            player: payer.key(),
+            referrer: referrer.key(),
+            reward_lamports: referral_fee_lamports,
+            referrals: stats.referrals,
+        });
+    }
 
     Ok(())
 }
@@ -0,0 +1,172 @@
+use anchor_lang::{prelude::*, system_program};
+
+use crate::{
+    constants::{
+        GAME_SEED, GAME_VERSION, LAMPORT_VAULT_SEED, PLAYER_SEED, PLAYER_STATE_VERSION,
+        PROGRAM_CONFIG_SEED,
+    },
+    id,
+    instructions::{check_entry, take_program_fee},
+    model::{EquilibrateError, PlayerEntered},
+    state::{game::Game, LamportVault, PlayerState, ProgramConfig},
+};
+
+#[derive(Accounts)]
+pub struct EnterLamportGame<'info> {
+    /// game account of the game being played
+    #[account(
+        mut,
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion,
+        constraint = game.config.is_lamport_game()
+        @EquilibrateError::InvalidGameMint
+    )]
+    pub game: Account<'info, Game>,
+
+    /// player state account of the new player
+    #[account(
+        init,
+        payer = payer,
+        space = PlayerState::get_space(),
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), payer.key().as_ref()],
+        bump,
+        owner = id(),
+    )]
+    pub player: Account<'info, PlayerState>,
+
+    /// program-wide settings, including where the program fee goes
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// CHECK: wallet where the program fee should be deposited
+    #[account(
+        mut,
+        constraint = program_fee_destination.key() == program_config.settings.program_fee_destination
+        @EquilibrateError::InvalidProgramFeeDestination
+    )]
+    pub program_fee_destination: AccountInfo<'info>,
+
+    /// vault holding the game's deposits
+    #[account(
+        mut,
+        seeds = [LAMPORT_VAULT_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+    )]
+    pub lamport_vault: Account<'info, LamportVault>,
+
+    /// CHECK: wallet of the game creator, which the creator fee is paid to
+    #[account(
+        mut,
+        constraint = game_creator.key() == game.creator
+        @EquilibrateError::GameCreatorMismatch
+    )]
+    pub game_creator: AccountInfo<'info>,
+
+    /// payer for creating player state, must be the player
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard rent sysvar, for determining rent for created accounts
+    pub rent: Sysvar<'info, Rent>,
+
+    /// standard system program, for creating accounts and transferring lamports
+    pub system_program: Program<'info, System>,
+}
+
+/// Like `enter_game`, for a game played for lamports. A player who was
+/// referred passes the same remaining accounts.
+pub fn enter_lamport_game<'info>(
+    ctx: Context<'_, '_, '_, 'info, EnterLamportGame<'info>>,
+    i_bucket: u8,
+    stake_decimal_tokens: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    let config = &ctx.accounts.game.config.clone();
+
+    // check constraints
+    check_entry(
+        &ctx.accounts.game,
+        i_bucket,
+        stake_decimal_tokens,
+        &allowlist_proof,
+        ctx.accounts.payer.key,
+        now_epoch_seconds,
+    )?;
+
+    take_program_fee(
+        ctx.accounts.game.id,
+        ctx.remaining_accounts,
+        &ctx.accounts.program_config.settings,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.program_fee_destination,
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // pay the creator their share of the entry deposit
+    let creator_fee_decimal_tokens = config.creator_fee_decimal_tokens(stake_decimal_tokens);
+    if creator_fee_decimal_tokens > 0 {
+        let creator_fee_transfer_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.game_creator.clone(),
+            },
+        );
+        system_program::transfer(creator_fee_transfer_context, creator_fee_decimal_tokens)?;
+    }
+
+    // transfer the rest of the lamports to the vault
+    let received_decimal_tokens = stake_decimal_tokens - creator_fee_decimal_tokens;
+    LamportVault::deposit(
+        &ctx.accounts.lamport_vault.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        received_decimal_tokens,
+    )?;
+
+    // update bucket balances and insert player into desired bucket
+    let game = &mut ctx.accounts.game;
+    game.creator_fees_decimal_tokens = game
+        .creator_fees_decimal_tokens
+        .checked_add(creator_fee_decimal_tokens)
+        .unwrap();
+    game.add_player(
+        i_bucket as usize,
+        stake_decimal_tokens,
+        received_decimal_tokens,
+        now_epoch_seconds,
+    );
+
+    // create player state account
+    let player = &mut ctx.accounts.player;
+    player.set_inner(PlayerState {
+        version: PLAYER_STATE_VERSION,
+        bucket: i_bucket,
+        burn_penalty_decimal_tokens: 0,
+        stake_decimal_tokens,
+        added_decimal_tokens: 0,
+        last_move_epoch_seconds: 0,
+        moves: 0,
+        has_session: false,
+    });
+    player.log_make();
+
+    emit!(PlayerEntered {
+        game_id: game.id,
+        player: ctx.accounts.payer.key(),
+        bucket: i_bucket,
+        stake_decimal_tokens,
+        creator_fee_decimal_tokens,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{PROGRAM_CONFIG_SEED, PROGRAM_CONFIG_VERSION},
+    model::EquilibrateError,
+    program::Equilibrate,
+    state::{ProgramConfig, ProgramSettings},
+};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// singleton account holding program-wide settings
+    #[account(
+        init,
+        seeds = [PROGRAM_CONFIG_SEED.as_ref()],
+        bump,
+        space = ProgramConfig::get_space(),
+        payer = payer,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// this program, for finding its program data account
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+        @EquilibrateError::InvalidProgramData
+    )]
+    pub program: Program<'info, Equilibrate>,
+
+    /// program data account of this program, for checking the upgrade authority
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+        @EquilibrateError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// payer to create the program config, must be the program's upgrade authority
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard system program, for creating accounts
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    admin: Pubkey,
+    settings: ProgramSettings,
+) -> Result<()> {
+    settings.validate()?;
+
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.set_inner(ProgramConfig {
+        version: PROGRAM_CONFIG_VERSION,
+        admin,
+        settings,
+    });
+    program_config.log_make();
+    program_config.log_update();
+
+    Ok(())
+}
@@ -1,11 +1,10 @@
 use anchor_lang::{prelude::*, AccountsClose};
-use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
 
 use crate::{
-    constants::{GAME_SEED, PLAYER_SEED, POOL_MANAGER_SEED},
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION},
     id,
-    model::EquilibrateError,
-    state::{game::Game, PlayerState, PoolManager},
+    model::{EquilibrateError, GameEnded, PlayerLeft},
+    state::{game::Game, token_interface, PlayerState, PoolManager, SessionAuthority},
 };
 
 #[derive(Accounts)]
@@ -14,17 +13,21 @@ pub struct LeaveGame<'info> {
     #[account(
         mut,
         seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
-        bump
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
     )]
     pub game: Account<'info, Game>,
 
-    /// mint of this game
+    /// CHECK: mint of this game, owned by `token_program`
     #[account(
         mut,
         constraint = game.config.mint == game_mint.key()
-        @EquilibrateError::InvalidBurnMint
+        @EquilibrateError::InvalidBurnMint,
+        constraint = *game_mint.owner == token_program.key()
+        @EquilibrateError::InvalidTokenProgram
     )]
-    pub game_mint: Account<'info, Mint>,
+    pub game_mint: AccountInfo<'info>,
 
     /// CHECK: wallet to which rent should be returned when closing the game account, which must be the same wallet used to make the game
     #[account(
@@ -42,41 +45,53 @@ pub struct LeaveGame<'info> {
         bump,
         owner = id(),
         close = payer,
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
     )]
     pub player: Account<'info, PlayerState>,
 
-    /// player's token acount to which their winnings are transferred;
-    /// owner/authority must be the payer
-    #[account(
-        mut,
-        token::mint = game.config.mint,
-        token::authority = payer,
-    )]
-    pub winnings_destination_account: Account<'info, TokenAccount>,
+    /// CHECK: player's token acount to which their winnings are transferred;
+    /// owner/authority must be the payer, checked in the handler
+    #[account(mut)]
+    pub winnings_destination_account: AccountInfo<'info>,
 
     /// token pool manager that signs the transaction to transfer
-    /// winnings to the player
+    /// winnings to the player, taking them off the pool's liabilities
+    #[account(mut)]
     pub pool_manager: Account<'info, PoolManager>,
 
-    /// token pool of the mint/game
-    #[account(
-        mut,
-        token::mint = game.config.mint,
-    )]
-    pub token_pool: Account<'info, TokenAccount>,
+    /// CHECK: token pool of the mint/game, checked in the handler
+    #[account(mut)]
+    pub token_pool: AccountInfo<'info>,
 
     /// transaction fee payer; receives rent of closed player account
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    /// standard SPL token program, for transferring winnings
-    pub token_program: Program<'info, Token>,
+    /// CHECK: SPL token or Token-2022 program, whichever owns the game mint,
+    /// for transferring winnings
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
 
     /// standard system program, for closing accounts
     pub system_program: Program<'info, System>,
 }
 
-pub fn leave_game(ctx: Context<LeaveGame>, cancel_on_loss: bool) -> Result<()> {
+/// A game that pays penalties to an account also passes, as a remaining
+/// account, a token account of the penalty recipient, writable. A game that
+/// pays its dust to its creator passes a token account of the creator, also
+/// writable, as the last remaining account when its last player leaves. A
+/// player who has made a session for the game passes its address, writable,
+/// in between, so it is closed along with their player state.
+pub fn leave_game<'info>(
+    ctx: Context<'_, '_, '_, 'info, LeaveGame<'info>>,
+    cancel_on_loss: bool,
+    min_winnings_decimal_tokens: Option<u64>,
+    max_burn_decimal_tokens: Option<u64>,
+) -> Result<()> {
     let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
 
     // check constraints
@@ -87,93 +102,205 @@ pub fn leave_game(ctx: Context<LeaveGame>, cancel_on_loss: bool) -> Result<()> {
     // leave it in for completeness.
     require_gt!(game_player_count, 0, EquilibrateError::GameIsOver);
 
+    let mint = token_interface::unpack_game_mint(&ctx.accounts.game_mint)?;
+    let token_program = &ctx.accounts.token_program;
+    let winnings_destination = token_interface::unpack_token_account(
+        &ctx.accounts.winnings_destination_account,
+        token_program.key,
+    )?;
+    require_keys_eq!(
+        winnings_destination.mint,
+        ctx.accounts.game.config.mint,
+        EquilibrateError::InvalidWinningsDestinationMint
+    );
+    require_keys_eq!(
+        winnings_destination.owner,
+        ctx.accounts.payer.key(),
+        EquilibrateError::InvalidWinningsDestinationOwner
+    );
+
     PoolManager::validate_token_pool(
         &ctx.accounts.token_pool,
+        token_program.key,
         ctx.accounts.pool_manager.key(),
         ctx.accounts.game.config.mint,
     )?;
 
-    // update bucket balances and remove player and their winnings from their bucket
-    let mut winnings: u64;
     let game = &mut ctx.accounts.game;
-    if game_player_count == 1 {
-        // if this is the player to end the game, give them all the remaining tokens
-        winnings = game.state.buckets.iter().map(|b| b.decimal_tokens).sum();
-    } else {
-        game.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
-        let i_current = ctx.accounts.player.bucket as usize;
-        winnings = game.state.buckets[i_current]
-            .decimal_tokens
-            .checked_div(game.state.buckets[i_current].players.into())
-            .unwrap();
-        game.state.buckets[i_current].decimal_tokens = game.state.buckets[i_current]
-            .decimal_tokens
-            .checked_sub(winnings)
-            .unwrap();
-        game.state.buckets[0].players = game.state.buckets[0].players.checked_sub(1).unwrap();
-        game.state.buckets[i_current].players = game.state.buckets[i_current]
-            .players
-            .checked_sub(1)
-            .unwrap();
-    }
-    game.state.last_update_epoch_seconds = now_epoch_seconds;
-
-    // adjust winnings for the burn penalty
-    let decimal_tokens_to_burn: u64;
-    if ctx.accounts.player.burn_penalty_decimal_tokens > winnings {
-        decimal_tokens_to_burn = winnings;
-        winnings = 0;
-    } else {
-        decimal_tokens_to_burn = ctx.accounts.player.burn_penalty_decimal_tokens;
-        winnings -= ctx.accounts.player.burn_penalty_decimal_tokens;
+    let (winnings, penalty_decimal_tokens, penalty_out_of_pool_decimal_tokens) =
+        remove_leaving_player(
+            game,
+            &ctx.accounts.player,
+            cancel_on_loss,
+            min_winnings_decimal_tokens,
+            max_burn_decimal_tokens,
+            now_epoch_seconds,
+        )?;
+
+    // pay out or burn the penalty and transfer the rest from the pool
+    let penalty_destination = PoolManager::penalty_destination_account(
+        &game.config.penalty_destination,
+        penalty_out_of_pool_decimal_tokens,
+        ctx.remaining_accounts,
+        token_program.key,
+        game.config.mint,
+    )?;
+    PoolManager::pay_out(
+        &mut ctx.accounts.pool_manager,
+        token_program,
+        &ctx.accounts.token_pool,
+        &ctx.accounts.game_mint,
+        &ctx.accounts.winnings_destination_account,
+        winnings,
+        penalty_out_of_pool_decimal_tokens,
+        penalty_destination,
+        mint.decimals,
+    )?;
+
+    ctx.accounts.player.log_leave(winnings);
+
+    SessionAuthority::close_for_leaving_player(
+        &ctx.accounts.player,
+        game.id,
+        &ctx.accounts.payer.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+
+    emit!(PlayerLeft {
+        game_id: game.id,
+        player: ctx.accounts.payer.key(),
+        bucket: ctx.accounts.player.bucket,
+        winnings_decimal_tokens: winnings,
+        penalty_decimal_tokens,
+        burned_decimal_tokens: match penalty_destination {
+            Some(_) => 0,
+            None => penalty_out_of_pool_decimal_tokens,
+        },
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
+
+    // if they were the last player, pay out or burn the game's dust, then close
+    // the game and return rent to the game creator
+    finish_game_if_empty(
+        game,
+        &ctx.accounts.game_creator,
+        ctx.accounts.payer.key(),
+        ctx.accounts.player.bucket,
+        &mut ctx.accounts.pool_manager,
+        token_program,
+        &ctx.accounts.token_pool,
+        &ctx.accounts.game_mint,
+        mint.decimals,
+        ctx.remaining_accounts,
+    )?;
+
+    Ok(())
+}
+
+/// Once the last player has left `game`, pays out or burns the game's dust,
+/// then closes the game and returns its rent to `game_creator`. `player_wallet`
+/// and `bucket` are those of the last player.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn finish_game_if_empty<'info>(
+    game: &mut Account<'info, Game>,
+    game_creator: &AccountInfo<'info>,
+    player_wallet: Pubkey,
+    bucket: u8,
+    pool_manager: &mut Account<'info, PoolManager>,
+    token_program: &AccountInfo<'info>,
+    token_pool: &AccountInfo<'info>,
+    game_mint: &AccountInfo<'info>,
+    decimals: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    if game.get_player_count() > 0 {
+        return Ok(());
     }
 
+    let dust_decimal_tokens = game.take_dust();
+    let dust_destination = PoolManager::dust_destination_account(
+        game.config.dust_destination,
+        dust_decimal_tokens,
+        remaining_accounts,
+        token_program.key,
+        game.config.mint,
+        game.creator,
+    )?;
+    PoolManager::pay_dust(
+        pool_manager,
+        token_program,
+        token_pool,
+        game_mint,
+        dust_decimal_tokens,
+        dust_destination,
+        decimals,
+    )?;
+
+    game.close(game_creator.clone())?;
+    game.log_end();
+
+    emit!(GameEnded {
+        game_id: game.id,
+        player: player_wallet,
+        bucket,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens,
+    });
+
+    Ok(())
+}
+
+/// Takes `player` and their share out of `game`, checking the share against
+/// the player's limits. Returns their winnings, their burn penalty, and the
+/// part of the penalty that leaves the pot.
+pub(crate) fn remove_leaving_player(
+    game: &mut Game,
+    player: &PlayerState,
+    cancel_on_loss: bool,
+    min_winnings_decimal_tokens: Option<u64>,
+    max_burn_decimal_tokens: Option<u64>,
+    now_epoch_seconds: i64,
+) -> Result<(u64, u64, u64)> {
+    // update bucket balances and remove player and their winnings from their bucket
+    let share = game.remove_player(
+        player.bucket as usize,
+        player.stake_decimal_tokens,
+        now_epoch_seconds,
+    );
+
+    // adjust winnings for the burn penalty, which may go back into the pot
+    let (winnings, penalty_decimal_tokens) =
+        player.apply_burn_penalty(&game.config, share, now_epoch_seconds);
+    let (winnings, penalty_out_of_pool_decimal_tokens) =
+        game.route_penalty(player.bucket as usize, winnings, penalty_decimal_tokens);
+    let penalty_decimal_tokens = share - winnings;
+
     if cancel_on_loss {
         require_gte!(
             winnings,
-            game.config.entry_fee_decimal_tokens,
+            player.deposited_decimal_tokens(),
             EquilibrateError::AbortLeaveOnLoss
         )
     }
-
-    // burn part of player's winnings
-    let token_program = ctx.accounts.token_program.to_account_info();
-    let mint = game.config.mint.key();
-    let seeds = &[
-        POOL_MANAGER_SEED.as_ref(),
-        mint.as_ref(),
-        &[ctx.accounts.pool_manager.bump],
-    ];
-    let signer = &[&seeds[..]];
-    if decimal_tokens_to_burn > 0 {
-        let burn_accounts: Burn = Burn {
-            mint: ctx.accounts.game_mint.to_account_info(),
-            from: ctx.accounts.token_pool.to_account_info(),
-            authority: ctx.accounts.pool_manager.to_account_info(),
-        };
-        let burn_context =
-            CpiContext::new_with_signer(token_program.clone(), burn_accounts, signer);
-        token::burn(burn_context, decimal_tokens_to_burn)?;
-        msg!("Burned {} decimal tokens", decimal_tokens_to_burn)
+    if let Some(min_winnings_decimal_tokens) = min_winnings_decimal_tokens {
+        require_gte!(
+            winnings,
+            min_winnings_decimal_tokens,
+            EquilibrateError::WinningsBelowMinimum
+        );
     }
-
-    // transfer game tokens from pool account
-    let winnings_transfer_accounts = Transfer {
-        from: ctx.accounts.token_pool.to_account_info(),
-        to: ctx.accounts.winnings_destination_account.to_account_info(),
-        authority: ctx.accounts.pool_manager.to_account_info(),
-    };
-    let winnings_transfer_context =
-        CpiContext::new_with_signer(token_program, winnings_transfer_accounts, signer);
-    token::transfer(winnings_transfer_context, winnings)?;
-
-    ctx.accounts.player.log_leave(winnings);
-
-    // close the game and return rent to the game creator
-    if game_player_count == 1 {
-        game.close(ctx.accounts.game_creator.to_account_info())?;
-        game.log_end();
+    if let Some(max_burn_decimal_tokens) = max_burn_decimal_tokens {
+        require_gte!(
+            max_burn_decimal_tokens,
+            penalty_decimal_tokens,
+            EquilibrateError::BurnAboveMaximum
+        );
     }
 
-    Ok(())
+    Ok((
+        winnings,
+        penalty_decimal_tokens,
+        penalty_out_of_pool_decimal_tokens,
+    ))
 }
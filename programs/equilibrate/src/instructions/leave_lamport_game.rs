@@ -0,0 +1,177 @@
+use anchor_lang::{prelude::*, AccountsClose};
+
+use crate::{
+    constants::{GAME_SEED, GAME_VERSION, LAMPORT_VAULT_SEED, PLAYER_SEED, PLAYER_STATE_VERSION},
+    id,
+    instructions::remove_leaving_player,
+    model::{EquilibrateError, GameEnded, PlayerLeft},
+    state::{game::Game, LamportVault, PlayerState, SessionAuthority},
+};
+
+#[derive(Accounts)]
+pub struct LeaveLamportGame<'info> {
+    /// game account of the game being played
+    #[account(
+        mut,
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion,
+        constraint = game.config.is_lamport_game()
+        @EquilibrateError::InvalidGameMint
+    )]
+    pub game: Account<'info, Game>,
+
+    /// CHECK: wallet to which rent should be returned when closing the game and vault accounts, which must be the same wallet used to make the game
+    #[account(
+        mut,
+        constraint = game_creator.key() == game.creator
+        @EquilibrateError::GameCreatorMismatch
+    )]
+    pub game_creator: AccountInfo<'info>,
+
+    /// player state account of the leaving player; rent will be returned
+    /// to the payer (who must be the player)
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), payer.key().as_ref()],
+        bump,
+        owner = id(),
+        close = payer,
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub player: Account<'info, PlayerState>,
+
+    /// vault holding the game's deposits, which pays the winnings
+    #[account(
+        mut,
+        seeds = [LAMPORT_VAULT_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+    )]
+    pub lamport_vault: Account<'info, LamportVault>,
+
+    /// transaction fee payer; receives their winnings and the rent of the
+    /// closed player account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard system program, for closing accounts
+    pub system_program: Program<'info, System>,
+}
+
+/// Like `leave_game`, for a game played for lamports. A game that pays
+/// penalties to an account also passes, as a remaining account, the wallet
+/// of the penalty recipient, writable. A player who has made a session for
+/// the game passes its address, writable, after that, so it is closed along
+/// with their player state.
+pub fn leave_lamport_game<'info>(
+    ctx: Context<'_, '_, '_, 'info, LeaveLamportGame<'info>>,
+    cancel_on_loss: bool,
+    min_winnings_decimal_tokens: Option<u64>,
+    max_burn_decimal_tokens: Option<u64>,
+) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    // check constraints
+    let game_player_count = ctx.accounts.game.get_player_count();
+
+    // This is untestable since the last person leaving the game
+    // also results in the game account being deleted. However, we'll
+    // leave it in for completeness.
+    require_gt!(game_player_count, 0, EquilibrateError::GameIsOver);
+
+    let game = &mut ctx.accounts.game;
+    let (winnings, penalty_decimal_tokens, penalty_out_of_pool_decimal_tokens) =
+        remove_leaving_player(
+            game,
+            &ctx.accounts.player,
+            cancel_on_loss,
+            min_winnings_decimal_tokens,
+            max_burn_decimal_tokens,
+            now_epoch_seconds,
+        )?;
+
+    // pay out the penalty and the winnings from the vault
+    let penalty_destination = LamportVault::penalty_destination_account(
+        &game.config.penalty_destination,
+        penalty_out_of_pool_decimal_tokens,
+        ctx.remaining_accounts,
+    )?;
+    LamportVault::pay_out(
+        &ctx.accounts.lamport_vault.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        winnings,
+        penalty_out_of_pool_decimal_tokens,
+        penalty_destination,
+    )?;
+
+    ctx.accounts.player.log_leave(winnings);
+
+    SessionAuthority::close_for_leaving_player(
+        &ctx.accounts.player,
+        game.id,
+        &ctx.accounts.payer.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+
+    emit!(PlayerLeft {
+        game_id: game.id,
+        player: ctx.accounts.payer.key(),
+        bucket: ctx.accounts.player.bucket,
+        winnings_decimal_tokens: winnings,
+        penalty_decimal_tokens,
+        burned_decimal_tokens: 0,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
+
+    // if they were the last player, pay the game's dust to the game creator, then
+    // close the game and the vault and return their rent to the game creator too
+    finish_lamport_game_if_empty(
+        game,
+        &ctx.accounts.lamport_vault,
+        &ctx.accounts.game_creator,
+        ctx.accounts.payer.key(),
+        ctx.accounts.player.bucket,
+    )?;
+
+    Ok(())
+}
+
+/// Once the last player has left `game`, pays the game's dust to
+/// `game_creator`, then closes the game and its vault and returns their rent
+/// to `game_creator` too. `player_wallet` and `bucket` are those of the last
+/// player.
+pub(crate) fn finish_lamport_game_if_empty<'info>(
+    game: &mut Account<'info, Game>,
+    lamport_vault: &Account<'info, LamportVault>,
+    game_creator: &AccountInfo<'info>,
+    player_wallet: Pubkey,
+    bucket: u8,
+) -> Result<()> {
+    if game.get_player_count() > 0 {
+        return Ok(());
+    }
+
+    let dust_decimal_tokens = game.take_dust();
+    LamportVault::pay_dust(
+        &lamport_vault.to_account_info(),
+        game_creator,
+        dust_decimal_tokens,
+    )?;
+
+    game.close(game_creator.clone())?;
+    lamport_vault.close(game_creator.clone())?;
+    game.log_end();
+
+    emit!(GameEnded {
+        game_id: game.id,
+        player: player_wallet,
+        bucket,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::GAME_VERSION,
+    id,
+    state::{migration, Game},
+};
+
+#[derive(Accounts)]
+pub struct MigrateGame<'info> {
+    /// CHECK: game account at an older version, whose layout is read in the
+    /// handler since it may not match the current `Game`
+    #[account(mut, owner = id())]
+    pub game: AccountInfo<'info>,
+
+    /// pays for any extra space the new layout needs
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard system program, for topping up rent
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_game(ctx: Context<MigrateGame>) -> Result<()> {
+    let account = &ctx.accounts.game;
+    let version = migration::read_version::<Game>(account, GAME_VERSION)?;
+    let migrated = {
+        let data = account.try_borrow_data()?;
+        migration::upgrade_game(version, &data[8..])?
+    };
+
+    migration::write_migrated(
+        account,
+        &migrated,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    msg!(
+        "Migrated game {} from version {} to {}",
+        account.key(),
+        version,
+        version + 1
+    );
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION},
+    id,
+    model::EquilibrateError,
+    state::{migration, Game, PlayerState},
+};
+
+#[derive(Accounts)]
+pub struct MigratePlayer<'info> {
+    /// game the player is in, which must be migrated first
+    #[account(
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub game: Account<'info, Game>,
+
+    /// CHECK: wallet of the player, only used to find their player state account
+    pub player_wallet: AccountInfo<'info>,
+
+    /// CHECK: player state account at an older version, whose layout is read
+    /// in the handler since it may not match the current `PlayerState`
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), player_wallet.key().as_ref()],
+        bump,
+        owner = id(),
+    )]
+    pub player: AccountInfo<'info>,
+
+    /// pays for any extra space the new layout needs
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard system program, for topping up rent
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_player(ctx: Context<MigratePlayer>) -> Result<()> {
+    let account = &ctx.accounts.player;
+    let version = migration::read_version::<PlayerState>(account, PLAYER_STATE_VERSION)?;
+    let migrated = {
+        let data = account.try_borrow_data()?;
+        migration::upgrade_player(version, &data[8..], &ctx.accounts.game)?
+    };
+
+    migration::write_migrated(
+        account,
+        &migrated,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    msg!(
+        "Migrated player state from version {} to {}",
+        version,
+        version + 1
+    );
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{POOL_MANAGER_SEED, POOL_SEED},
+    id,
+    model::EquilibrateError,
+    state::{migration, token_interface, PoolManager},
+};
+
+#[derive(Accounts)]
+pub struct MigratePoolManager<'info> {
+    /// CHECK: pool manager with the layout from before it recorded
+    /// liabilities, which is read in the handler
+    #[account(
+        mut,
+        seeds = [POOL_MANAGER_SEED.as_ref(), game_mint.key().as_ref()],
+        bump,
+        owner = id(),
+    )]
+    pub pool_manager: AccountInfo<'info>,
+
+    /// CHECK: token pool of the mint, whose balance becomes the liabilities
+    #[account(
+        seeds = [
+            POOL_SEED.as_ref(),
+            game_mint.key().as_ref(),
+            pool_manager.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub token_pool: AccountInfo<'info>,
+
+    /// CHECK: mint of the token pool, only used for addresses
+    pub game_mint: AccountInfo<'info>,
+
+    /// CHECK: SPL token or Token-2022 program, whichever owns the token pool
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
+
+    /// pays for the extra space the new layout needs
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard system program, for topping up rent
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_pool_manager(ctx: Context<MigratePoolManager>) -> Result<()> {
+    let pool_decimal_tokens = token_interface::unpack_token_account(
+        &ctx.accounts.token_pool,
+        ctx.accounts.token_program.key,
+    )?
+    .amount;
+    let account = &ctx.accounts.pool_manager;
+    let pool_manager = {
+        let data = account.try_borrow_data()?;
+        migration::upgrade_pool_manager(&data, pool_decimal_tokens)?
+    };
+
+    migration::write_migrated(
+        account,
+        &migration::MigratedAccount::new::<PoolManager>(&pool_manager, PoolManager::get_space())?,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    msg!(
+        "Migrated pool manager of mint {} with liabilities of {}",
+        ctx.accounts.game_mint.key(),
+        pool_manager.liabilities_decimal_tokens
+    );
+
+    Ok(())
+}
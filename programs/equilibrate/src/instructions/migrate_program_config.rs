@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{PROGRAM_CONFIG_SEED, PROGRAM_CONFIG_VERSION},
+    id,
+    state::{migration, ProgramConfig},
+};
+
+#[derive(Accounts)]
+pub struct MigrateProgramConfig<'info> {
+    /// CHECK: program config at an older version, whose layout is read in the
+    /// handler since it may not match the current `ProgramConfig`
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED.as_ref()],
+        bump,
+        owner = id(),
+    )]
+    pub program_config: AccountInfo<'info>,
+
+    /// pays for any extra space the new layout needs
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard system program, for topping up rent
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_program_config(ctx: Context<MigrateProgramConfig>) -> Result<()> {
+    let account = &ctx.accounts.program_config;
+    let version = migration::read_version::<ProgramConfig>(account, PROGRAM_CONFIG_VERSION)?;
+    let migrated = {
+        let data = account.try_borrow_data()?;
+        migration::upgrade_program_config(version, &data[8..])?
+    };
+
+    migration::write_migrated(
+        account,
+        &migrated,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    msg!(
+        "Migrated program config from version {} to {}",
+        version,
+        version + 1
+    );
+
+    Ok(())
+}
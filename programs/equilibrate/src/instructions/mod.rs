@@ -4,11 +4,68 @@ pub use new_game::*;
 pub mod enter_game;
 pub use enter_game::*;
 
+pub mod add_deposit;
+pub use add_deposit::*;
+
+pub mod withdraw;
+pub use withdraw::*;
+
 pub mod leave_game;
 pub use leave_game::*;
 
 pub mod move_buckets;
 pub use move_buckets::*;
 
+pub mod settle_player;
+pub use settle_player::*;
+
+pub mod create_session;
+pub use create_session::*;
+
+pub mod session_move_buckets;
+pub use session_move_buckets::*;
+
+pub mod session_leave_game;
+pub use session_leave_game::*;
+
+pub mod revoke_session;
+pub use revoke_session::*;
+
+pub mod new_lamport_game;
+pub use new_lamport_game::*;
+
+pub mod enter_lamport_game;
+pub use enter_lamport_game::*;
+
+pub mod leave_lamport_game;
+pub use leave_lamport_game::*;
+
+pub mod settle_lamport_player;
+pub use settle_lamport_player::*;
+
+pub mod update_game;
+pub use update_game::*;
+
 pub mod create_pool;
 pub use create_pool::*;
+
+pub mod audit_pool;
+pub use audit_pool::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod migrate_game;
+pub use migrate_game::*;
+
+pub mod migrate_player;
+pub use migrate_player::*;
+
+pub mod migrate_program_config;
+pub use migrate_program_config::*;
+
+pub mod migrate_pool_manager;
+pub use migrate_pool_manager::*;
@@ -1,9 +1,9 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    constants::{GAME_SEED, PLAYER_SEED},
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION},
     id,
-    model::EquilibrateError,
+    model::{EquilibrateError, PlayerMoved},
     state::{game::Game, PlayerState},
 };
 
@@ -13,7 +13,9 @@ pub struct MoveBuckets<'info> {
     #[account(
         mut,
         seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
-        bump
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
     )]
     pub game: Account<'info, Game>,
 
@@ -22,7 +24,9 @@ pub struct MoveBuckets<'info> {
         mut,
         seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), payer.key().as_ref()],
         bump,
-        owner = id()
+        owner = id(),
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
     )]
     pub player: Account<'info, PlayerState>,
 
@@ -31,51 +35,85 @@ pub struct MoveBuckets<'info> {
     pub payer: Signer<'info>,
 }
 
-pub fn move_buckets(ctx: Context<MoveBuckets>, i_bucket: u8) -> Result<()> {
+pub fn move_buckets(
+    ctx: Context<MoveBuckets>,
+    i_bucket: u8,
+    expected_bucket_players: Option<u16>,
+) -> Result<()> {
+    let player_wallet = ctx.accounts.payer.key();
+    move_player_to_bucket(
+        &mut ctx.accounts.game,
+        &mut ctx.accounts.player,
+        player_wallet,
+        i_bucket,
+        expected_bucket_players,
+    )
+}
+
+/// Moves the player of `player_wallet` into bucket `i_bucket`, whoever signed
+/// for them
+pub(crate) fn move_player_to_bucket(
+    game: &mut Game,
+    player: &mut PlayerState,
+    player_wallet: Pubkey,
+    i_bucket: u8,
+    expected_bucket_players: Option<u16>,
+) -> Result<()> {
     let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
 
     // check constraints
-    require_neq!(
-        ctx.accounts.player.bucket,
-        i_bucket,
-        EquilibrateError::AlreadyInBucket
-    );
+    require_neq!(player.bucket, i_bucket, EquilibrateError::AlreadyInBucket);
 
     require_gt!(
         // there is one more bucket than the creator configures: the holding bucket
-        ctx.accounts.game.state.buckets.len(),
+        game.state.buckets.len(),
         i_bucket as usize,
         EquilibrateError::BucketDoesNotExist
     );
 
     require_gt!(i_bucket, 0, EquilibrateError::CannotEnterHoldingBucket);
 
-    let game = &mut ctx.accounts.game;
     let game_player_count = game.get_player_count();
     require_gt!(game_player_count, 0, EquilibrateError::GameIsOver);
+    require!(
+        !game.is_past_end(now_epoch_seconds),
+        EquilibrateError::GameHasEnded
+    );
+    require!(
+        game.config
+            .is_move_cooldown_over(player.last_move_epoch_seconds, now_epoch_seconds),
+        EquilibrateError::MoveCooldownActive
+    );
+    if let Some(expected_bucket_players) = expected_bucket_players {
+        require_eq!(
+            game.state.buckets[i_bucket as usize].players,
+            expected_bucket_players,
+            EquilibrateError::UnexpectedBucketPlayers
+        );
+    }
 
     // update bucket balances and move player to their new bucket
-    game.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
-    let i_current = ctx.accounts.player.bucket as usize;
-    game.state.buckets[i_current].players = game.state.buckets[i_current]
-        .players
-        .checked_sub(1)
-        .unwrap();
-    game.state.buckets[i_bucket as usize].players = game.state.buckets[i_bucket as usize]
-        .players
-        .checked_add(1)
-        .unwrap();
-    game.state.last_update_epoch_seconds = now_epoch_seconds;
+    let i_current = player.bucket as usize;
+    let share = game.move_player(
+        i_current,
+        i_bucket as usize,
+        player.stake_decimal_tokens,
+        now_epoch_seconds,
+    );
+
+    // update player state account, adding this move's burn to their penalty
+    player.record_move(&game.config, i_bucket, share, now_epoch_seconds);
+    player.log_move();
 
-    // update player state account
-    ctx.accounts.player.bucket = i_bucket;
-    ctx.accounts.player.burn_penalty_decimal_tokens = ctx
-        .accounts
-        .player
-        .burn_penalty_decimal_tokens
-        .checked_add(game.config.burn_rate_decimal_tokens_per_move)
-        .unwrap();
-    ctx.accounts.player.log_move();
+    emit!(PlayerMoved {
+        game_id: game.id,
+        player: player_wallet,
+        from_bucket: i_current as u8,
+        to_bucket: i_bucket,
+        burn_penalty_decimal_tokens: player.burn_penalty_decimal_tokens,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
 
     Ok(())
 }
@@ -1,23 +1,17 @@
 use anchor_lang::{prelude::*, system_program};
-use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Token, TokenAccount, Transfer},
-};
+use anchor_spl::associated_token::AssociatedToken;
 
 use crate::{
-    constants::{
-        ACCOUNTS_VERSION, GAME_MAX_BUCKETS, GAME_MAX_PLAYERS, GAME_SEED, NATIVE_MINT, PLAYER_SEED,
-        PROGRAM_FEE_DESTINATION, PROGRAM_FEE_LAMPORTS,
-    },
-    model::EquilibrateError,
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION, PROGRAM_CONFIG_SEED},
+    model::{EquilibrateError, GameCreated},
     state::{
-        game::{Bucket, Game, GameConfig, GameState},
-        PlayerState, PoolManager,
+        game::{Bucket, DustDestination, Game, GameConfig, GameState, PenaltyDestination},
+        token_interface, PlayerState, PoolManager, ProgramConfig, ProgramSettings,
     },
 };
 
 #[derive(Accounts)]
-#[instruction(config: GameConfig, game_id: u64, pool_manager: Pubkey)]
+#[instruction(config: GameConfig, game_id: u64)]
 pub struct NewGame<'info> {
     #[account(
         init,
@@ -37,148 +31,266 @@ pub struct NewGame<'info> {
     )]
     pub first_player: Account<'info, PlayerState>,
 
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
     /// CHECK: wallet where the program fee should be deposited
     #[account(
         mut,
-        constraint = program_fee_destination.key().as_ref() == PROGRAM_FEE_DESTINATION
+        constraint = program_fee_destination.key() == program_config.settings.program_fee_destination
         @EquilibrateError::InvalidProgramFeeDestination
     )]
     pub program_fee_destination: AccountInfo<'info>,
 
+    /// CHECK: mint of this game, owned by `token_program`
     #[account(
-        mut,
-        token::mint = config.mint,
+        constraint = game_mint.key() == config.mint
+        @EquilibrateError::InvalidGameMint,
+        constraint = *game_mint.owner == token_program.key()
+        @EquilibrateError::InvalidTokenProgram
     )]
-    pub deposit_source_account: Account<'info, TokenAccount>,
+    pub game_mint: AccountInfo<'info>,
 
-    #[account(
-        mut,
-        token::mint = config.mint,
-        token::authority = pool_manager,
-    )]
-    pub token_pool: Account<'info, TokenAccount>,
+    /// CHECK: token account of the game mint that the entry deposit is taken
+    /// from, checked in the handler
+    #[account(mut)]
+    pub deposit_source_account: AccountInfo<'info>,
+
+    /// token pool manager, which records what the pool owes the game
+    #[account(mut)]
+    pub pool_manager: Account<'info, PoolManager>,
+
+    /// CHECK: token pool of the mint, checked in the handler
+    #[account(mut)]
+    pub token_pool: AccountInfo<'info>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    /// CHECK: SPL token or Token-2022 program, whichever owns the game mint
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn new_game(
-    ctx: Context<NewGame>,
-    config: GameConfig,
-    game_id: u64,
-    pool_manager: Pubkey,
-) -> Result<()> {
+pub fn new_game(ctx: Context<NewGame>, config: GameConfig, game_id: u64) -> Result<()> {
     let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+    let settings = &ctx.accounts.program_config.settings;
+
+    validate_config(&config, settings, now_epoch_seconds)?;
+
+    let mint = token_interface::unpack_game_mint(&ctx.accounts.game_mint)?;
+    let token_program = &ctx.accounts.token_program;
+    let deposit_source = token_interface::unpack_token_account(
+        &ctx.accounts.deposit_source_account,
+        token_program.key,
+    )?;
+    require_keys_eq!(
+        deposit_source.mint,
+        config.mint,
+        EquilibrateError::InvalidTokenSourceMint
+    );
+
+    PoolManager::validate_token_pool(
+        &ctx.accounts.token_pool,
+        token_program.key,
+        ctx.accounts.pool_manager.key(),
+        config.mint,
+    )?;
+
+    let program_fee_transfer_context = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        system_program::Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: ctx.accounts.program_fee_destination.clone(),
+        },
+    );
+    system_program::transfer(program_fee_transfer_context, settings.program_fee_lamports)?;
+
+    // the creator stakes the entry fee, and their fee on their own entry
+    // never leaves their deposit account
+    let stake_decimal_tokens = config.entry_fee_decimal_tokens;
+    let creator_fee_decimal_tokens = config.creator_fee_decimal_tokens(stake_decimal_tokens);
+    let received_decimal_tokens = token_interface::deposit(
+        token_program,
+        &ctx.accounts.deposit_source_account,
+        &ctx.accounts.game_mint,
+        &ctx.accounts.token_pool,
+        &ctx.accounts.payer.to_account_info(),
+        stake_decimal_tokens - creator_fee_decimal_tokens,
+        mint.decimals,
+    )?;
+    ctx.accounts
+        .pool_manager
+        .add_liabilities(received_decimal_tokens);
+
+    let state = initial_state(
+        config.n_buckets,
+        stake_decimal_tokens,
+        received_decimal_tokens,
+        now_epoch_seconds,
+    );
+
+    let game = &mut ctx.accounts.game;
+    game.set_inner(Game {
+        version: GAME_VERSION,
+        id: game_id,
+        creator: ctx.accounts.payer.key(),
+        config,
+        state,
+        creator_fees_decimal_tokens: creator_fee_decimal_tokens,
+        dust_decimal_tokens: 0,
+    });
+    game.log_make();
+
+    let player = &mut ctx.accounts.first_player;
+    player.set_inner(PlayerState {
+        version: PLAYER_STATE_VERSION,
+        // first player always goes into the first bucket
+        bucket: 1,
+        burn_penalty_decimal_tokens: 0,
+        stake_decimal_tokens,
+        added_decimal_tokens: 0,
+        last_move_epoch_seconds: 0,
+        moves: 0,
+        has_session: false,
+    });
+    player.log_make();
 
+    emit!(GameCreated {
+        game_id: game.id,
+        player: ctx.accounts.payer.key(),
+        bucket: player.bucket,
+        creator_fee_decimal_tokens,
+        buckets: game.state.buckets.clone(),
+    });
+
+    Ok(())
+}
+
+/// Checks the config of a game about to be made against the program settings
+pub(crate) fn validate_config(
+    config: &GameConfig,
+    settings: &ProgramSettings,
+    now_epoch_seconds: i64,
+) -> Result<()> {
     require_gt!(
         config.entry_fee_decimal_tokens,
         0,
         EquilibrateError::InvalidEntryFee
     );
+    require_gte!(
+        config.max_stake_decimal_tokens,
+        config.entry_fee_decimal_tokens,
+        EquilibrateError::InvalidMaxStake
+    );
     require_gt!(config.n_buckets, 1, EquilibrateError::TooFewBuckets);
     require!(
-        config.n_buckets <= GAME_MAX_BUCKETS,
+        config.n_buckets <= settings.game_max_buckets,
         EquilibrateError::TooManyBuckets
     );
 
-    require_gt!(
-        config.spill_rate_decimal_tokens_per_second_per_player,
-        0,
-        EquilibrateError::InvalidSpillRate
-    );
+    config
+        .spill_curve
+        .validate(config.spill_rate_decimal_tokens_per_second_per_player)?;
     require_gt!(config.max_players, 1, EquilibrateError::MaxPlayersTooSmall);
     require_gte!(
-        GAME_MAX_PLAYERS,
+        settings.game_max_players,
         config.max_players,
         EquilibrateError::MaxPlayersTooLarge
     );
+    require_gte!(
+        settings.game_max_creator_fee_bps,
+        config.creator_fee_bps,
+        EquilibrateError::CreatorFeeTooLarge
+    );
 
-    if config.mint.to_string() == NATIVE_MINT {
-        require_eq!(
-            0,
-            config.burn_rate_decimal_tokens_per_move,
-            EquilibrateError::CannotBurnNativeMint
+    if let Some(end_epoch_seconds) = config.end_epoch_seconds {
+        require_gt!(
+            end_epoch_seconds,
+            now_epoch_seconds,
+            EquilibrateError::InvalidEndTime
         );
     }
 
-    PoolManager::validate_token_pool(&ctx.accounts.token_pool, pool_manager, config.mint)?;
-
-    let program_fee_transfer_context = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: ctx.accounts.payer.to_account_info(),
-            to: ctx.accounts.program_fee_destination.clone(),
-        },
+    config.burn_schedule.validate()?;
+    require!(
+        config.burn_penalty_half_life_seconds != Some(0),
+        EquilibrateError::InvalidBurnSchedule
     );
-    system_program::transfer(program_fee_transfer_context, PROGRAM_FEE_LAMPORTS)?;
 
-    let pool_transfer_accounts = Transfer {
-        from: ctx.accounts.deposit_source_account.to_account_info(),
-        to: ctx.accounts.token_pool.to_account_info(),
-        authority: ctx.accounts.payer.to_account_info(),
-    };
-    let token_program = ctx.accounts.token_program.to_account_info();
-    let pool_transfer_context = CpiContext::new(token_program, pool_transfer_accounts);
-    token::transfer(pool_transfer_context, config.entry_fee_decimal_tokens)?;
+    // penalties and dust in native-mint and lamport games have to go somewhere
+    // other than a burn
+    if token_interface::is_native_mint(&config.mint) || config.is_lamport_game() {
+        if config.penalty_destination == PenaltyDestination::Burn {
+            require!(
+                !config
+                    .burn_schedule
+                    .burns(config.burn_rate_decimal_tokens_per_move),
+                EquilibrateError::CannotBurnNativeMint
+            );
+        }
+        require!(
+            config.dust_destination != DustDestination::Burn,
+            EquilibrateError::CannotBurnNativeMint
+        );
+    }
+
+    Ok(())
+}
 
+/// State of a game whose creator just entered bucket 1 with a stake of
+/// `stake_decimal_tokens`, of which the pot received `received_decimal_tokens`
+pub(crate) fn initial_state(
+    n_buckets: u8,
+    stake_decimal_tokens: u64,
+    received_decimal_tokens: u64,
+    now_epoch_seconds: i64,
+) -> GameState {
     let mut buckets = vec![
         // first bucket is the holding bucket, where fees live until they
         // have been distributed over the other buckets
         Bucket {
             // entry fee is held in the holding bucket and distributed to
-            // other buckets as the game progresses
-            decimal_tokens: config.entry_fee_decimal_tokens,
+            // other buckets as the game progresses; any transfer fee
+            // never reaches the pool
+            decimal_tokens: received_decimal_tokens,
 
             // the number of players in the holding bucket is always
             // the number of players in the game (to adjust how fast
             // tokens leave this bucket)
             players: 1,
+            stake_decimal_tokens,
         },
         // second bucket is the first one that players can enter
         // and this is the one that the first player always enters
         Bucket {
             decimal_tokens: 0,
             players: 1,
+            stake_decimal_tokens,
         },
     ];
 
     // remaining buckets are initialized to empty since no one
     // goes into these buckets at the beginning of the game
-    for _ in 2..(config.n_buckets + 1) {
+    for _ in 2..(n_buckets + 1) {
         buckets.push(Bucket {
             decimal_tokens: 0,
             players: 0,
+            stake_decimal_tokens: 0,
         });
     }
 
-    let state = GameState {
+    GameState {
         buckets,
         last_update_epoch_seconds: now_epoch_seconds,
-    };
-
-    let game = &mut ctx.accounts.game;
-    game.set_inner(Game {
-        version: ACCOUNTS_VERSION,
-        id: game_id,
-        creator: ctx.accounts.payer.key(),
-        config,
-        state,
-    });
-    game.log_make();
-
-    let player = &mut ctx.accounts.first_player;
-    player.set_inner(PlayerState {
-        version: ACCOUNTS_VERSION,
-        // first player always goes into the first bucket
-        bucket: 1,
-        burn_penalty_decimal_tokens: 0,
-    });
-    player.log_make();
-
-    Ok(())
+    }
 }
@@ -0,0 +1,149 @@
+use anchor_lang::{prelude::*, system_program};
+
+use crate::{
+    constants::{
+        GAME_SEED, GAME_VERSION, LAMPORT_VAULT_SEED, PLAYER_SEED, PLAYER_STATE_VERSION,
+        PROGRAM_CONFIG_SEED,
+    },
+    instructions::{initial_state, validate_config},
+    model::{EquilibrateError, GameCreated},
+    state::{
+        game::{Game, GameConfig},
+        LamportVault, PlayerState, ProgramConfig,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction(config: GameConfig, game_id: u64)]
+pub struct NewLamportGame<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Game::get_space(config.n_buckets),
+        seeds = [GAME_SEED.as_ref(), &game_id.to_le_bytes()],
+        bump,
+    )]
+    pub game: Account<'info, Game>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PlayerState::get_space(),
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub first_player: Account<'info, PlayerState>,
+
+    /// vault holding the game's deposits; its rent is returned to the creator
+    /// when the game ends
+    #[account(
+        init,
+        payer = payer,
+        space = LamportVault::get_space(),
+        seeds = [LAMPORT_VAULT_SEED.as_ref(), &game_id.to_le_bytes()],
+        bump,
+    )]
+    pub lamport_vault: Account<'info, LamportVault>,
+
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// CHECK: wallet where the program fee should be deposited
+    #[account(
+        mut,
+        constraint = program_fee_destination.key() == program_config.settings.program_fee_destination
+        @EquilibrateError::InvalidProgramFeeDestination
+    )]
+    pub program_fee_destination: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Like `new_game`, for a game played for lamports. Its config has the
+/// default pubkey as its mint.
+pub fn new_lamport_game(
+    ctx: Context<NewLamportGame>,
+    config: GameConfig,
+    game_id: u64,
+) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+    let settings = &ctx.accounts.program_config.settings;
+
+    require!(config.is_lamport_game(), EquilibrateError::InvalidGameMint);
+    validate_config(&config, settings, now_epoch_seconds)?;
+
+    let program_fee_transfer_context = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        system_program::Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: ctx.accounts.program_fee_destination.clone(),
+        },
+    );
+    system_program::transfer(program_fee_transfer_context, settings.program_fee_lamports)?;
+
+    // the creator stakes the entry fee, and their fee on their own entry
+    // never leaves their wallet
+    let stake_decimal_tokens = config.entry_fee_decimal_tokens;
+    let creator_fee_decimal_tokens = config.creator_fee_decimal_tokens(stake_decimal_tokens);
+    let received_decimal_tokens = stake_decimal_tokens - creator_fee_decimal_tokens;
+    LamportVault::deposit(
+        &ctx.accounts.lamport_vault.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        received_decimal_tokens,
+    )?;
+
+    let state = initial_state(
+        config.n_buckets,
+        stake_decimal_tokens,
+        received_decimal_tokens,
+        now_epoch_seconds,
+    );
+
+    let lamport_vault = &mut ctx.accounts.lamport_vault;
+    lamport_vault.set_inner(LamportVault { game_id });
+    lamport_vault.log_make();
+
+    let game = &mut ctx.accounts.game;
+    game.set_inner(Game {
+        version: GAME_VERSION,
+        id: game_id,
+        creator: ctx.accounts.payer.key(),
+        config,
+        state,
+        creator_fees_decimal_tokens: creator_fee_decimal_tokens,
+        dust_decimal_tokens: 0,
+    });
+    game.log_make();
+
+    let player = &mut ctx.accounts.first_player;
+    player.set_inner(PlayerState {
+        version: PLAYER_STATE_VERSION,
+        // first player always goes into the first bucket
+        bucket: 1,
+        burn_penalty_decimal_tokens: 0,
+        stake_decimal_tokens,
+        added_decimal_tokens: 0,
+        last_move_epoch_seconds: 0,
+        moves: 0,
+        has_session: false,
+    });
+    player.log_make();
+
+    emit!(GameCreated {
+        game_id: game.id,
+        player: ctx.accounts.payer.key(),
+        bucket: player.bucket,
+        creator_fee_decimal_tokens,
+        buckets: game.state.buckets.clone(),
+    });
+
+    Ok(())
+}
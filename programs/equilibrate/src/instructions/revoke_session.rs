@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::SESSION_SEED, model::SessionRevoked, state::SessionAuthority};
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    /// session being revoked; rent is returned to the player. The game
+    /// needn't exist anymore.
+    #[account(
+        mut,
+        seeds = [SESSION_SEED.as_ref(), &session.game_id.to_le_bytes(), payer.key().as_ref()],
+        bump,
+        close = payer,
+    )]
+    pub session: Account<'info, SessionAuthority>,
+
+    /// the player who created the session
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    session.log_revoke();
+
+    emit!(SessionRevoked {
+        game_id: session.game_id,
+        player: session.owner,
+        session_key: session.session_key,
+    });
+
+    Ok(())
+}
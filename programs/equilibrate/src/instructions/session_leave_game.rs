@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION, SESSION_SEED},
+    id,
+    instructions::{finish_game_if_empty, remove_leaving_player},
+    model::{EquilibrateError, PlayerLeft},
+    state::{game::Game, token_interface, PlayerState, PoolManager, SessionAuthority},
+};
+
+#[derive(Accounts)]
+pub struct SessionLeaveGame<'info> {
+    /// game account of the game being played
+    #[account(
+        mut,
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub game: Account<'info, Game>,
+
+    /// CHECK: mint of this game, owned by `token_program`
+    #[account(
+        mut,
+        constraint = game.config.mint == game_mint.key()
+        @EquilibrateError::InvalidBurnMint,
+        constraint = *game_mint.owner == token_program.key()
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub game_mint: AccountInfo<'info>,
+
+    /// CHECK: wallet to which rent should be returned when closing the game account, which must be the same wallet used to make the game
+    #[account(
+        mut,
+        constraint = game_creator.key() == game.creator
+        @EquilibrateError::GameCreatorMismatch
+    )]
+    pub game_creator: AccountInfo<'info>,
+
+    /// CHECK: wallet of the player the session key leaves for; receives the
+    /// rent of their closed player state and session accounts
+    #[account(mut)]
+    pub player_wallet: AccountInfo<'info>,
+
+    /// player state account of the leaving player
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), player_wallet.key().as_ref()],
+        bump,
+        owner = id(),
+        close = player_wallet,
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub player: Account<'info, PlayerState>,
+
+    /// session the player created for this game, which is closed along with
+    /// the player state account
+    #[account(
+        mut,
+        seeds = [SESSION_SEED.as_ref(), &game.id.to_le_bytes(), player_wallet.key().as_ref()],
+        bump,
+        close = player_wallet,
+        constraint = session.session_key == payer.key()
+        @EquilibrateError::InvalidSessionKey
+    )]
+    pub session: Account<'info, SessionAuthority>,
+
+    /// CHECK: player's token acount to which their winnings are transferred;
+    /// owner/authority must be the player wallet, checked in the handler
+    #[account(mut)]
+    pub winnings_destination_account: AccountInfo<'info>,
+
+    /// token pool manager that signs the transaction to transfer
+    /// winnings to the player, taking them off the pool's liabilities
+    #[account(mut)]
+    pub pool_manager: Account<'info, PoolManager>,
+
+    /// CHECK: token pool of the mint/game, checked in the handler
+    #[account(mut)]
+    pub token_pool: AccountInfo<'info>,
+
+    /// session key, which pays the transaction fee
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: SPL token or Token-2022 program, whichever owns the game mint,
+    /// for transferring winnings
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
+
+    /// standard system program, for closing accounts
+    pub system_program: Program<'info, System>,
+}
+
+/// A game that pays penalties to an account also passes, as a remaining
+/// account, a token account of the penalty recipient, writable. A game that
+/// pays its dust to its creator passes a token account of the creator, also
+/// writable, as the last remaining account when its last player leaves.
+pub fn session_leave_game<'info>(
+    ctx: Context<'_, '_, '_, 'info, SessionLeaveGame<'info>>,
+    cancel_on_loss: bool,
+    min_winnings_decimal_tokens: Option<u64>,
+    max_burn_decimal_tokens: Option<u64>,
+) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    // check constraints
+    let session = &ctx.accounts.session;
+    session.validate(now_epoch_seconds)?;
+    require!(session.can_leave, EquilibrateError::SessionCannotLeave);
+
+    let game_player_count = ctx.accounts.game.get_player_count();
+
+    // untestable for the same reason as in leave_game
+    require_gt!(game_player_count, 0, EquilibrateError::GameIsOver);
+
+    // winnings can only go to the player, never to the session key
+    let mint = token_interface::unpack_game_mint(&ctx.accounts.game_mint)?;
+    let token_program = &ctx.accounts.token_program;
+    let winnings_destination = token_interface::unpack_token_account(
+        &ctx.accounts.winnings_destination_account,
+        token_program.key,
+    )?;
+    require_keys_eq!(
+        winnings_destination.mint,
+        ctx.accounts.game.config.mint,
+        EquilibrateError::InvalidWinningsDestinationMint
+    );
+    require_keys_eq!(
+        winnings_destination.owner,
+        ctx.accounts.player_wallet.key(),
+        EquilibrateError::InvalidWinningsDestinationOwner
+    );
+
+    PoolManager::validate_token_pool(
+        &ctx.accounts.token_pool,
+        token_program.key,
+        ctx.accounts.pool_manager.key(),
+        ctx.accounts.game.config.mint,
+    )?;
+
+    let game = &mut ctx.accounts.game;
+    let (winnings, penalty_decimal_tokens, penalty_out_of_pool_decimal_tokens) =
+        remove_leaving_player(
+            game,
+            &ctx.accounts.player,
+            cancel_on_loss,
+            min_winnings_decimal_tokens,
+            max_burn_decimal_tokens,
+            now_epoch_seconds,
+        )?;
+
+    // pay out or burn the penalty and transfer the rest from the pool
+    let penalty_destination = PoolManager::penalty_destination_account(
+        &game.config.penalty_destination,
+        penalty_out_of_pool_decimal_tokens,
+        ctx.remaining_accounts,
+        token_program.key,
+        game.config.mint,
+    )?;
+    PoolManager::pay_out(
+        &mut ctx.accounts.pool_manager,
+        token_program,
+        &ctx.accounts.token_pool,
+        &ctx.accounts.game_mint,
+        &ctx.accounts.winnings_destination_account,
+        winnings,
+        penalty_out_of_pool_decimal_tokens,
+        penalty_destination,
+        mint.decimals,
+    )?;
+
+    ctx.accounts.player.log_leave(winnings);
+
+    emit!(PlayerLeft {
+        game_id: game.id,
+        player: ctx.accounts.player_wallet.key(),
+        bucket: ctx.accounts.player.bucket,
+        winnings_decimal_tokens: winnings,
+        penalty_decimal_tokens,
+        burned_decimal_tokens: match penalty_destination {
+            Some(_) => 0,
+            None => penalty_out_of_pool_decimal_tokens,
+        },
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
+
+    // if they were the last player, pay out or burn the game's dust, then close
+    // the game and return rent to the game creator
+    finish_game_if_empty(
+        game,
+        &ctx.accounts.game_creator,
+        ctx.accounts.player_wallet.key(),
+        ctx.accounts.player.bucket,
+        &mut ctx.accounts.pool_manager,
+        token_program,
+        &ctx.accounts.token_pool,
+        &ctx.accounts.game_mint,
+        mint.decimals,
+        ctx.remaining_accounts,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION, SESSION_SEED},
+    id,
+    instructions::move_player_to_bucket,
+    model::EquilibrateError,
+    state::{game::Game, PlayerState, SessionAuthority},
+};
+
+#[derive(Accounts)]
+pub struct SessionMoveBuckets<'info> {
+    /// game account of the game being played
+    #[account(
+        mut,
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub game: Account<'info, Game>,
+
+    /// CHECK: wallet of the player the session key moves for
+    pub player_wallet: AccountInfo<'info>,
+
+    /// player state account of the moving player
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), player_wallet.key().as_ref()],
+        bump,
+        owner = id(),
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub player: Account<'info, PlayerState>,
+
+    /// session the player created for this game
+    #[account(
+        seeds = [SESSION_SEED.as_ref(), &game.id.to_le_bytes(), player_wallet.key().as_ref()],
+        bump,
+        constraint = session.session_key == payer.key()
+        @EquilibrateError::InvalidSessionKey
+    )]
+    pub session: Account<'info, SessionAuthority>,
+
+    /// session key, which pays the transaction fee
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub fn session_move_buckets(
+    ctx: Context<SessionMoveBuckets>,
+    i_bucket: u8,
+    expected_bucket_players: Option<u16>,
+) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    // check constraints
+    ctx.accounts.session.validate(now_epoch_seconds)?;
+
+    move_player_to_bucket(
+        &mut ctx.accounts.game,
+        &mut ctx.accounts.player,
+        ctx.accounts.player_wallet.key(),
+        i_bucket,
+        expected_bucket_players,
+    )
+}
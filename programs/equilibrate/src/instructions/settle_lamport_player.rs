@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{GAME_SEED, GAME_VERSION, LAMPORT_VAULT_SEED, PLAYER_SEED, PLAYER_STATE_VERSION},
+    id,
+    instructions::{finish_lamport_game_if_empty, remove_leaving_player},
+    model::{EquilibrateError, PlayerSettled},
+    state::{game::Game, LamportVault, PlayerState, SessionAuthority},
+};
+
+#[derive(Accounts)]
+pub struct SettleLamportPlayer<'info> {
+    /// game account of the game that has ended
+    #[account(
+        mut,
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion,
+        constraint = game.config.is_lamport_game()
+        @EquilibrateError::InvalidGameMint
+    )]
+    pub game: Account<'info, Game>,
+
+    /// CHECK: wallet to which rent should be returned when closing the game and vault accounts, which must be the same wallet used to make the game
+    #[account(
+        mut,
+        constraint = game_creator.key() == game.creator
+        @EquilibrateError::GameCreatorMismatch
+    )]
+    pub game_creator: AccountInfo<'info>,
+
+    /// CHECK: wallet of the player being settled; receives their winnings and
+    /// the rent of their closed player state account
+    #[account(mut)]
+    pub player_wallet: AccountInfo<'info>,
+
+    /// player state account of the player being settled
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), player_wallet.key().as_ref()],
+        bump,
+        owner = id(),
+        close = player_wallet,
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub player: Account<'info, PlayerState>,
+
+    /// vault holding the game's deposits, which pays the winnings
+    #[account(
+        mut,
+        seeds = [LAMPORT_VAULT_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+    )]
+    pub lamport_vault: Account<'info, LamportVault>,
+
+    /// transaction fee payer, which can be anyone once the game has ended
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// standard system program, for closing accounts
+    pub system_program: Program<'info, System>,
+}
+
+/// Like `settle_player`, for a game played for lamports. A game that pays
+/// penalties to an account also passes, as a remaining account, the wallet
+/// of the penalty recipient, writable. A player who has made a session for
+/// the game has its address passed, writable, after that, so it is closed
+/// along with their player state.
+pub fn settle_lamport_player<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleLamportPlayer<'info>>,
+) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    // check constraints
+    let game_player_count = ctx.accounts.game.get_player_count();
+
+    // untestable for the same reason as in leave_game
+    require_gt!(game_player_count, 0, EquilibrateError::GameIsOver);
+
+    require!(
+        ctx.accounts.game.is_past_end(now_epoch_seconds),
+        EquilibrateError::GameHasNotEnded
+    );
+
+    // remove player with their share of their bucket and of the holding bucket,
+    // without the limits a leaving player can set
+    let game = &mut ctx.accounts.game;
+    let (winnings, penalty_decimal_tokens, penalty_out_of_pool_decimal_tokens) =
+        remove_leaving_player(
+            game,
+            &ctx.accounts.player,
+            false,
+            None,
+            None,
+            now_epoch_seconds,
+        )?;
+
+    // pay out the penalty and the winnings from the vault
+    let penalty_destination = LamportVault::penalty_destination_account(
+        &game.config.penalty_destination,
+        penalty_out_of_pool_decimal_tokens,
+        ctx.remaining_accounts,
+    )?;
+    LamportVault::pay_out(
+        &ctx.accounts.lamport_vault.to_account_info(),
+        &ctx.accounts.player_wallet,
+        winnings,
+        penalty_out_of_pool_decimal_tokens,
+        penalty_destination,
+    )?;
+
+    ctx.accounts.player.log_settle(winnings);
+
+    SessionAuthority::close_for_leaving_player(
+        &ctx.accounts.player,
+        game.id,
+        &ctx.accounts.player_wallet,
+        ctx.remaining_accounts,
+    )?;
+
+    emit!(PlayerSettled {
+        game_id: game.id,
+        player: ctx.accounts.player_wallet.key(),
+        settled_by: ctx.accounts.payer.key(),
+        bucket: ctx.accounts.player.bucket,
+        winnings_decimal_tokens: winnings,
+        penalty_decimal_tokens,
+        burned_decimal_tokens: 0,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
+
+    // the final settlement pays the game's dust to the game creator, closes the
+    // game and the vault and returns their rent to the game creator
+    finish_lamport_game_if_empty(
+        game,
+        &ctx.accounts.lamport_vault,
+        &ctx.accounts.game_creator,
+        ctx.accounts.player_wallet.key(),
+        ctx.accounts.player.bucket,
+    )?;
+
+    Ok(())
+}
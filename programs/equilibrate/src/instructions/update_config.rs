@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::PROGRAM_CONFIG_SEED,
+    model::EquilibrateError,
+    state::{ProgramConfig, ProgramSettings},
+};
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    /// singleton account holding program-wide settings
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED.as_ref()],
+        bump,
+        has_one = admin @EquilibrateError::ProgramConfigAdminMismatch,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// current admin of the program config
+    pub admin: Signer<'info>,
+}
+
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    new_admin: Pubkey,
+    settings: ProgramSettings,
+) -> Result<()> {
+    settings.validate()?;
+
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.admin = new_admin;
+    program_config.settings = settings;
+    program_config.log_update();
+
+    Ok(())
+}
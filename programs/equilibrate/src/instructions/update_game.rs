@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{GAME_SEED, GAME_VERSION},
+    model::{EquilibrateError, GameUpdated},
+    state::game::Game,
+};
+
+#[derive(Accounts)]
+pub struct UpdateGame<'info> {
+    /// game account of the game being updated
+    #[account(
+        mut,
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub game: Account<'info, Game>,
+}
+
+pub fn update_game(ctx: Context<UpdateGame>) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    // untestable for the same reason as in leave_game
+    let game = &mut ctx.accounts.game;
+    require_gt!(game.get_player_count(), 0, EquilibrateError::GameIsOver);
+
+    game.update(now_epoch_seconds);
+    game.log_update();
+
+    emit!(GameUpdated {
+        game_id: game.id,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+        last_update_epoch_seconds: game.state.last_update_epoch_seconds,
+    });
+
+    Ok(())
+}
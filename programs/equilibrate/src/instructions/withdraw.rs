@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{GAME_SEED, GAME_VERSION, PLAYER_SEED, PLAYER_STATE_VERSION},
+    id,
+    model::{EquilibrateError, PlayerWithdrew},
+    state::{game::Game, token_interface, PlayerState, PoolManager},
+};
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// game account of the game being played
+    #[account(
+        mut,
+        seeds = [GAME_SEED.as_ref(), &game.id.to_le_bytes()],
+        bump,
+        constraint = game.version == GAME_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub game: Account<'info, Game>,
+
+    /// CHECK: mint of this game, owned by `token_program`
+    #[account(
+        mut,
+        constraint = game.config.mint == game_mint.key()
+        @EquilibrateError::InvalidBurnMint,
+        constraint = *game_mint.owner == token_program.key()
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub game_mint: AccountInfo<'info>,
+
+    /// player state account of the withdrawing player
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_ref(), game.key().as_ref(), payer.key().as_ref()],
+        bump,
+        owner = id(),
+        constraint = player.version == PLAYER_STATE_VERSION
+        @EquilibrateError::StaleAccountVersion
+    )]
+    pub player: Account<'info, PlayerState>,
+
+    /// CHECK: player's token acount to which the withdrawal is transferred;
+    /// owner/authority must be the payer, checked in the handler
+    #[account(mut)]
+    pub winnings_destination_account: AccountInfo<'info>,
+
+    /// token pool manager that signs the transaction to transfer
+    /// the withdrawal to the player, taking it off the pool's liabilities
+    #[account(mut)]
+    pub pool_manager: Account<'info, PoolManager>,
+
+    /// CHECK: token pool of the mint/game, checked in the handler
+    #[account(mut)]
+    pub token_pool: AccountInfo<'info>,
+
+    /// the withdrawing player
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: SPL token or Token-2022 program, whichever owns the game mint,
+    /// for transferring the withdrawal
+    #[account(
+        constraint = token_interface::is_token_program(token_program.key)
+        @EquilibrateError::InvalidTokenProgram
+    )]
+    pub token_program: AccountInfo<'info>,
+}
+
+/// A game that pays penalties to an account also passes, as a remaining
+/// account, a token account of the penalty recipient, writable
+pub fn withdraw<'info>(
+    ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>,
+    fraction_bps: u16,
+) -> Result<()> {
+    let now_epoch_seconds = Clock::get().unwrap().unix_timestamp;
+
+    // check constraints
+    require!(
+        (1..10_000).contains(&fraction_bps),
+        EquilibrateError::InvalidWithdrawalFraction
+    );
+
+    let stake_decimal_tokens = ctx.accounts.player.stake_fraction(fraction_bps);
+    require_gt!(
+        stake_decimal_tokens,
+        0,
+        EquilibrateError::WithdrawalTooSmall
+    );
+
+    let game = &ctx.accounts.game;
+    require_gt!(game.get_player_count(), 0, EquilibrateError::GameIsOver);
+    // once the game ends, players are settled with their whole share instead
+    require!(
+        !game.is_past_end(now_epoch_seconds),
+        EquilibrateError::GameHasEnded
+    );
+
+    let mint = token_interface::unpack_game_mint(&ctx.accounts.game_mint)?;
+    let token_program = &ctx.accounts.token_program;
+    let winnings_destination = token_interface::unpack_token_account(
+        &ctx.accounts.winnings_destination_account,
+        token_program.key,
+    )?;
+    require_keys_eq!(
+        winnings_destination.mint,
+        game.config.mint,
+        EquilibrateError::InvalidWinningsDestinationMint
+    );
+    require_keys_eq!(
+        winnings_destination.owner,
+        ctx.accounts.payer.key(),
+        EquilibrateError::InvalidWinningsDestinationOwner
+    );
+
+    PoolManager::validate_token_pool(
+        &ctx.accounts.token_pool,
+        token_program.key,
+        ctx.accounts.pool_manager.key(),
+        game.config.mint,
+    )?;
+
+    // update bucket balances and take the withdrawn share out of the player's bucket
+    let player = &mut ctx.accounts.player;
+    let game = &mut ctx.accounts.game;
+    let share = game.withdraw(
+        player.bucket as usize,
+        stake_decimal_tokens,
+        now_epoch_seconds,
+    );
+
+    // shrink the player's position, charging their share of the burn penalty
+    let (winnings, penalty_decimal_tokens) =
+        player.withdraw(&game.config, stake_decimal_tokens, share, now_epoch_seconds);
+    let (winnings, penalty_out_of_pool_decimal_tokens) =
+        game.route_penalty(player.bucket as usize, winnings, penalty_decimal_tokens);
+
+    let penalty_destination = PoolManager::penalty_destination_account(
+        &game.config.penalty_destination,
+        penalty_out_of_pool_decimal_tokens,
+        ctx.remaining_accounts,
+        token_program.key,
+        game.config.mint,
+    )?;
+    PoolManager::pay_out(
+        &mut ctx.accounts.pool_manager,
+        token_program,
+        &ctx.accounts.token_pool,
+        &ctx.accounts.game_mint,
+        &ctx.accounts.winnings_destination_account,
+        winnings,
+        penalty_out_of_pool_decimal_tokens,
+        penalty_destination,
+        mint.decimals,
+    )?;
+
+    player.log_withdraw(winnings);
+
+    emit!(PlayerWithdrew {
+        game_id: game.id,
+        player: ctx.accounts.payer.key(),
+        bucket: player.bucket,
+        winnings_decimal_tokens: winnings,
+        penalty_decimal_tokens,
+        burned_decimal_tokens: match penalty_destination {
+            Some(_) => 0,
+            None => penalty_out_of_pool_decimal_tokens,
+        },
+        stake_decimal_tokens: player.stake_decimal_tokens,
+        buckets: game.state.buckets.clone(),
+        dust_decimal_tokens: game.dust_decimal_tokens,
+    });
+
+    Ok(())
+}
@@ -19,30 +19,236 @@ pub mod equilibrate {
         instructions::create_pool(ctx, pool_manager_bump)
     }
 
-    /// Creates/starts a new game and enters the payer/player into it
-    pub fn new_game(
-        ctx: Context<NewGame>,
-        config: GameConfig,
-        game_id: u64,
-        pool_manager: Pubkey,
+    /// Checks that the token pool of a mint holds at least what it owes the live
+    /// games of that mint, and reports any surplus no game owns, such as tokens
+    /// sent to the pool directly. Anyone can audit any pool.
+    pub fn audit_pool(ctx: Context<AuditPool>) -> Result<()> {
+        instructions::audit_pool(ctx)
+    }
+
+    /// Creates the singleton program config. Can only be called by the program's
+    /// upgrade authority, and only once.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        admin: Pubkey,
+        settings: ProgramSettings,
+    ) -> Result<()> {
+        instructions::initialize_config(ctx, admin, settings)
+    }
+
+    /// Replaces the program config settings and admin. Must be signed by the current admin.
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        new_admin: Pubkey,
+        settings: ProgramSettings,
     ) -> Result<()> {
-        instructions::new_game(ctx, config, game_id, pool_manager)
+        instructions::update_config(ctx, new_admin, settings)
     }
 
-    /// Enters the player into an existing game
-    pub fn enter_game(ctx: Context<EnterGame>, bucket: u8, pool_manager: Pubkey) -> Result<()> {
-        instructions::enter_game(ctx, bucket, pool_manager)
+    /// Creates/starts a new game and enters the payer/player into it, staking the entry fee
+    pub fn new_game(ctx: Context<NewGame>, config: GameConfig, game_id: u64) -> Result<()> {
+        instructions::new_game(ctx, config, game_id)
+    }
+
+    /// Enters the player into an existing game with a stake between the game's entry
+    /// fee and maximum stake, which weighs their share of buckets. Games with an allowlist need a
+    /// proof that the player is on it; public games ignore the proof. Games with
+    /// a creator fee pay that share of the entry deposit to the creator. A referred
+    /// player also passes the referrer's wallet and `ReferralStats` account as
+    /// remaining accounts, and the referrer gets a share of the program fee.
+    pub fn enter_game<'info>(
+        ctx: Context<'_, '_, '_, 'info, EnterGame<'info>>,
+        bucket: u8,
+        stake_decimal_tokens: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::enter_game(ctx, bucket, stake_decimal_tokens, allowlist_proof)
+    }
+
+    /// Adds tokens to the player's position after entering. The game config
+    /// decides whether they go to the holding bucket or the player's bucket.
+    /// Topping up doesnt change the player's stake or share of a bucket.
+    pub fn add_deposit(ctx: Context<AddDeposit>, deposit_decimal_tokens: u64) -> Result<()> {
+        instructions::add_deposit(ctx, deposit_decimal_tokens)
     }
 
     /// Moves the player from one bucket into another. Trying to move into the same
-    /// bucket the player is already in will result in a failure.
-    pub fn move_buckets(ctx: Context<MoveBuckets>, bucket: u8) -> Result<()> {
-        instructions::move_buckets(ctx, bucket)
+    /// bucket the player is already in will result in a failure, as will moving
+    /// into a bucket that doesnt have `expected_bucket_players` players, if given.
+    pub fn move_buckets(
+        ctx: Context<MoveBuckets>,
+        bucket: u8,
+        expected_bucket_players: Option<u16>,
+    ) -> Result<()> {
+        instructions::move_buckets(ctx, bucket, expected_bucket_players)
+    }
+
+    /// Withdraws `fraction_bps` basis points of the player's position while they
+    /// stay in the game, transferring that share of their bucket into their token
+    /// account. The same fraction of their stake and burn penalty goes with it.
+    /// Their claim on the holding bucket shrinks with their stake but none of it
+    /// is paid out, so the other players get a larger share of it once the game
+    /// ends.
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>,
+        fraction_bps: u16,
+    ) -> Result<()> {
+        instructions::withdraw(ctx, fraction_bps)
     }
 
     /// Leaves the game, transferring any winnings into the player's token account
     /// The last player to leave gets all the unclaimed tokens in the game.
-    pub fn leave_game(ctx: Context<LeaveGame>, cancel_on_loss: bool) -> Result<()> {
-        instructions::leave_game(ctx, cancel_on_loss)
+    /// Leaving fails if the winnings are below `min_winnings_decimal_tokens`
+    /// or the burn penalty is more than `max_burn_decimal_tokens`, when given, so the
+    /// player is protected from the game changing before the transaction lands.
+    /// The player's session for the game, which must be passed if they made
+    /// one, is closed too, so it can't act for them if they enter again.
+    pub fn leave_game<'info>(
+        ctx: Context<'_, '_, '_, 'info, LeaveGame<'info>>,
+        cancel_on_loss: bool,
+        min_winnings_decimal_tokens: Option<u64>,
+        max_burn_decimal_tokens: Option<u64>,
+    ) -> Result<()> {
+        instructions::leave_game(
+            ctx,
+            cancel_on_loss,
+            min_winnings_decimal_tokens,
+            max_burn_decimal_tokens,
+        )
+    }
+
+    /// Brings a game's bucket balances up to date without moving any players.
+    /// Anyone can update any game.
+    pub fn update_game(ctx: Context<UpdateGame>) -> Result<()> {
+        instructions::update_game(ctx)
+    }
+
+    /// Settles a player once the game is past its end time, transferring their
+    /// share of their bucket and of the holding bucket into their token account.
+    /// Anyone can settle any player; settling the last player closes the game.
+    pub fn settle_player<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettlePlayer<'info>>,
+    ) -> Result<()> {
+        instructions::settle_player(ctx)
+    }
+
+    /// Lets `session_key` move buckets for the player in this game until
+    /// `expires_epoch_seconds`, and leave the game for them if `can_leave`.
+    /// Creating a session again replaces the player's current one.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        expires_epoch_seconds: i64,
+        can_leave: bool,
+    ) -> Result<()> {
+        instructions::create_session(ctx, session_key, expires_epoch_seconds, can_leave)
+    }
+
+    /// Like `move_buckets`, signed by the player's session key instead of their wallet.
+    pub fn session_move_buckets(
+        ctx: Context<SessionMoveBuckets>,
+        bucket: u8,
+        expected_bucket_players: Option<u16>,
+    ) -> Result<()> {
+        instructions::session_move_buckets(ctx, bucket, expected_bucket_players)
+    }
+
+    /// Like `leave_game`, signed by the player's session key instead of their
+    /// wallet. Winnings are always paid to the player, and the session is closed.
+    pub fn session_leave_game<'info>(
+        ctx: Context<'_, '_, '_, 'info, SessionLeaveGame<'info>>,
+        cancel_on_loss: bool,
+        min_winnings_decimal_tokens: Option<u64>,
+        max_burn_decimal_tokens: Option<u64>,
+    ) -> Result<()> {
+        instructions::session_leave_game(
+            ctx,
+            cancel_on_loss,
+            min_winnings_decimal_tokens,
+            max_burn_decimal_tokens,
+        )
+    }
+
+    /// Revokes the player's session key, returning the session account's rent.
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        instructions::revoke_session(ctx)
+    }
+
+    /// Like `new_game`, for a game played for lamports instead of tokens, which
+    /// needs neither token accounts nor a token pool. The config's mint must be
+    /// the default pubkey. Deposits are held in a vault made for the game, whose
+    /// rent the creator gets back when the game ends. Players move with
+    /// `move_buckets` as usual, but the instructions that only work with token
+    /// accounts (topping up, withdrawing and leaving with a session key) aren't
+    /// available to these games.
+    pub fn new_lamport_game(
+        ctx: Context<NewLamportGame>,
+        config: GameConfig,
+        game_id: u64,
+    ) -> Result<()> {
+        instructions::new_lamport_game(ctx, config, game_id)
+    }
+
+    /// Like `enter_game`, for a game played for lamports. The creator fee is
+    /// paid to the creator's wallet.
+    pub fn enter_lamport_game<'info>(
+        ctx: Context<'_, '_, '_, 'info, EnterLamportGame<'info>>,
+        bucket: u8,
+        stake_decimal_tokens: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::enter_lamport_game(ctx, bucket, stake_decimal_tokens, allowlist_proof)
+    }
+
+    /// Like `leave_game`, for a game played for lamports. Winnings are paid to
+    /// the player's wallet. Lamports can't be burned, so these games send
+    /// penalties back into the pot or to an account.
+    pub fn leave_lamport_game<'info>(
+        ctx: Context<'_, '_, '_, 'info, LeaveLamportGame<'info>>,
+        cancel_on_loss: bool,
+        min_winnings_decimal_tokens: Option<u64>,
+        max_burn_decimal_tokens: Option<u64>,
+    ) -> Result<()> {
+        instructions::leave_lamport_game(
+            ctx,
+            cancel_on_loss,
+            min_winnings_decimal_tokens,
+            max_burn_decimal_tokens,
+        )
+    }
+
+    /// Like `settle_player`, for a game played for lamports. Winnings are paid
+    /// to the player's wallet.
+    pub fn settle_lamport_player<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleLamportPlayer<'info>>,
+    ) -> Result<()> {
+        instructions::settle_lamport_player(ctx)
+    }
+
+    /// Upgrades a game account from an older version to the next one, growing
+    /// it if the new layout needs more space. A game several versions behind is
+    /// migrated once per version. Anyone can migrate any game.
+    pub fn migrate_game(ctx: Context<MigrateGame>) -> Result<()> {
+        instructions::migrate_game(ctx)
+    }
+
+    /// Upgrades a player state account from an older version to the next one,
+    /// growing it if the new layout needs more space. The player's game must be
+    /// fully migrated first. Anyone can migrate any player.
+    pub fn migrate_player(ctx: Context<MigratePlayer>) -> Result<()> {
+        instructions::migrate_player(ctx)
+    }
+
+    /// Upgrades the program config from an older version to the next one,
+    /// growing it if the new layout needs more space. Anyone can migrate it.
+    pub fn migrate_program_config(ctx: Context<MigrateProgramConfig>) -> Result<()> {
+        instructions::migrate_program_config(ctx)
+    }
+
+    /// Upgrades a pool manager made before it recorded the pool's liabilities,
+    /// taking everything the pool holds at that point to be owed to games.
+    /// Anyone can migrate any pool manager.
+    pub fn migrate_pool_manager(ctx: Context<MigratePoolManager>) -> Result<()> {
+        instructions::migrate_pool_manager(ctx)
     }
 }
@@ -8,7 +8,7 @@ pub enum EquilibrateError {
     #[msg("Number of buckets must be positive")]
     TooFewBuckets,
 
-    #[msg("Game cannot have more than 64 buckets")]
+    #[msg("Game has more buckets than the program config allows")]
     TooManyBuckets,
 
     #[msg("Game is at capacity")]
@@ -23,7 +23,7 @@ pub enum EquilibrateError {
     #[msg("Game must allow more than 1 player")]
     MaxPlayersTooSmall,
 
-    #[msg("Game cannot have more than 10,000 players")]
+    #[msg("Game allows more players than the program config allows")]
     MaxPlayersTooLarge,
 
     #[msg("Attempted to enter a bucket that doesnt exist")]
@@ -38,7 +38,7 @@ pub enum EquilibrateError {
     #[msg("Attempted to enter a game that has already ended")]
     GameIsOver,
 
-    #[msg("Program fee pubkey must be the one hard-coded in the program")]
+    #[msg("Program fee pubkey must be the one in the program config")]
     InvalidProgramFeeDestination,
 
     #[msg("Pool mint must be the one configured for the game")]
@@ -73,4 +73,132 @@ pub enum EquilibrateError {
 
     #[msg("Player chose to abort leaving rather than lose tokens")]
     AbortLeaveOnLoss,
+
+    #[msg("Program data account must belong to this program")]
+    InvalidProgramData,
+
+    #[msg("Only the program upgrade authority can initialize the program config")]
+    NotUpgradeAuthority,
+
+    #[msg("Given admin doesnt match the program config admin")]
+    ProgramConfigAdminMismatch,
+
+    #[msg("Token program must be SPL Token or Token-2022 and own the game mint")]
+    InvalidTokenProgram,
+
+    #[msg("Mint has an extension that would break pool accounting")]
+    UnsupportedMintExtension,
+
+    #[msg("Game mint must be the one configured for the game")]
+    InvalidGameMint,
+
+    #[msg("Token winnings acount owner must be the player")]
+    InvalidWinningsDestinationOwner,
+
+    #[msg("Account is at an older version and must be migrated first")]
+    StaleAccountVersion,
+
+    #[msg("Account version is not one this program can migrate")]
+    UnknownAccountVersion,
+
+    #[msg("Account is already at the current version")]
+    AccountAlreadyMigrated,
+
+    #[msg("Game end time must be in the future")]
+    InvalidEndTime,
+
+    #[msg("Game is past its end time")]
+    GameHasEnded,
+
+    #[msg("Other players can only be settled once the game is past its end time")]
+    GameHasNotEnded,
+
+    #[msg("Spill curve parameters are invalid")]
+    InvalidSpillCurve,
+
+    #[msg("Player is not on the game's allowlist")]
+    NotAllowlisted,
+
+    #[msg("Creator fee is larger than the maximum allowed")]
+    CreatorFeeTooLarge,
+
+    #[msg("Creator fee destination must be a token account of the game mint owned by the game creator")]
+    InvalidCreatorFeeDestination,
+
+    #[msg("Referral fee can be at most the whole program fee")]
+    ReferralFeeTooLarge,
+
+    #[msg("Players cannot refer themselves")]
+    SelfReferral,
+
+    #[msg("Referral stats account must be the PDA of the referrer")]
+    InvalidReferralStats,
+
+    #[msg("A referral needs both the referrer wallet and their referral stats account")]
+    MissingReferralAccounts,
+
+    #[msg("Maximum stake must be at least the entry fee")]
+    InvalidMaxStake,
+
+    #[msg("Stake must be between the game's entry fee and maximum stake")]
+    InvalidStake,
+
+    #[msg("Top-up must be more than zero tokens")]
+    EmptyTopUp,
+
+    #[msg("Withdrawal must be between 1 and 9999 basis points of the position; leave the game to withdraw everything")]
+    InvalidWithdrawalFraction,
+
+    #[msg("Withdrawal is too small to give up any stake")]
+    WithdrawalTooSmall,
+
+    #[msg("Leaving was aborted because winnings would be below the player's minimum")]
+    WinningsBelowMinimum,
+
+    #[msg("Leaving was aborted because more than the player's maximum would be burned")]
+    BurnAboveMaximum,
+
+    #[msg("Moving was aborted because the bucket doesnt have the expected number of players")]
+    UnexpectedBucketPlayers,
+
+    #[msg("Session must expire in the future and within the maximum session length")]
+    InvalidSessionExpiry,
+
+    #[msg("Session key doesnt match the player's session")]
+    InvalidSessionKey,
+
+    #[msg("Session has expired")]
+    SessionExpired,
+
+    #[msg("Session key isnt allowed to leave the game")]
+    SessionCannotLeave,
+
+    #[msg("Player has to wait longer after their last move before moving again")]
+    MoveCooldownActive,
+
+    #[msg("Burn schedule parameters are invalid")]
+    InvalidBurnSchedule,
+
+    #[msg(
+        "Game pays penalties to an account, which must be passed as the first remaining account"
+    )]
+    MissingPenaltyDestination,
+
+    #[msg("Penalty destination must be a token account of the game mint owned by the game's penalty recipient, or the recipient itself in lamport games")]
+    InvalidPenaltyDestination,
+
+    #[msg("Payout is more than the token pool owes all of its games")]
+    PoolLiabilitiesExceeded,
+
+    #[msg("Token pool holds less than it owes its games")]
+    PoolUnderfunded,
+
+    #[msg("Game pays its dust to its creator, who must be passed as the last remaining account")]
+    MissingDustDestination,
+
+    #[msg("Dust destination must be a token account of the game mint owned by the game creator")]
+    InvalidDustDestination,
+
+    #[msg("Player has made a session, whose address must be passed to close it")]
+    MissingSession,
 }
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Bucket;
+
+#[event]
+pub struct GameCreated {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub bucket: u8,
+    /// part of the creator's entry deposit kept as their creator fee
+    pub creator_fee_decimal_tokens: u64,
+    /// bucket balances after the game was created
+    pub buckets: Vec<Bucket>,
+}
+
+#[event]
+pub struct PlayerEntered {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub bucket: u8,
+    /// what the player chose to deposit
+    pub stake_decimal_tokens: u64,
+    /// part of the entry deposit paid to the game creator
+    pub creator_fee_decimal_tokens: u64,
+    /// bucket balances after the player entered
+    pub buckets: Vec<Bucket>,
+    /// game's dust after the player entered
+    pub dust_decimal_tokens: u64,
+}
+
+#[event]
+pub struct PlayerReferred {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+    /// share of the program fee paid to the referrer
+    pub reward_lamports: u64,
+    /// referrals by this referrer so far, including this one
+    pub referrals: u64,
+}
+
+#[event]
+pub struct DepositAdded {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub bucket: u8,
+    /// what the pool received for this top-up
+    pub deposit_decimal_tokens: u64,
+    /// everything the player has added since entering, including this top-up
+    pub added_decimal_tokens: u64,
+    /// bucket balances after the deposit was added
+    pub buckets: Vec<Bucket>,
+    /// game's dust after the deposit was added
+    pub dust_decimal_tokens: u64,
+}
+
+#[event]
+pub struct PlayerMoved {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub from_bucket: u8,
+    pub to_bucket: u8,
+    /// player's accumulated burn penalty after the move
+    pub burn_penalty_decimal_tokens: u64,
+    /// bucket balances after the player moved
+    pub buckets: Vec<Bucket>,
+    /// game's dust after the player moved
+    pub dust_decimal_tokens: u64,
+}
+
+#[event]
+pub struct GameUpdated {
+    pub game_id: u64,
+    /// bucket balances as of `last_update_epoch_seconds`
+    pub buckets: Vec<Bucket>,
+    /// game's dust as of `last_update_epoch_seconds`
+    pub dust_decimal_tokens: u64,
+    pub last_update_epoch_seconds: i64,
+}
+
+#[event]
+pub struct PlayerLeft {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub bucket: u8,
+    pub winnings_decimal_tokens: u64,
+    /// burn penalty charged to the player, wherever the game sends it
+    pub penalty_decimal_tokens: u64,
+    /// part of the penalty that was burned
+    pub burned_decimal_tokens: u64,
+    /// bucket balances after the player left
+    pub buckets: Vec<Bucket>,
+    /// game's dust after the player left
+    pub dust_decimal_tokens: u64,
+}
+
+#[event]
+pub struct PlayerWithdrew {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub bucket: u8,
+    pub winnings_decimal_tokens: u64,
+    /// burn penalty charged to the player, wherever the game sends it
+    pub penalty_decimal_tokens: u64,
+    /// part of the penalty that was burned
+    pub burned_decimal_tokens: u64,
+    /// what the player still has at stake after withdrawing
+    pub stake_decimal_tokens: u64,
+    /// bucket balances after the player withdrew
+    pub buckets: Vec<Bucket>,
+    /// game's dust after the player withdrew
+    pub dust_decimal_tokens: u64,
+}
+
+#[event]
+pub struct PlayerSettled {
+    pub game_id: u64,
+    pub player: Pubkey,
+    /// wallet that paid for the settlement, which can be anyone
+    pub settled_by: Pubkey,
+    pub bucket: u8,
+    pub winnings_decimal_tokens: u64,
+    /// burn penalty charged to the player, wherever the game sends it
+    pub penalty_decimal_tokens: u64,
+    /// part of the penalty that was burned
+    pub burned_decimal_tokens: u64,
+    /// bucket balances after the player was settled
+    pub buckets: Vec<Bucket>,
+    /// game's dust after the player was settled
+    pub dust_decimal_tokens: u64,
+}
+
+#[event]
+pub struct SessionCreated {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_epoch_seconds: i64,
+    pub can_leave: bool,
+}
+
+#[event]
+pub struct SessionRevoked {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct GameEnded {
+    pub game_id: u64,
+    /// last player to leave the game
+    pub player: Pubkey,
+    pub bucket: u8,
+    /// bucket balances when the game closed
+    pub buckets: Vec<Bucket>,
+    /// dust paid to the creator or burned when the game closed; dust that goes
+    /// to the last player is part of their winnings instead
+    pub dust_decimal_tokens: u64,
+}
+
+#[event]
+pub struct PoolAudited {
+    pub mint: Pubkey,
+    /// balance of the token pool
+    pub pool_decimal_tokens: u64,
+    /// what the pool owes the live games of the mint
+    pub liabilities_decimal_tokens: u64,
+    /// balance that no game owns, such as direct donations or dust
+    pub surplus_decimal_tokens: u64,
+}
@@ -1,2 +1,5 @@
 pub mod error;
 pub use error::*;
+
+pub mod events;
+pub use events::*;
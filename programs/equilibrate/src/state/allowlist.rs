@@ -0,0 +1,79 @@
+use anchor_lang::{prelude::*, solana_program::keccak};
+
+// Allowlists are Merkle trees of keccak hashes over the allowed wallets. Leaves
+// and nodes are hashed with different prefixes so a node cant pass for a
+// wallet, and each pair is hashed in sorted order so proofs needn't say which
+// side each sibling is on.
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn leaf_hash(wallet: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[LEAF_PREFIX, wallet.as_ref()]).to_bytes()
+}
+
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    keccak::hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Whether `proof` shows that `wallet` is in the allowlist with `root`
+pub fn verify(root: &[u8; 32], proof: &[[u8; 32]], wallet: &Pubkey) -> bool {
+    let hash = proof
+        .iter()
+        .fold(leaf_hash(wallet), |hash, sibling| node_hash(&hash, sibling));
+    hash == *root
+}
+
+/// Merkle tree of an allowlist, built off-chain to get the root for a game's
+/// config and the proof each wallet passes to `enter_game`
+#[derive(Debug, Clone)]
+pub struct Allowlist {
+    /// leaves first, root last
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl Allowlist {
+    /// Panics if `wallets` is empty, since nobody could ever enter the game
+    pub fn new(wallets: &[Pubkey]) -> Self {
+        assert!(!wallets.is_empty(), "allowlist must have a wallet");
+        let mut leaves: Vec<[u8; 32]> = wallets.iter().map(leaf_hash).collect();
+        leaves.sort_unstable();
+        leaves.dedup();
+
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            // an unpaired last node moves up a layer as it is
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => node_hash(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Self { layers }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// Sibling hashes from `wallet`'s leaf up to the root, or `None` if the
+    /// wallet isnt in the allowlist
+    pub fn proof(&self, wallet: &Pubkey) -> Option<Vec<[u8; 32]>> {
+        let mut index = self.layers[0].binary_search(&leaf_hash(wallet)).ok()?;
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
@@ -0,0 +1,127 @@
+use std::cmp::min;
+
+use anchor_lang::prelude::*;
+
+use crate::model::EquilibrateError;
+
+/// Fixed point scale for the factor a burn grows by over several moves
+const GROWTH_SCALE: u128 = 1_000_000_000;
+const BPS_SCALE: u128 = 10_000;
+
+/// How much each move adds to a player's burn penalty
+#[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum BurnSchedule {
+    /// Every move burns the game's burn rate
+    Flat,
+    /// The first move burns the game's burn rate, and each move after it burns
+    /// `increment_decimal_tokens_per_move` more than the one before
+    Linear {
+        increment_decimal_tokens_per_move: u64,
+    },
+    /// The first move burns the game's burn rate, and each move after it burns
+    /// `growth_bps` basis points of what the one before burned
+    Geometric { growth_bps: u32 },
+    /// Each move burns `share_bps` basis points of the player's share of the
+    /// bucket they leave. The game's burn rate is unused.
+    ShareOfBucket { share_bps: u16 },
+}
+
+impl BurnSchedule {
+    pub fn get_space() -> usize {
+        1 + // variant
+        8 // largest variant's parameter
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            // moves never get cheaper
+            BurnSchedule::Geometric { growth_bps } => {
+                require_gte!(
+                    *growth_bps as u128,
+                    BPS_SCALE,
+                    EquilibrateError::InvalidBurnSchedule
+                );
+            }
+            BurnSchedule::ShareOfBucket { share_bps } => {
+                require!(
+                    (1..=(BPS_SCALE as u16)).contains(share_bps),
+                    EquilibrateError::InvalidBurnSchedule
+                );
+            }
+            BurnSchedule::Flat | BurnSchedule::Linear { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Whether any move can burn tokens
+    pub fn burns(&self, burn_rate: u64) -> bool {
+        match self {
+            BurnSchedule::Flat | BurnSchedule::Geometric { .. } => burn_rate > 0,
+            BurnSchedule::Linear {
+                increment_decimal_tokens_per_move,
+            } => burn_rate > 0 || *increment_decimal_tokens_per_move > 0,
+            BurnSchedule::ShareOfBucket { share_bps } => *share_bps > 0,
+        }
+    }
+
+    /// Tokens the player's `move_number`th move burns (counting from 1), given
+    /// their share of the bucket they leave
+    pub fn compute_burn(&self, burn_rate: u64, move_number: u32, share_decimal_tokens: u64) -> u64 {
+        let earlier_moves = move_number.saturating_sub(1);
+        match self {
+            BurnSchedule::Flat => burn_rate,
+            BurnSchedule::Linear {
+                increment_decimal_tokens_per_move,
+            } => burn_rate.saturating_add(
+                increment_decimal_tokens_per_move.saturating_mul(earlier_moves as u64),
+            ),
+            BurnSchedule::Geometric { growth_bps } => {
+                let factor = grow(
+                    *growth_bps as u128 * GROWTH_SCALE / BPS_SCALE,
+                    earlier_moves,
+                );
+                min(
+                    u64::MAX as u128,
+                    (burn_rate as u128).saturating_mul(factor) / GROWTH_SCALE,
+                ) as u64
+            }
+            BurnSchedule::ShareOfBucket { share_bps } => {
+                (share_decimal_tokens as u128 * *share_bps as u128 / BPS_SCALE) as u64
+            }
+        }
+    }
+}
+
+/// What is left of `burn_penalty_decimal_tokens` after a player stays put for
+/// `seconds`. It halves every `half_life_seconds`, and shrinks linearly
+/// toward the next halving in between.
+pub fn decay_burn_penalty(
+    burn_penalty_decimal_tokens: u64,
+    half_life_seconds: u32,
+    seconds: u64,
+) -> u64 {
+    let half_life_seconds = half_life_seconds as u64;
+    let halvings = seconds / half_life_seconds;
+    if halvings >= u64::BITS as u64 {
+        return 0;
+    }
+    let halved = burn_penalty_decimal_tokens >> halvings;
+    let into_half_life = (seconds % half_life_seconds) as u128;
+    halved - (halved as u128 * into_half_life / (2 * half_life_seconds as u128)) as u64
+}
+
+/// `base` (scaled by `GROWTH_SCALE`) to the power of `exponent`, saturating
+/// instead of overflowing
+fn grow(mut base: u128, mut exponent: u32) -> u128 {
+    let mut result = GROWTH_SCALE;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.saturating_mul(base) / GROWTH_SCALE;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = base.saturating_mul(base) / GROWTH_SCALE;
+        }
+    }
+    result
+}
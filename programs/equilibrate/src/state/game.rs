@@ -2,6 +2,8 @@ use std::{cmp::min, mem::replace};
 
 use anchor_lang::prelude::*;
 
+use crate::state::{BurnSchedule, SpillCurve};
+
 #[account]
 #[derive(Debug, PartialEq)]
 pub struct Game {
@@ -10,6 +12,11 @@ pub struct Game {
     pub creator: Pubkey,
     pub config: GameConfig,
     pub state: GameState,
+    /// Total taken from entry deposits for the creator, including their own
+    pub creator_fees_decimal_tokens: u64,
+    /// Rounding remainders of shares that no player can claim, which go
+    /// wherever `config.dust_destination` says when the game ends
+    pub dust_decimal_tokens: u64,
 }
 
 impl Game {
@@ -19,13 +26,31 @@ impl Game {
         8 + // id
         32 + // creator
         GameConfig::get_space() +
-        GameState::get_space(n_buckets_configured)
+        GameState::get_space(n_buckets_configured) +
+        8 + // creator_fees_decimal_tokens
+        8 // dust_decimal_tokens
+    }
+
+    /// Whether the game has an end time and `now_epoch_seconds` is past it
+    pub fn is_past_end(&self, now_epoch_seconds: i64) -> bool {
+        match self.config.end_epoch_seconds {
+            Some(end_epoch_seconds) => now_epoch_seconds >= end_epoch_seconds,
+            None => false,
+        }
+    }
+
+    /// Tokens stop spilling at the game's end time, so balances are never
+    /// brought up to date past it
+    fn clamp_to_end(&self, now_epoch_seconds: i64) -> i64 {
+        match self.config.end_epoch_seconds {
+            Some(end_epoch_seconds) => min(now_epoch_seconds, end_epoch_seconds),
+            None => now_epoch_seconds,
+        }
     }
 
     pub fn update_bucket_balances(&mut self, now_epoch_seconds: u64) {
-        let seconds_since_last_update = now_epoch_seconds
-            .checked_sub(self.state.last_update_epoch_seconds.try_into().unwrap())
-            .unwrap();
+        let now_epoch_seconds = self.clamp_to_end(now_epoch_seconds.try_into().unwrap());
+        let last_update_epoch_seconds = self.state.last_update_epoch_seconds;
 
         // compute the spillover from each bucket, then
         // equally distribute that across other buckets
@@ -33,26 +58,29 @@ impl Game {
         let mut inflow: Vec<u64> = vec![0; n_buckets_including_holding];
         let mut outflow: Vec<u64> = vec![0; n_buckets_including_holding];
 
-        let bucket_players = self
+        let bucket_stakes = self
             .state
             .buckets
             .iter()
-            .map(|b| b.players)
-            .collect::<Vec<u16>>();
+            .map(|b| b.stake_decimal_tokens)
+            .collect::<Vec<u64>>();
 
         let buckets = &mut self.state.buckets;
         for i in 0..n_buckets_including_holding {
             let bucket = &mut buckets[i];
-            let spillover_i_desired = bucket.compute_spillover(
+            let spillover_i_desired = self.config.spill_curve.compute_spillover(
+                bucket,
                 self.config.spill_rate_decimal_tokens_per_second_per_player,
-                seconds_since_last_update,
+                last_update_epoch_seconds,
+                now_epoch_seconds,
             );
-            // only spill over to buckets with fewer players than this one
+            // only spill over to buckets with less at stake than this one, which
+            // is the same as having fewer players when everyone stakes the same
             let target_indices = (0..n_buckets_including_holding)
                 .collect::<Vec<usize>>()
                 .into_iter()
                 .filter_map(|j| {
-                    if (j != 0) && (j != i) && (&bucket_players[j] < &bucket.players) {
+                    if (j != 0) && (j != i) && (&bucket_stakes[j] < &bucket.stake_decimal_tokens) {
                         Some(j)
                     } else {
                         None
@@ -88,6 +116,191 @@ impl Game {
         }
     }
 
+    fn add_dust(&mut self, dust_decimal_tokens: u64) {
+        self.dust_decimal_tokens = self
+            .dust_decimal_tokens
+            .checked_add(dust_decimal_tokens)
+            .unwrap();
+    }
+
+    /// Takes all of the game's dust, which is then paid out
+    pub fn take_dust(&mut self) -> u64 {
+        replace(&mut self.dust_decimal_tokens, 0)
+    }
+
+    /// Takes the share of bucket `i_bucket` owed to `stake_decimal_tokens` of its
+    /// stake, moving the rounding remainder that the rest of the stake can't
+    /// split to the game's dust
+    fn take_share(&mut self, i_bucket: usize, stake_decimal_tokens: u64) -> u64 {
+        let (share, dust) = self.state.buckets[i_bucket].take_share(stake_decimal_tokens);
+        self.add_dust(dust);
+        share
+    }
+
+    /// Brings bucket balances up to date without moving any players
+    pub fn update(&mut self, now_epoch_seconds: i64) {
+        let now_epoch_seconds = self.clamp_to_end(now_epoch_seconds);
+        self.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
+        self.state.last_update_epoch_seconds = now_epoch_seconds;
+    }
+
+    /// Brings bucket balances up to date and adds a player with `stake_decimal_tokens`
+    /// to bucket `i_bucket`, crediting their deposit to the holding bucket.
+    pub fn add_player(
+        &mut self,
+        i_bucket: usize,
+        stake_decimal_tokens: u64,
+        deposit_decimal_tokens: u64,
+        now_epoch_seconds: i64,
+    ) {
+        let now_epoch_seconds = self.clamp_to_end(now_epoch_seconds);
+        self.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
+        let buckets = &mut self.state.buckets;
+        buckets[0].decimal_tokens = buckets[0]
+            .decimal_tokens
+            .checked_add(deposit_decimal_tokens)
+            .unwrap();
+        buckets[0].add(stake_decimal_tokens);
+        buckets[i_bucket].add(stake_decimal_tokens);
+        self.state.last_update_epoch_seconds = now_epoch_seconds;
+    }
+
+    /// Brings bucket balances up to date and moves a player with `stake_decimal_tokens`
+    /// between buckets. Returns the player's share of the bucket they left, which
+    /// stays behind in it.
+    pub fn move_player(
+        &mut self,
+        i_from: usize,
+        i_to: usize,
+        stake_decimal_tokens: u64,
+        now_epoch_seconds: i64,
+    ) -> u64 {
+        let now_epoch_seconds = self.clamp_to_end(now_epoch_seconds);
+        self.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
+        let buckets = &mut self.state.buckets;
+        let share = buckets[i_from].share(stake_decimal_tokens);
+        buckets[i_from].remove(stake_decimal_tokens);
+        buckets[i_to].add(stake_decimal_tokens);
+        self.state.last_update_epoch_seconds = now_epoch_seconds;
+        share
+    }
+
+    /// Brings bucket balances up to date and credits a top-up from a player in
+    /// bucket `i_bucket` wherever the game sends top-ups
+    pub fn top_up(&mut self, i_bucket: usize, deposit_decimal_tokens: u64, now_epoch_seconds: i64) {
+        let now_epoch_seconds = self.clamp_to_end(now_epoch_seconds);
+        self.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
+        let i_destination = match self.config.top_up_destination {
+            TopUpDestination::HoldingBucket => 0,
+            TopUpDestination::PlayerBucket => i_bucket,
+        };
+        self.state.buckets[i_destination].credit(deposit_decimal_tokens);
+        self.state.last_update_epoch_seconds = now_epoch_seconds;
+    }
+
+    /// Routes a burn penalty of `penalty_decimal_tokens`, charged to a player in
+    /// bucket `i_bucket` who is left with `winnings_decimal_tokens`, to wherever
+    /// the game sends penalties. Penalties that go back into the pot are credited
+    /// to its buckets, or given back to the player when no one is left to share
+    /// them. Returns the player's winnings and the part of the penalty that
+    /// leaves the pool.
+    pub fn route_penalty(
+        &mut self,
+        i_bucket: usize,
+        winnings_decimal_tokens: u64,
+        penalty_decimal_tokens: u64,
+    ) -> (u64, u64) {
+        let buckets = &mut self.state.buckets;
+        match self.config.penalty_destination {
+            PenaltyDestination::Burn | PenaltyDestination::Account { .. } => {
+                return (winnings_decimal_tokens, penalty_decimal_tokens)
+            }
+            // the last player already took everything left in the pot
+            _ if buckets[0].players == 0 => {
+                return (
+                    winnings_decimal_tokens
+                        .checked_add(penalty_decimal_tokens)
+                        .unwrap(),
+                    0,
+                )
+            }
+            PenaltyDestination::HoldingBucket => buckets[0].credit(penalty_decimal_tokens),
+            PenaltyDestination::OtherBuckets => {
+                // buckets other than the holding bucket and the player's
+                let n_other_buckets = (buckets.len() - 2) as u64;
+                let to_each_bucket = penalty_decimal_tokens / n_other_buckets;
+                for (i, bucket) in buckets.iter_mut().enumerate().skip(1) {
+                    if i != i_bucket {
+                        bucket.credit(to_each_bucket);
+                    }
+                }
+                buckets[0].credit(penalty_decimal_tokens - to_each_bucket * n_other_buckets);
+            }
+        }
+        (winnings_decimal_tokens, 0)
+    }
+
+    /// Brings bucket balances up to date and takes the share of bucket `i_bucket`
+    /// owed to `stake_decimal_tokens` of a player's stake, which they give up.
+    /// The player stays in the game with the rest of their stake. The holding
+    /// bucket loses the stake but keeps its tokens, which go to the other
+    /// players' shares of it.
+    pub fn withdraw(
+        &mut self,
+        i_bucket: usize,
+        stake_decimal_tokens: u64,
+        now_epoch_seconds: i64,
+    ) -> u64 {
+        let now_epoch_seconds = self.clamp_to_end(now_epoch_seconds);
+        self.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
+        let share = self.take_share(i_bucket, stake_decimal_tokens);
+        let buckets = &mut self.state.buckets;
+        buckets[0].reduce_stake(stake_decimal_tokens);
+        buckets[i_bucket].reduce_stake(stake_decimal_tokens);
+        self.state.last_update_epoch_seconds = now_epoch_seconds;
+        share
+    }
+
+    /// Removes a player with `stake_decimal_tokens` from bucket `i_bucket` and returns
+    /// their stake-weighted share of the bucket, before any burn penalty. The last
+    /// player in the game gets all remaining tokens, and the game's dust if it goes
+    /// to them. Once the game is past its end time, players also get a stake-weighted
+    /// share of whatever is left in the holding bucket.
+    pub fn remove_player(
+        &mut self,
+        i_bucket: usize,
+        stake_decimal_tokens: u64,
+        now_epoch_seconds: i64,
+    ) -> u64 {
+        let is_past_end = self.is_past_end(now_epoch_seconds);
+        let now_epoch_seconds = self.clamp_to_end(now_epoch_seconds);
+        let mut winnings: u64;
+        if self.get_player_count() == 1 {
+            // if this is the player to end the game, give them all the remaining tokens
+            winnings = self.state.buckets.iter().map(|b| b.decimal_tokens).sum();
+            for bucket in self.state.buckets.iter_mut() {
+                bucket.decimal_tokens = 0;
+                bucket.players = 0;
+                bucket.stake_decimal_tokens = 0;
+            }
+            if self.config.dust_destination == DustDestination::LastLeaver {
+                winnings = winnings.checked_add(self.take_dust()).unwrap();
+            }
+        } else {
+            self.update_bucket_balances(now_epoch_seconds.try_into().unwrap());
+            winnings = self.take_share(i_bucket, stake_decimal_tokens);
+            if is_past_end {
+                let holding_share = self.take_share(0, stake_decimal_tokens);
+                winnings = winnings.checked_add(holding_share).unwrap();
+            }
+            let buckets = &mut self.state.buckets;
+            buckets[0].remove(stake_decimal_tokens);
+            buckets[i_bucket].remove(stake_decimal_tokens);
+        }
+        self.state.last_update_epoch_seconds = now_epoch_seconds;
+        winnings
+    }
+
     pub fn get_player_count(&self) -> u16 {
         self.state
             .buckets
@@ -102,6 +315,14 @@ impl Game {
         msg!("Initialized game {}", self.id);
     }
 
+    pub fn log_update(&self) {
+        msg!(
+            "Updated game {} to {}",
+            self.id,
+            self.state.last_update_epoch_seconds
+        );
+    }
+
     pub fn log_end(&self) {
         msg!("Ended game {}", self.id);
     }
@@ -109,12 +330,41 @@ impl Game {
 
 #[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct GameConfig {
+    /// Mint of the tokens the game is played for, or the default pubkey for a
+    /// game played for lamports, whose decimal tokens are then lamports
     pub mint: Pubkey,
     pub entry_fee_decimal_tokens: u64,
     pub spill_rate_decimal_tokens_per_second_per_player: u64,
     pub n_buckets: u8,
     pub max_players: u16,
     pub burn_rate_decimal_tokens_per_move: u64,
+    /// Time after which players can no longer enter or move, and tokens stop
+    /// spilling. Remaining players can then be settled by anyone.
+    pub end_epoch_seconds: Option<i64>,
+    /// How fast tokens spill out of buckets
+    pub spill_curve: SpillCurve,
+    /// Merkle root of the wallets allowed to enter, or `None` for a public game.
+    /// See `state::allowlist`.
+    pub allowlist_root: Option<[u8; 32]>,
+    /// Share of each entry deposit paid to the game creator instead of the
+    /// holding bucket, in basis points
+    pub creator_fee_bps: u16,
+    /// Most a player can stake when entering. `entry_fee_decimal_tokens` is the
+    /// least, and games where the two are equal have a fixed stake.
+    pub max_stake_decimal_tokens: u64,
+    /// Which bucket tokens that players add after entering are credited to
+    pub top_up_destination: TopUpDestination,
+    /// Least time a player has to wait after moving before they can move again
+    pub min_seconds_between_moves: u32,
+    /// How much each move adds to a player's burn penalty
+    pub burn_schedule: BurnSchedule,
+    /// Time over which a player's burn penalty halves while they stay in their
+    /// bucket, or `None` if it never shrinks
+    pub burn_penalty_half_life_seconds: Option<u32>,
+    /// Where burn penalties go when players leave or withdraw
+    pub penalty_destination: PenaltyDestination,
+    /// Where the game's dust goes when it ends
+    pub dust_destination: DustDestination,
 }
 
 impl GameConfig {
@@ -124,10 +374,98 @@ impl GameConfig {
         8 + // spill_rate_decimal_tokens_per_second_per_player
         1 + // n_buckets
         8 + // max_players
-        8 // burn_rate_decimal_tokens_per_move
+        8 + // burn_rate_decimal_tokens_per_move
+        1 + 8 + // end_epoch_seconds
+        SpillCurve::get_space() +
+        1 + 32 + // allowlist_root
+        2 + // creator_fee_bps
+        8 + // max_stake_decimal_tokens
+        1 + // top_up_destination
+        4 + // min_seconds_between_moves
+        BurnSchedule::get_space() +
+        1 + 4 + // burn_penalty_half_life_seconds
+        PenaltyDestination::get_space() +
+        1 // dust_destination
+    }
+
+    /// Whether deposits are lamports held in the game's `LamportVault` rather
+    /// than tokens held in the mint's token pool
+    pub fn is_lamport_game(&self) -> bool {
+        self.mint == Pubkey::default()
+    }
+
+    /// Whether a player can enter with `stake_decimal_tokens`
+    pub fn is_valid_stake(&self, stake_decimal_tokens: u64) -> bool {
+        (self.entry_fee_decimal_tokens..=self.max_stake_decimal_tokens)
+            .contains(&stake_decimal_tokens)
+    }
+
+    /// Whether a player who last moved at `last_move_epoch_seconds` can move
+    /// again at `now_epoch_seconds`. Players who never moved have 0 there and
+    /// can always move.
+    pub fn is_move_cooldown_over(
+        &self,
+        last_move_epoch_seconds: i64,
+        now_epoch_seconds: i64,
+    ) -> bool {
+        last_move_epoch_seconds == 0
+            || now_epoch_seconds
+                >= last_move_epoch_seconds
+                    .checked_add(self.min_seconds_between_moves as i64)
+                    .unwrap()
+    }
+
+    /// Part of an entry deposit of `stake_decimal_tokens` that goes to the creator,
+    /// rounded down
+    pub fn creator_fee_decimal_tokens(&self, stake_decimal_tokens: u64) -> u64 {
+        (stake_decimal_tokens as u128 * self.creator_fee_bps as u128 / 10_000) as u64
     }
 }
 
+/// Where tokens that a player adds to their position go
+#[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum TopUpDestination {
+    /// Shared by every player in the game, like entry deposits
+    HoldingBucket,
+    /// Shared by the players in the bucket of the player topping up
+    PlayerBucket,
+}
+
+/// Where the burn penalty charged to a player who leaves or withdraws goes
+#[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum PenaltyDestination {
+    /// Burned, reducing the mint's supply
+    Burn,
+    /// Back into the holding bucket, shared by every player in the game
+    HoldingBucket,
+    /// Split evenly among the buckets other than the player's, with any
+    /// remainder going to the holding bucket
+    OtherBuckets,
+    /// Paid to a token account of `owner`, such as the game creator or a
+    /// treasury, which is passed to the instruction charging the penalty.
+    /// Lamport games pay `owner` directly.
+    Account { owner: Pubkey },
+}
+
+impl PenaltyDestination {
+    pub fn get_space() -> usize {
+        1 + // variant
+        32 // owner
+    }
+}
+
+/// Who gets a game's dust when the game ends
+#[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum DustDestination {
+    /// The last player to leave, along with everything else left in the game
+    LastLeaver,
+    /// The game creator, paid into a token account of theirs passed to the
+    /// instruction ending the game. Lamport games pay the creator's wallet.
+    Creator,
+    /// Burned, reducing the mint's supply
+    Burn,
+}
+
 #[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct GameState {
     pub buckets: Vec<Bucket>,
@@ -148,12 +486,63 @@ pub struct Bucket {
     /// Number of players currently occupying this bucket. For the holding bucket
     /// this will be the total number of players in the game.
     pub players: u16,
+    /// Sum of the stakes of the players in this bucket, which weighs their
+    /// shares of it. For the holding bucket this is the game's total stake.
+    pub stake_decimal_tokens: u64,
 }
 
 impl Bucket {
     pub fn get_space() -> usize {
         8 + // decimal_tokens
-        2 // players
+        2 + // players
+        8 // stake_decimal_tokens
+    }
+
+    fn add(&mut self, stake_decimal_tokens: u64) {
+        self.players = self.players.checked_add(1).unwrap();
+        self.stake_decimal_tokens = self
+            .stake_decimal_tokens
+            .checked_add(stake_decimal_tokens)
+            .unwrap();
+    }
+
+    fn credit(&mut self, decimal_tokens: u64) {
+        self.decimal_tokens = self.decimal_tokens.checked_add(decimal_tokens).unwrap();
+    }
+
+    fn remove(&mut self, stake_decimal_tokens: u64) {
+        self.players = self.players.checked_sub(1).unwrap();
+        self.reduce_stake(stake_decimal_tokens);
+    }
+
+    fn reduce_stake(&mut self, stake_decimal_tokens: u64) {
+        self.stake_decimal_tokens = self
+            .stake_decimal_tokens
+            .checked_sub(stake_decimal_tokens)
+            .unwrap();
+    }
+
+    /// Share of the balance owed to `stake_decimal_tokens` of the bucket's
+    /// stake, rounded down
+    fn share(&self, stake_decimal_tokens: u64) -> u64 {
+        (self.decimal_tokens as u128)
+            .checked_mul(stake_decimal_tokens as u128)
+            .unwrap()
+            .checked_div(self.stake_decimal_tokens as u128)
+            .unwrap() as u64
+    }
+
+    /// Takes the share of the balance owed to `stake_decimal_tokens` of the
+    /// bucket's stake, rounded down, leaving the share owed to the rest of the
+    /// stake, also rounded down. Returns the share and the remainder neither
+    /// share gets, which is also taken.
+    fn take_share(&mut self, stake_decimal_tokens: u64) -> (u64, u64) {
+        let share = self.share(stake_decimal_tokens);
+        let rest_stake_decimal_tokens = self.stake_decimal_tokens - stake_decimal_tokens;
+        let rest = self.share(rest_stake_decimal_tokens);
+        let remainder = self.decimal_tokens - share - rest;
+        self.decimal_tokens = rest;
+        (share, remainder)
     }
 
     pub fn compute_spillover(
@@ -0,0 +1,117 @@
+use anchor_lang::{prelude::*, system_program};
+
+use crate::{
+    constants::LAMPORT_VAULT_SEED, id, model::EquilibrateError, state::PenaltyDestination,
+};
+
+/// Holds the deposits of a game played for lamports, on top of its own rent.
+/// The program owns it, so payouts are debited from it directly.
+#[account]
+#[derive(Debug, Copy, PartialEq)]
+pub struct LamportVault {
+    pub game_id: u64,
+}
+
+impl LamportVault {
+    pub fn get_space() -> usize {
+        8 + // account discriminator
+        8 // game_id
+    }
+
+    pub fn get_address(game_id: u64) -> (Pubkey, u8) {
+        let seeds = [LAMPORT_VAULT_SEED.as_ref(), &game_id.to_le_bytes()];
+        Pubkey::find_program_address(&seeds, &id())
+    }
+
+    /// Transfers `lamports` from `payer` into `vault`
+    pub fn deposit<'info>(
+        vault: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        lamports: u64,
+    ) -> Result<()> {
+        let context = CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: vault.clone(),
+            },
+        );
+        system_program::transfer(context, lamports)
+    }
+
+    /// Wallet that a penalty of `penalty_lamports` leaving the vault is paid to,
+    /// passed as the first of `remaining_accounts`, or `None` if there is no
+    /// penalty to pay. Lamports can't be burned, so that is the only place a
+    /// penalty can leave the vault for.
+    pub fn penalty_destination_account<'a, 'info>(
+        penalty_destination: &PenaltyDestination,
+        penalty_lamports: u64,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Option<&'a AccountInfo<'info>>> {
+        if penalty_lamports == 0 {
+            return Ok(None);
+        }
+        let owner = match penalty_destination {
+            PenaltyDestination::Account { owner } => owner,
+            // games that would burn penalties are rejected when they are made
+            _ => return err!(EquilibrateError::CannotBurnNativeMint),
+        };
+        let account = remaining_accounts
+            .first()
+            .ok_or(EquilibrateError::MissingPenaltyDestination)?;
+        require_keys_eq!(
+            account.key(),
+            *owner,
+            EquilibrateError::InvalidPenaltyDestination
+        );
+        Ok(Some(account))
+    }
+
+    /// Takes `penalty_lamports` out of `vault`, paying it to `penalty_destination`,
+    /// and pays `winnings_lamports` to `destination`
+    pub fn pay_out(
+        vault: &AccountInfo,
+        destination: &AccountInfo,
+        winnings_lamports: u64,
+        penalty_lamports: u64,
+        penalty_destination: Option<&AccountInfo>,
+    ) -> Result<()> {
+        if let Some(penalty_destination) = penalty_destination {
+            move_lamports(vault, penalty_destination, penalty_lamports)?;
+            msg!("Paid a penalty of {} lamports", penalty_lamports);
+        }
+        move_lamports(vault, destination, winnings_lamports)
+    }
+
+    /// Takes the `dust_lamports` of a game that ended out of `vault`, paying it to
+    /// the game creator's wallet, the only place it can go since lamports can't be
+    /// burned
+    pub fn pay_dust(
+        vault: &AccountInfo,
+        game_creator: &AccountInfo,
+        dust_lamports: u64,
+    ) -> Result<()> {
+        move_lamports(vault, game_creator, dust_lamports)?;
+        if dust_lamports > 0 {
+            msg!("Paid dust of {} lamports", dust_lamports);
+        }
+        Ok(())
+    }
+
+    pub fn log_make(&self) {
+        msg!("Initialized lamport vault of game {}", self.game_id);
+    }
+}
+
+/// Moves `lamports` from `from`, which the program must own, to `to`
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> Result<()> {
+    let from_lamports = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let to_lamports = to.lamports().checked_add(lamports).unwrap();
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    Ok(())
+}
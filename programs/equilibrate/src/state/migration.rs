@@ -0,0 +1,308 @@
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+use crate::{
+    model::EquilibrateError,
+    state::{
+        Bucket, BurnSchedule, DustDestination, Game, GameConfig, GameState, PenaltyDestination,
+        PlayerState, PoolManager, ProgramConfig, ProgramSettings, SpillCurve, TopUpDestination,
+    },
+};
+
+// `Game`, `PlayerState` and `ProgramConfig` each have their own version. Every
+// change to one of their layouts bumps that type's version and adds a step that
+// takes an account at the previous version to the new one, so accounts of the
+// other types are left alone. A copy of each old layout is kept for the steps
+// to read. Each migration applies one step, so an account is migrated once per
+// version it is behind, and stays unusable until it reaches the current one.
+// The first release wrote every account at version 1, and a single step takes
+// each type from there to version 2.
+//
+// The discriminator and version always come first, so any version can be read.
+//
+// `PoolManager` has no version. Its only older layout is told apart by its size.
+
+/// An account upgraded to a newer layout, serialized with its discriminator,
+/// and the space the account needs to hold it
+pub struct MigratedAccount {
+    data: Vec<u8>,
+    space: usize,
+}
+
+impl MigratedAccount {
+    pub fn new<T: Discriminator>(account: &impl AnchorSerialize, min_space: usize) -> Result<Self> {
+        let mut data = T::discriminator().to_vec();
+        account.serialize(&mut data)?;
+        Ok(MigratedAccount {
+            space: min_space.max(data.len()),
+            data,
+        })
+    }
+}
+
+/// Returns the version of an account of type `T` written by this program,
+/// which must be older than `current_version` to be migrated
+pub fn read_version<T: Discriminator>(account: &AccountInfo, current_version: u8) -> Result<u8> {
+    let data = account.try_borrow_data()?;
+    require!(data.len() > 8, ErrorCode::AccountDiscriminatorNotFound);
+    require!(
+        data[..8] == T::discriminator(),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let version = data[8];
+    require_neq!(
+        version,
+        current_version,
+        EquilibrateError::AccountAlreadyMigrated
+    );
+    Ok(version)
+}
+
+/// `Game` at version 1, as the first release wrote it
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GameV1 {
+    pub version: u8,
+    pub id: u64,
+    pub creator: Pubkey,
+    pub config: GameConfigV1,
+    pub state: GameStateV1,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GameConfigV1 {
+    pub mint: Pubkey,
+    pub entry_fee_decimal_tokens: u64,
+    pub spill_rate_decimal_tokens_per_second_per_player: u64,
+    pub n_buckets: u8,
+    pub max_players: u16,
+    pub burn_rate_decimal_tokens_per_move: u64,
+}
+
+/// `GameState` at version 1, before buckets tracked their stake
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GameStateV1 {
+    pub buckets: Vec<BucketV1>,
+    pub last_update_epoch_seconds: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BucketV1 {
+    pub decimal_tokens: u64,
+    pub players: u16,
+}
+
+fn game_v1_to_v2(game: GameV1) -> Game {
+    // everyone in the first games staked the entry fee
+    let entry_fee_decimal_tokens = game.config.entry_fee_decimal_tokens;
+    Game {
+        version: 2,
+        id: game.id,
+        creator: game.creator,
+        config: GameConfig {
+            mint: game.config.mint,
+            entry_fee_decimal_tokens,
+            spill_rate_decimal_tokens_per_second_per_player: game
+                .config
+                .spill_rate_decimal_tokens_per_second_per_player,
+            n_buckets: game.config.n_buckets,
+            max_players: game.config.max_players,
+            burn_rate_decimal_tokens_per_move: game.config.burn_rate_decimal_tokens_per_move,
+            // the first games ran until the last player left, spilled linearly,
+            // were public and charged no creator fee
+            end_epoch_seconds: None,
+            spill_curve: SpillCurve::Linear,
+            allowlist_root: None,
+            creator_fee_bps: 0,
+            max_stake_decimal_tokens: entry_fee_decimal_tokens,
+            // top-ups are shared like entry deposits
+            top_up_destination: TopUpDestination::HoldingBucket,
+            // players could move as often as they liked, every move burned the
+            // burn rate, and penalties never shrank and were always burned
+            min_seconds_between_moves: 0,
+            burn_schedule: BurnSchedule::Flat,
+            burn_penalty_half_life_seconds: None,
+            penalty_destination: PenaltyDestination::Burn,
+            // remainders stayed in the buckets, where the last player took them
+            dust_destination: DustDestination::LastLeaver,
+        },
+        state: GameState {
+            buckets: game
+                .state
+                .buckets
+                .into_iter()
+                .map(|bucket| Bucket {
+                    decimal_tokens: bucket.decimal_tokens,
+                    players: bucket.players,
+                    stake_decimal_tokens: (bucket.players as u64)
+                        .checked_mul(entry_fee_decimal_tokens)
+                        .unwrap(),
+                })
+                .collect(),
+            last_update_epoch_seconds: game.state.last_update_epoch_seconds,
+        },
+        creator_fees_decimal_tokens: 0,
+        // remainders already in the buckets aren't told apart from the rest
+        dust_decimal_tokens: 0,
+    }
+}
+
+/// Reads a game at `version` (without its discriminator) and upgrades it to
+/// the next version
+pub fn upgrade_game(version: u8, mut data: &[u8]) -> Result<MigratedAccount> {
+    match version {
+        1 => {
+            let game = game_v1_to_v2(GameV1::deserialize(&mut data)?);
+            MigratedAccount::new::<Game>(&game, Game::get_space(game.config.n_buckets))
+        }
+        _ => {
+            msg!("Cannot migrate game from version {}", version);
+            err!(EquilibrateError::UnknownAccountVersion)
+        }
+    }
+}
+
+/// `PlayerState` at version 1, as the first release wrote it
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PlayerStateV1 {
+    pub version: u8,
+    pub bucket: u8,
+    pub burn_penalty_decimal_tokens: u64,
+}
+
+fn player_v1_to_v2(player: PlayerStateV1, entry_fee_decimal_tokens: u64) -> PlayerState {
+    PlayerState {
+        version: 2,
+        bucket: player.bucket,
+        burn_penalty_decimal_tokens: player.burn_penalty_decimal_tokens,
+        // players in the first games all staked the entry fee and never topped up
+        stake_decimal_tokens: entry_fee_decimal_tokens,
+        added_decimal_tokens: 0,
+        // no cooldown applied before, so treat the player as never having moved
+        last_move_epoch_seconds: 0,
+        // the first games burn the same for every move, so earlier moves dont matter
+        moves: 0,
+        has_session: false,
+    }
+}
+
+/// Reads a player state at `version` (without its discriminator) and upgrades
+/// it to the next version. `game` is the player's game, already migrated.
+pub fn upgrade_player(version: u8, mut data: &[u8], game: &Game) -> Result<MigratedAccount> {
+    match version {
+        1 => {
+            let player = player_v1_to_v2(
+                PlayerStateV1::deserialize(&mut data)?,
+                game.config.entry_fee_decimal_tokens,
+            );
+            MigratedAccount::new::<PlayerState>(&player, PlayerState::get_space())
+        }
+        _ => {
+            msg!("Cannot migrate player state from version {}", version);
+            err!(EquilibrateError::UnknownAccountVersion)
+        }
+    }
+}
+
+/// `ProgramConfig` at version 1, as the first release wrote it
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProgramConfigV1 {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub settings: ProgramSettingsV1,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProgramSettingsV1 {
+    pub program_fee_destination: Pubkey,
+    pub program_fee_lamports: u64,
+    pub game_max_buckets: u8,
+    pub game_max_players: u16,
+}
+
+fn program_config_v1_to_v2(program_config: ProgramConfigV1) -> ProgramConfig {
+    ProgramConfig {
+        version: 2,
+        admin: program_config.admin,
+        settings: ProgramSettings {
+            program_fee_destination: program_config.settings.program_fee_destination,
+            program_fee_lamports: program_config.settings.program_fee_lamports,
+            game_max_buckets: program_config.settings.game_max_buckets,
+            game_max_players: program_config.settings.game_max_players,
+            // creators can't take a fee until the admin allows it
+            game_max_creator_fee_bps: 0,
+            // the whole program fee goes to the program until the admin says otherwise
+            referral_fee_bps: 0,
+        },
+    }
+}
+
+/// Reads a program config at `version` (without its discriminator) and
+/// upgrades it to the next version
+pub fn upgrade_program_config(version: u8, mut data: &[u8]) -> Result<MigratedAccount> {
+    match version {
+        1 => MigratedAccount::new::<ProgramConfig>(
+            &program_config_v1_to_v2(ProgramConfigV1::deserialize(&mut data)?),
+            ProgramConfig::get_space(),
+        ),
+        _ => {
+            msg!("Cannot migrate program config from version {}", version);
+            err!(EquilibrateError::UnknownAccountVersion)
+        }
+    }
+}
+
+/// `PoolManager` before it recorded the pool's liabilities
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PoolManagerV1 {
+    pub bump: u8,
+}
+
+/// Upgrades a pool manager that doesnt record liabilities yet. Nothing says
+/// how much of the pool its games own by then, so all of
+/// `pool_decimal_tokens` is taken to be owed to them, and only a surplus
+/// that builds up afterwards shows in audits.
+pub fn upgrade_pool_manager(data: &[u8], pool_decimal_tokens: u64) -> Result<PoolManager> {
+    require!(data.len() > 8, ErrorCode::AccountDiscriminatorNotFound);
+    require!(
+        data[..8] == PoolManager::discriminator(),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    require_gt!(
+        PoolManager::get_space(),
+        data.len(),
+        EquilibrateError::AccountAlreadyMigrated
+    );
+    let pool_manager = PoolManagerV1::deserialize(&mut &data[8..])?;
+    Ok(PoolManager {
+        bump: pool_manager.bump,
+        liabilities_decimal_tokens: pool_decimal_tokens,
+    })
+}
+
+/// Writes `migrated` over `account`, first growing it and topping up its rent
+/// from `payer` if the new layout is larger. Accounts never shrink.
+pub fn write_migrated<'info>(
+    account: &AccountInfo<'info>,
+    migrated: &MigratedAccount,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let space = migrated.space;
+    if space > account.data_len() {
+        let missing_rent = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(account.lamports());
+        if missing_rent > 0 {
+            let accounts = system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            };
+            let context = CpiContext::new(system_program.clone(), accounts);
+            system_program::transfer(context, missing_rent)?;
+        }
+        account.realloc(space, true)?;
+    }
+
+    let mut data = account.try_borrow_mut_data()?;
+    data[..migrated.data.len()].copy_from_slice(&migrated.data);
+    Ok(())
+}
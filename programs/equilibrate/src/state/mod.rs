@@ -1,8 +1,34 @@
 pub mod game;
 pub use game::*;
 
+pub mod spill_curve;
+pub use spill_curve::*;
+
+pub mod burn_schedule;
+pub use burn_schedule::*;
+
 pub mod player;
 pub use player::*;
 
 pub mod pool_manager;
 pub use pool_manager::*;
+
+pub mod lamport_vault;
+pub use lamport_vault::*;
+
+pub mod program_config;
+pub use program_config::*;
+
+pub mod referral_stats;
+pub use referral_stats::*;
+
+pub mod session_authority;
+pub use session_authority::*;
+
+pub mod token_interface;
+
+pub mod system_interface;
+
+pub mod allowlist;
+
+pub mod migration;
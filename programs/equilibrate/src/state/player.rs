@@ -1,11 +1,30 @@
+use std::cmp::min;
+
 use anchor_lang::prelude::*;
 
+use crate::state::{decay_burn_penalty, GameConfig};
+
 #[account]
 #[derive(Debug, Copy, PartialEq)]
 pub struct PlayerState {
     pub version: u8,
     pub bucket: u8,
+    /// Burn penalty as of the player's last move, which decays from there in
+    /// games whose penalties decay. See `resolved_burn_penalty`.
     pub burn_penalty_decimal_tokens: u64,
+    /// What the player deposited to enter, which weighs their share of a bucket.
+    /// Withdrawing gives up part of it.
+    pub stake_decimal_tokens: u64,
+    /// What the player has added since entering, less the part given up by
+    /// withdrawing. It doesnt change their share.
+    pub added_decimal_tokens: u64,
+    /// When the player last moved buckets, or 0 if they never have
+    pub last_move_epoch_seconds: i64,
+    /// How many times the player has moved buckets
+    pub moves: u32,
+    /// Whether the player has made a session for the game. Leaving or being
+    /// settled then needs the session's address, to close it if it's still open.
+    pub has_session: bool,
 }
 
 impl PlayerState {
@@ -13,7 +32,118 @@ impl PlayerState {
         8 + // account discriminator
         1 + // version
         1 + // bucket
-        8 // burn_penalty_decimal_tokens
+        8 + // burn_penalty_decimal_tokens
+        8 + // stake_decimal_tokens
+        8 + // added_decimal_tokens
+        8 + // last_move_epoch_seconds
+        4 + // moves
+        1 // has_session
+    }
+
+    /// Everything the player has put into the game
+    pub fn deposited_decimal_tokens(&self) -> u64 {
+        self.stake_decimal_tokens
+            .checked_add(self.added_decimal_tokens)
+            .unwrap()
+    }
+
+    /// The player's burn penalty at `now_epoch_seconds`, once it has decayed for
+    /// the time they stayed in their bucket since their last move. Time past the
+    /// game's end doesnt count.
+    pub fn resolved_burn_penalty(&self, config: &GameConfig, now_epoch_seconds: i64) -> u64 {
+        match config.burn_penalty_half_life_seconds {
+            // players who never moved have no penalty to decay
+            Some(half_life_seconds) if self.last_move_epoch_seconds > 0 => {
+                let now_epoch_seconds = config
+                    .end_epoch_seconds
+                    .map_or(now_epoch_seconds, |end| min(now_epoch_seconds, end));
+                let still_seconds = (now_epoch_seconds - self.last_move_epoch_seconds).max(0);
+                decay_burn_penalty(
+                    self.burn_penalty_decimal_tokens,
+                    half_life_seconds,
+                    still_seconds as u64,
+                )
+            }
+            _ => self.burn_penalty_decimal_tokens,
+        }
+    }
+
+    /// Moves the player to bucket `i_bucket`, adding what the game's burn
+    /// schedule burns for this move to their penalty after it decays.
+    /// `share_decimal_tokens` is their share of the bucket they left.
+    pub fn record_move(
+        &mut self,
+        config: &GameConfig,
+        i_bucket: u8,
+        share_decimal_tokens: u64,
+        now_epoch_seconds: i64,
+    ) {
+        let move_number = self.moves.checked_add(1).unwrap();
+        let burn_decimal_tokens = config.burn_schedule.compute_burn(
+            config.burn_rate_decimal_tokens_per_move,
+            move_number,
+            share_decimal_tokens,
+        );
+        self.burn_penalty_decimal_tokens = self
+            .resolved_burn_penalty(config, now_epoch_seconds)
+            .saturating_add(burn_decimal_tokens);
+        self.bucket = i_bucket;
+        self.moves = move_number;
+        self.last_move_epoch_seconds = now_epoch_seconds;
+    }
+
+    /// Splits the player's share of the game into their winnings and the part
+    /// burned for their moves, which is never more than the share
+    pub fn apply_burn_penalty(
+        &self,
+        config: &GameConfig,
+        share_decimal_tokens: u64,
+        now_epoch_seconds: i64,
+    ) -> (u64, u64) {
+        let burn_decimal_tokens = min(
+            share_decimal_tokens,
+            self.resolved_burn_penalty(config, now_epoch_seconds),
+        );
+        (
+            share_decimal_tokens - burn_decimal_tokens,
+            burn_decimal_tokens,
+        )
+    }
+
+    /// Part of the player's stake given up by withdrawing `fraction_bps` of
+    /// their position, rounded down
+    pub fn stake_fraction(&self, fraction_bps: u16) -> u64 {
+        (self.stake_decimal_tokens as u128 * fraction_bps as u128 / 10_000) as u64
+    }
+
+    /// Shrinks the player's position by `stake_decimal_tokens` of their stake,
+    /// along with the same fraction of what they added and of their burn
+    /// penalty. Splits the share they withdrew into their winnings and the
+    /// part burned, like `apply_burn_penalty` does for the whole penalty.
+    pub fn withdraw(
+        &mut self,
+        config: &GameConfig,
+        stake_decimal_tokens: u64,
+        share_decimal_tokens: u64,
+        now_epoch_seconds: i64,
+    ) -> (u64, u64) {
+        let fraction_of = |decimal_tokens: u64| {
+            (decimal_tokens as u128 * stake_decimal_tokens as u128
+                / self.stake_decimal_tokens as u128) as u64
+        };
+        let resolved_burn_decimal_tokens =
+            fraction_of(self.resolved_burn_penalty(config, now_epoch_seconds));
+        let burn_penalty_decimal_tokens = fraction_of(self.burn_penalty_decimal_tokens);
+        let added_decimal_tokens = fraction_of(self.added_decimal_tokens);
+        self.burn_penalty_decimal_tokens -= burn_penalty_decimal_tokens;
+        self.added_decimal_tokens -= added_decimal_tokens;
+        self.stake_decimal_tokens -= stake_decimal_tokens;
+
+        let burn_decimal_tokens = min(share_decimal_tokens, resolved_burn_decimal_tokens);
+        (
+            share_decimal_tokens - burn_decimal_tokens,
+            burn_decimal_tokens,
+        )
     }
 
     pub fn log_make(&self) {
@@ -32,7 +162,27 @@ impl PlayerState {
         }
     }
 
+    pub fn log_top_up(&self, deposit_decimal_tokens: u64) {
+        msg!(
+            "Added {} decimal tokens, {} in total",
+            deposit_decimal_tokens,
+            self.added_decimal_tokens
+        );
+    }
+
+    pub fn log_withdraw(&self, winnings: u64) {
+        msg!(
+            "Withdrew {} decimal tokens, {} decimal tokens still at stake",
+            winnings,
+            self.stake_decimal_tokens
+        );
+    }
+
     pub fn log_leave(&self, winnings: u64) {
         msg!("Left and won {} decimal tokens", winnings);
     }
+
+    pub fn log_settle(&self, winnings: u64) {
+        msg!("Settled and won {} decimal tokens", winnings);
+    }
 }
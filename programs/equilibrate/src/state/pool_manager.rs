@@ -1,26 +1,49 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::TokenAccount;
 
 use crate::{
     constants::{POOL_MANAGER_SEED, POOL_SEED},
     id,
     model::EquilibrateError,
+    state::{token_interface, DustDestination, PenaltyDestination},
 };
 
 #[account]
 #[derive(Debug, Copy, PartialEq)]
 pub struct PoolManager {
     pub bump: u8,
+    /// what the token pool owes the live games of its mint, which is the sum
+    /// of their bucket balances and dust
+    pub liabilities_decimal_tokens: u64,
 }
 
 impl PoolManager {
     pub fn get_space() -> usize {
         8 + // account discriminator
-        1 // manager bump
+        1 + // manager bump
+        8 // liabilities_decimal_tokens
+    }
+
+    /// Records `decimal_tokens` that the token pool received for a game
+    pub fn add_liabilities(&mut self, decimal_tokens: u64) {
+        self.liabilities_decimal_tokens = self
+            .liabilities_decimal_tokens
+            .checked_add(decimal_tokens)
+            .unwrap();
+    }
+
+    /// Takes `decimal_tokens` leaving the token pool off its liabilities, failing
+    /// if the pool doesn't owe that much
+    fn take_liabilities(&mut self, decimal_tokens: u64) -> Result<()> {
+        self.liabilities_decimal_tokens = self
+            .liabilities_decimal_tokens
+            .checked_sub(decimal_tokens)
+            .ok_or(EquilibrateError::PoolLiabilitiesExceeded)?;
+        Ok(())
     }
 
     pub fn validate_token_pool(
-        token_pool: &Account<'_, TokenAccount>,
+        token_pool: &AccountInfo,
+        token_program: &Pubkey,
         manager_address: Pubkey,
         game_mint: Pubkey,
     ) -> Result<()> {
@@ -41,6 +64,12 @@ impl PoolManager {
             expected_address,
             EquilibrateError::InvalidTokenPool
         );
+        let token_pool = token_interface::unpack_token_account(token_pool, token_program)?;
+        require_keys_eq!(
+            token_pool.mint,
+            game_mint,
+            EquilibrateError::InvalidPoolMint
+        );
         require_keys_eq!(
             token_pool.owner,
             manager_address,
@@ -49,7 +78,148 @@ impl PoolManager {
         Ok(())
     }
 
-    fn get_address(game_mint: Pubkey) -> (Pubkey, u8) {
+    /// Token account that a penalty of `penalty_decimal_tokens` leaving the pool
+    /// is paid into, or `None` if it is burned instead. Games that pay penalties
+    /// to an account take it as the first of `remaining_accounts`, but only when
+    /// there is a penalty to pay.
+    pub fn penalty_destination_account<'a, 'info>(
+        penalty_destination: &PenaltyDestination,
+        penalty_decimal_tokens: u64,
+        remaining_accounts: &'a [AccountInfo<'info>],
+        token_program: &Pubkey,
+        game_mint: Pubkey,
+    ) -> Result<Option<&'a AccountInfo<'info>>> {
+        let owner = match penalty_destination {
+            PenaltyDestination::Account { owner } if penalty_decimal_tokens > 0 => owner,
+            _ => return Ok(None),
+        };
+        let account = remaining_accounts
+            .first()
+            .ok_or(EquilibrateError::MissingPenaltyDestination)?;
+        let token_account = token_interface::unpack_token_account(account, token_program)?;
+        require!(
+            token_account.mint == game_mint && token_account.owner == *owner,
+            EquilibrateError::InvalidPenaltyDestination
+        );
+        Ok(Some(account))
+    }
+
+    /// Token account that `dust_decimal_tokens` of a game ending is paid into, or
+    /// `None` if it is burned instead. Games whose dust goes to their creator take
+    /// a token account of the creator as the last of `remaining_accounts`, but
+    /// only when there is dust to pay.
+    pub fn dust_destination_account<'a, 'info>(
+        dust_destination: DustDestination,
+        dust_decimal_tokens: u64,
+        remaining_accounts: &'a [AccountInfo<'info>],
+        token_program: &Pubkey,
+        game_mint: Pubkey,
+        creator: Pubkey,
+    ) -> Result<Option<&'a AccountInfo<'info>>> {
+        if dust_destination != DustDestination::Creator || dust_decimal_tokens == 0 {
+            return Ok(None);
+        }
+        let account = remaining_accounts
+            .last()
+            .ok_or(EquilibrateError::MissingDustDestination)?;
+        let token_account = token_interface::unpack_token_account(account, token_program)?;
+        require!(
+            token_account.mint == game_mint && token_account.owner == creator,
+            EquilibrateError::InvalidDustDestination
+        );
+        Ok(Some(account))
+    }
+
+    /// Takes `penalty_decimal_tokens` out of the token pool, paying it into
+    /// `penalty_destination` or burning it if there is none, and transfers
+    /// `winnings_decimal_tokens` to `destination`, signing as the pool manager.
+    /// A transfer fee on the winnings is paid by the player. Both leave the
+    /// pool's liabilities, which can never be paid more than they hold.
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_out<'info>(
+        pool_manager: &mut Account<'info, PoolManager>,
+        token_program: &AccountInfo<'info>,
+        token_pool: &AccountInfo<'info>,
+        game_mint: &AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+        winnings_decimal_tokens: u64,
+        penalty_decimal_tokens: u64,
+        penalty_destination: Option<&AccountInfo<'info>>,
+        decimals: u8,
+    ) -> Result<()> {
+        let seeds = &[
+            POOL_MANAGER_SEED.as_ref(),
+            game_mint.key.as_ref(),
+            &[pool_manager.bump],
+        ];
+        let signer = &[&seeds[..]];
+        pool_manager.take_liabilities(winnings_decimal_tokens + penalty_decimal_tokens)?;
+        let pool_manager = pool_manager.to_account_info();
+        pay_or_burn(
+            token_program,
+            token_pool,
+            game_mint,
+            &pool_manager,
+            penalty_destination,
+            penalty_decimal_tokens,
+            decimals,
+            signer,
+        )?;
+        if penalty_destination.is_some() {
+            msg!(
+                "Paid a penalty of {} decimal tokens",
+                penalty_decimal_tokens
+            )
+        }
+
+        token_interface::transfer(
+            token_program,
+            token_pool,
+            game_mint,
+            destination,
+            &pool_manager,
+            winnings_decimal_tokens,
+            decimals,
+            signer,
+        )
+    }
+
+    /// Takes the `dust_decimal_tokens` of a game that ended out of the token pool,
+    /// paying it into `dust_destination` or burning it if there is none
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_dust<'info>(
+        pool_manager: &mut Account<'info, PoolManager>,
+        token_program: &AccountInfo<'info>,
+        token_pool: &AccountInfo<'info>,
+        game_mint: &AccountInfo<'info>,
+        dust_decimal_tokens: u64,
+        dust_destination: Option<&AccountInfo<'info>>,
+        decimals: u8,
+    ) -> Result<()> {
+        let seeds = &[
+            POOL_MANAGER_SEED.as_ref(),
+            game_mint.key.as_ref(),
+            &[pool_manager.bump],
+        ];
+        let signer = &[&seeds[..]];
+        pool_manager.take_liabilities(dust_decimal_tokens)?;
+        pay_or_burn(
+            token_program,
+            token_pool,
+            game_mint,
+            &pool_manager.to_account_info(),
+            dust_destination,
+            dust_decimal_tokens,
+            decimals,
+            signer,
+        )?;
+        if dust_destination.is_some() {
+            msg!("Paid dust of {} decimal tokens", dust_decimal_tokens)
+        }
+        Ok(())
+    }
+
+    pub fn get_address(game_mint: Pubkey) -> (Pubkey, u8) {
         let seeds = [POOL_MANAGER_SEED.as_ref(), game_mint.as_ref()];
         Pubkey::find_program_address(&seeds, &id())
     }
@@ -57,4 +227,52 @@ impl PoolManager {
     pub fn log_make(&self) {
         msg!("Initialized pool manager with bump {}", self.bump);
     }
+
+    pub fn log_audit(&self, pool_decimal_tokens: u64) {
+        msg!(
+            "Token pool holds {} decimal tokens against liabilities of {}",
+            pool_decimal_tokens,
+            self.liabilities_decimal_tokens
+        );
+    }
+}
+
+/// Transfers `decimal_tokens` out of the token pool into `destination`, or burns
+/// them if there is no destination
+#[allow(clippy::too_many_arguments)]
+fn pay_or_burn<'info>(
+    token_program: &AccountInfo<'info>,
+    token_pool: &AccountInfo<'info>,
+    game_mint: &AccountInfo<'info>,
+    pool_manager: &AccountInfo<'info>,
+    destination: Option<&AccountInfo<'info>>,
+    decimal_tokens: u64,
+    decimals: u8,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    match destination {
+        Some(destination) => token_interface::transfer(
+            token_program,
+            token_pool,
+            game_mint,
+            destination,
+            pool_manager,
+            decimal_tokens,
+            decimals,
+            signer,
+        ),
+        None if decimal_tokens > 0 => {
+            token_interface::burn(
+                token_program,
+                token_pool,
+                game_mint,
+                pool_manager,
+                decimal_tokens,
+                signer,
+            )?;
+            msg!("Burned {} decimal tokens", decimal_tokens);
+            Ok(())
+        }
+        None => Ok(()),
+    }
 }
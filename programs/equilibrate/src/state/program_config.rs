@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{GAME_MAX_BUCKETS, GAME_MAX_CREATOR_FEE_BPS, GAME_MAX_PLAYERS},
+    model::EquilibrateError,
+};
+
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct ProgramConfig {
+    pub version: u8,
+    /// wallet allowed to update the program config
+    pub admin: Pubkey,
+    pub settings: ProgramSettings,
+}
+
+impl ProgramConfig {
+    pub fn get_space() -> usize {
+        8 + // account discriminator
+        1 + // version
+        32 + // admin
+        ProgramSettings::get_space()
+    }
+
+    pub fn log_make(&self) {
+        msg!("Initialized program config with admin {}", self.admin);
+    }
+
+    pub fn log_update(&self) {
+        msg!(
+            "Updated program config: fee {} lamports to {} ({} bps to referrers), max {} buckets, max {} players, max creator fee {} bps",
+            self.settings.program_fee_lamports,
+            self.settings.program_fee_destination,
+            self.settings.referral_fee_bps,
+            self.settings.game_max_buckets,
+            self.settings.game_max_players,
+            self.settings.game_max_creator_fee_bps
+        );
+    }
+}
+
+#[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProgramSettings {
+    /// wallet where program fees are deposited
+    pub program_fee_destination: Pubkey,
+    /// fee charged to each player entering a game
+    pub program_fee_lamports: u64,
+    pub game_max_buckets: u8,
+    pub game_max_players: u16,
+    /// largest share of each entry deposit a game creator can take, in basis points
+    pub game_max_creator_fee_bps: u16,
+    /// share of the program fee paid to whoever referred a player entering a
+    /// game, in basis points
+    pub referral_fee_bps: u16,
+}
+
+impl ProgramSettings {
+    pub fn get_space() -> usize {
+        32 + // program_fee_destination
+        8 + // program_fee_lamports
+        1 + // game_max_buckets
+        2 + // game_max_players
+        2 + // game_max_creator_fee_bps
+        2 // referral_fee_bps
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        // a game needs at least 2 buckets and 2 players to be playable
+        require_gt!(self.game_max_buckets, 1, EquilibrateError::TooFewBuckets);
+        require_gte!(
+            GAME_MAX_BUCKETS,
+            self.game_max_buckets,
+            EquilibrateError::TooManyBuckets
+        );
+        require_gt!(
+            self.game_max_players,
+            1,
+            EquilibrateError::MaxPlayersTooSmall
+        );
+        require_gte!(
+            GAME_MAX_PLAYERS,
+            self.game_max_players,
+            EquilibrateError::MaxPlayersTooLarge
+        );
+        require_gte!(
+            GAME_MAX_CREATOR_FEE_BPS,
+            self.game_max_creator_fee_bps,
+            EquilibrateError::CreatorFeeTooLarge
+        );
+        require_gte!(
+            10_000,
+            self.referral_fee_bps,
+            EquilibrateError::ReferralFeeTooLarge
+        );
+        Ok(())
+    }
+
+    /// Part of the program fee that goes to a player's referrer, rounded down
+    pub fn referral_fee_lamports(&self) -> u64 {
+        (self.program_fee_lamports as u128 * self.referral_fee_bps as u128 / 10_000) as u64
+    }
+}
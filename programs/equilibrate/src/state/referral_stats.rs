@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{REFERRAL_SEED, REFERRAL_STATS_VERSION},
+    id,
+    model::EquilibrateError,
+    state::system_interface,
+};
+
+/// Running totals for everyone a wallet has referred, across all games
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct ReferralStats {
+    pub version: u8,
+    pub referrer: Pubkey,
+    /// players who entered a game with this referrer
+    pub referrals: u64,
+    /// program fees paid by those players, including the referrer's share
+    pub volume_lamports: u64,
+    /// share of those program fees paid to the referrer
+    pub rewards_lamports: u64,
+}
+
+impl ReferralStats {
+    pub fn get_space() -> usize {
+        8 + // account discriminator
+        1 + // version
+        32 + // referrer
+        8 + // referrals
+        8 + // volume_lamports
+        8 // rewards_lamports
+    }
+
+    pub fn get_address(referrer: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[REFERRAL_SEED.as_ref(), referrer.as_ref()], &id())
+    }
+
+    /// Adds a referral to the stats of `referrer`, creating their stats
+    /// account first if this is their first referral
+    pub fn record<'info>(
+        account: &AccountInfo<'info>,
+        referrer: &Pubkey,
+        volume_lamports: u64,
+        rewards_lamports: u64,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<ReferralStats> {
+        let (address, bump) = ReferralStats::get_address(referrer);
+        require_keys_eq!(
+            account.key(),
+            address,
+            EquilibrateError::InvalidReferralStats
+        );
+
+        let mut stats = if *account.owner == id() {
+            let data = account.try_borrow_data()?;
+            ReferralStats::try_deserialize(&mut &data[..])?
+        } else {
+            system_interface::create_pda_account(
+                account,
+                &[REFERRAL_SEED.as_ref(), referrer.as_ref(), &[bump]],
+                ReferralStats::get_space(),
+                &id(),
+                payer,
+                system_program,
+            )?;
+            ReferralStats {
+                version: REFERRAL_STATS_VERSION,
+                referrer: *referrer,
+                referrals: 0,
+                volume_lamports: 0,
+                rewards_lamports: 0,
+            }
+        };
+
+        stats.referrals += 1;
+        stats.volume_lamports = stats.volume_lamports.saturating_add(volume_lamports);
+        stats.rewards_lamports = stats.rewards_lamports.saturating_add(rewards_lamports);
+        let mut data = account.try_borrow_mut_data()?;
+        stats.try_serialize(&mut &mut data[..])?;
+        Ok(stats)
+    }
+}
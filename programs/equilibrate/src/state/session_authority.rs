@@ -0,0 +1,87 @@
+use anchor_lang::{prelude::*, AccountsClose};
+
+use crate::{constants::SESSION_SEED, id, model::EquilibrateError, state::PlayerState};
+
+/// A key the player lets act for them in one game, until it expires or the
+/// player revokes it. It can always move buckets, and can leave the game only
+/// if the player allowed it, in which case winnings still go to the player.
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct SessionAuthority {
+    pub version: u8,
+    /// the player's wallet
+    pub owner: Pubkey,
+    /// game the session is scoped to
+    pub game_id: u64,
+    pub session_key: Pubkey,
+    pub expires_epoch_seconds: i64,
+    pub can_leave: bool,
+}
+
+impl SessionAuthority {
+    pub fn get_space() -> usize {
+        8 + // account discriminator
+        1 + // version
+        32 + // owner
+        8 + // game_id
+        32 + // session_key
+        8 + // expires_epoch_seconds
+        1 // can_leave
+    }
+
+    /// Checks that the session hasnt expired at `now_epoch_seconds`
+    pub fn validate(&self, now_epoch_seconds: i64) -> Result<()> {
+        require_gt!(
+            self.expires_epoch_seconds,
+            now_epoch_seconds,
+            EquilibrateError::SessionExpired
+        );
+        Ok(())
+    }
+
+    /// Closes the session `owner` made for game `game_id` as they leave it as
+    /// `player`, returning its rent to `owner`. A player who has made a session
+    /// must pass its address among `remaining_accounts`, and it is closed unless
+    /// they already revoked it. A session only lasts as long as the player is in
+    /// the game, so leaving ends it.
+    pub fn close_for_leaving_player<'info>(
+        player: &PlayerState,
+        game_id: u64,
+        owner: &AccountInfo<'info>,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        if !player.has_session {
+            return Ok(());
+        }
+        let (address, _) = Pubkey::find_program_address(
+            &[
+                SESSION_SEED.as_ref(),
+                &game_id.to_le_bytes(),
+                owner.key.as_ref(),
+            ],
+            &id(),
+        );
+        let account = remaining_accounts
+            .iter()
+            .find(|a| a.key() == address)
+            .ok_or(EquilibrateError::MissingSession)?;
+        if *account.owner == id() {
+            let session = Account::<SessionAuthority>::try_from(account)?;
+            session.close(owner.clone())?;
+            session.log_revoke();
+        }
+        Ok(())
+    }
+
+    pub fn log_create(&self) {
+        msg!(
+            "Created session for key {} until {}",
+            self.session_key,
+            self.expires_epoch_seconds
+        );
+    }
+
+    pub fn log_revoke(&self) {
+        msg!("Revoked session for key {}", self.session_key);
+    }
+}
@@ -0,0 +1,158 @@
+use std::cmp::{max, min};
+
+use anchor_lang::prelude::*;
+
+use crate::{constants::SPILL_CURVE_MAX_STEPS, model::EquilibrateError, state::Bucket};
+
+/// Fixed point scale for the fraction of a balance left after decaying
+const DECAY_SCALE: u128 = 1_000_000_000_000_000_000;
+const BPS_SCALE: u128 = 10_000;
+
+/// How fast tokens spill out of a bucket, depending on its players and balance
+#[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum SpillCurve {
+    /// Each player in a bucket spills the game's spill rate every second
+    Linear,
+    /// Each player in a bucket spills `decay_bps_per_second` basis points of
+    /// the bucket's balance every second, so balances decay exponentially and
+    /// never quite empty. The game's spill rate is unused.
+    ProportionalToBalance { decay_bps_per_second: u16 },
+    /// A bucket spills the game's spill rate times the square root of its
+    /// players every second, so crowded buckets drain slower than linear
+    SqrtOfPlayers,
+    /// Like linear, but the spill rate changes to each step's rate from the
+    /// step's start time. Before the first step the game's spill rate applies.
+    Piecewise { steps: Vec<SpillStep> },
+}
+
+#[derive(Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SpillStep {
+    pub start_epoch_seconds: i64,
+    pub spill_rate_decimal_tokens_per_second_per_player: u64,
+}
+
+impl SpillStep {
+    pub fn get_space() -> usize {
+        8 + // start_epoch_seconds
+        8 // spill_rate_decimal_tokens_per_second_per_player
+    }
+}
+
+impl SpillCurve {
+    /// Space for the largest variant, so any curve fits the game account
+    pub fn get_space() -> usize {
+        1 + // variant
+        4 + SpillStep::get_space() * (SPILL_CURVE_MAX_STEPS as usize) // piecewise steps
+    }
+
+    pub fn validate(&self, spill_rate: u64) -> Result<()> {
+        match self {
+            SpillCurve::ProportionalToBalance {
+                decay_bps_per_second,
+            } => {
+                require!(
+                    (1..=(BPS_SCALE as u16)).contains(decay_bps_per_second),
+                    EquilibrateError::InvalidSpillCurve
+                );
+            }
+            SpillCurve::Piecewise { steps } => {
+                require_gt!(spill_rate, 0, EquilibrateError::InvalidSpillRate);
+                require!(
+                    !steps.is_empty() && steps.len() <= SPILL_CURVE_MAX_STEPS as usize,
+                    EquilibrateError::InvalidSpillCurve
+                );
+                require!(
+                    steps
+                        .windows(2)
+                        .all(|w| w[0].start_epoch_seconds < w[1].start_epoch_seconds),
+                    EquilibrateError::InvalidSpillCurve
+                );
+            }
+            SpillCurve::Linear | SpillCurve::SqrtOfPlayers => {
+                require_gt!(spill_rate, 0, EquilibrateError::InvalidSpillRate);
+            }
+        }
+        Ok(())
+    }
+
+    /// Tokens `bucket` spills between the two times, which is at most what it holds
+    pub fn compute_spillover(
+        &self,
+        bucket: &Bucket,
+        spill_rate: u64,
+        from_epoch_seconds: i64,
+        to_epoch_seconds: i64,
+    ) -> u64 {
+        let seconds: u64 = (to_epoch_seconds - from_epoch_seconds).try_into().unwrap();
+        match self {
+            SpillCurve::Linear => bucket.compute_spillover(spill_rate, seconds),
+            SpillCurve::ProportionalToBalance {
+                decay_bps_per_second,
+            } => {
+                let exponent = (bucket.players as u64).checked_mul(seconds).unwrap();
+                let base = (BPS_SCALE - *decay_bps_per_second as u128) * DECAY_SCALE / BPS_SCALE;
+                let remaining =
+                    (bucket.decimal_tokens as u128) * decay(base, exponent) / DECAY_SCALE;
+                bucket.decimal_tokens - remaining as u64
+            }
+            SpillCurve::SqrtOfPlayers => {
+                // sqrt(players) to three decimal places
+                let sqrt_players_millis = isqrt((bucket.players as u64) * 1_000_000) as u128;
+                let desired = (spill_rate as u128)
+                    .saturating_mul(seconds as u128)
+                    .saturating_mul(sqrt_players_millis)
+                    / 1_000;
+                min(bucket.decimal_tokens as u128, desired) as u64
+            }
+            SpillCurve::Piecewise { steps } => {
+                // integrate the rate over each stretch of time it applies to
+                let mut desired: u128 = 0;
+                let mut rate = spill_rate;
+                let mut stretch_start = from_epoch_seconds;
+                for step in steps.iter() {
+                    if step.start_epoch_seconds >= to_epoch_seconds {
+                        break;
+                    }
+                    let stretch_end = max(step.start_epoch_seconds, stretch_start);
+                    desired = desired
+                        .saturating_add((rate as u128) * ((stretch_end - stretch_start) as u128));
+                    stretch_start = stretch_end;
+                    rate = step.spill_rate_decimal_tokens_per_second_per_player;
+                }
+                desired = desired
+                    .saturating_add((rate as u128) * ((to_epoch_seconds - stretch_start) as u128));
+                min(
+                    bucket.decimal_tokens as u128,
+                    desired.saturating_mul(bucket.players as u128),
+                ) as u64
+            }
+        }
+    }
+}
+
+/// `base` (scaled by `DECAY_SCALE`) to the power of `exponent`
+fn decay(mut base: u128, mut exponent: u64) -> u128 {
+    let mut result = DECAY_SCALE;
+    while exponent > 0 && result > 0 {
+        if exponent & 1 == 1 {
+            result = result * base / DECAY_SCALE;
+        }
+        base = base * base / DECAY_SCALE;
+        exponent >>= 1;
+    }
+    result
+}
+
+fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method from an overestimate converges down to the floor
+    let mut x = n;
+    let mut y = (x + n / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
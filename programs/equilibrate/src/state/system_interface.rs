@@ -0,0 +1,47 @@
+use anchor_lang::{prelude::*, system_program};
+
+/// Creates an account of `space` bytes owned by `owner` at a PDA of this
+/// program, with `payer` paying its rent
+pub fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    account_seeds: &[&[u8]],
+    space: usize,
+    owner: &Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let signer = &[account_seeds];
+
+    // like anchor's `init`, don't let lamports sent to the address ahead of
+    // time block its creation
+    if account.lamports() == 0 {
+        let accounts = system_program::CreateAccount {
+            from: payer.clone(),
+            to: account.clone(),
+        };
+        let context = CpiContext::new_with_signer(system_program.clone(), accounts, signer);
+        system_program::create_account(context, rent, space as u64, owner)?;
+    } else {
+        let missing_rent = rent.saturating_sub(account.lamports());
+        if missing_rent > 0 {
+            let accounts = system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            };
+            let context = CpiContext::new(system_program.clone(), accounts);
+            system_program::transfer(context, missing_rent)?;
+        }
+        let accounts = system_program::Allocate {
+            account_to_allocate: account.clone(),
+        };
+        let context = CpiContext::new_with_signer(system_program.clone(), accounts, signer);
+        system_program::allocate(context, space as u64)?;
+        let accounts = system_program::Assign {
+            account_to_assign: account.clone(),
+        };
+        let context = CpiContext::new_with_signer(system_program.clone(), accounts, signer);
+        system_program::assign(context, owner)?;
+    }
+    Ok(())
+}
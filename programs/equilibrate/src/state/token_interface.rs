@@ -0,0 +1,183 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::program::{invoke, invoke_signed},
+};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::{Account, Mint},
+};
+
+use crate::{constants::NATIVE_MINT, model::EquilibrateError, state::system_interface};
+
+// anchor-spl 0.26 only types the legacy token program, so token accounts and
+// mints are taken as plain account infos and handled here for either program.
+// The base layout of accounts and mints is the same for both programs, and
+// Token-2022 uses the legacy instruction encoding for the instructions below.
+
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::ID || *program_id == spl_token_2022::ID
+}
+
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    mint.to_string() == NATIVE_MINT || *mint == spl_token_2022::native_mint::ID
+}
+
+pub fn unpack_token_account(account: &AccountInfo, token_program: &Pubkey) -> Result<Account> {
+    require_keys_eq!(
+        *account.owner,
+        *token_program,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    let data = account.try_borrow_data()?;
+    Ok(StateWithExtensions::<Account>::unpack(&data)?.base)
+}
+
+/// Unpacks a game mint, refusing mints with extensions that would let tokens
+/// leave the pool without the pool manager, or stop them leaving at all
+pub fn unpack_game_mint(mint: &AccountInfo) -> Result<Mint> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        match extension {
+            ExtensionType::TransferFeeConfig
+            | ExtensionType::MintCloseAuthority
+            | ExtensionType::InterestBearingConfig => {}
+            _ => {
+                msg!("Unsupported mint extension {:?}", extension);
+                return err!(EquilibrateError::UnsupportedMintExtension);
+            }
+        }
+    }
+    Ok(state.base)
+}
+
+/// Creates and initializes a token account at a PDA of this program, sized
+/// for the extensions the mint requires of its token accounts
+pub fn create_token_account<'info>(
+    account: &AccountInfo<'info>,
+    account_seeds: &[&[u8]],
+    mint: &AccountInfo<'info>,
+    owner: &Pubkey,
+    payer: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let space = {
+        let data = mint.try_borrow_data()?;
+        let mint_extensions = StateWithExtensions::<Mint>::unpack(&data)?.get_extension_types()?;
+        ExtensionType::get_account_len::<Account>(
+            &ExtensionType::get_required_init_account_extensions(&mint_extensions),
+        )
+    };
+    system_interface::create_pda_account(
+        account,
+        account_seeds,
+        space,
+        token_program.key,
+        payer,
+        system_program,
+    )?;
+
+    let instruction = spl_token_2022::instruction::initialize_account3(
+        token_program.key,
+        account.key,
+        mint.key,
+        owner,
+    )?;
+    invoke(
+        &instruction,
+        &[account.clone(), mint.clone(), token_program.clone()],
+    )?;
+    Ok(())
+}
+
+/// Transfers `amount` into `destination` and returns how much it actually
+/// received, which is less than `amount` when the mint charges a transfer fee
+#[allow(clippy::too_many_arguments)]
+pub fn deposit<'info>(
+    token_program: &AccountInfo<'info>,
+    source: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+) -> Result<u64> {
+    let balance_before = unpack_token_account(destination, token_program.key)?.amount;
+    transfer(
+        token_program,
+        source,
+        mint,
+        destination,
+        authority,
+        amount,
+        decimals,
+        &[],
+    )?;
+    let balance_after = unpack_token_account(destination, token_program.key)?.amount;
+    Ok(balance_after - balance_before)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn transfer<'info>(
+    token_program: &AccountInfo<'info>,
+    source: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let instruction = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &instruction,
+        &[
+            source.clone(),
+            mint.clone(),
+            destination.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+pub fn burn<'info>(
+    token_program: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let instruction = spl_token_2022::instruction::burn(
+        token_program.key,
+        account.key,
+        mint.key,
+        authority.key,
+        &[],
+        amount,
+    )?;
+    invoke_signed(
+        &instruction,
+        &[
+            account.clone(),
+            mint.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::Pubkey};
+use common::*;
+use equilibrate::{
+    model::{DepositAdded, EquilibrateError},
+    state::{GameConfig, TopUpDestination},
+};
+
+const DEPOSIT_DECIMAL_TOKENS: u64 = 2 * ENTRY_FEE_DECIMAL_TOKENS;
+
+#[test]
+fn add_deposit_all_good_holding_bucket_is_credited() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        top_up_destination: TopUpDestination::HoldingBucket,
+        ..default_game_config(context.mint)
+    });
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+
+    context
+        .add_deposit(&player, game_id, DEPOSIT_DECIMAL_TOKENS)
+        .unwrap();
+
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS - DEPOSIT_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        pool_balance + DEPOSIT_DECIMAL_TOKENS
+    );
+    let state = context.player_state(game_id, &player).unwrap();
+    assert_eq!(state.added_decimal_tokens, DEPOSIT_DECIMAL_TOKENS);
+    assert_eq!(state.stake_decimal_tokens, ENTRY_FEE_DECIMAL_TOKENS);
+    let buckets = context.game(game_id).unwrap().state.buckets;
+    assert_eq!(
+        buckets[0].decimal_tokens,
+        2 * ENTRY_FEE_DECIMAL_TOKENS + DEPOSIT_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        buckets[0].stake_decimal_tokens,
+        2 * ENTRY_FEE_DECIMAL_TOKENS
+    );
+    assert_eq!(buckets[2].decimal_tokens, 0);
+    let events = context.bank.events::<DepositAdded>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].bucket, 2);
+    assert_eq!(events[0].deposit_decimal_tokens, DEPOSIT_DECIMAL_TOKENS);
+    assert_eq!(events[0].added_decimal_tokens, DEPOSIT_DECIMAL_TOKENS);
+}
+
+#[test]
+fn add_deposit_all_good_player_bucket_is_credited() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        top_up_destination: TopUpDestination::PlayerBucket,
+        ..default_game_config(context.mint)
+    });
+
+    context
+        .add_deposit(&player, game_id, DEPOSIT_DECIMAL_TOKENS)
+        .unwrap();
+
+    let buckets = context.game(game_id).unwrap().state.buckets;
+    assert_eq!(buckets[0].decimal_tokens, 2 * ENTRY_FEE_DECIMAL_TOKENS);
+    assert_eq!(buckets[2].decimal_tokens, DEPOSIT_DECIMAL_TOKENS);
+    assert_eq!(buckets[2].stake_decimal_tokens, ENTRY_FEE_DECIMAL_TOKENS);
+}
+
+#[test]
+fn add_deposit_all_good_top_ups_add_up() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        top_up_destination: TopUpDestination::HoldingBucket,
+        ..default_game_config(context.mint)
+    });
+
+    context.add_deposit(&player, game_id, 5).unwrap();
+    context.bank.warp_seconds(10);
+    context.add_deposit(&player, game_id, 7).unwrap();
+
+    assert_eq!(
+        context
+            .player_state(game_id, &player)
+            .unwrap()
+            .added_decimal_tokens,
+        12
+    );
+    let events = context.bank.events::<DepositAdded>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].deposit_decimal_tokens, 7);
+    assert_eq!(events[0].added_decimal_tokens, 12);
+}
+
+#[test]
+fn leave_game_cancel_on_loss_counts_top_ups() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        top_up_destination: TopUpDestination::PlayerBucket,
+        ..default_game_config(context.mint)
+    });
+    context
+        .add_deposit(&player, game_id, DEPOSIT_DECIMAL_TOKENS)
+        .unwrap();
+
+    // the player is alone in their bucket, so they can take the top-up back
+    // out, but that is less than they put in
+    assert_error(
+        context.leave_game_with(&player, game_id, true),
+        EquilibrateError::AbortLeaveOnLoss,
+    );
+
+    context.leave_game(&player, game_id).unwrap();
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn add_deposit_nothing_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        top_up_destination: TopUpDestination::HoldingBucket,
+        ..default_game_config(context.mint)
+    });
+
+    assert_error(
+        context.add_deposit(&player, game_id, 0),
+        EquilibrateError::EmptyTopUp,
+    );
+}
+
+#[test]
+fn add_deposit_after_end_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + 100),
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(&creator, game_id, config).unwrap();
+    context.bank.warp_seconds(100);
+
+    assert_error(
+        context.add_deposit(&creator, game_id, DEPOSIT_DECIMAL_TOKENS),
+        EquilibrateError::GameHasEnded,
+    );
+}
+
+#[test]
+fn add_deposit_not_in_game_fails() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game_with(GameConfig {
+        top_up_destination: TopUpDestination::HoldingBucket,
+        ..default_game_config(context.mint)
+    });
+    let outsider = context.new_player();
+
+    assert_error(
+        context.add_deposit(&outsider, game_id, DEPOSIT_DECIMAL_TOKENS),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn add_deposit_source_of_other_mint_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        top_up_destination: TopUpDestination::HoldingBucket,
+        ..default_game_config(context.mint)
+    });
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    let other_account = context.new_wallet();
+    context.create_token_account(
+        other_account,
+        other_mint,
+        player.wallet,
+        WALLET_DECIMAL_TOKENS,
+    );
+    let mut accounts = context.add_deposit_accounts(&player, game_id);
+    accounts.deposit_source_account = other_account;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::AddDeposit {
+            deposit_decimal_tokens: DEPOSIT_DECIMAL_TOKENS,
+        },
+    );
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidTokenSourceMint,
+    );
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use equilibrate::{
+    model::EquilibrateError,
+    state::{allowlist, allowlist::Allowlist, GameConfig},
+};
+
+/// New game only `players` can enter
+fn new_private_game(context: &mut TestContext, creator: &Player, players: &[&Player]) -> u64 {
+    let wallets: Vec<Pubkey> = players.iter().map(|player| player.wallet).collect();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        allowlist_root: Some(Allowlist::new(&wallets).root()),
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(creator, game_id, config).unwrap();
+    game_id
+}
+
+fn proof_for(players: &[&Player], player: &Player) -> Vec<[u8; 32]> {
+    let wallets: Vec<Pubkey> = players.iter().map(|player| player.wallet).collect();
+    Allowlist::new(&wallets).proof(&player.wallet).unwrap()
+}
+
+#[test]
+fn enter_game_all_good_allowlisted() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let invited = [
+        context.new_player(),
+        context.new_player(),
+        context.new_player(),
+    ];
+    let allowed: Vec<&Player> = invited.iter().collect();
+    let game_id = new_private_game(&mut context, &creator, &allowed);
+
+    for (i, player) in invited.iter().enumerate() {
+        let proof = proof_for(&allowed, player);
+        context
+            .enter_game_with_proof(player, game_id, 1 + i as u8 % 2, proof)
+            .unwrap();
+    }
+
+    assert_eq!(context.game(game_id).unwrap().state.buckets[0].players, 4);
+}
+
+#[test]
+fn enter_game_all_good_single_wallet_allowlist_needs_no_proof() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let invited = context.new_player();
+    let game_id = new_private_game(&mut context, &creator, &[&invited]);
+
+    assert!(proof_for(&[&invited], &invited).is_empty());
+    context
+        .enter_game_with_proof(&invited, game_id, 2, vec![])
+        .unwrap();
+}
+
+#[test]
+fn enter_game_all_good_public_game_ignores_proof() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+
+    context
+        .enter_game_with_proof(&player, game_id, 2, vec![[7; 32]])
+        .unwrap();
+}
+
+#[test]
+fn enter_game_without_proof_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let invited = [context.new_player(), context.new_player()];
+    let allowed: Vec<&Player> = invited.iter().collect();
+    let game_id = new_private_game(&mut context, &creator, &allowed);
+
+    assert_error(
+        context.enter_game(&invited[0], game_id, 2),
+        EquilibrateError::NotAllowlisted,
+    );
+}
+
+#[test]
+fn enter_game_not_allowlisted_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let invited = [context.new_player(), context.new_player()];
+    let allowed: Vec<&Player> = invited.iter().collect();
+    let game_id = new_private_game(&mut context, &creator, &allowed);
+    let outsider = context.new_player();
+
+    // someone else's proof doesnt prove the outsider is on the list
+    let proof = proof_for(&allowed, &invited[0]);
+    assert_error(
+        context.enter_game_with_proof(&outsider, game_id, 2, proof),
+        EquilibrateError::NotAllowlisted,
+    );
+    assert_error(
+        context.enter_game(&outsider, game_id, 2),
+        EquilibrateError::NotAllowlisted,
+    );
+}
+
+#[test]
+fn enter_game_other_players_proof_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let invited = [
+        context.new_player(),
+        context.new_player(),
+        context.new_player(),
+    ];
+    let allowed: Vec<&Player> = invited.iter().collect();
+    let game_id = new_private_game(&mut context, &creator, &allowed);
+
+    let proof = proof_for(&allowed, &invited[1]);
+    assert_error(
+        context.enter_game_with_proof(&invited[0], game_id, 2, proof),
+        EquilibrateError::NotAllowlisted,
+    );
+}
+
+#[test]
+fn allowlist_proofs_verify_for_every_member() {
+    for size in 1..=20 {
+        let wallets: Vec<Pubkey> = (0..size).map(|_| Pubkey::new_unique()).collect();
+        let tree = Allowlist::new(&wallets);
+        let root = tree.root();
+
+        for wallet in &wallets {
+            let proof = tree.proof(wallet).unwrap();
+            assert!(allowlist::verify(&root, &proof, wallet), "size {size}");
+        }
+
+        let outsider = Pubkey::new_unique();
+        assert!(tree.proof(&outsider).is_none());
+        let member_proof = tree.proof(&wallets[0]).unwrap();
+        assert!(!allowlist::verify(&root, &member_proof, &outsider));
+    }
+}
@@ -0,0 +1,268 @@
+mod common;
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, PlayerLeft},
+    state::{BurnSchedule, GameConfig},
+};
+
+const BURN_RATE_DECIMAL_TOKENS: u64 = 1_000_000;
+const HALF_LIFE_SECONDS: u32 = 10;
+
+/// Config of a game that burns `BURN_RATE_DECIMAL_TOKENS` on the first move
+fn burning_config(
+    mint: Pubkey,
+    burn_schedule: BurnSchedule,
+    burn_penalty_half_life_seconds: Option<u32>,
+) -> GameConfig {
+    GameConfig {
+        burn_rate_decimal_tokens_per_move: BURN_RATE_DECIMAL_TOKENS,
+        burn_schedule,
+        burn_penalty_half_life_seconds,
+        ..default_game_config(mint)
+    }
+}
+
+/// Moves the player back and forth between buckets 3 and 2 `moves` times
+fn move_back_and_forth(context: &mut TestContext, player: &Player, game_id: u64, moves: u32) {
+    for i in 0..moves {
+        let bucket = if i % 2 == 0 { 3 } else { 2 };
+        context.move_buckets(player, game_id, bucket).unwrap();
+    }
+}
+
+fn burn_penalty(context: &TestContext, game_id: u64, player: &Player) -> u64 {
+    context
+        .player_state(game_id, player)
+        .unwrap()
+        .burn_penalty_decimal_tokens
+}
+
+#[test]
+fn move_buckets_all_good_flat_schedule_burns_the_same_each_move() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) =
+        context.two_player_game_with(burning_config(context.mint, BurnSchedule::Flat, None));
+
+    move_back_and_forth(&mut context, &player, game_id, 3);
+
+    let state = context.player_state(game_id, &player).unwrap();
+    assert_eq!(state.moves, 3);
+    assert_eq!(
+        state.burn_penalty_decimal_tokens,
+        3 * BURN_RATE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn move_buckets_all_good_linear_schedule_escalates_by_increment() {
+    let mut context = TestContext::new();
+    let increment = BURN_RATE_DECIMAL_TOKENS / 2;
+    let (_, player, game_id) = context.two_player_game_with(burning_config(
+        context.mint,
+        BurnSchedule::Linear {
+            increment_decimal_tokens_per_move: increment,
+        },
+        None,
+    ));
+
+    move_back_and_forth(&mut context, &player, game_id, 3);
+
+    assert_eq!(
+        burn_penalty(&context, game_id, &player),
+        3 * BURN_RATE_DECIMAL_TOKENS + 3 * increment
+    );
+}
+
+#[test]
+fn move_buckets_all_good_geometric_schedule_escalates_by_growth() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(burning_config(
+        context.mint,
+        BurnSchedule::Geometric { growth_bps: 20_000 },
+        None,
+    ));
+
+    move_back_and_forth(&mut context, &player, game_id, 3);
+
+    assert_eq!(
+        burn_penalty(&context, game_id, &player),
+        (1 + 2 + 4) * BURN_RATE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn move_buckets_all_good_geometric_schedule_saturates() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(burning_config(
+        context.mint,
+        BurnSchedule::Geometric {
+            growth_bps: u32::MAX,
+        },
+        None,
+    ));
+
+    move_back_and_forth(&mut context, &player, game_id, 4);
+
+    assert_eq!(burn_penalty(&context, game_id, &player), u64::MAX);
+    context.leave_game(&player, game_id).unwrap();
+}
+
+#[test]
+fn move_buckets_all_good_share_of_bucket_schedule_burns_part_of_share() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(burning_config(
+        context.mint,
+        BurnSchedule::ShareOfBucket { share_bps: 2_500 },
+        None,
+    ));
+    context.bank.warp_seconds(10);
+    let mut game = context.game(game_id).unwrap();
+    game.update(context.bank.now_epoch_seconds());
+    let share = game.state.buckets[2].decimal_tokens;
+    assert!(share > 0);
+
+    context.move_buckets(&player, game_id, 3).unwrap();
+
+    assert_eq!(burn_penalty(&context, game_id, &player), share / 4);
+}
+
+#[test]
+fn move_buckets_all_good_penalty_decays_before_next_move_adds_to_it() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(burning_config(
+        context.mint,
+        BurnSchedule::Flat,
+        Some(HALF_LIFE_SECONDS),
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(HALF_LIFE_SECONDS as i64);
+
+    context.move_buckets(&player, game_id, 2).unwrap();
+
+    assert_eq!(
+        burn_penalty(&context, game_id, &player),
+        BURN_RATE_DECIMAL_TOKENS / 2 + BURN_RATE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn resolved_burn_penalty_shrinks_linearly_between_halvings() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(burning_config(
+        context.mint,
+        BurnSchedule::Flat,
+        Some(HALF_LIFE_SECONDS),
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context
+        .bank
+        .warp_seconds((HALF_LIFE_SECONDS + HALF_LIFE_SECONDS / 2) as i64);
+
+    let state = context.player_state(game_id, &player).unwrap();
+    let config = context.game(game_id).unwrap().config;
+    // halved once, then a quarter of the way to the next halving
+    assert_eq!(
+        state.resolved_burn_penalty(&config, context.bank.now_epoch_seconds()),
+        BURN_RATE_DECIMAL_TOKENS / 2 - BURN_RATE_DECIMAL_TOKENS / 8
+    );
+    // the stored penalty only changes on the next move
+    assert_eq!(state.burn_penalty_decimal_tokens, BURN_RATE_DECIMAL_TOKENS);
+}
+
+#[test]
+fn resolved_burn_penalty_stops_decaying_at_game_end() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        burn_rate_decimal_tokens_per_move: BURN_RATE_DECIMAL_TOKENS,
+        burn_penalty_half_life_seconds: Some(HALF_LIFE_SECONDS),
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + HALF_LIFE_SECONDS as i64),
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(&creator, game_id, config).unwrap();
+    context.move_buckets(&creator, game_id, 2).unwrap();
+    context.bank.warp_seconds(3 * HALF_LIFE_SECONDS as i64);
+
+    let state = context.player_state(game_id, &creator).unwrap();
+    let config = context.game(game_id).unwrap().config;
+    assert_eq!(
+        state.resolved_burn_penalty(&config, context.bank.now_epoch_seconds()),
+        BURN_RATE_DECIMAL_TOKENS / 2
+    );
+}
+
+#[test]
+fn leave_game_all_good_burns_decayed_penalty() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(burning_config(
+        context.mint,
+        BurnSchedule::Flat,
+        Some(HALF_LIFE_SECONDS),
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(HALF_LIFE_SECONDS as i64);
+    let supply_before = context.mint_supply(&context.mint);
+
+    context.leave_game(&player, game_id).unwrap();
+
+    // the holding bucket spilled into all three buckets since the move
+    let share = 2 * HALF_LIFE_SECONDS as u64 * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER / 3;
+    let burned = BURN_RATE_DECIMAL_TOKENS / 2;
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(events[0].burned_decimal_tokens, burned);
+    assert_eq!(events[0].winnings_decimal_tokens, share - burned);
+    assert_eq!(context.mint_supply(&context.mint), supply_before - burned);
+}
+
+#[test]
+fn new_game_invalid_burn_schedule_fails() {
+    for (burn_schedule, burn_penalty_half_life_seconds) in [
+        (BurnSchedule::Geometric { growth_bps: 9_999 }, None),
+        (BurnSchedule::ShareOfBucket { share_bps: 0 }, None),
+        (BurnSchedule::ShareOfBucket { share_bps: 10_001 }, None),
+        (BurnSchedule::Flat, Some(0)),
+    ] {
+        let mut context = TestContext::new();
+        let creator = context.new_player();
+        let game_id = context.next_game_id();
+        let config = GameConfig {
+            burn_schedule,
+            burn_penalty_half_life_seconds,
+            ..default_game_config(context.mint)
+        };
+
+        assert_error(
+            context.new_game_with(&creator, game_id, config),
+            EquilibrateError::InvalidBurnSchedule,
+        );
+        assert!(context.game(game_id).is_none());
+    }
+}
+
+#[test]
+fn new_game_burning_schedule_with_native_mint_fails() {
+    for burn_schedule in [
+        BurnSchedule::Linear {
+            increment_decimal_tokens_per_move: 1,
+        },
+        BurnSchedule::ShareOfBucket { share_bps: 1 },
+    ] {
+        let mut context = TestContext::new();
+        let mint = context.create_mint(native_mint());
+        context.create_pool(mint).unwrap();
+        let player = context.new_player_of(mint);
+        let config = GameConfig {
+            burn_schedule,
+            ..default_game_config(mint)
+        };
+        let game_id = context.next_game_id();
+
+        assert_error(
+            context.new_game_with(&player, game_id, config),
+            EquilibrateError::CannotBurnNativeMint,
+        );
+    }
+}
@@ -0,0 +1,503 @@
+//! A minimal in-process bank that runs the program natively. The program's
+//! entrypoint is called directly with accounts serialized the same way the BPF
+//! loader lays them out, and CPIs to the system, SPL token and Token-2022
+//! programs are served by syscall stubs. The clock can be warped to exercise time-dependent spillover.
+
+use std::{cell::RefCell, collections::HashMap, mem::size_of, sync::Once};
+
+use anchor_lang::{
+    solana_program::{
+        account_info::AccountInfo,
+        bpf_loader, bpf_loader_upgradeable,
+        bpf_loader_upgradeable::UpgradeableLoaderState,
+        clock::Clock,
+        entrypoint::{
+            deserialize, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE,
+            NON_DUP_MARKER,
+        },
+        instruction::Instruction,
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack},
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction::{SystemError, SystemInstruction},
+        system_program, sysvar,
+    },
+    AccountDeserialize, AccountSerialize, Event, Owner,
+};
+
+/// Unix timestamp every bank starts at
+pub const GENESIS_EPOCH_SECONDS: i64 = 1_670_000_000;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StoredAccount {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+pub struct Bank {
+    accounts: HashMap<Pubkey, StoredAccount>,
+    clock: Clock,
+    logs: Vec<String>,
+    event_data: Vec<Vec<u8>>,
+}
+
+impl Bank {
+    /// Creates a bank with the system, SPL token, Token-2022 and associated token programs,
+    /// the rent sysvar, and this program deployed with `upgrade_authority`.
+    pub fn new(upgrade_authority: Pubkey) -> Self {
+        install_stubs();
+        let mut bank = Bank {
+            accounts: HashMap::new(),
+            clock: Clock {
+                unix_timestamp: GENESIS_EPOCH_SECONDS,
+                ..Clock::default()
+            },
+            logs: Vec::new(),
+            event_data: Vec::new(),
+        };
+
+        for program_id in [
+            system_program::ID,
+            anchor_spl::token::ID,
+            spl_token_2022::ID,
+            anchor_spl::associated_token::ID,
+        ] {
+            bank.set_account(
+                program_id,
+                StoredAccount {
+                    lamports: 1,
+                    data: Vec::new(),
+                    owner: bpf_loader::ID,
+                    executable: true,
+                },
+            );
+        }
+
+        let (program_data_address, _) =
+            Pubkey::find_program_address(&[equilibrate::ID.as_ref()], &bpf_loader_upgradeable::ID);
+        bank.set_account(
+            equilibrate::ID,
+            StoredAccount {
+                lamports: 1,
+                data: bincode::serialize(&UpgradeableLoaderState::Program {
+                    programdata_address: program_data_address,
+                })
+                .unwrap(),
+                owner: bpf_loader_upgradeable::ID,
+                executable: true,
+            },
+        );
+        bank.set_account(
+            program_data_address,
+            StoredAccount {
+                lamports: 1,
+                data: bincode::serialize(&UpgradeableLoaderState::ProgramData {
+                    slot: 0,
+                    upgrade_authority_address: Some(upgrade_authority),
+                })
+                .unwrap(),
+                owner: bpf_loader_upgradeable::ID,
+                executable: false,
+            },
+        );
+
+        let rent = Rent::default();
+        bank.set_account(
+            sysvar::rent::ID,
+            StoredAccount {
+                lamports: 1,
+                data: bincode::serialize(&rent).unwrap(),
+                owner: sysvar::ID,
+                executable: false,
+            },
+        );
+
+        bank
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn now_epoch_seconds(&self) -> i64 {
+        self.clock.unix_timestamp
+    }
+
+    /// Moves the clock forward (or backward) by `seconds`
+    pub fn warp_seconds(&mut self, seconds: i64) {
+        self.clock.unix_timestamp = self.clock.unix_timestamp.checked_add(seconds).unwrap();
+        self.clock.slot = self.clock.slot.checked_add(1).unwrap();
+    }
+
+    pub fn get_account(&self, address: &Pubkey) -> Option<&StoredAccount> {
+        self.accounts.get(address)
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, account: StoredAccount) {
+        self.accounts.insert(address, account);
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.get_account(address).map(|a| a.lamports).unwrap_or(0)
+    }
+
+    pub fn airdrop(&mut self, address: Pubkey, lamports: u64) {
+        let account = self.accounts.entry(address).or_insert(StoredAccount {
+            owner: system_program::ID,
+            ..StoredAccount::default()
+        });
+        account.lamports = account.lamports.checked_add(lamports).unwrap();
+    }
+
+    pub fn minimum_balance(&self, data_len: usize) -> u64 {
+        Rent::default().minimum_balance(data_len)
+    }
+
+    /// Deserializes an anchor account, or `None` if it doesnt exist
+    pub fn get_anchor_account<T: AccountDeserialize>(&self, address: &Pubkey) -> Option<T> {
+        self.get_account(address)
+            .map(|a| T::try_deserialize(&mut a.data.as_slice()).unwrap())
+    }
+
+    /// Writes an anchor account directly, bypassing the program. `space` must
+    /// include the discriminator.
+    pub fn set_anchor_account<T: AccountSerialize + Owner>(
+        &mut self,
+        address: Pubkey,
+        account: &T,
+        space: usize,
+    ) {
+        let mut data = Vec::with_capacity(space);
+        account.try_serialize(&mut data).unwrap();
+        data.resize(space, 0);
+        self.set_account(
+            address,
+            StoredAccount {
+                lamports: self.minimum_balance(space),
+                data,
+                owner: T::owner(),
+                executable: false,
+            },
+        );
+    }
+
+    pub fn get_packed<T: Pack + IsInitialized>(&self, address: &Pubkey) -> T {
+        T::unpack(&self.get_account(address).unwrap().data).unwrap()
+    }
+
+    /// Writes a packed SPL token account or mint directly, bypassing the token program
+    pub fn set_packed<T: Pack>(&mut self, address: Pubkey, state: T) {
+        let mut data = vec![0; T::LEN];
+        T::pack(state, &mut data).unwrap();
+        self.set_account(
+            address,
+            StoredAccount {
+                lamports: self.minimum_balance(T::LEN),
+                data,
+                owner: anchor_spl::token::ID,
+                executable: false,
+            },
+        );
+    }
+
+    /// Log messages of the last processed instruction
+    pub fn logs(&self) -> &[String] {
+        &self.logs
+    }
+
+    /// Events of type `T` emitted by the last processed instruction
+    pub fn events<T: Event>(&self) -> Vec<T> {
+        self.event_data
+            .iter()
+            .filter(|data| data.starts_with(&T::discriminator()))
+            .map(|data| T::try_from_slice(&data[8..]).unwrap())
+            .collect()
+    }
+
+    /// Processes an instruction as if it were the only one in a transaction signed
+    /// by `signers`. Account changes are only kept if the instruction succeeds.
+    pub fn process(&mut self, instruction: &Instruction, signers: &[Pubkey]) -> ProgramResult {
+        for meta in instruction.accounts.iter().filter(|m| m.is_signer) {
+            if !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+
+        let mut input = self.serialize(instruction);
+        INVOCATION.with(|i| {
+            let mut invocation = i.borrow_mut();
+            invocation.clock = self.clock.clone();
+            invocation.logs.clear();
+            invocation.event_data.clear();
+        });
+
+        let updates = {
+            let (program_id, account_infos, data) =
+                unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+            let result = invoke(program_id, &account_infos, data);
+            result.and_then(|_| {
+                let mut updates: Vec<(Pubkey, StoredAccount)> = Vec::new();
+                for info in account_infos.iter() {
+                    if updates.iter().any(|(key, _)| key == info.key) {
+                        continue;
+                    }
+                    let updated = StoredAccount {
+                        lamports: **info.lamports.borrow(),
+                        data: info.data.borrow().to_vec(),
+                        owner: *info.owner,
+                        executable: info.executable,
+                    };
+                    let original = self.accounts.get(info.key).cloned().unwrap_or_default();
+                    let changed = (updated.lamports != original.lamports)
+                        || (updated.data != original.data)
+                        || ((updated.owner != original.owner) && (original.lamports > 0));
+                    if changed && !info.is_writable {
+                        // the runtime's ReadonlyDataModified has no ProgramError counterpart
+                        return Err(ProgramError::InvalidArgument);
+                    }
+                    updates.push((*info.key, updated));
+                }
+                Ok(updates)
+            })
+        };
+
+        INVOCATION.with(|i| {
+            let mut invocation = i.borrow_mut();
+            self.logs = std::mem::take(&mut invocation.logs);
+            self.event_data = std::mem::take(&mut invocation.event_data);
+        });
+
+        for (key, account) in updates? {
+            // like the runtime, accounts drained of lamports are garbage collected
+            if account.lamports == 0 {
+                self.accounts.remove(&key);
+            } else {
+                self.accounts.insert(key, account);
+            }
+        }
+        Ok(())
+    }
+
+    /// Serializes the instruction's accounts and data the way the BPF loader does,
+    /// with room for each account to grow. Returned as words so the buffer is aligned.
+    fn serialize(&self, instruction: &Instruction) -> Vec<u64> {
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend((instruction.accounts.len() as u64).to_le_bytes());
+        for (i, meta) in instruction.accounts.iter().enumerate() {
+            let first = instruction
+                .accounts
+                .iter()
+                .position(|m| m.pubkey == meta.pubkey)
+                .unwrap();
+            if first < i {
+                bytes.push(first as u8);
+                bytes.extend([0u8; 7]);
+                continue;
+            }
+            // duplicates get the union of their privileges
+            let metas = instruction
+                .accounts
+                .iter()
+                .filter(|m| m.pubkey == meta.pubkey);
+            let is_signer = metas.clone().any(|m| m.is_signer);
+            let is_writable = metas.clone().any(|m| m.is_writable);
+            let account = self
+                .accounts
+                .get(&meta.pubkey)
+                .cloned()
+                .unwrap_or(StoredAccount {
+                    owner: system_program::ID,
+                    ..StoredAccount::default()
+                });
+            bytes.push(NON_DUP_MARKER);
+            bytes.push(is_signer as u8);
+            bytes.push(is_writable as u8);
+            bytes.push(account.executable as u8);
+            bytes.extend([0u8; 4]);
+            bytes.extend(meta.pubkey.as_ref());
+            bytes.extend(account.owner.as_ref());
+            bytes.extend(account.lamports.to_le_bytes());
+            bytes.extend((account.data.len() as u64).to_le_bytes());
+            bytes.extend(&account.data);
+            bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            let padding = (bytes.len() as *const u8).align_offset(BPF_ALIGN_OF_U128);
+            bytes.resize(bytes.len() + padding, 0);
+            bytes.extend(0u64.to_le_bytes()); // rent epoch
+        }
+        bytes.extend((instruction.data.len() as u64).to_le_bytes());
+        bytes.extend(&instruction.data);
+        bytes.extend(instruction.program_id.as_ref());
+
+        let mut words = vec![0u64; bytes.len() / size_of::<u64>() + 1];
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                words.as_mut_ptr() as *mut u8,
+                bytes.len(),
+            );
+        }
+        words
+    }
+}
+
+#[derive(Default)]
+struct Invocation {
+    clock: Clock,
+    /// programs currently executing, innermost last
+    program_ids: Vec<Pubkey>,
+    logs: Vec<String>,
+    event_data: Vec<Vec<u8>>,
+}
+
+thread_local! {
+    static INVOCATION: RefCell<Invocation> = RefCell::new(Invocation::default());
+}
+
+fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(BankStubs));
+    });
+}
+
+fn invoke(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    INVOCATION.with(|i| i.borrow_mut().program_ids.push(*program_id));
+    let result = if *program_id == equilibrate::ID {
+        equilibrate::entry(program_id, accounts, data)
+    } else if *program_id == anchor_spl::token::ID {
+        spl_token::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == spl_token_2022::ID {
+        spl_token_2022::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == system_program::ID {
+        process_system_instruction(accounts, data)
+    } else {
+        Err(ProgramError::IncorrectProgramId)
+    };
+    INVOCATION.with(|i| i.borrow_mut().program_ids.pop());
+    result
+}
+
+struct BankStubs;
+
+impl SyscallStubs for BankStubs {
+    fn sol_log(&self, message: &str) {
+        INVOCATION.with(|i| i.borrow_mut().logs.push(message.to_string()));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        INVOCATION.with(|i| i.borrow_mut().event_data.push(fields.concat()));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = INVOCATION.with(|i| *i.borrow().program_ids.last().unwrap());
+        let pda_signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<Pubkey>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut callee_infos: Vec<AccountInfo> = Vec::with_capacity(instruction.accounts.len());
+        for meta in instruction.accounts.iter() {
+            let info = account_infos
+                .iter()
+                .find(|i| *i.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            // callers can pass on their own privileges, but not escalate them
+            if meta.is_signer && !(info.is_signer || pda_signers.contains(info.key)) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let mut callee_info = info.clone();
+            callee_info.is_signer = meta.is_signer;
+            callee_info.is_writable = meta.is_writable;
+            callee_infos.push(callee_info);
+        }
+
+        invoke(&instruction.program_id, &callee_infos, &instruction.data)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = INVOCATION.with(|i| i.borrow().clock.clone());
+        unsafe { std::ptr::write(var_addr as *mut Clock, clock) };
+        anchor_lang::solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write(var_addr as *mut Rent, Rent::default()) };
+        anchor_lang::solana_program::entrypoint::SUCCESS
+    }
+}
+
+/// The subset of the system program the program and token program rely on
+fn process_system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction: SystemInstruction =
+        bincode::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            let (from, to) = (&accounts[0], &accounts[1]);
+            if to.lamports() > 0 {
+                return Err(ProgramError::Custom(
+                    SystemError::AccountAlreadyInUse as u32,
+                ));
+            }
+            allocate(to, space)?;
+            to.assign(&owner);
+            transfer(from, to, lamports)
+        }
+        SystemInstruction::Transfer { lamports } => transfer(&accounts[0], &accounts[1], lamports),
+        SystemInstruction::Allocate { space } => allocate(&accounts[0], space),
+        SystemInstruction::Assign { owner } => {
+            let account = &accounts[0];
+            if !account.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            account.assign(&owner);
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn allocate(account: &AccountInfo, space: u64) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !account.data_is_empty() || (*account.owner != system_program::ID) {
+        return Err(ProgramError::Custom(
+            SystemError::AccountAlreadyInUse as u32,
+        ));
+    }
+    account.realloc(space as usize, true)
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !from.data_is_empty() || (*from.owner != system_program::ID) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if from.lamports() < lamports {
+        return Err(ProgramError::Custom(
+            SystemError::ResultWithNegativeLamports as u32,
+        ));
+    }
+    **from.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
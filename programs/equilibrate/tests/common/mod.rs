@@ -0,0 +1,1350 @@
+//! Fixtures shared by the instruction tests. Every test gets its own [`Bank`],
+//! so tests are independent and can run in parallel.
+//!
+//! A few `EquilibrateError` variants have no test: `InvalidProgramId` is
+//! unused, `InvalidPoolManagerOwner` guards an `init` that always assigns the
+//! owner, and `InvalidPoolMint` can only fail for a pool at the derived
+//! address, which the program always creates with the right mint.
+
+// each test binary only uses some of the fixtures
+#![allow(dead_code)]
+
+pub mod bank;
+pub use bank::*;
+
+use anchor_lang::{
+    error::Error,
+    solana_program::{
+        bpf_loader_upgradeable,
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        program_option::COption,
+        pubkey::Pubkey,
+        system_program, sysvar,
+    },
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use equilibrate::{
+    constants::{
+        GAME_MAX_BUCKETS, GAME_MAX_CREATOR_FEE_BPS, GAME_MAX_PLAYERS, GAME_SEED, NATIVE_MINT,
+        PLAYER_SEED, POOL_SEED, PROGRAM_CONFIG_SEED, REFERRAL_SEED, SESSION_SEED,
+    },
+    state::{
+        BurnSchedule, DustDestination, Game, GameConfig, LamportVault, PenaltyDestination,
+        PlayerState, PoolManager, ProgramConfig, ProgramSettings, ReferralStats, SessionAuthority,
+        SpillCurve, TopUpDestination,
+    },
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{
+        mint_close_authority::MintCloseAuthority,
+        non_transferable::NonTransferable,
+        permanent_delegate::PermanentDelegate,
+        transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions, StateWithExtensionsMut,
+    },
+    pod::OptionalNonZeroPubkey,
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+
+pub const MINT_DECIMALS: u8 = 9;
+pub const PROGRAM_FEE_LAMPORTS: u64 = 25_000_000;
+pub const REFERRAL_FEE_BPS: u16 = 2_000;
+pub const WALLET_LAMPORTS: u64 = 10_000_000_000;
+pub const WALLET_DECIMAL_TOKENS: u64 = 1_000_000_000_000;
+pub const ENTRY_FEE_DECIMAL_TOKENS: u64 = 1_000_000_000;
+pub const SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER: u64 = 1_000_000;
+
+pub fn default_program_settings(program_fee_destination: Pubkey) -> ProgramSettings {
+    ProgramSettings {
+        program_fee_destination,
+        program_fee_lamports: PROGRAM_FEE_LAMPORTS,
+        game_max_buckets: GAME_MAX_BUCKETS,
+        game_max_players: GAME_MAX_PLAYERS,
+        game_max_creator_fee_bps: GAME_MAX_CREATOR_FEE_BPS,
+        referral_fee_bps: REFERRAL_FEE_BPS,
+    }
+}
+
+pub fn default_game_config(mint: Pubkey) -> GameConfig {
+    GameConfig {
+        mint,
+        entry_fee_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+        spill_rate_decimal_tokens_per_second_per_player:
+            SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER,
+        n_buckets: 3,
+        max_players: 10,
+        burn_rate_decimal_tokens_per_move: 0,
+        end_epoch_seconds: None,
+        spill_curve: SpillCurve::Linear,
+        allowlist_root: None,
+        creator_fee_bps: 0,
+        max_stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+        top_up_destination: TopUpDestination::HoldingBucket,
+        min_seconds_between_moves: 0,
+        burn_schedule: BurnSchedule::Flat,
+        burn_penalty_half_life_seconds: None,
+        penalty_destination: PenaltyDestination::Burn,
+        dust_destination: DustDestination::LastLeaver,
+    }
+}
+
+pub fn native_mint() -> Pubkey {
+    NATIVE_MINT.parse().unwrap()
+}
+
+pub fn game_address(game_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[GAME_SEED.as_ref(), &game_id.to_le_bytes()],
+        &equilibrate::ID,
+    )
+    .0
+}
+
+pub fn player_state_address(game: &Pubkey, player: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[PLAYER_SEED.as_ref(), game.as_ref(), player.as_ref()],
+        &equilibrate::ID,
+    )
+    .0
+}
+
+pub fn pool_manager_address(mint: &Pubkey) -> Pubkey {
+    PoolManager::get_address(*mint).0
+}
+
+pub fn token_pool_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            POOL_SEED.as_ref(),
+            mint.as_ref(),
+            pool_manager_address(mint).as_ref(),
+        ],
+        &equilibrate::ID,
+    )
+    .0
+}
+
+pub fn lamport_vault_address(game_id: u64) -> Pubkey {
+    LamportVault::get_address(game_id).0
+}
+
+pub fn program_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[PROGRAM_CONFIG_SEED.as_ref()], &equilibrate::ID).0
+}
+
+pub fn referral_stats_address(referrer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[REFERRAL_SEED.as_ref(), referrer.as_ref()],
+        &equilibrate::ID,
+    )
+    .0
+}
+
+pub fn session_address(game_id: u64, player: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            SESSION_SEED.as_ref(),
+            &game_id.to_le_bytes(),
+            player.as_ref(),
+        ],
+        &equilibrate::ID,
+    )
+    .0
+}
+
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[equilibrate::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Builds an instruction for this program from its anchor accounts and arguments
+pub fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: equilibrate::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Asserts that `result` failed with an `EquilibrateError` or anchor `ErrorCode`
+pub fn assert_error(result: ProgramResult, error: impl Into<Error>) {
+    assert_eq!(result, Err(ProgramError::from(error.into())));
+}
+
+/// Token-2022 mint extensions that tests can create mints with
+#[derive(Debug, Clone, Copy)]
+pub enum MintExtension {
+    TransferFee { basis_points: u16, maximum_fee: u64 },
+    MintCloseAuthority,
+    NonTransferable,
+    PermanentDelegate,
+}
+
+/// A wallet with lamports and a token account of the test mint
+#[derive(Debug, Clone, Copy)]
+pub struct Player {
+    pub wallet: Pubkey,
+    pub token_account: Pubkey,
+}
+
+pub struct TestContext {
+    pub bank: Bank,
+    pub upgrade_authority: Pubkey,
+    pub admin: Pubkey,
+    pub program_fee_destination: Pubkey,
+    /// mint of the games in the test, which already has a token pool
+    pub mint: Pubkey,
+    /// token program that owns `mint`
+    pub token_program: Pubkey,
+    next_game_id: u64,
+}
+
+impl TestContext {
+    /// Deploys the program, initializes the program config with the default
+    /// settings, and creates a mint and its token pool.
+    pub fn new() -> Self {
+        let mut context = Self::without_config();
+        let settings = default_program_settings(context.program_fee_destination);
+        context.initialize_config(settings).unwrap();
+        context.mint = context.create_mint(Pubkey::new_unique());
+        context.create_pool(context.mint).unwrap();
+        context
+    }
+
+    /// Like [`TestContext::new`], but the games' mint is a Token-2022 mint with
+    /// `extensions`.
+    pub fn with_token_2022(extensions: &[MintExtension]) -> Self {
+        let mut context = Self::without_config();
+        let settings = default_program_settings(context.program_fee_destination);
+        context.initialize_config(settings).unwrap();
+        context.mint = context.create_mint_2022(Pubkey::new_unique(), extensions);
+        context.token_program = spl_token_2022::ID;
+        context.create_pool(context.mint).unwrap();
+        context
+    }
+
+    /// Only deploys the program.
+    pub fn without_config() -> Self {
+        let upgrade_authority = Pubkey::new_unique();
+        let mut bank = Bank::new(upgrade_authority);
+        bank.airdrop(upgrade_authority, WALLET_LAMPORTS);
+        let program_fee_destination = Pubkey::new_unique();
+        bank.airdrop(program_fee_destination, bank.minimum_balance(0));
+        Self {
+            bank,
+            upgrade_authority,
+            admin: Pubkey::new_unique(),
+            program_fee_destination,
+            mint: Pubkey::default(),
+            token_program: spl_token::ID,
+            next_game_id: 1,
+        }
+    }
+
+    pub fn next_game_id(&mut self) -> u64 {
+        let game_id = self.next_game_id;
+        self.next_game_id += 1;
+        game_id
+    }
+
+    pub fn new_wallet(&mut self) -> Pubkey {
+        let wallet = Pubkey::new_unique();
+        self.bank.airdrop(wallet, WALLET_LAMPORTS);
+        wallet
+    }
+
+    pub fn create_mint(&mut self, address: Pubkey) -> Pubkey {
+        self.bank.set_packed(address, new_mint_state());
+        address
+    }
+
+    pub fn create_mint_2022(&mut self, address: Pubkey, extensions: &[MintExtension]) -> Pubkey {
+        let extension_types: Vec<ExtensionType> = extensions
+            .iter()
+            .map(|extension| match extension {
+                MintExtension::TransferFee { .. } => ExtensionType::TransferFeeConfig,
+                MintExtension::MintCloseAuthority => ExtensionType::MintCloseAuthority,
+                MintExtension::NonTransferable => ExtensionType::NonTransferable,
+                MintExtension::PermanentDelegate => ExtensionType::PermanentDelegate,
+            })
+            .collect();
+        let mut data = vec![0; ExtensionType::get_account_len::<Mint>(&extension_types)];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let authority = OptionalNonZeroPubkey::try_from(Some(Pubkey::new_unique())).unwrap();
+        for extension in extensions {
+            match *extension {
+                MintExtension::TransferFee {
+                    basis_points,
+                    maximum_fee,
+                } => {
+                    let fee = TransferFee {
+                        epoch: 0.into(),
+                        maximum_fee: maximum_fee.into(),
+                        transfer_fee_basis_points: basis_points.into(),
+                    };
+                    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+                    config.transfer_fee_config_authority = authority;
+                    config.withdraw_withheld_authority = authority;
+                    config.older_transfer_fee = fee;
+                    config.newer_transfer_fee = fee;
+                }
+                MintExtension::MintCloseAuthority => {
+                    state
+                        .init_extension::<MintCloseAuthority>(true)
+                        .unwrap()
+                        .close_authority = authority;
+                }
+                MintExtension::NonTransferable => {
+                    state.init_extension::<NonTransferable>(true).unwrap();
+                }
+                MintExtension::PermanentDelegate => {
+                    state
+                        .init_extension::<PermanentDelegate>(true)
+                        .unwrap()
+                        .delegate = authority;
+                }
+            }
+        }
+        state.base = new_mint_state();
+        state.pack_base();
+        state.init_account_type().unwrap();
+        self.bank.set_account(
+            address,
+            StoredAccount {
+                lamports: self.bank.minimum_balance(data.len()),
+                data,
+                owner: spl_token_2022::ID,
+                executable: false,
+            },
+        );
+        address
+    }
+
+    /// Writes a token account holding `amount` newly minted tokens, owned by
+    /// the same token program as `mint` and with the extensions it requires
+    pub fn create_token_account(
+        &mut self,
+        address: Pubkey,
+        mint: Pubkey,
+        owner: Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let mut mint_account = self.bank.get_account(&mint).unwrap().clone();
+        let mint_extensions = {
+            let mut state = StateWithExtensionsMut::<Mint>::unpack(&mut mint_account.data).unwrap();
+            state.base.supply = state.base.supply.checked_add(amount).unwrap();
+            state.pack_base();
+            state.get_extension_types().unwrap()
+        };
+        let token_program = mint_account.owner;
+        self.bank.set_account(mint, mint_account);
+
+        let account_extensions =
+            ExtensionType::get_required_init_account_extensions(&mint_extensions);
+        let mut data = vec![0; ExtensionType::get_account_len::<TokenAccount>(&account_extensions)];
+        let rent_exempt_reserve = self.bank.minimum_balance(data.len());
+        let is_native = mint == native_mint();
+        let mut state =
+            StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+        for extension in account_extensions {
+            assert_eq!(extension, ExtensionType::TransferFeeAmount);
+            state.init_extension::<TransferFeeAmount>(true).unwrap();
+        }
+        state.base = TokenAccount {
+            mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: if is_native {
+                COption::Some(rent_exempt_reserve)
+            } else {
+                COption::None
+            },
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        self.bank.set_account(
+            address,
+            StoredAccount {
+                // wrapped SOL accounts hold their balance as lamports
+                lamports: rent_exempt_reserve + if is_native { amount } else { 0 },
+                data,
+                owner: token_program,
+                executable: false,
+            },
+        );
+        address
+    }
+
+    /// A funded wallet with tokens of `mint` in its associated token account
+    pub fn new_player_of(&mut self, mint: Pubkey) -> Player {
+        let wallet = self.new_wallet();
+        let token_program = self.bank.get_account(&mint).unwrap().owner;
+        let token_account =
+            get_associated_token_address_with_program_id(&wallet, &mint, &token_program);
+        self.create_token_account(token_account, mint, wallet, WALLET_DECIMAL_TOKENS);
+        Player {
+            wallet,
+            token_account,
+        }
+    }
+
+    pub fn new_player(&mut self) -> Player {
+        self.new_player_of(self.mint)
+    }
+
+    pub fn token_account(&self, address: &Pubkey) -> TokenAccount {
+        let data = &self.bank.get_account(address).unwrap().data;
+        StateWithExtensions::<TokenAccount>::unpack(data)
+            .unwrap()
+            .base
+    }
+
+    pub fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        self.token_account(token_account).amount
+    }
+
+    pub fn mint_supply(&self, mint: &Pubkey) -> u64 {
+        let data = &self.bank.get_account(mint).unwrap().data;
+        StateWithExtensions::<Mint>::unpack(data)
+            .unwrap()
+            .base
+            .supply
+    }
+
+    /// The token program that owns `mint`, or SPL token if it doesnt exist
+    pub fn token_program_of(&self, mint: &Pubkey) -> Pubkey {
+        self.bank
+            .get_account(mint)
+            .map_or(spl_token::ID, |account| account.owner)
+    }
+
+    pub fn program_config(&self) -> Option<ProgramConfig> {
+        self.bank.get_anchor_account(&program_config_address())
+    }
+
+    pub fn referral_stats(&self, referrer: &Pubkey) -> Option<ReferralStats> {
+        self.bank
+            .get_anchor_account(&referral_stats_address(referrer))
+    }
+
+    pub fn session(&self, game_id: u64, player: &Player) -> Option<SessionAuthority> {
+        self.bank
+            .get_anchor_account(&session_address(game_id, &player.wallet))
+    }
+
+    pub fn pool_manager(&self, mint: &Pubkey) -> Option<PoolManager> {
+        self.bank.get_anchor_account(&pool_manager_address(mint))
+    }
+
+    pub fn lamport_vault(&self, game_id: u64) -> Option<LamportVault> {
+        self.bank
+            .get_anchor_account(&lamport_vault_address(game_id))
+    }
+
+    pub fn game(&self, game_id: u64) -> Option<Game> {
+        self.bank.get_anchor_account(&game_address(game_id))
+    }
+
+    pub fn player_state(&self, game_id: u64, player: &Player) -> Option<PlayerState> {
+        self.bank.get_anchor_account(&player_state_address(
+            &game_address(game_id),
+            &player.wallet,
+        ))
+    }
+
+    /// Overwrites the game account, e.g. to reach states the program cant get to
+    pub fn set_game(&mut self, game: &Game) {
+        self.bank.set_anchor_account(
+            game_address(game.id),
+            game,
+            Game::get_space(game.config.n_buckets),
+        );
+    }
+
+    /// Overwrites a player state account, e.g. to reach states the program cant get to
+    pub fn set_player_state(&mut self, game_id: u64, player: &Player, state: &PlayerState) {
+        self.bank.set_anchor_account(
+            player_state_address(&game_address(game_id), &player.wallet),
+            state,
+            PlayerState::get_space(),
+        );
+    }
+
+    pub fn migrate_game(&mut self, game_id: u64) -> ProgramResult {
+        let payer = self.new_wallet();
+        let ix = instruction(
+            equilibrate::accounts::MigrateGame {
+                game: game_address(game_id),
+                payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::MigrateGame {},
+        );
+        self.bank.process(&ix, &[payer])
+    }
+
+    pub fn migrate_program_config(&mut self) -> ProgramResult {
+        let payer = self.new_wallet();
+        let ix = instruction(
+            equilibrate::accounts::MigrateProgramConfig {
+                program_config: program_config_address(),
+                payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::MigrateProgramConfig {},
+        );
+        self.bank.process(&ix, &[payer])
+    }
+
+    pub fn migrate_pool_manager(&mut self, mint: Pubkey) -> ProgramResult {
+        let payer = self.new_wallet();
+        let ix = instruction(
+            equilibrate::accounts::MigratePoolManager {
+                pool_manager: pool_manager_address(&mint),
+                token_pool: token_pool_address(&mint),
+                game_mint: mint,
+                token_program: self.token_program_of(&mint),
+                payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::MigratePoolManager {},
+        );
+        self.bank.process(&ix, &[payer])
+    }
+
+    pub fn migrate_player(&mut self, game_id: u64, player: &Player) -> ProgramResult {
+        let payer = self.new_wallet();
+        let ix = instruction(
+            equilibrate::accounts::MigratePlayer {
+                game: game_address(game_id),
+                player_wallet: player.wallet,
+                player: player_state_address(&game_address(game_id), &player.wallet),
+                payer,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::MigratePlayer {},
+        );
+        self.bank.process(&ix, &[payer])
+    }
+
+    pub fn initialize_config_accounts(
+        &self,
+        payer: Pubkey,
+    ) -> equilibrate::accounts::InitializeConfig {
+        equilibrate::accounts::InitializeConfig {
+            program_config: program_config_address(),
+            program: equilibrate::ID,
+            program_data: program_data_address(),
+            payer,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn initialize_config(&mut self, settings: ProgramSettings) -> ProgramResult {
+        let ix = instruction(
+            self.initialize_config_accounts(self.upgrade_authority),
+            equilibrate::instruction::InitializeConfig {
+                admin: self.admin,
+                settings,
+            },
+        );
+        self.bank.process(&ix, &[self.upgrade_authority])
+    }
+
+    pub fn update_config(
+        &mut self,
+        admin: Pubkey,
+        new_admin: Pubkey,
+        settings: ProgramSettings,
+    ) -> ProgramResult {
+        let ix = instruction(
+            equilibrate::accounts::UpdateConfig {
+                program_config: program_config_address(),
+                admin,
+            },
+            equilibrate::instruction::UpdateConfig {
+                new_admin,
+                settings,
+            },
+        );
+        self.bank.process(&ix, &[admin])
+    }
+
+    pub fn create_pool_accounts(
+        &self,
+        payer: Pubkey,
+        mint: Pubkey,
+    ) -> equilibrate::accounts::CreatePool {
+        equilibrate::accounts::CreatePool {
+            pool_manager: pool_manager_address(&mint),
+            token_pool: token_pool_address(&mint),
+            payer,
+            game_mint: mint,
+            token_program: self.token_program_of(&mint),
+            rent: sysvar::rent::ID,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn create_pool(&mut self, mint: Pubkey) -> ProgramResult {
+        let payer = self.new_wallet();
+        let ix = instruction(
+            self.create_pool_accounts(payer, mint),
+            equilibrate::instruction::CreatePool {
+                pool_manager_bump: PoolManager::get_address(mint).1,
+            },
+        );
+        self.bank.process(&ix, &[payer])
+    }
+
+    pub fn audit_pool_accounts(&self, mint: Pubkey) -> equilibrate::accounts::AuditPool {
+        equilibrate::accounts::AuditPool {
+            pool_manager: pool_manager_address(&mint),
+            token_pool: token_pool_address(&mint),
+            game_mint: mint,
+            token_program: self.token_program_of(&mint),
+        }
+    }
+
+    pub fn audit_pool(&mut self, mint: Pubkey) -> ProgramResult {
+        let payer = self.new_wallet();
+        let ix = instruction(
+            self.audit_pool_accounts(mint),
+            equilibrate::instruction::AuditPool {},
+        );
+        self.bank.process(&ix, &[payer])
+    }
+
+    pub fn new_game_accounts(
+        &self,
+        player: &Player,
+        game_id: u64,
+        mint: Pubkey,
+    ) -> equilibrate::accounts::NewGame {
+        let game = game_address(game_id);
+        equilibrate::accounts::NewGame {
+            game,
+            first_player: player_state_address(&game, &player.wallet),
+            program_config: program_config_address(),
+            program_fee_destination: self.program_fee_destination,
+            game_mint: mint,
+            deposit_source_account: player.token_account,
+            pool_manager: pool_manager_address(&mint),
+            token_pool: token_pool_address(&mint),
+            payer: player.wallet,
+            associated_token_program: anchor_spl::associated_token::ID,
+            token_program: self.token_program_of(&mint),
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+    }
+
+    pub fn new_game_with(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        config: GameConfig,
+    ) -> ProgramResult {
+        let ix = instruction(
+            self.new_game_accounts(player, game_id, config.mint),
+            equilibrate::instruction::NewGame { config, game_id },
+        );
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    /// Creates a game with the default config and returns its ID
+    pub fn new_game(&mut self, player: &Player) -> u64 {
+        let game_id = self.next_game_id();
+        self.new_game_with(player, game_id, default_game_config(self.mint))
+            .unwrap();
+        game_id
+    }
+
+    pub fn enter_game_accounts(
+        &self,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::EnterGame {
+        let game = game_address(game_id);
+        // the creator's associated token account, if the game exists
+        let creator_fee_destination = self.game(game_id).map_or(player.token_account, |game| {
+            get_associated_token_address_with_program_id(
+                &game.creator,
+                &self.mint,
+                &self.token_program,
+            )
+        });
+        equilibrate::accounts::EnterGame {
+            game,
+            player: player_state_address(&game, &player.wallet),
+            program_config: program_config_address(),
+            program_fee_destination: self.program_fee_destination,
+            game_mint: self.mint,
+            deposit_source_account: player.token_account,
+            creator_fee_destination,
+            pool_manager: pool_manager_address(&self.mint),
+            token_pool: token_pool_address(&self.mint),
+            payer: player.wallet,
+            token_program: self.token_program,
+            rent: sysvar::rent::ID,
+            system_program: system_program::ID,
+        }
+    }
+
+    /// Entry fee of the game, or the default one if it doesn't exist
+    pub fn entry_fee_decimal_tokens(&self, game_id: u64) -> u64 {
+        self.game(game_id).map_or(ENTRY_FEE_DECIMAL_TOKENS, |game| {
+            game.config.entry_fee_decimal_tokens
+        })
+    }
+
+    pub fn enter_game(&mut self, player: &Player, game_id: u64, bucket: u8) -> ProgramResult {
+        self.enter_game_with_proof(player, game_id, bucket, vec![])
+    }
+
+    /// Creator in bucket 1 and a player in bucket 2 of a game with the default config
+    pub fn two_player_game(&mut self) -> (Player, Player, u64) {
+        self.two_player_game_with(default_game_config(self.mint))
+    }
+
+    /// Creator in bucket 1 and a player in bucket 2 of a game made with `config`
+    pub fn two_player_game_with(&mut self, config: GameConfig) -> (Player, Player, u64) {
+        let creator = self.new_player();
+        let game_id = self.next_game_id();
+        self.new_game_with(&creator, game_id, config).unwrap();
+        let player = self.new_player();
+        self.enter_game(&player, game_id, 2).unwrap();
+        (creator, player, game_id)
+    }
+
+    pub fn enter_game_with_proof(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        bucket: u8,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> ProgramResult {
+        let stake_decimal_tokens = self.entry_fee_decimal_tokens(game_id);
+        self.enter_game_with(
+            player,
+            game_id,
+            bucket,
+            stake_decimal_tokens,
+            allowlist_proof,
+        )
+    }
+
+    pub fn enter_game_with_stake(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        bucket: u8,
+        stake_decimal_tokens: u64,
+    ) -> ProgramResult {
+        self.enter_game_with(player, game_id, bucket, stake_decimal_tokens, vec![])
+    }
+
+    fn enter_game_with(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        bucket: u8,
+        stake_decimal_tokens: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> ProgramResult {
+        let ix = instruction(
+            self.enter_game_accounts(player, game_id),
+            equilibrate::instruction::EnterGame {
+                bucket,
+                stake_decimal_tokens,
+                allowlist_proof,
+            },
+        );
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    pub fn enter_game_referred(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        bucket: u8,
+        referrer: Pubkey,
+    ) -> ProgramResult {
+        let mut ix = instruction(
+            self.enter_game_accounts(player, game_id),
+            equilibrate::instruction::EnterGame {
+                bucket,
+                stake_decimal_tokens: self.entry_fee_decimal_tokens(game_id),
+                allowlist_proof: vec![],
+            },
+        );
+        ix.accounts.extend([
+            AccountMeta::new(referrer, false),
+            AccountMeta::new(referral_stats_address(&referrer), false),
+        ]);
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    pub fn add_deposit_accounts(
+        &self,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::AddDeposit {
+        let game = game_address(game_id);
+        equilibrate::accounts::AddDeposit {
+            game,
+            player: player_state_address(&game, &player.wallet),
+            game_mint: self.mint,
+            deposit_source_account: player.token_account,
+            pool_manager: pool_manager_address(&self.mint),
+            token_pool: token_pool_address(&self.mint),
+            payer: player.wallet,
+            token_program: self.token_program,
+        }
+    }
+
+    pub fn add_deposit(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        deposit_decimal_tokens: u64,
+    ) -> ProgramResult {
+        let ix = instruction(
+            self.add_deposit_accounts(player, game_id),
+            equilibrate::instruction::AddDeposit {
+                deposit_decimal_tokens,
+            },
+        );
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    pub fn move_buckets_accounts(
+        &self,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::MoveBuckets {
+        let game = game_address(game_id);
+        equilibrate::accounts::MoveBuckets {
+            game,
+            player: player_state_address(&game, &player.wallet),
+            payer: player.wallet,
+        }
+    }
+
+    pub fn move_buckets(&mut self, player: &Player, game_id: u64, bucket: u8) -> ProgramResult {
+        self.move_buckets_expecting(player, game_id, bucket, None)
+    }
+
+    pub fn move_buckets_expecting(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        bucket: u8,
+        expected_bucket_players: Option<u16>,
+    ) -> ProgramResult {
+        let ix = instruction(
+            self.move_buckets_accounts(player, game_id),
+            equilibrate::instruction::MoveBuckets {
+                bucket,
+                expected_bucket_players,
+            },
+        );
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    pub fn withdraw_accounts(
+        &self,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::Withdraw {
+        let game = game_address(game_id);
+        equilibrate::accounts::Withdraw {
+            game,
+            game_mint: self.mint,
+            player: player_state_address(&game, &player.wallet),
+            winnings_destination_account: player.token_account,
+            pool_manager: pool_manager_address(&self.mint),
+            token_pool: token_pool_address(&self.mint),
+            payer: player.wallet,
+            token_program: self.token_program,
+        }
+    }
+
+    /// Remaining accounts for charging a penalty in a game that pays penalties
+    /// to an account: the recipient's associated token account, or their wallet
+    /// in lamport games
+    pub fn penalty_destination_accounts(&self, game_id: u64) -> Vec<AccountMeta> {
+        match self.game(game_id).map(|game| game.config) {
+            Some(
+                config @ GameConfig {
+                    penalty_destination: PenaltyDestination::Account { owner },
+                    ..
+                },
+            ) if config.is_lamport_game() => vec![AccountMeta::new(owner, false)],
+            Some(GameConfig {
+                mint,
+                penalty_destination: PenaltyDestination::Account { owner },
+                ..
+            }) => {
+                let token_program = self.bank.get_account(&mint).unwrap().owner;
+                vec![AccountMeta::new(
+                    get_associated_token_address_with_program_id(&owner, &mint, &token_program),
+                    false,
+                )]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Remaining account that closes the session `player` has made for the
+    /// game, if they have made one. It goes between any penalty and dust
+    /// destinations.
+    pub fn session_accounts(&self, game_id: u64, player: &Pubkey) -> Vec<AccountMeta> {
+        // a player state in an older layout was never given a session
+        let has_session = self
+            .bank
+            .get_account(&player_state_address(&game_address(game_id), player))
+            .and_then(|a| PlayerState::try_deserialize(&mut a.data.as_slice()).ok())
+            .is_some_and(|state| state.has_session);
+        if has_session {
+            vec![AccountMeta::new(session_address(game_id, player), false)]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Remaining account for paying the dust of a token game that pays its dust
+    /// to its creator: the creator's associated token account. It goes last, so
+    /// it comes after any penalty destination.
+    pub fn dust_destination_accounts(&self, game_id: u64) -> Vec<AccountMeta> {
+        match self.game(game_id) {
+            Some(game)
+                if game.config.dust_destination == DustDestination::Creator
+                    && !game.config.is_lamport_game() =>
+            {
+                let mint = game.config.mint;
+                let token_program = self.bank.get_account(&mint).unwrap().owner;
+                vec![AccountMeta::new(
+                    get_associated_token_address_with_program_id(
+                        &game.creator,
+                        &mint,
+                        &token_program,
+                    ),
+                    false,
+                )]
+            }
+            _ => vec![],
+        }
+    }
+
+    pub fn withdraw(&mut self, player: &Player, game_id: u64, fraction_bps: u16) -> ProgramResult {
+        let mut ix = instruction(
+            self.withdraw_accounts(player, game_id),
+            equilibrate::instruction::Withdraw { fraction_bps },
+        );
+        ix.accounts
+            .extend(self.penalty_destination_accounts(game_id));
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    /// Accounts for leaving a game that exists
+    pub fn leave_game_accounts(
+        &self,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::LeaveGame {
+        let game = game_address(game_id);
+        equilibrate::accounts::LeaveGame {
+            game,
+            game_mint: self.mint,
+            game_creator: self.game(game_id).unwrap().creator,
+            player: player_state_address(&game, &player.wallet),
+            winnings_destination_account: player.token_account,
+            pool_manager: pool_manager_address(&self.mint),
+            token_pool: token_pool_address(&self.mint),
+            payer: player.wallet,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn leave_game(&mut self, player: &Player, game_id: u64) -> ProgramResult {
+        self.leave_game_with(player, game_id, false)
+    }
+
+    pub fn leave_game_with(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        cancel_on_loss: bool,
+    ) -> ProgramResult {
+        let mut ix = instruction(
+            self.leave_game_accounts(player, game_id),
+            equilibrate::instruction::LeaveGame {
+                cancel_on_loss,
+                min_winnings_decimal_tokens: None,
+                max_burn_decimal_tokens: None,
+            },
+        );
+        ix.accounts
+            .extend(self.penalty_destination_accounts(game_id));
+        ix.accounts
+            .extend(self.session_accounts(game_id, &player.wallet));
+        ix.accounts.extend(self.dust_destination_accounts(game_id));
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    pub fn leave_game_guarded(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        min_winnings_decimal_tokens: Option<u64>,
+        max_burn_decimal_tokens: Option<u64>,
+    ) -> ProgramResult {
+        let mut ix = instruction(
+            self.leave_game_accounts(player, game_id),
+            equilibrate::instruction::LeaveGame {
+                cancel_on_loss: false,
+                min_winnings_decimal_tokens,
+                max_burn_decimal_tokens,
+            },
+        );
+        ix.accounts
+            .extend(self.penalty_destination_accounts(game_id));
+        ix.accounts
+            .extend(self.session_accounts(game_id, &player.wallet));
+        ix.accounts.extend(self.dust_destination_accounts(game_id));
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    pub fn new_lamport_game_with(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        config: GameConfig,
+    ) -> ProgramResult {
+        let game = game_address(game_id);
+        let ix = instruction(
+            equilibrate::accounts::NewLamportGame {
+                game,
+                first_player: player_state_address(&game, &player.wallet),
+                lamport_vault: lamport_vault_address(game_id),
+                program_config: program_config_address(),
+                program_fee_destination: self.program_fee_destination,
+                payer: player.wallet,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            equilibrate::instruction::NewLamportGame { config, game_id },
+        );
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    /// Creates a lamport game with the default config and returns its ID
+    pub fn new_lamport_game(&mut self, player: &Player) -> u64 {
+        let game_id = self.next_game_id();
+        self.new_lamport_game_with(player, game_id, default_game_config(Pubkey::default()))
+            .unwrap();
+        game_id
+    }
+
+    /// Accounts for entering a lamport game that exists
+    pub fn enter_lamport_game_accounts(
+        &self,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::EnterLamportGame {
+        let game = game_address(game_id);
+        equilibrate::accounts::EnterLamportGame {
+            game,
+            player: player_state_address(&game, &player.wallet),
+            program_config: program_config_address(),
+            program_fee_destination: self.program_fee_destination,
+            lamport_vault: lamport_vault_address(game_id),
+            game_creator: self.game(game_id).unwrap().creator,
+            payer: player.wallet,
+            rent: sysvar::rent::ID,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn enter_lamport_game(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        bucket: u8,
+    ) -> ProgramResult {
+        let ix = instruction(
+            self.enter_lamport_game_accounts(player, game_id),
+            equilibrate::instruction::EnterLamportGame {
+                bucket,
+                stake_decimal_tokens: self.entry_fee_decimal_tokens(game_id),
+                allowlist_proof: vec![],
+            },
+        );
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    /// Creator in bucket 1 and a player in bucket 2 of a lamport game made with `config`
+    pub fn two_player_lamport_game_with(&mut self, config: GameConfig) -> (Player, Player, u64) {
+        let creator = self.new_player();
+        let game_id = self.next_game_id();
+        self.new_lamport_game_with(&creator, game_id, config)
+            .unwrap();
+        let player = self.new_player();
+        self.enter_lamport_game(&player, game_id, 2).unwrap();
+        (creator, player, game_id)
+    }
+
+    /// Accounts for leaving a lamport game that exists
+    pub fn leave_lamport_game_accounts(
+        &self,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::LeaveLamportGame {
+        let game = game_address(game_id);
+        equilibrate::accounts::LeaveLamportGame {
+            game,
+            game_creator: self.game(game_id).unwrap().creator,
+            player: player_state_address(&game, &player.wallet),
+            lamport_vault: lamport_vault_address(game_id),
+            payer: player.wallet,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn leave_lamport_game(&mut self, player: &Player, game_id: u64) -> ProgramResult {
+        let mut ix = instruction(
+            self.leave_lamport_game_accounts(player, game_id),
+            equilibrate::instruction::LeaveLamportGame {
+                cancel_on_loss: false,
+                min_winnings_decimal_tokens: None,
+                max_burn_decimal_tokens: None,
+            },
+        );
+        ix.accounts
+            .extend(self.penalty_destination_accounts(game_id));
+        ix.accounts
+            .extend(self.session_accounts(game_id, &player.wallet));
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    /// Accounts for settling a player of a lamport game that exists
+    pub fn settle_lamport_player_accounts(
+        &self,
+        settler: Pubkey,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::SettleLamportPlayer {
+        let game = game_address(game_id);
+        equilibrate::accounts::SettleLamportPlayer {
+            game,
+            game_creator: self.game(game_id).unwrap().creator,
+            player_wallet: player.wallet,
+            player: player_state_address(&game, &player.wallet),
+            lamport_vault: lamport_vault_address(game_id),
+            payer: settler,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn settle_lamport_player(
+        &mut self,
+        settler: Pubkey,
+        player: &Player,
+        game_id: u64,
+    ) -> ProgramResult {
+        let mut ix = instruction(
+            self.settle_lamport_player_accounts(settler, player, game_id),
+            equilibrate::instruction::SettleLamportPlayer {},
+        );
+        ix.accounts
+            .extend(self.penalty_destination_accounts(game_id));
+        ix.accounts
+            .extend(self.session_accounts(game_id, &player.wallet));
+        self.bank.process(&ix, &[settler])
+    }
+
+    pub fn update_game(&mut self, game_id: u64) -> ProgramResult {
+        let ix = instruction(
+            equilibrate::accounts::UpdateGame {
+                game: game_address(game_id),
+            },
+            equilibrate::instruction::UpdateGame {},
+        );
+        self.bank.process(&ix, &[])
+    }
+
+    pub fn settle_player_accounts(
+        &self,
+        settler: Pubkey,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::SettlePlayer {
+        let game = game_address(game_id);
+        equilibrate::accounts::SettlePlayer {
+            game,
+            game_mint: self.mint,
+            game_creator: self.game(game_id).unwrap().creator,
+            player_wallet: player.wallet,
+            player: player_state_address(&game, &player.wallet),
+            winnings_destination_account: player.token_account,
+            pool_manager: pool_manager_address(&self.mint),
+            token_pool: token_pool_address(&self.mint),
+            payer: settler,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        }
+    }
+
+    /// Settles `player` with the transaction paid for and signed by `settler`
+    pub fn settle_player(
+        &mut self,
+        settler: Pubkey,
+        player: &Player,
+        game_id: u64,
+    ) -> ProgramResult {
+        let mut ix = instruction(
+            self.settle_player_accounts(settler, player, game_id),
+            equilibrate::instruction::SettlePlayer {},
+        );
+        ix.accounts
+            .extend(self.penalty_destination_accounts(game_id));
+        ix.accounts
+            .extend(self.session_accounts(game_id, &player.wallet));
+        ix.accounts.extend(self.dust_destination_accounts(game_id));
+        self.bank.process(&ix, &[settler])
+    }
+
+    /// Lets `session_key` act for `player` in the game for `seconds`
+    pub fn create_session(
+        &mut self,
+        player: &Player,
+        game_id: u64,
+        session_key: Pubkey,
+        seconds: i64,
+        can_leave: bool,
+    ) -> ProgramResult {
+        let game = game_address(game_id);
+        let ix = instruction(
+            equilibrate::accounts::CreateSession {
+                game,
+                player: player_state_address(&game, &player.wallet),
+                session: session_address(game_id, &player.wallet),
+                payer: player.wallet,
+                system_program: system_program::ID,
+            },
+            equilibrate::instruction::CreateSession {
+                session_key,
+                expires_epoch_seconds: self.bank.now_epoch_seconds() + seconds,
+                can_leave,
+            },
+        );
+        self.bank.process(&ix, &[player.wallet])
+    }
+
+    pub fn session_move_buckets_accounts(
+        &self,
+        session_key: Pubkey,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::SessionMoveBuckets {
+        let game = game_address(game_id);
+        equilibrate::accounts::SessionMoveBuckets {
+            game,
+            player_wallet: player.wallet,
+            player: player_state_address(&game, &player.wallet),
+            session: session_address(game_id, &player.wallet),
+            payer: session_key,
+        }
+    }
+
+    /// Moves `player` with the transaction signed by `session_key`
+    pub fn session_move_buckets(
+        &mut self,
+        session_key: Pubkey,
+        player: &Player,
+        game_id: u64,
+        bucket: u8,
+    ) -> ProgramResult {
+        let ix = instruction(
+            self.session_move_buckets_accounts(session_key, player, game_id),
+            equilibrate::instruction::SessionMoveBuckets {
+                bucket,
+                expected_bucket_players: None,
+            },
+        );
+        self.bank.process(&ix, &[session_key])
+    }
+
+    pub fn session_leave_game_accounts(
+        &self,
+        session_key: Pubkey,
+        player: &Player,
+        game_id: u64,
+    ) -> equilibrate::accounts::SessionLeaveGame {
+        let game = game_address(game_id);
+        equilibrate::accounts::SessionLeaveGame {
+            game,
+            game_mint: self.mint,
+            game_creator: self.game(game_id).unwrap().creator,
+            player_wallet: player.wallet,
+            player: player_state_address(&game, &player.wallet),
+            session: session_address(game_id, &player.wallet),
+            winnings_destination_account: player.token_account,
+            pool_manager: pool_manager_address(&self.mint),
+            token_pool: token_pool_address(&self.mint),
+            payer: session_key,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        }
+    }
+
+    /// Leaves the game for `player` with the transaction signed by `session_key`
+    pub fn session_leave_game(
+        &mut self,
+        session_key: Pubkey,
+        player: &Player,
+        game_id: u64,
+    ) -> ProgramResult {
+        let mut ix = instruction(
+            self.session_leave_game_accounts(session_key, player, game_id),
+            equilibrate::instruction::SessionLeaveGame {
+                cancel_on_loss: false,
+                min_winnings_decimal_tokens: None,
+                max_burn_decimal_tokens: None,
+            },
+        );
+        ix.accounts
+            .extend(self.penalty_destination_accounts(game_id));
+        ix.accounts.extend(self.dust_destination_accounts(game_id));
+        self.bank.process(&ix, &[session_key])
+    }
+
+    pub fn revoke_session(&mut self, player: &Player, game_id: u64) -> ProgramResult {
+        let ix = instruction(
+            equilibrate::accounts::RevokeSession {
+                session: session_address(game_id, &player.wallet),
+                payer: player.wallet,
+            },
+            equilibrate::instruction::RevokeSession {},
+        );
+        self.bank.process(&ix, &[player.wallet])
+    }
+}
+
+fn new_mint_state() -> Mint {
+    Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: 0,
+        decimals: MINT_DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+}
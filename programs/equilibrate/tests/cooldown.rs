@@ -0,0 +1,91 @@
+mod common;
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use common::*;
+use equilibrate::{model::EquilibrateError, state::GameConfig};
+
+const MIN_SECONDS_BETWEEN_MOVES: u32 = 60;
+
+/// Config of a game with a move cooldown
+fn cooldown_config(mint: Pubkey) -> GameConfig {
+    GameConfig {
+        min_seconds_between_moves: MIN_SECONDS_BETWEEN_MOVES,
+        ..default_game_config(mint)
+    }
+}
+
+#[test]
+fn move_buckets_all_good_first_move_is_allowed_and_recorded() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(cooldown_config(context.mint));
+    assert_eq!(
+        context
+            .player_state(game_id, &player)
+            .unwrap()
+            .last_move_epoch_seconds,
+        0
+    );
+
+    context.move_buckets(&player, game_id, 3).unwrap();
+
+    assert_eq!(
+        context
+            .player_state(game_id, &player)
+            .unwrap()
+            .last_move_epoch_seconds,
+        context.bank.now_epoch_seconds()
+    );
+}
+
+#[test]
+fn move_buckets_all_good_once_cooldown_is_over() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(cooldown_config(context.mint));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(MIN_SECONDS_BETWEEN_MOVES as i64);
+
+    context.move_buckets(&player, game_id, 2).unwrap();
+
+    assert_eq!(context.player_state(game_id, &player).unwrap().bucket, 2);
+}
+
+#[test]
+fn move_buckets_all_good_cooldown_is_per_player() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game_with(cooldown_config(context.mint));
+    context.move_buckets(&player, game_id, 3).unwrap();
+
+    context.move_buckets(&creator, game_id, 2).unwrap();
+}
+
+#[test]
+fn move_buckets_during_cooldown_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(cooldown_config(context.mint));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context
+        .bank
+        .warp_seconds(MIN_SECONDS_BETWEEN_MOVES as i64 - 1);
+
+    assert_error(
+        context.move_buckets(&player, game_id, 2),
+        EquilibrateError::MoveCooldownActive,
+    );
+    assert_eq!(context.player_state(game_id, &player).unwrap().bucket, 3);
+}
+
+#[test]
+fn session_move_buckets_during_cooldown_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(cooldown_config(context.mint));
+    let session_key = context.new_wallet();
+    context
+        .create_session(&player, game_id, session_key, 60 * 60, false)
+        .unwrap();
+    context.move_buckets(&player, game_id, 3).unwrap();
+
+    assert_error(
+        context.session_move_buckets(session_key, &player, game_id, 2),
+        EquilibrateError::MoveCooldownActive,
+    );
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{program_option::COption, pubkey::Pubkey},
+};
+use common::*;
+use equilibrate::{constants::POOL_SEED, state::PoolManager};
+use spl_token::state::Account as TokenAccount;
+
+#[test]
+fn create_pool_all_good_pool_is_owned_by_manager() {
+    let context = TestContext::new();
+    let (manager_address, bump) = PoolManager::get_address(context.mint);
+
+    let manager: PoolManager = context.bank.get_anchor_account(&manager_address).unwrap();
+    assert_eq!(manager.bump, bump);
+
+    let pool: TokenAccount = context.bank.get_packed(&token_pool_address(&context.mint));
+    assert_eq!(pool.mint, context.mint);
+    assert_eq!(pool.owner, manager_address);
+    assert_eq!(pool.amount, 0);
+    assert_eq!(pool.delegate, COption::None);
+}
+
+#[test]
+fn create_pool_twice_fails() {
+    let mut context = TestContext::new();
+    assert!(context.create_pool(context.mint).is_err());
+}
+
+#[test]
+fn create_pool_pool_manager_bad_seed_fails() {
+    let mut context = TestContext::new();
+    let mint = context.create_mint(Pubkey::new_unique());
+    let payer = context.new_wallet();
+    let mut accounts = context.create_pool_accounts(payer, mint);
+    accounts.pool_manager = pool_manager_address(&context.mint);
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::CreatePool {
+            pool_manager_bump: PoolManager::get_address(mint).1,
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[payer]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn create_pool_token_pool_bad_seed_fails() {
+    let mut context = TestContext::new();
+    let mint = context.create_mint(Pubkey::new_unique());
+    let payer = context.new_wallet();
+    let mut accounts = context.create_pool_accounts(payer, mint);
+    accounts.token_pool = Pubkey::find_program_address(
+        &[
+            POOL_SEED.as_ref(),
+            context.mint.as_ref(),
+            pool_manager_address(&mint).as_ref(),
+        ],
+        &equilibrate::ID,
+    )
+    .0;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::CreatePool {
+            pool_manager_bump: PoolManager::get_address(mint).1,
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[payer]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use equilibrate::{model::EquilibrateError, state::GameConfig};
+
+const CREATOR_FEE_BPS: u16 = 250;
+const CREATOR_FEE_DECIMAL_TOKENS: u64 = ENTRY_FEE_DECIMAL_TOKENS / 40;
+
+fn new_game_with_creator_fee(context: &mut TestContext, creator: &Player, bps: u16) -> u64 {
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        creator_fee_bps: bps,
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(creator, game_id, config).unwrap();
+    game_id
+}
+
+fn holding_decimal_tokens(context: &TestContext, game_id: u64) -> u64 {
+    context.game(game_id).unwrap().state.buckets[0].decimal_tokens
+}
+
+#[test]
+fn new_game_all_good_creator_keeps_their_fee() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_game_with_creator_fee(&mut context, &creator, CREATOR_FEE_BPS);
+
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.creator_fees_decimal_tokens, CREATOR_FEE_DECIMAL_TOKENS);
+    assert_eq!(
+        holding_decimal_tokens(&context, game_id),
+        ENTRY_FEE_DECIMAL_TOKENS - CREATOR_FEE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + CREATOR_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn enter_game_all_good_creator_is_paid() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_game_with_creator_fee(&mut context, &creator, CREATOR_FEE_BPS);
+    let creator_balance = context.token_balance(&creator.token_account);
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+
+    for bucket in [1, 2, 3] {
+        let player = context.new_player();
+        context.enter_game(&player, game_id, bucket).unwrap();
+        assert_eq!(
+            context.token_balance(&player.token_account),
+            WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS
+        );
+    }
+
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        creator_balance + 3 * CREATOR_FEE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        pool_balance + 3 * (ENTRY_FEE_DECIMAL_TOKENS - CREATOR_FEE_DECIMAL_TOKENS)
+    );
+    assert_eq!(
+        context.game(game_id).unwrap().creator_fees_decimal_tokens,
+        4 * CREATOR_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn enter_game_all_good_no_creator_fee_ignores_destination() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    let mut accounts = context.enter_game_accounts(&player, game_id);
+    accounts.creator_fee_destination = Pubkey::new_unique();
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::EnterGame {
+            bucket: 2,
+            stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+            allowlist_proof: vec![],
+        },
+    );
+
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+    assert_eq!(
+        context.game(game_id).unwrap().creator_fees_decimal_tokens,
+        0
+    );
+}
+
+#[test]
+fn new_game_creator_fee_above_program_max_fails() {
+    let mut context = TestContext::new();
+    let mut settings = default_program_settings(context.program_fee_destination);
+    settings.game_max_creator_fee_bps = CREATOR_FEE_BPS;
+    context
+        .update_config(context.admin, context.admin, settings)
+        .unwrap();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        creator_fee_bps: CREATOR_FEE_BPS + 1,
+        ..default_game_config(context.mint)
+    };
+
+    assert_error(
+        context.new_game_with(&creator, game_id, config),
+        EquilibrateError::CreatorFeeTooLarge,
+    );
+    assert!(context.game(game_id).is_none());
+}
+
+#[test]
+fn enter_game_creator_fee_to_someone_else_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_game_with_creator_fee(&mut context, &creator, CREATOR_FEE_BPS);
+    let player = context.new_player();
+    let mut accounts = context.enter_game_accounts(&player, game_id);
+    accounts.creator_fee_destination = player.token_account;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::EnterGame {
+            bucket: 2,
+            stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+            allowlist_proof: vec![],
+        },
+    );
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidCreatorFeeDestination,
+    );
+}
+
+#[test]
+fn enter_game_creator_fee_destination_of_other_mint_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_game_with_creator_fee(&mut context, &creator, CREATOR_FEE_BPS);
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    let creator_other_account = context.new_wallet();
+    context.create_token_account(creator_other_account, other_mint, creator.wallet, 0);
+    let player = context.new_player();
+    let mut accounts = context.enter_game_accounts(&player, game_id);
+    accounts.creator_fee_destination = creator_other_account;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::EnterGame {
+            bucket: 2,
+            stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+            allowlist_proof: vec![],
+        },
+    );
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidCreatorFeeDestination,
+    );
+}
@@ -0,0 +1,345 @@
+mod common;
+
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult, instruction::AccountMeta, pubkey::Pubkey,
+};
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, GameEnded, PlayerLeft, PlayerMoved, PlayerWithdrew},
+    state::{DustDestination, GameConfig, LamportVault, PlayerState},
+};
+
+/// Odd spill rate, so that over an odd number of seconds a bucket two players
+/// share fills up with a balance that doesn't split evenly between them
+const ODD_SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER: u64 = 1_000_001;
+
+fn game_balance(context: &TestContext, game_id: u64) -> u64 {
+    let game = context.game(game_id).unwrap();
+    game.state.buckets.iter().map(|b| b.decimal_tokens).sum()
+}
+
+fn dust(context: &TestContext, game_id: u64) -> u64 {
+    context.game(game_id).unwrap().dust_decimal_tokens
+}
+
+/// Creator in bucket 1 and a player in bucket 2 of a game with some dust: a
+/// third player shared bucket 2 with the player, and left when the bucket's
+/// balance didn't split evenly between their stakes
+fn two_player_game_with_dust(
+    context: &mut TestContext,
+    dust_destination: DustDestination,
+) -> (Player, Player, u64) {
+    let (creator, player, game_id) = context.two_player_game_with(GameConfig {
+        spill_rate_decimal_tokens_per_second_per_player:
+            ODD_SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER,
+        dust_destination,
+        ..default_game_config(context.mint)
+    });
+    let bucket_mate = context.new_player();
+    context.enter_game(&bucket_mate, game_id, 2).unwrap();
+    context.bank.warp_seconds(7);
+    context.leave_game(&bucket_mate, game_id).unwrap();
+    assert!(dust(context, game_id) > 0);
+    (creator, player, game_id)
+}
+
+fn leave_game_without_dust_destination(
+    context: &mut TestContext,
+    player: &Player,
+    game_id: u64,
+) -> ProgramResult {
+    let ix = instruction(
+        context.leave_game_accounts(player, game_id),
+        equilibrate::instruction::LeaveGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+    context.bank.process(&ix, &[player.wallet])
+}
+
+#[test]
+fn move_buckets_all_good_spill_remainder_stays_in_bucket() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        dust_destination: DustDestination::LastLeaver,
+        ..default_game_config(context.mint)
+    });
+    // the holding bucket's spill doesn't split evenly over the three buckets
+    assert_ne!(
+        2 * 10 * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER % 3,
+        0
+    );
+    context.bank.warp_seconds(10);
+
+    context.move_buckets(&player, game_id, 3).unwrap();
+
+    assert_eq!(dust(&context, game_id), 0);
+    assert_eq!(
+        context.bank.events::<PlayerMoved>()[0].dust_decimal_tokens,
+        0
+    );
+    assert_eq!(
+        game_balance(&context, game_id) + dust(&context, game_id),
+        2 * ENTRY_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn withdraw_all_good_share_remainder_becomes_dust() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        dust_destination: DustDestination::LastLeaver,
+        ..default_game_config(context.mint)
+    });
+    context.bank.warp_seconds(10);
+    // someone else shares bucket 2, so the player's share splits the bucket
+    let bucket_mate = context.new_player();
+    context.enter_game(&bucket_mate, game_id, 2).unwrap();
+    let dust_before = dust(&context, game_id);
+    let bucket_before = context.game(game_id).unwrap().state.buckets[2].decimal_tokens;
+
+    context.withdraw(&player, game_id, 3_333).unwrap();
+
+    let event = &context.bank.events::<PlayerWithdrew>()[0];
+    let bucket_after = context.game(game_id).unwrap().state.buckets[2].decimal_tokens;
+    let share_dust = bucket_before - event.winnings_decimal_tokens - bucket_after;
+    assert!(share_dust > 0);
+    assert_eq!(dust(&context, game_id), dust_before + share_dust);
+    assert_eq!(event.dust_decimal_tokens, dust(&context, game_id));
+    // the pool still holds exactly what the game accounts for
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        game_balance(&context, game_id) + dust(&context, game_id)
+    );
+}
+
+#[test]
+fn leave_game_all_good_last_leaver_takes_dust() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) =
+        two_player_game_with_dust(&mut context, DustDestination::LastLeaver);
+    context.leave_game(&player, game_id).unwrap();
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+    assert!(dust(&context, game_id) > 0);
+
+    context.leave_game(&creator, game_id).unwrap();
+
+    assert_eq!(
+        context.bank.events::<PlayerLeft>()[0].winnings_decimal_tokens,
+        pool_balance
+    );
+    assert_eq!(context.bank.events::<GameEnded>()[0].dust_decimal_tokens, 0);
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+}
+
+#[test]
+fn leave_game_all_good_creator_gets_dust() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) =
+        two_player_game_with_dust(&mut context, DustDestination::Creator);
+    context.leave_game(&creator, game_id).unwrap();
+    let game_dust = dust(&context, game_id);
+    assert!(game_dust > 0);
+    let creator_before = context.token_balance(&creator.token_account);
+    let player_before = context.token_balance(&player.token_account);
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+
+    context.leave_game(&player, game_id).unwrap();
+
+    let left = &context.bank.events::<PlayerLeft>()[0];
+    assert_eq!(left.winnings_decimal_tokens, pool_balance - game_dust);
+    assert_eq!(left.dust_decimal_tokens, game_dust);
+    assert_eq!(
+        context.bank.events::<GameEnded>()[0].dust_decimal_tokens,
+        game_dust
+    );
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        creator_before + game_dust
+    );
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        player_before + pool_balance - game_dust
+    );
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+    assert_eq!(
+        context
+            .pool_manager(&context.mint)
+            .unwrap()
+            .liabilities_decimal_tokens,
+        0
+    );
+}
+
+#[test]
+fn leave_game_all_good_dust_is_burned() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = two_player_game_with_dust(&mut context, DustDestination::Burn);
+    context.leave_game(&player, game_id).unwrap();
+    let game_dust = dust(&context, game_id);
+    let supply_before = context.mint_supply(&context.mint);
+
+    context.leave_game(&creator, game_id).unwrap();
+
+    assert_eq!(
+        context.bank.events::<GameEnded>()[0].dust_decimal_tokens,
+        game_dust
+    );
+    assert_eq!(
+        context.mint_supply(&context.mint),
+        supply_before - game_dust
+    );
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+}
+
+#[test]
+fn leave_game_not_last_player_leaves_dust_in_game() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) =
+        two_player_game_with_dust(&mut context, DustDestination::Creator);
+    let dust_before = dust(&context, game_id);
+    let creator_before = context.token_balance(&creator.token_account);
+
+    // only the last player needs the dust destination
+    leave_game_without_dust_destination(&mut context, &player, game_id).unwrap();
+
+    assert_eq!(dust(&context, game_id), dust_before);
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        creator_before
+    );
+}
+
+#[test]
+fn leave_game_dust_destination_missing_fails() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) =
+        two_player_game_with_dust(&mut context, DustDestination::Creator);
+    context.leave_game(&player, game_id).unwrap();
+
+    assert_error(
+        leave_game_without_dust_destination(&mut context, &creator, game_id),
+        EquilibrateError::MissingDustDestination,
+    );
+}
+
+#[test]
+fn leave_game_dust_destination_of_someone_else_fails() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) =
+        two_player_game_with_dust(&mut context, DustDestination::Creator);
+    context.leave_game(&creator, game_id).unwrap();
+    let mut ix = instruction(
+        context.leave_game_accounts(&player, game_id),
+        equilibrate::instruction::LeaveGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+    ix.accounts
+        .push(AccountMeta::new(player.token_account, false));
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidDustDestination,
+    );
+}
+
+#[test]
+fn settle_player_all_good_final_settlement_pays_creator_dust() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + 10),
+        dust_destination: DustDestination::Creator,
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(&creator, game_id, config).unwrap();
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+    context.bank.warp_seconds(20);
+    let settler = context.new_wallet();
+    context.settle_player(settler, &player, game_id).unwrap();
+    let game_dust = dust(&context, game_id);
+    let creator_before = context.token_balance(&creator.token_account);
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+
+    context.settle_player(settler, &creator, game_id).unwrap();
+
+    assert_eq!(
+        context.bank.events::<GameEnded>()[0].dust_decimal_tokens,
+        game_dust
+    );
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        creator_before + pool_balance
+    );
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+}
+
+#[test]
+fn new_lamport_game_burning_dust_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        dust_destination: DustDestination::Burn,
+        ..default_game_config(Pubkey::default())
+    };
+
+    assert_error(
+        context.new_lamport_game_with(&creator, game_id, config),
+        EquilibrateError::CannotBurnNativeMint,
+    );
+}
+
+#[test]
+fn leave_lamport_game_all_good_creator_gets_dust() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        spill_rate_decimal_tokens_per_second_per_player:
+            ODD_SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER,
+        dust_destination: DustDestination::Creator,
+        ..default_game_config(Pubkey::default())
+    };
+    context
+        .new_lamport_game_with(&creator, game_id, config)
+        .unwrap();
+    let player = context.new_player();
+    context.enter_lamport_game(&player, game_id, 2).unwrap();
+    let bucket_mate = context.new_player();
+    context
+        .enter_lamport_game(&bucket_mate, game_id, 2)
+        .unwrap();
+    context.bank.warp_seconds(7);
+    context.leave_lamport_game(&bucket_mate, game_id).unwrap();
+    context.leave_lamport_game(&creator, game_id).unwrap();
+    let game_dust = dust(&context, game_id);
+    assert!(game_dust > 0);
+    let creator_before = context.bank.lamports(&creator.wallet);
+    let rent = context.bank.minimum_balance(LamportVault::get_space())
+        + context.bank.lamports(&game_address(game_id));
+    let player_before = context.bank.lamports(&player.wallet);
+    let pot = game_balance(&context, game_id);
+
+    context.leave_lamport_game(&player, game_id).unwrap();
+
+    assert_eq!(
+        context.bank.events::<GameEnded>()[0].dust_decimal_tokens,
+        game_dust
+    );
+    assert_eq!(
+        context.bank.lamports(&creator.wallet),
+        creator_before + game_dust + rent
+    );
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        player_before + pot + context.bank.minimum_balance(PlayerState::get_space())
+    );
+}
@@ -0,0 +1,375 @@
+mod common;
+
+use anchor_lang::{error::ErrorCode, solana_program::pubkey::Pubkey};
+use common::*;
+use equilibrate::{
+    constants::PLAYER_STATE_VERSION,
+    model::{EquilibrateError, PlayerEntered},
+    state::Bucket,
+};
+
+fn enter_game_with_pool(
+    context: &mut TestContext,
+    player: &Player,
+    game_id: u64,
+    token_pool: Pubkey,
+    pool_manager: Pubkey,
+) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+    let mut accounts = context.enter_game_accounts(player, game_id);
+    accounts.token_pool = token_pool;
+    accounts.pool_manager = pool_manager;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::EnterGame {
+            bucket: 1,
+            stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+            allowlist_proof: vec![],
+        },
+    );
+    context.bank.process(&ix, &[player.wallet])
+}
+
+#[test]
+fn enter_game_all_good_player_is_deposited_into_correct_bucket() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 3).unwrap();
+
+    let player_state = context.player_state(game_id, &player).unwrap();
+    assert_eq!(player_state.version, PLAYER_STATE_VERSION);
+    assert_eq!(player_state.bucket, 3);
+    assert_eq!(player_state.burn_penalty_decimal_tokens, 0);
+
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.state.buckets[0].players, 2);
+    assert_eq!(game.state.buckets[1].players, 1);
+    assert_eq!(game.state.buckets[3].players, 1);
+}
+
+#[test]
+fn enter_game_all_good_program_fee_is_transferred() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    let fee_balance_before = context.bank.lamports(&context.program_fee_destination);
+    context.enter_game(&player, game_id, 2).unwrap();
+    assert_eq!(
+        context.bank.lamports(&context.program_fee_destination),
+        fee_balance_before + PROGRAM_FEE_LAMPORTS
+    );
+}
+
+#[test]
+fn enter_game_all_good_game_tokens_are_transferred() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        2 * ENTRY_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn enter_game_after_time_passes_bucket_balances_update_correctly() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    context.bank.warp_seconds(10);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+
+    // the holding bucket has 1 player, so it spills into the two empty buckets
+    // but not the creator's bucket
+    let spilled = 10 * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER;
+    let game = context.game(game_id).unwrap();
+    assert_eq!(
+        game.state.buckets,
+        vec![
+            Bucket {
+                decimal_tokens: 2 * ENTRY_FEE_DECIMAL_TOKENS - spilled,
+                players: 2,
+                stake_decimal_tokens: 2 * ENTRY_FEE_DECIMAL_TOKENS
+            },
+            Bucket {
+                decimal_tokens: 0,
+                players: 1,
+                stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS
+            },
+            Bucket {
+                decimal_tokens: spilled / 2,
+                players: 1,
+                stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS
+            },
+            Bucket {
+                decimal_tokens: spilled / 2,
+                players: 0,
+                stake_decimal_tokens: 0
+            },
+        ]
+    );
+    assert_eq!(
+        game.state.last_update_epoch_seconds,
+        context.bank.now_epoch_seconds()
+    );
+}
+
+#[test]
+fn enter_game_all_good_prize_pool_balance_remains_consistent() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    for i in 0..8u8 {
+        context.bank.warp_seconds(37 * (i as i64 + 1));
+        let player = context.new_player();
+        context.enter_game(&player, game_id, i % 3 + 1).unwrap();
+    }
+    let game = context.game(game_id).unwrap();
+    let game_tokens: u64 = game
+        .state
+        .buckets
+        .iter()
+        .map(|b| b.decimal_tokens)
+        .sum::<u64>()
+        + game.dust_decimal_tokens;
+    assert_eq!(game_tokens, 9 * ENTRY_FEE_DECIMAL_TOKENS);
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        game_tokens
+    );
+}
+
+#[test]
+fn enter_game_all_good_player_entered_event_is_emitted() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+
+    let events = context.bank.events::<PlayerEntered>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].game_id, game_id);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].bucket, 2);
+    assert_eq!(
+        events[0].buckets,
+        context.game(game_id).unwrap().state.buckets
+    );
+}
+
+#[test]
+fn enter_game_twice_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+    assert!(context.enter_game(&player, game_id, 3).is_err());
+    assert!(context.enter_game(&creator, game_id, 3).is_err());
+}
+
+#[test]
+fn enter_game_game_is_at_capacity_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let mut config = default_game_config(context.mint);
+    config.max_players = 2;
+    context.new_game_with(&creator, game_id, config).unwrap();
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+
+    let late_player = context.new_player();
+    assert_error(
+        context.enter_game(&late_player, game_id, 2),
+        EquilibrateError::GameAtCapacity,
+    );
+}
+
+#[test]
+fn enter_game_game_is_over_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    // the last player leaving closes the game, so an empty game has to be forged
+    let mut game = context.game(game_id).unwrap();
+    for bucket in game.state.buckets.iter_mut() {
+        bucket.players = 0;
+    }
+    context.set_game(&game);
+
+    let player = context.new_player();
+    assert_error(
+        context.enter_game(&player, game_id, 2),
+        EquilibrateError::GameIsOver,
+    );
+}
+
+#[test]
+fn enter_game_game_hasnt_been_created_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.next_game_id();
+    assert_error(
+        context.enter_game(&player, game_id, 2),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn enter_game_bucket_index_too_high_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    assert_error(
+        context.enter_game(&player, game_id, 4),
+        EquilibrateError::BucketDoesNotExist,
+    );
+}
+
+#[test]
+fn enter_game_player_tries_to_enter_holding_bucket_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    assert_error(
+        context.enter_game(&player, game_id, 0),
+        EquilibrateError::CannotEnterHoldingBucket,
+    );
+}
+
+#[test]
+fn enter_game_player_bad_seed_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    let mut accounts = context.enter_game_accounts(&player, game_id);
+    accounts.player = player_state_address(&game_address(game_id), &creator.wallet);
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::EnterGame {
+            bucket: 2,
+            stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+            allowlist_proof: vec![],
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn enter_game_program_fee_destination_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    let mut accounts = context.enter_game_accounts(&player, game_id);
+    accounts.program_fee_destination = Pubkey::new_unique();
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::EnterGame {
+            bucket: 2,
+            stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+            allowlist_proof: vec![],
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidProgramFeeDestination,
+    );
+}
+
+#[test]
+fn enter_game_deposit_source_account_wrong_mint_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    let player = context.new_player_of(other_mint);
+    assert_error(
+        context.enter_game(&player, game_id, 2),
+        EquilibrateError::InvalidTokenSourceMint,
+    );
+}
+
+#[test]
+fn enter_game_token_pool_wrong_mint_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    context.create_pool(other_mint).unwrap();
+    let player = context.new_player();
+    assert_error(
+        enter_game_with_pool(
+            &mut context,
+            &player,
+            game_id,
+            token_pool_address(&other_mint),
+            pool_manager_address(&other_mint),
+        ),
+        EquilibrateError::InvalidPoolManager,
+    );
+}
+
+#[test]
+fn enter_game_pool_manager_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    context.create_pool(other_mint).unwrap();
+    let player = context.new_player();
+    let pool = token_pool_address(&context.mint);
+    let imposter_manager = pool_manager_address(&other_mint);
+    assert_error(
+        enter_game_with_pool(&mut context, &player, game_id, pool, imposter_manager),
+        EquilibrateError::InvalidPoolManager,
+    );
+}
+
+#[test]
+fn enter_game_token_pool_address_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    let manager = pool_manager_address(&context.mint);
+    let imposter_pool = Pubkey::new_unique();
+    context.create_token_account(imposter_pool, context.mint, manager, 0);
+    assert_error(
+        enter_game_with_pool(&mut context, &player, game_id, imposter_pool, manager),
+        EquilibrateError::InvalidTokenPool,
+    );
+}
+
+#[test]
+fn enter_game_token_pool_owner_isnt_manager_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    // a pool at the right address whose authority has been changed
+    let pool = token_pool_address(&context.mint);
+    let manager = pool_manager_address(&context.mint);
+    context.create_token_account(pool, context.mint, player.wallet, 0);
+    assert_error(
+        enter_game_with_pool(&mut context, &player, game_id, pool, manager),
+        EquilibrateError::InvalidTokenPoolOwner,
+    );
+}
@@ -0,0 +1,398 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey},
+};
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, GameEnded, PlayerLeft, PlayerSettled},
+    state::{GameConfig, LamportVault, PenaltyDestination, PlayerState},
+};
+
+const BURN_RATE_LAMPORTS: u64 = 1_000_000;
+
+fn lamport_game_config() -> GameConfig {
+    default_game_config(Pubkey::default())
+}
+
+fn vault_rent(context: &TestContext) -> u64 {
+    context.bank.minimum_balance(LamportVault::get_space())
+}
+
+fn assert_vault_holds_game(context: &TestContext, game_id: u64) {
+    let game = context.game(game_id).unwrap();
+    let game_lamports: u64 = game
+        .state
+        .buckets
+        .iter()
+        .map(|b| b.decimal_tokens)
+        .sum::<u64>()
+        + game.dust_decimal_tokens;
+    assert_eq!(
+        context.bank.lamports(&lamport_vault_address(game_id)),
+        vault_rent(context) + game_lamports
+    );
+}
+
+#[test]
+fn new_lamport_game_all_good() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let wallet_before = context.bank.lamports(&creator.wallet);
+
+    context
+        .new_lamport_game_with(&creator, game_id, lamport_game_config())
+        .unwrap();
+
+    let game = context.game(game_id).unwrap();
+    assert!(game.config.is_lamport_game());
+    assert_eq!(
+        game.state.buckets[0].decimal_tokens,
+        ENTRY_FEE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context.lamport_vault(game_id).unwrap(),
+        LamportVault { game_id }
+    );
+    assert_vault_holds_game(&context, game_id);
+    let rent = vault_rent(&context)
+        + context.bank.minimum_balance(PlayerState::get_space())
+        + context.bank.lamports(&game_address(game_id));
+    assert_eq!(
+        context.bank.lamports(&creator.wallet),
+        wallet_before - ENTRY_FEE_DECIMAL_TOKENS - PROGRAM_FEE_LAMPORTS - rent
+    );
+}
+
+#[test]
+fn new_lamport_game_with_mint_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+
+    assert_error(
+        context.new_lamport_game_with(&creator, game_id, default_game_config(context.mint)),
+        EquilibrateError::InvalidGameMint,
+    );
+    assert!(context.game(game_id).is_none());
+}
+
+#[test]
+fn new_lamport_game_burning_penalties_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        burn_rate_decimal_tokens_per_move: BURN_RATE_LAMPORTS,
+        penalty_destination: PenaltyDestination::Burn,
+        ..lamport_game_config()
+    };
+
+    assert_error(
+        context.new_lamport_game_with(&creator, game_id, config),
+        EquilibrateError::CannotBurnNativeMint,
+    );
+}
+
+#[test]
+fn enter_lamport_game_all_good() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_lamport_game(&creator);
+    let player = context.new_player();
+    let wallet_before = context.bank.lamports(&player.wallet);
+    let token_balance_before = context.token_balance(&player.token_account);
+
+    context.enter_lamport_game(&player, game_id, 2).unwrap();
+
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.state.buckets[2].players, 1);
+    assert_eq!(
+        game.state.buckets[0].decimal_tokens,
+        2 * ENTRY_FEE_DECIMAL_TOKENS
+    );
+    assert_vault_holds_game(&context, game_id);
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        wallet_before
+            - ENTRY_FEE_DECIMAL_TOKENS
+            - PROGRAM_FEE_LAMPORTS
+            - context.bank.minimum_balance(PlayerState::get_space())
+    );
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        token_balance_before
+    );
+}
+
+#[test]
+fn enter_lamport_game_all_good_pays_creator_fee_to_wallet() {
+    let mut context = TestContext::new();
+    let config = GameConfig {
+        creator_fee_bps: 1_000,
+        ..lamport_game_config()
+    };
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    context
+        .new_lamport_game_with(&creator, game_id, config)
+        .unwrap();
+    let creator_before = context.bank.lamports(&creator.wallet);
+    let player = context.new_player();
+
+    context.enter_lamport_game(&player, game_id, 2).unwrap();
+
+    let creator_fee = ENTRY_FEE_DECIMAL_TOKENS / 10;
+    assert_eq!(
+        context.bank.lamports(&creator.wallet),
+        creator_before + creator_fee
+    );
+    assert_eq!(
+        context.game(game_id).unwrap().creator_fees_decimal_tokens,
+        2 * creator_fee
+    );
+    assert_vault_holds_game(&context, game_id);
+}
+
+#[test]
+fn enter_lamport_game_of_token_game_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+
+    // token games have no vault
+    assert_error(
+        context.enter_lamport_game(&player, game_id, 2),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn enter_game_of_lamport_game_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_lamport_game(&creator);
+    let player = context.new_player();
+
+    assert_error(
+        context.enter_game(&player, game_id, 2),
+        EquilibrateError::InvalidGameMint,
+    );
+}
+
+#[test]
+fn leave_lamport_game_all_good_pays_winnings_in_lamports() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_lamport_game_with(lamport_game_config());
+    context.bank.warp_seconds(30);
+    let wallet_before = context.bank.lamports(&player.wallet);
+
+    context.leave_lamport_game(&player, game_id).unwrap();
+
+    let winnings = context.bank.events::<PlayerLeft>()[0].winnings_decimal_tokens;
+    assert!(winnings > 0);
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        wallet_before + winnings + context.bank.minimum_balance(PlayerState::get_space())
+    );
+    assert!(context.player_state(game_id, &player).is_none());
+    assert_vault_holds_game(&context, game_id);
+}
+
+#[test]
+fn leave_lamport_game_all_good_last_player_closes_game_and_vault() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_lamport_game_with(lamport_game_config());
+    context.leave_lamport_game(&player, game_id).unwrap();
+    let vault_lamports = context.bank.lamports(&lamport_vault_address(game_id));
+    let game_rent = context.bank.lamports(&game_address(game_id));
+    let creator_before = context.bank.lamports(&creator.wallet);
+
+    context.leave_lamport_game(&creator, game_id).unwrap();
+
+    assert!(context.game(game_id).is_none());
+    assert!(context.lamport_vault(game_id).is_none());
+    assert_eq!(context.bank.lamports(&lamport_vault_address(game_id)), 0);
+    // the creator gets the whole pot as the last player, plus all the rent
+    assert_eq!(
+        context.bank.lamports(&creator.wallet),
+        creator_before
+            + vault_lamports
+            + game_rent
+            + context.bank.minimum_balance(PlayerState::get_space())
+    );
+}
+
+#[test]
+fn leave_lamport_game_all_good_account_gets_penalty() {
+    let mut context = TestContext::new();
+    let treasury = context.new_wallet();
+    let config = GameConfig {
+        burn_rate_decimal_tokens_per_move: BURN_RATE_LAMPORTS,
+        penalty_destination: PenaltyDestination::Account { owner: treasury },
+        ..lamport_game_config()
+    };
+    let (_, player, game_id) = context.two_player_lamport_game_with(config);
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+    let treasury_before = context.bank.lamports(&treasury);
+
+    context.leave_lamport_game(&player, game_id).unwrap();
+
+    assert_eq!(
+        context.bank.lamports(&treasury),
+        treasury_before + BURN_RATE_LAMPORTS
+    );
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(events[0].penalty_decimal_tokens, BURN_RATE_LAMPORTS);
+    assert_eq!(events[0].burned_decimal_tokens, 0);
+    assert_vault_holds_game(&context, game_id);
+}
+
+#[test]
+fn leave_lamport_game_all_good_holding_bucket_gets_penalty() {
+    let mut context = TestContext::new();
+    let config = GameConfig {
+        burn_rate_decimal_tokens_per_move: BURN_RATE_LAMPORTS,
+        penalty_destination: PenaltyDestination::HoldingBucket,
+        ..lamport_game_config()
+    };
+    let (_, player, game_id) = context.two_player_lamport_game_with(config);
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+
+    context.leave_lamport_game(&player, game_id).unwrap();
+
+    assert_eq!(
+        context.bank.events::<PlayerLeft>()[0].penalty_decimal_tokens,
+        BURN_RATE_LAMPORTS
+    );
+    assert_vault_holds_game(&context, game_id);
+}
+
+/// Lamport game config that ends after `seconds`
+fn ending_lamport_game_config(context: &TestContext, seconds: i64) -> GameConfig {
+    GameConfig {
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + seconds),
+        ..lamport_game_config()
+    }
+}
+
+#[test]
+fn settle_lamport_player_all_good_pays_winnings_in_lamports() {
+    let mut context = TestContext::new();
+    let config = ending_lamport_game_config(&context, 30);
+    let (_, player, game_id) = context.two_player_lamport_game_with(config);
+    context.bank.warp_seconds(60);
+    let wallet_before = context.bank.lamports(&player.wallet);
+    let settler = context.new_wallet();
+
+    context
+        .settle_lamport_player(settler, &player, game_id)
+        .unwrap();
+
+    let events = context.bank.events::<PlayerSettled>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].settled_by, settler);
+    let winnings = events[0].winnings_decimal_tokens;
+    assert!(winnings > 0);
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        wallet_before + winnings + context.bank.minimum_balance(PlayerState::get_space())
+    );
+    assert!(context.player_state(game_id, &player).is_none());
+    assert_vault_holds_game(&context, game_id);
+}
+
+#[test]
+fn settle_lamport_player_all_good_last_player_closes_game_and_vault() {
+    let mut context = TestContext::new();
+    let config = ending_lamport_game_config(&context, 30);
+    let (creator, player, game_id) = context.two_player_lamport_game_with(config);
+    context.bank.warp_seconds(60);
+    let settler = context.new_wallet();
+    context
+        .settle_lamport_player(settler, &player, game_id)
+        .unwrap();
+    let vault_lamports = context.bank.lamports(&lamport_vault_address(game_id));
+    let game_rent = context.bank.lamports(&game_address(game_id));
+    let creator_before = context.bank.lamports(&creator.wallet);
+
+    context
+        .settle_lamport_player(settler, &creator, game_id)
+        .unwrap();
+
+    assert!(context.game(game_id).is_none());
+    assert!(context.lamport_vault(game_id).is_none());
+    assert_eq!(
+        context.bank.lamports(&creator.wallet),
+        creator_before
+            + vault_lamports
+            + game_rent
+            + context.bank.minimum_balance(PlayerState::get_space())
+    );
+    assert_eq!(context.bank.events::<GameEnded>().len(), 1);
+}
+
+#[test]
+fn settle_lamport_player_before_end_fails() {
+    let mut context = TestContext::new();
+    let config = ending_lamport_game_config(&context, 30);
+    let (_, player, game_id) = context.two_player_lamport_game_with(config);
+    let settler = context.new_wallet();
+
+    assert_error(
+        context.settle_lamport_player(settler, &player, game_id),
+        EquilibrateError::GameHasNotEnded,
+    );
+}
+
+#[test]
+fn settle_lamport_player_of_token_game_fails() {
+    let mut context = TestContext::new();
+    let config = GameConfig {
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + 30),
+        ..default_game_config(context.mint)
+    };
+    let (_, player, game_id) = context.two_player_game_with(config);
+    context.bank.warp_seconds(60);
+    let settler = context.new_wallet();
+
+    // token games have no vault
+    assert_error(
+        context.settle_lamport_player(settler, &player, game_id),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn leave_lamport_game_penalty_destination_of_someone_else_fails() {
+    let mut context = TestContext::new();
+    let treasury = context.new_wallet();
+    let config = GameConfig {
+        burn_rate_decimal_tokens_per_move: BURN_RATE_LAMPORTS,
+        penalty_destination: PenaltyDestination::Account { owner: treasury },
+        ..lamport_game_config()
+    };
+    let (_, player, game_id) = context.two_player_lamport_game_with(config);
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+    let mut ix = instruction(
+        context.leave_lamport_game_accounts(&player, game_id),
+        equilibrate::instruction::LeaveLamportGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+    ix.accounts.push(AccountMeta::new(player.wallet, false));
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidPenaltyDestination,
+    );
+}
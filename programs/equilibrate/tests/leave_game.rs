@@ -0,0 +1,499 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{entrypoint::ProgramResult, pubkey::Pubkey},
+};
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, GameEnded, PlayerLeft},
+    state::{Game, GameConfig, PlayerState},
+};
+
+/// Share of each bucket after `seconds` in a two player game nobody has moved in.
+/// The holding bucket spills into all three buckets since each has fewer players.
+fn two_player_share(seconds: u64) -> u64 {
+    2 * seconds * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER / 3
+}
+
+fn leave_game_with_accounts(
+    context: &mut TestContext,
+    player: &Player,
+    game_id: u64,
+    modify: impl FnOnce(&mut equilibrate::accounts::LeaveGame),
+) -> ProgramResult {
+    let mut accounts = context.leave_game_accounts(player, game_id);
+    modify(&mut accounts);
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::LeaveGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+    context.bank.process(&ix, &[player.wallet])
+}
+
+#[test]
+fn leave_game_non_last_player_gets_proportion_of_their_bucket() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+    context.leave_game(&player, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + two_player_share(10)
+    );
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.state.buckets[2].decimal_tokens, 0);
+    assert_eq!(game.state.buckets[2].players, 0);
+    assert_eq!(game.state.buckets[0].players, 1);
+    let game_tokens: u64 = game
+        .state
+        .buckets
+        .iter()
+        .map(|b| b.decimal_tokens)
+        .sum::<u64>()
+        + game.dust_decimal_tokens;
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        game_tokens
+    );
+}
+
+#[test]
+fn leave_game_player_state_account_is_closed_and_rent_returned_to_payer() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let lamports_before = context.bank.lamports(&player.wallet);
+    context.leave_game(&player, game_id).unwrap();
+
+    assert!(context.player_state(game_id, &player).is_none());
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        lamports_before + context.bank.minimum_balance(PlayerState::get_space())
+    );
+}
+
+#[test]
+fn leave_game_last_player_gets_remaining_tokens_and_game_is_closed() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+    context.leave_game(&player, game_id).unwrap();
+    context.bank.warp_seconds(10);
+    let creator_lamports_before = context.bank.lamports(&creator.wallet);
+    context.leave_game(&creator, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        WALLET_DECIMAL_TOKENS + ENTRY_FEE_DECIMAL_TOKENS - two_player_share(10)
+    );
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+    assert!(context.game(game_id).is_none());
+    assert!(context.player_state(game_id, &creator).is_none());
+    // the creator gets the rent of both the game and their player state
+    let game_rent = context.bank.minimum_balance(Game::get_space(3));
+    let player_rent = context.bank.minimum_balance(PlayerState::get_space());
+    assert_eq!(
+        context.bank.lamports(&creator.wallet),
+        creator_lamports_before + game_rent + player_rent
+    );
+}
+
+#[test]
+fn leave_game_game_creator_is_only_one_to_play_gets_remaining_tokens() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    context.bank.warp_seconds(100);
+    context.leave_game(&creator, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        WALLET_DECIMAL_TOKENS
+    );
+    assert!(context.game(game_id).is_none());
+}
+
+#[test]
+fn leave_game_last_player_isnt_creator_rent_still_returned_to_creator() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    context.leave_game(&creator, game_id).unwrap();
+    let creator_lamports_before = context.bank.lamports(&creator.wallet);
+    let mut accounts = context.leave_game_accounts(&player, game_id);
+    accounts.game_creator = creator.wallet;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::LeaveGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+    context.bank.process(&ix, &[player.wallet]).unwrap();
+
+    assert!(context.game(game_id).is_none());
+    assert_eq!(
+        context.bank.lamports(&creator.wallet),
+        creator_lamports_before + context.bank.minimum_balance(Game::get_space(3))
+    );
+}
+
+#[test]
+fn leave_game_all_good_player_left_and_game_ended_events_are_emitted() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+    context.leave_game(&player, game_id).unwrap();
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].game_id, game_id);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].bucket, 2);
+    assert_eq!(events[0].winnings_decimal_tokens, two_player_share(10));
+    assert_eq!(events[0].burned_decimal_tokens, 0);
+    assert!(context.bank.events::<GameEnded>().is_empty());
+
+    context.leave_game(&creator, game_id).unwrap();
+    assert_eq!(context.bank.events::<PlayerLeft>().len(), 1);
+    let events = context.bank.events::<GameEnded>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].game_id, game_id);
+    assert_eq!(events[0].player, creator.wallet);
+    assert!(events[0].buckets.iter().all(|b| b.decimal_tokens == 0));
+}
+
+#[test]
+fn leave_game_player_will_lose_tokens_cancel_on_loss_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+    assert_error(
+        context.leave_game_with(&player, game_id, true),
+        EquilibrateError::AbortLeaveOnLoss,
+    );
+    assert!(context.player_state(game_id, &player).is_some());
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn leave_game_player_will_gain_tokens_cancel_on_loss_succeeds() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+    context.leave_game(&player, game_id).unwrap();
+    context.leave_game_with(&creator, game_id, true).unwrap();
+    assert!(context.token_balance(&creator.token_account) > WALLET_DECIMAL_TOKENS);
+}
+
+#[test]
+fn leave_game_with_penalty_player_will_lose_tokens_cancel_on_loss_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let mut config = default_game_config(context.mint);
+    config.burn_rate_decimal_tokens_per_move = 1;
+    context.new_game_with(&creator, game_id, config).unwrap();
+    context.move_buckets(&creator, game_id, 2).unwrap();
+    // alone in the game the creator would get exactly their entry fee back
+    assert_error(
+        context.leave_game_with(&creator, game_id, true),
+        EquilibrateError::AbortLeaveOnLoss,
+    );
+}
+
+#[test]
+fn leave_game_winnings_exceed_penalty_penalty_is_burned() {
+    let burn_rate = 1_000_000;
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        burn_rate_decimal_tokens_per_move: burn_rate,
+        ..default_game_config(context.mint)
+    });
+    // moving without time passing keeps every bucket's share the same
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(10);
+    let supply_before = context.mint_supply(&context.mint);
+    context.leave_game(&player, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + two_player_share(10) - burn_rate
+    );
+    assert_eq!(
+        context.mint_supply(&context.mint),
+        supply_before - burn_rate
+    );
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(events[0].burned_decimal_tokens, burn_rate);
+}
+
+#[test]
+fn leave_game_penalty_exceeds_winnings_winnings_are_burned() {
+    let burn_rate = 100_000_000;
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        burn_rate_decimal_tokens_per_move: burn_rate,
+        ..default_game_config(context.mint)
+    });
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(10);
+    let supply_before = context.mint_supply(&context.mint);
+    context.leave_game(&player, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context.mint_supply(&context.mint),
+        supply_before - two_player_share(10)
+    );
+}
+
+#[test]
+fn leave_game_winnings_meet_minimum_succeeds() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+
+    context
+        .leave_game_guarded(&player, game_id, Some(two_player_share(10)), None)
+        .unwrap();
+    assert!(context.player_state(game_id, &player).is_none());
+}
+
+#[test]
+fn leave_game_winnings_below_minimum_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+
+    assert_error(
+        context.leave_game_guarded(&player, game_id, Some(two_player_share(10) + 1), None),
+        EquilibrateError::WinningsBelowMinimum,
+    );
+    assert!(context.player_state(game_id, &player).is_some());
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn leave_game_burn_within_maximum_succeeds() {
+    let burn_rate = 1_000_000;
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        burn_rate_decimal_tokens_per_move: burn_rate,
+        ..default_game_config(context.mint)
+    });
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(10);
+
+    context
+        .leave_game_guarded(&player, game_id, None, Some(burn_rate))
+        .unwrap();
+    assert!(context.player_state(game_id, &player).is_none());
+}
+
+#[test]
+fn leave_game_burn_above_maximum_fails() {
+    let burn_rate = 1_000_000;
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        burn_rate_decimal_tokens_per_move: burn_rate,
+        ..default_game_config(context.mint)
+    });
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(10);
+    let supply_before = context.mint_supply(&context.mint);
+
+    assert_error(
+        context.leave_game_guarded(&player, game_id, None, Some(burn_rate - 1)),
+        EquilibrateError::BurnAboveMaximum,
+    );
+    assert!(context.player_state(game_id, &player).is_some());
+    assert_eq!(context.mint_supply(&context.mint), supply_before);
+}
+
+#[test]
+fn leave_game_everyone_leaves_tokens_are_conserved() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let mut players = vec![creator];
+    for i in 0..6u8 {
+        context.bank.warp_seconds(13);
+        let player = context.new_player();
+        context.enter_game(&player, game_id, i % 3 + 1).unwrap();
+        players.push(player);
+    }
+    for (i, player) in players.iter().enumerate() {
+        context.bank.warp_seconds(7 * i as i64);
+        context.leave_game(player, game_id).unwrap();
+    }
+
+    let total_balance: u64 = players
+        .iter()
+        .map(|p| context.token_balance(&p.token_account))
+        .sum();
+    assert_eq!(total_balance, WALLET_DECIMAL_TOKENS * players.len() as u64);
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+    assert!(context.game(game_id).is_none());
+}
+
+#[test]
+fn leave_game_game_is_over_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    // the last player leaving closes the game, so an empty game has to be forged
+    let mut game = context.game(game_id).unwrap();
+    for bucket in game.state.buckets.iter_mut() {
+        bucket.players = 0;
+    }
+    context.set_game(&game);
+
+    assert_error(
+        context.leave_game(&creator, game_id),
+        EquilibrateError::GameIsOver,
+    );
+}
+
+#[test]
+fn leave_game_wrong_game_mint_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    assert_error(
+        leave_game_with_accounts(&mut context, &player, game_id, |a| a.game_mint = other_mint),
+        EquilibrateError::InvalidBurnMint,
+    );
+}
+
+#[test]
+fn leave_game_wrong_game_creator_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    assert_error(
+        leave_game_with_accounts(&mut context, &player, game_id, |a| {
+            a.game_creator = player.wallet
+        }),
+        EquilibrateError::GameCreatorMismatch,
+    );
+}
+
+#[test]
+fn leave_game_player_bad_seed_fails() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    assert_error(
+        leave_game_with_accounts(&mut context, &player, game_id, |a| {
+            a.player = player_state_address(&game_address(game_id), &creator.wallet)
+        }),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn leave_game_player_isnt_in_game_fails() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    let outsider = context.new_player();
+    assert_error(
+        context.leave_game(&outsider, game_id),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn leave_game_winnings_destination_account_wrong_mint_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    let other_account =
+        context.create_token_account(Pubkey::new_unique(), other_mint, player.wallet, 0);
+    assert_error(
+        leave_game_with_accounts(&mut context, &player, game_id, |a| {
+            a.winnings_destination_account = other_account
+        }),
+        EquilibrateError::InvalidWinningsDestinationMint,
+    );
+}
+
+#[test]
+fn leave_game_winnings_destination_account_not_owned_by_player_fails() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    assert_error(
+        leave_game_with_accounts(&mut context, &player, game_id, |a| {
+            a.winnings_destination_account = creator.token_account
+        }),
+        EquilibrateError::InvalidWinningsDestinationOwner,
+    );
+}
+
+#[test]
+fn leave_game_token_pool_wrong_mint_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    context.create_pool(other_mint).unwrap();
+    assert_error(
+        leave_game_with_accounts(&mut context, &player, game_id, |a| {
+            a.token_pool = token_pool_address(&other_mint)
+        }),
+        EquilibrateError::InvalidTokenPool,
+    );
+}
+
+#[test]
+fn leave_game_pool_manager_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    context.create_pool(other_mint).unwrap();
+    assert_error(
+        leave_game_with_accounts(&mut context, &player, game_id, |a| {
+            a.pool_manager = pool_manager_address(&other_mint)
+        }),
+        EquilibrateError::InvalidPoolManager,
+    );
+}
+
+#[test]
+fn leave_game_token_pool_address_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let manager = pool_manager_address(&context.mint);
+    let imposter_pool = Pubkey::new_unique();
+    context.create_token_account(imposter_pool, context.mint, manager, 0);
+    assert_error(
+        leave_game_with_accounts(&mut context, &player, game_id, |a| {
+            a.token_pool = imposter_pool
+        }),
+        EquilibrateError::InvalidTokenPool,
+    );
+}
+
+#[test]
+fn leave_game_token_pool_owner_isnt_manager_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    // a pool at the right address whose authority has been changed
+    let pool = token_pool_address(&context.mint);
+    let pool_balance = context.token_balance(&pool);
+    context.create_token_account(pool, context.mint, player.wallet, pool_balance);
+    assert_error(
+        context.leave_game(&player, game_id),
+        EquilibrateError::InvalidTokenPoolOwner,
+    );
+}
@@ -0,0 +1,491 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode, prelude::Pubkey, solana_program::entrypoint::ProgramResult, AnchorSerialize,
+    Discriminator,
+};
+use common::*;
+use equilibrate::{
+    constants::{GAME_VERSION, PLAYER_STATE_VERSION, PROGRAM_CONFIG_VERSION},
+    model::EquilibrateError,
+    state::{
+        migration::{
+            BucketV1, GameConfigV1, GameStateV1, GameV1, PlayerStateV1, ProgramConfigV1,
+            ProgramSettingsV1,
+        },
+        BurnSchedule, DustDestination, Game, GameConfig, PenaltyDestination, PlayerState,
+        ProgramConfig, SpillCurve, TopUpDestination,
+    },
+};
+
+const STALE_GAME_VERSION: u8 = GAME_VERSION - 1;
+const STALE_PLAYER_STATE_VERSION: u8 = PLAYER_STATE_VERSION - 1;
+
+/// Migrates the account at `address` once per version it is behind, checking
+/// each migration takes it up by exactly one version
+fn migrate_step_by_step(
+    context: &mut TestContext,
+    address: Pubkey,
+    current_version: u8,
+    migrate: impl Fn(&mut TestContext) -> ProgramResult,
+) {
+    let version_of = |context: &TestContext| context.bank.get_account(&address).unwrap().data[8];
+    let mut version = version_of(context);
+    while version < current_version {
+        migrate(context).unwrap();
+        assert_eq!(version_of(context), version + 1);
+        version += 1;
+    }
+}
+
+fn set_game_version(context: &mut TestContext, game_id: u64, version: u8) {
+    let mut game = context.game(game_id).unwrap();
+    game.version = version;
+    context.set_game(&game);
+}
+
+fn set_player_version(context: &mut TestContext, game_id: u64, player: &Player, version: u8) {
+    let mut state = context.player_state(game_id, player).unwrap();
+    state.version = version;
+    context.set_player_state(game_id, player, &state);
+}
+
+/// Writes `game` with the layout of an older version, sized for that layout
+fn set_old_game(context: &mut TestContext, game_id: u64, game: &impl AnchorSerialize) -> usize {
+    set_old_account::<Game>(context, game_address(game_id), game)
+}
+
+fn set_old_account<T: Discriminator>(
+    context: &mut TestContext,
+    address: Pubkey,
+    account: &impl AnchorSerialize,
+) -> usize {
+    let mut data = T::discriminator().to_vec();
+    account.serialize(&mut data).unwrap();
+    let space = data.len();
+    context.bank.set_account(
+        address,
+        StoredAccount {
+            lamports: context.bank.minimum_balance(space),
+            data,
+            owner: equilibrate::ID,
+            executable: false,
+        },
+    );
+    space
+}
+
+fn game_v1(game: &Game) -> GameV1 {
+    GameV1 {
+        version: 1,
+        id: game.id,
+        creator: game.creator,
+        config: GameConfigV1 {
+            mint: game.config.mint,
+            entry_fee_decimal_tokens: game.config.entry_fee_decimal_tokens,
+            spill_rate_decimal_tokens_per_second_per_player: game
+                .config
+                .spill_rate_decimal_tokens_per_second_per_player,
+            n_buckets: game.config.n_buckets,
+            max_players: game.config.max_players,
+            burn_rate_decimal_tokens_per_move: game.config.burn_rate_decimal_tokens_per_move,
+        },
+        state: GameStateV1 {
+            buckets: game
+                .state
+                .buckets
+                .iter()
+                .map(|bucket| BucketV1 {
+                    decimal_tokens: bucket.decimal_tokens,
+                    players: bucket.players,
+                })
+                .collect(),
+            last_update_epoch_seconds: game.state.last_update_epoch_seconds,
+        },
+    }
+}
+
+fn program_config_v1(program_config: &ProgramConfig) -> ProgramConfigV1 {
+    let settings = &program_config.settings;
+    ProgramConfigV1 {
+        version: 1,
+        admin: program_config.admin,
+        settings: ProgramSettingsV1 {
+            program_fee_destination: settings.program_fee_destination,
+            program_fee_lamports: settings.program_fee_lamports,
+            game_max_buckets: settings.game_max_buckets,
+            game_max_players: settings.game_max_players,
+        },
+    }
+}
+
+/// Migrates the program config written at an older layout and checks it comes
+/// out as `expected`
+fn assert_program_config_migrates_to(
+    context: &mut TestContext,
+    expected: &ProgramConfig,
+    old_space: usize,
+) {
+    // stale layouts cant be read until migrated
+    let settings = expected.settings.clone();
+    assert!(context
+        .update_config(context.admin, context.admin, settings.clone())
+        .is_err());
+
+    migrate_step_by_step(
+        context,
+        program_config_address(),
+        PROGRAM_CONFIG_VERSION,
+        |context| context.migrate_program_config(),
+    );
+
+    assert_eq!(&context.program_config().unwrap(), expected);
+    let account = context.bank.get_account(&program_config_address()).unwrap();
+    let space = ProgramConfig::get_space();
+    assert!(space > old_space);
+    assert_eq!(account.data.len(), space);
+    assert_eq!(account.lamports, context.bank.minimum_balance(space));
+    context
+        .update_config(context.admin, context.admin, settings)
+        .unwrap();
+}
+
+fn migrate_player_step_by_step(context: &mut TestContext, game_id: u64, player: &Player) {
+    let address = player_state_address(&game_address(game_id), &player.wallet);
+    migrate_step_by_step(context, address, PLAYER_STATE_VERSION, |context| {
+        context.migrate_player(game_id, player)
+    });
+}
+
+/// Migrates a game written at an older layout and checks it comes out as `game`
+fn assert_migrates_to(context: &mut TestContext, game: &Game, old_space: usize, player: &Player) {
+    // stale layouts cant be used until migrated
+    assert!(context.move_buckets(player, game.id, 3).is_err());
+
+    migrate_step_by_step(context, game_address(game.id), GAME_VERSION, |context| {
+        context.migrate_game(game.id)
+    });
+
+    assert_eq!(&context.game(game.id).unwrap(), game);
+    let account = context.bank.get_account(&game_address(game.id)).unwrap();
+    let space = Game::get_space(game.config.n_buckets);
+    assert!(space > old_space);
+    assert_eq!(account.data.len(), space);
+    assert_eq!(account.lamports, context.bank.minimum_balance(space));
+    context.move_buckets(player, game.id, 3).unwrap();
+}
+
+#[test]
+fn migrate_game_all_good_v1_gets_what_the_first_games_did() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let game = context.game(game_id).unwrap();
+    let old_space = set_old_game(&mut context, game_id, &game_v1(&game));
+    assert_migrates_to(&mut context, &game, old_space, &player);
+}
+
+#[test]
+fn migrate_game_all_good_v1_drops_settings_the_first_games_didnt_have() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + 60 * 60),
+        spill_curve: SpillCurve::ProportionalToBalance {
+            decay_bps_per_second: 100,
+        },
+        max_stake_decimal_tokens: 3 * ENTRY_FEE_DECIMAL_TOKENS,
+        top_up_destination: TopUpDestination::PlayerBucket,
+        burn_schedule: BurnSchedule::Linear {
+            increment_decimal_tokens_per_move: 1,
+        },
+        penalty_destination: PenaltyDestination::OtherBuckets,
+        dust_destination: DustDestination::Creator,
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(&creator, game_id, config).unwrap();
+    let current = context.game(game_id).unwrap();
+    let old_space = set_old_game(&mut context, game_id, &game_v1(&current));
+
+    let mut game = current;
+    game.config = GameConfig {
+        mint: game.config.mint,
+        entry_fee_decimal_tokens: game.config.entry_fee_decimal_tokens,
+        spill_rate_decimal_tokens_per_second_per_player: game
+            .config
+            .spill_rate_decimal_tokens_per_second_per_player,
+        n_buckets: game.config.n_buckets,
+        max_players: game.config.max_players,
+        burn_rate_decimal_tokens_per_move: game.config.burn_rate_decimal_tokens_per_move,
+        ..default_game_config(context.mint)
+    };
+    assert_migrates_to(&mut context, &game, old_space, &creator);
+}
+
+#[test]
+fn migrate_game_all_good_v1_migrates_in_one_step() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let game = context.game(game_id).unwrap();
+    set_old_game(&mut context, game_id, &game_v1(&game));
+
+    context.migrate_game(game_id).unwrap();
+
+    assert_eq!(context.game(game_id).unwrap().version, GAME_VERSION);
+    context.move_buckets(&player, game_id, 3).unwrap();
+    assert_error(
+        context.migrate_game(game_id),
+        EquilibrateError::AccountAlreadyMigrated,
+    );
+}
+
+#[test]
+fn migrate_program_config_all_good_v1_gets_no_creator_fee_or_referral_fee() {
+    let mut context = TestContext::new();
+    let current = context.program_config().unwrap();
+    let old_space = set_old_account::<ProgramConfig>(
+        &mut context,
+        program_config_address(),
+        &program_config_v1(&current),
+    );
+    let mut expected = current;
+    expected.settings.game_max_creator_fee_bps = 0;
+    expected.settings.referral_fee_bps = 0;
+    assert_program_config_migrates_to(&mut context, &expected, old_space);
+}
+
+#[test]
+fn migrate_program_config_already_current_fails() {
+    let mut context = TestContext::new();
+    assert_error(
+        context.migrate_program_config(),
+        EquilibrateError::AccountAlreadyMigrated,
+    );
+}
+
+#[test]
+fn migrate_player_all_good_v1_staked_the_entry_fee() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let state = context.player_state(game_id, &player).unwrap();
+    let old_space = set_old_account::<PlayerState>(
+        &mut context,
+        player_state_address(&game_address(game_id), &player.wallet),
+        &PlayerStateV1 {
+            version: 1,
+            bucket: state.bucket,
+            burn_penalty_decimal_tokens: state.burn_penalty_decimal_tokens,
+        },
+    );
+    assert!(context.leave_game(&player, game_id).is_err());
+
+    migrate_player_step_by_step(&mut context, game_id, &player);
+
+    assert_eq!(context.player_state(game_id, &player).unwrap(), state);
+    let account = context
+        .bank
+        .get_account(&player_state_address(
+            &game_address(game_id),
+            &player.wallet,
+        ))
+        .unwrap();
+    assert!(PlayerState::get_space() > old_space);
+    assert_eq!(account.data.len(), PlayerState::get_space());
+    context.leave_game(&player, game_id).unwrap();
+}
+
+#[test]
+fn migrate_player_all_good_v1_keeps_bucket_and_penalty_and_never_moved() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    context.add_deposit(&player, game_id, 5).unwrap();
+    context.move_buckets(&player, game_id, 3).unwrap();
+    let mut state = context.player_state(game_id, &player).unwrap();
+    assert!(state.last_move_epoch_seconds > 0);
+    set_old_account::<PlayerState>(
+        &mut context,
+        player_state_address(&game_address(game_id), &player.wallet),
+        &PlayerStateV1 {
+            version: 1,
+            bucket: state.bucket,
+            burn_penalty_decimal_tokens: state.burn_penalty_decimal_tokens,
+        },
+    );
+    assert!(context.move_buckets(&player, game_id, 2).is_err());
+
+    migrate_player_step_by_step(&mut context, game_id, &player);
+
+    state.stake_decimal_tokens = ENTRY_FEE_DECIMAL_TOKENS;
+    state.added_decimal_tokens = 0;
+    state.last_move_epoch_seconds = 0;
+    state.moves = 0;
+    assert_eq!(context.player_state(game_id, &player).unwrap(), state);
+    context.move_buckets(&player, game_id, 2).unwrap();
+}
+
+#[test]
+fn migrate_player_stale_game_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    set_player_version(&mut context, game_id, &player, STALE_PLAYER_STATE_VERSION);
+    set_game_version(&mut context, game_id, STALE_GAME_VERSION);
+    assert_error(
+        context.migrate_player(game_id, &player),
+        EquilibrateError::StaleAccountVersion,
+    );
+}
+
+#[test]
+fn migrate_game_already_current_fails() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    assert_error(
+        context.migrate_game(game_id),
+        EquilibrateError::AccountAlreadyMigrated,
+    );
+}
+
+#[test]
+fn migrate_game_unknown_version_fails() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    for version in [0, GAME_VERSION + 1] {
+        set_game_version(&mut context, game_id, version);
+        assert_error(
+            context.migrate_game(game_id),
+            EquilibrateError::UnknownAccountVersion,
+        );
+        assert_eq!(context.game(game_id).unwrap().version, version);
+    }
+}
+
+#[test]
+fn migrate_game_account_isnt_a_game_fails() {
+    let mut context = TestContext::new();
+    let (creator, _, game_id) = context.two_player_game();
+    let payer = context.new_wallet();
+    let ix = instruction(
+        equilibrate::accounts::MigrateGame {
+            game: player_state_address(&game_address(game_id), &creator.wallet),
+            payer,
+            system_program: anchor_lang::system_program::ID,
+        },
+        equilibrate::instruction::MigrateGame {},
+    );
+    assert_error(
+        context.bank.process(&ix, &[payer]),
+        ErrorCode::AccountDiscriminatorMismatch,
+    );
+}
+
+#[test]
+fn migrate_game_account_not_owned_by_program_fails() {
+    let mut context = TestContext::new();
+    let payer = context.new_wallet();
+    let ix = instruction(
+        equilibrate::accounts::MigrateGame {
+            game: context.new_wallet(),
+            payer,
+            system_program: anchor_lang::system_program::ID,
+        },
+        equilibrate::instruction::MigrateGame {},
+    );
+    assert_error(
+        context.bank.process(&ix, &[payer]),
+        ErrorCode::ConstraintOwner,
+    );
+}
+
+#[test]
+fn migrate_player_already_current_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    assert_error(
+        context.migrate_player(game_id, &player),
+        EquilibrateError::AccountAlreadyMigrated,
+    );
+}
+
+#[test]
+fn migrate_player_unknown_version_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    for version in [0, PLAYER_STATE_VERSION + 1] {
+        set_player_version(&mut context, game_id, &player, version);
+        assert_error(
+            context.migrate_player(game_id, &player),
+            EquilibrateError::UnknownAccountVersion,
+        );
+    }
+}
+
+#[test]
+fn migrate_player_account_of_other_player_fails() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    set_player_version(&mut context, game_id, &creator, STALE_PLAYER_STATE_VERSION);
+    let payer = context.new_wallet();
+    let ix = instruction(
+        equilibrate::accounts::MigratePlayer {
+            game: game_address(game_id),
+            player_wallet: player.wallet,
+            player: player_state_address(&game_address(game_id), &creator.wallet),
+            payer,
+            system_program: anchor_lang::system_program::ID,
+        },
+        equilibrate::instruction::MigratePlayer {},
+    );
+    assert_error(
+        context.bank.process(&ix, &[payer]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn enter_game_stale_game_fails() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    set_game_version(&mut context, game_id, STALE_GAME_VERSION);
+    let late_player = context.new_player();
+    assert_error(
+        context.enter_game(&late_player, game_id, 3),
+        EquilibrateError::StaleAccountVersion,
+    );
+}
+
+#[test]
+fn move_buckets_stale_game_or_player_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    set_game_version(&mut context, game_id, STALE_GAME_VERSION);
+    assert_error(
+        context.move_buckets(&player, game_id, 3),
+        EquilibrateError::StaleAccountVersion,
+    );
+
+    set_game_version(&mut context, game_id, GAME_VERSION);
+    set_player_version(&mut context, game_id, &player, STALE_PLAYER_STATE_VERSION);
+    assert_error(
+        context.move_buckets(&player, game_id, 3),
+        EquilibrateError::StaleAccountVersion,
+    );
+}
+
+#[test]
+fn leave_game_stale_game_or_player_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    set_game_version(&mut context, game_id, STALE_GAME_VERSION);
+    assert_error(
+        context.leave_game(&player, game_id),
+        EquilibrateError::StaleAccountVersion,
+    );
+
+    set_game_version(&mut context, game_id, GAME_VERSION);
+    set_player_version(&mut context, game_id, &player, STALE_PLAYER_STATE_VERSION);
+    assert_error(
+        context.leave_game(&player, game_id),
+        EquilibrateError::StaleAccountVersion,
+    );
+    assert!(context.player_state(game_id, &player).is_some());
+}
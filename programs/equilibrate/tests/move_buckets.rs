@@ -0,0 +1,257 @@
+mod common;
+
+use anchor_lang::{error::ErrorCode, solana_program::pubkey::Pubkey};
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, PlayerMoved},
+    state::Bucket,
+};
+
+#[test]
+fn move_buckets_all_good_player_is_moved_to_desired_bucket() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+    context.move_buckets(&player, game_id, 3).unwrap();
+
+    assert_eq!(context.player_state(game_id, &player).unwrap().bucket, 3);
+    let game = context.game(game_id).unwrap();
+    let players: Vec<u16> = game.state.buckets.iter().map(|b| b.players).collect();
+    assert_eq!(players, vec![2, 1, 0, 1]);
+}
+
+#[test]
+fn move_buckets_after_time_passes_bucket_balances_update_correctly() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+    context.bank.warp_seconds(10);
+    context.move_buckets(&player, game_id, 3).unwrap();
+
+    // the holding bucket has 2 players, so it spills into all three buckets,
+    // and the remainder of the integer division stays behind
+    let spilled_to_each = 2 * 10 * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER / 3;
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.dust_decimal_tokens, 0);
+    assert_eq!(
+        game.state.buckets,
+        vec![
+            Bucket {
+                decimal_tokens: 2 * ENTRY_FEE_DECIMAL_TOKENS - 3 * spilled_to_each,
+                players: 2,
+                stake_decimal_tokens: 2 * ENTRY_FEE_DECIMAL_TOKENS
+            },
+            Bucket {
+                decimal_tokens: spilled_to_each,
+                players: 1,
+                stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS
+            },
+            Bucket {
+                decimal_tokens: spilled_to_each,
+                players: 0,
+                stake_decimal_tokens: 0
+            },
+            Bucket {
+                decimal_tokens: spilled_to_each,
+                players: 1,
+                stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS
+            },
+        ]
+    );
+    assert_eq!(
+        game.state.last_update_epoch_seconds,
+        context.bank.now_epoch_seconds()
+    );
+}
+
+#[test]
+fn move_buckets_all_good_burn_penalty_accrues() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let mut config = default_game_config(context.mint);
+    config.burn_rate_decimal_tokens_per_move = 1000;
+    context.new_game_with(&creator, game_id, config).unwrap();
+    context.move_buckets(&creator, game_id, 2).unwrap();
+    context.move_buckets(&creator, game_id, 3).unwrap();
+
+    let player_state = context.player_state(game_id, &creator).unwrap();
+    assert_eq!(player_state.burn_penalty_decimal_tokens, 2000);
+}
+
+#[test]
+fn move_buckets_all_good_player_moved_event_is_emitted() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    context.bank.warp_seconds(5);
+    context.move_buckets(&creator, game_id, 2).unwrap();
+
+    let events = context.bank.events::<PlayerMoved>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].game_id, game_id);
+    assert_eq!(events[0].player, creator.wallet);
+    assert_eq!(events[0].from_bucket, 1);
+    assert_eq!(events[0].to_bucket, 2);
+    assert_eq!(events[0].burn_penalty_decimal_tokens, 0);
+    assert_eq!(
+        events[0].buckets,
+        context.game(game_id).unwrap().state.buckets
+    );
+}
+
+#[test]
+fn move_buckets_all_good_bucket_has_expected_players() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+
+    context
+        .move_buckets_expecting(&creator, game_id, 2, Some(1))
+        .unwrap();
+    assert_eq!(context.player_state(game_id, &creator).unwrap().bucket, 2);
+}
+
+#[test]
+fn move_buckets_bucket_filled_up_before_move_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    // the creator sees an empty bucket 3, but another player gets there first
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 3).unwrap();
+
+    assert_error(
+        context.move_buckets_expecting(&creator, game_id, 3, Some(0)),
+        EquilibrateError::UnexpectedBucketPlayers,
+    );
+    assert_eq!(context.player_state(game_id, &creator).unwrap().bucket, 1);
+}
+
+#[test]
+fn move_buckets_player_tries_to_move_to_the_same_bucket_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    assert_error(
+        context.move_buckets(&creator, game_id, 1),
+        EquilibrateError::AlreadyInBucket,
+    );
+}
+
+#[test]
+fn move_buckets_player_tries_to_move_to_a_bucket_that_doesnt_exist_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    assert_error(
+        context.move_buckets(&creator, game_id, 4),
+        EquilibrateError::BucketDoesNotExist,
+    );
+}
+
+#[test]
+fn move_buckets_player_tries_to_move_to_holding_bucket_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    assert_error(
+        context.move_buckets(&creator, game_id, 0),
+        EquilibrateError::CannotEnterHoldingBucket,
+    );
+}
+
+#[test]
+fn move_buckets_game_is_over_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    // the last player leaving closes the game, so an empty game has to be forged
+    let mut game = context.game(game_id).unwrap();
+    for bucket in game.state.buckets.iter_mut() {
+        bucket.players = 0;
+    }
+    context.set_game(&game);
+
+    assert_error(
+        context.move_buckets(&creator, game_id, 2),
+        EquilibrateError::GameIsOver,
+    );
+}
+
+#[test]
+fn move_buckets_player_isnt_in_game_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    assert_error(
+        context.move_buckets(&player, game_id, 2),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn move_buckets_player_bad_seed_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+    let mut accounts = context.move_buckets_accounts(&player, game_id);
+    accounts.player = player_state_address(&game_address(game_id), &creator.wallet);
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::MoveBuckets {
+            bucket: 3,
+            expected_bucket_players: None,
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn move_buckets_player_of_another_game_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let other_game_id = context.new_game(&creator);
+    let mut accounts = context.move_buckets_accounts(&creator, game_id);
+    accounts.player = player_state_address(&game_address(other_game_id), &creator.wallet);
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::MoveBuckets {
+            bucket: 3,
+            expected_bucket_players: None,
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[creator.wallet]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn move_buckets_payer_didnt_sign_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let ix = instruction(
+        context.move_buckets_accounts(&creator, game_id),
+        equilibrate::instruction::MoveBuckets {
+            bucket: 2,
+            expected_bucket_players: None,
+        },
+    );
+    assert!(context.bank.process(&ix, &[Pubkey::new_unique()]).is_err());
+    assert_eq!(context.player_state(game_id, &creator).unwrap().bucket, 1);
+}
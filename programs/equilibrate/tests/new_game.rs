@@ -0,0 +1,319 @@
+mod common;
+
+use anchor_lang::{error::ErrorCode, solana_program::pubkey::Pubkey};
+use common::*;
+use equilibrate::{
+    constants::{GAME_MAX_BUCKETS, GAME_MAX_PLAYERS, GAME_VERSION, PLAYER_STATE_VERSION},
+    model::{EquilibrateError, GameCreated},
+    state::{Bucket, GameConfig},
+};
+
+#[test]
+fn new_game_all_good_config_and_initial_state_are_as_expected() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.new_game(&player);
+
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.version, GAME_VERSION);
+    assert_eq!(game.id, game_id);
+    assert_eq!(game.creator, player.wallet);
+    assert_eq!(game.config, default_game_config(context.mint));
+    assert_eq!(
+        game.state.last_update_epoch_seconds,
+        context.bank.now_epoch_seconds()
+    );
+    assert_eq!(
+        game.state.buckets,
+        vec![
+            Bucket {
+                decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+                players: 1,
+                stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS
+            },
+            Bucket {
+                decimal_tokens: 0,
+                players: 1,
+                stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS
+            },
+            Bucket {
+                decimal_tokens: 0,
+                players: 0,
+                stake_decimal_tokens: 0
+            },
+            Bucket {
+                decimal_tokens: 0,
+                players: 0,
+                stake_decimal_tokens: 0
+            },
+        ]
+    );
+
+    let player_state = context.player_state(game_id, &player).unwrap();
+    assert_eq!(player_state.version, PLAYER_STATE_VERSION);
+    assert_eq!(player_state.bucket, 1);
+    assert_eq!(player_state.burn_penalty_decimal_tokens, 0);
+}
+
+#[test]
+fn new_game_all_good_program_fee_is_transferred() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let fee_balance_before = context.bank.lamports(&context.program_fee_destination);
+    context.new_game(&player);
+    assert_eq!(
+        context.bank.lamports(&context.program_fee_destination),
+        fee_balance_before + PROGRAM_FEE_LAMPORTS
+    );
+}
+
+#[test]
+fn new_game_all_good_game_tokens_are_transferred() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    context.new_game(&player);
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        ENTRY_FEE_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn new_game_all_good_game_created_event_is_emitted() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.new_game(&player);
+
+    let events = context.bank.events::<GameCreated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].game_id, game_id);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].bucket, 1);
+    assert_eq!(
+        events[0].buckets,
+        context.game(game_id).unwrap().state.buckets
+    );
+}
+
+#[test]
+fn new_game_same_id_twice_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.new_game(&player);
+    let other_player = context.new_player();
+    assert!(context
+        .new_game_with(&other_player, game_id, default_game_config(context.mint))
+        .is_err());
+}
+
+#[test]
+fn new_game_game_bad_seed_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.next_game_id();
+    let config = default_game_config(context.mint);
+    let mut accounts = context.new_game_accounts(&player, game_id, context.mint);
+    accounts.game = game_address(game_id + 1);
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::NewGame { config, game_id },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn new_game_first_player_bad_seed_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.next_game_id();
+    let config = default_game_config(context.mint);
+    let mut accounts = context.new_game_accounts(&player, game_id, context.mint);
+    accounts.first_player = player_state_address(&game_address(game_id), &Pubkey::new_unique());
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::NewGame { config, game_id },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+type ConfigChange = fn(&mut GameConfig);
+
+#[test]
+fn new_game_invalid_config_fails() {
+    let cases: Vec<(ConfigChange, EquilibrateError)> = vec![
+        (
+            |c| c.entry_fee_decimal_tokens = 0,
+            EquilibrateError::InvalidEntryFee,
+        ),
+        (|c| c.n_buckets = 1, EquilibrateError::TooFewBuckets),
+        (
+            |c| c.n_buckets = GAME_MAX_BUCKETS + 1,
+            EquilibrateError::TooManyBuckets,
+        ),
+        (
+            |c| c.spill_rate_decimal_tokens_per_second_per_player = 0,
+            EquilibrateError::InvalidSpillRate,
+        ),
+        (|c| c.max_players = 1, EquilibrateError::MaxPlayersTooSmall),
+        (
+            |c| c.max_players = GAME_MAX_PLAYERS + 1,
+            EquilibrateError::MaxPlayersTooLarge,
+        ),
+    ];
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    for (modify, error) in cases {
+        let mut config = default_game_config(context.mint);
+        modify(&mut config);
+        let game_id = context.next_game_id();
+        assert_error(context.new_game_with(&player, game_id, config), error);
+        assert!(context.game(game_id).is_none());
+    }
+}
+
+#[test]
+fn new_game_limits_come_from_program_config() {
+    let mut context = TestContext::new();
+    let mut settings = default_program_settings(context.program_fee_destination);
+    settings.game_max_buckets = 4;
+    settings.game_max_players = 5;
+    context
+        .update_config(context.admin, context.admin, settings)
+        .unwrap();
+    let player = context.new_player();
+
+    let mut config = default_game_config(context.mint);
+    config.n_buckets = 5;
+    let game_id = context.next_game_id();
+    assert_error(
+        context.new_game_with(&player, game_id, config),
+        EquilibrateError::TooManyBuckets,
+    );
+
+    let mut config = default_game_config(context.mint);
+    config.max_players = 6;
+    assert_error(
+        context.new_game_with(&player, game_id, config),
+        EquilibrateError::MaxPlayersTooLarge,
+    );
+
+    let mut config = default_game_config(context.mint);
+    config.n_buckets = 4;
+    config.max_players = 5;
+    context.new_game_with(&player, game_id, config).unwrap();
+}
+
+#[test]
+fn new_game_burn_rate_with_native_mint_fails() {
+    let mut context = TestContext::new();
+    let mint = context.create_mint(native_mint());
+    context.create_pool(mint).unwrap();
+    let player = context.new_player_of(mint);
+    let mut config = default_game_config(mint);
+    config.burn_rate_decimal_tokens_per_move = 1;
+    let game_id = context.next_game_id();
+    assert_error(
+        context.new_game_with(&player, game_id, config.clone()),
+        EquilibrateError::CannotBurnNativeMint,
+    );
+
+    config.burn_rate_decimal_tokens_per_move = 0;
+    context.new_game_with(&player, game_id, config).unwrap();
+}
+
+#[test]
+fn new_game_program_fee_destination_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.next_game_id();
+    let config = default_game_config(context.mint);
+    let mut accounts = context.new_game_accounts(&player, game_id, context.mint);
+    accounts.program_fee_destination = Pubkey::new_unique();
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::NewGame { config, game_id },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidProgramFeeDestination,
+    );
+}
+
+#[test]
+fn new_game_deposit_source_wrong_mint_fails() {
+    let mut context = TestContext::new();
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    let player = context.new_player_of(other_mint);
+    let game_id = context.next_game_id();
+    assert_error(
+        context.new_game_with(&player, game_id, default_game_config(context.mint)),
+        EquilibrateError::InvalidTokenSourceMint,
+    );
+}
+
+#[test]
+fn new_game_pool_manager_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.next_game_id();
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    context.create_pool(other_mint).unwrap();
+    let config = default_game_config(context.mint);
+    let mut accounts = context.new_game_accounts(&player, game_id, context.mint);
+    accounts.pool_manager = pool_manager_address(&other_mint);
+    accounts.token_pool = token_pool_address(&other_mint);
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::NewGame { config, game_id },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidPoolManager,
+    );
+}
+
+#[test]
+fn new_game_token_pool_address_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.next_game_id();
+    let manager = pool_manager_address(&context.mint);
+    let imposter_pool = Pubkey::new_unique();
+    context.create_token_account(imposter_pool, context.mint, manager, 0);
+    let config = default_game_config(context.mint);
+    let mut accounts = context.new_game_accounts(&player, game_id, context.mint);
+    accounts.token_pool = imposter_pool;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::NewGame { config, game_id },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidTokenPool,
+    );
+}
+
+#[test]
+fn new_game_payer_didnt_sign_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.next_game_id();
+    let ix = instruction(
+        context.new_game_accounts(&player, game_id, context.mint),
+        equilibrate::instruction::NewGame {
+            config: default_game_config(context.mint),
+            game_id,
+        },
+    );
+    assert!(context.bank.process(&ix, &[]).is_err());
+}
@@ -0,0 +1,311 @@
+mod common;
+
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult, instruction::AccountMeta, pubkey::Pubkey,
+};
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, PlayerLeft, PlayerWithdrew},
+    state::{Game, GameConfig, PenaltyDestination},
+};
+
+const BURN_RATE_DECIMAL_TOKENS: u64 = 1_000_000;
+
+/// Config of a game that charges `burn_rate` per move and sends penalties to
+/// `penalty_destination`
+fn penalty_config(
+    mint: Pubkey,
+    burn_rate: u64,
+    penalty_destination: PenaltyDestination,
+) -> GameConfig {
+    GameConfig {
+        burn_rate_decimal_tokens_per_move: burn_rate,
+        penalty_destination,
+        ..default_game_config(mint)
+    }
+}
+
+/// The game as it will be just before anyone acts at the current time
+fn updated_game(context: &TestContext, game_id: u64) -> Game {
+    let mut game = context.game(game_id).unwrap();
+    game.update(context.bank.now_epoch_seconds());
+    game
+}
+
+fn assert_pool_holds_game(context: &TestContext, game_id: u64) {
+    let game_tokens: u64 = context
+        .game(game_id)
+        .unwrap()
+        .state
+        .buckets
+        .iter()
+        .map(|b| b.decimal_tokens)
+        .sum();
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        game_tokens
+    );
+}
+
+/// Leaves passing only `remaining_accounts`, whatever the game's penalty destination
+fn leave_game_passing(
+    context: &mut TestContext,
+    player: &Player,
+    game_id: u64,
+    remaining_accounts: Vec<AccountMeta>,
+) -> ProgramResult {
+    let mut ix = instruction(
+        context.leave_game_accounts(player, game_id),
+        equilibrate::instruction::LeaveGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+    ix.accounts.extend(remaining_accounts);
+    context.bank.process(&ix, &[player.wallet])
+}
+
+#[test]
+fn leave_game_all_good_holding_bucket_gets_penalty() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        BURN_RATE_DECIMAL_TOKENS,
+        PenaltyDestination::HoldingBucket,
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+    let game = updated_game(&context, game_id);
+    let share = game.state.buckets[3].decimal_tokens;
+    let supply_before = context.mint_supply(&context.mint);
+
+    context.leave_game(&player, game_id).unwrap();
+
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(
+        events[0].winnings_decimal_tokens,
+        share - BURN_RATE_DECIMAL_TOKENS
+    );
+    assert_eq!(events[0].penalty_decimal_tokens, BURN_RATE_DECIMAL_TOKENS);
+    assert_eq!(events[0].burned_decimal_tokens, 0);
+    assert_eq!(
+        context.game(game_id).unwrap().state.buckets[0].decimal_tokens,
+        game.state.buckets[0].decimal_tokens + BURN_RATE_DECIMAL_TOKENS
+    );
+    assert_eq!(context.mint_supply(&context.mint), supply_before);
+    assert_pool_holds_game(&context, game_id);
+}
+
+#[test]
+fn leave_game_all_good_other_buckets_split_penalty() {
+    let mut context = TestContext::new();
+    // odd, so splitting between the two other buckets leaves a remainder
+    let burn_rate = BURN_RATE_DECIMAL_TOKENS + 1;
+    let (_, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        burn_rate,
+        PenaltyDestination::OtherBuckets,
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+    let game = updated_game(&context, game_id);
+
+    context.leave_game(&player, game_id).unwrap();
+
+    let buckets = context.game(game_id).unwrap().state.buckets;
+    assert_eq!(
+        buckets[0].decimal_tokens,
+        game.state.buckets[0].decimal_tokens + 1
+    );
+    for i in [1, 2] {
+        assert_eq!(
+            buckets[i].decimal_tokens,
+            game.state.buckets[i].decimal_tokens + burn_rate / 2
+        );
+    }
+    assert_eq!(buckets[3].decimal_tokens, 0);
+    assert_eq!(
+        context.bank.events::<PlayerLeft>()[0].burned_decimal_tokens,
+        0
+    );
+    assert_pool_holds_game(&context, game_id);
+}
+
+#[test]
+fn leave_game_all_good_last_player_keeps_penalty_meant_for_pot() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        BURN_RATE_DECIMAL_TOKENS,
+        PenaltyDestination::HoldingBucket,
+    ));
+    context.leave_game(&player, game_id).unwrap();
+    context.move_buckets(&creator, game_id, 2).unwrap();
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+
+    context.leave_game(&creator, game_id).unwrap();
+
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(events[0].winnings_decimal_tokens, pool_balance);
+    assert_eq!(events[0].penalty_decimal_tokens, 0);
+    assert!(context.game(game_id).is_none());
+}
+
+#[test]
+fn leave_game_all_good_account_gets_penalty() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let (_, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        BURN_RATE_DECIMAL_TOKENS,
+        PenaltyDestination::Account {
+            owner: creator.wallet,
+        },
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+    let treasury_before = context.token_balance(&creator.token_account);
+    let supply_before = context.mint_supply(&context.mint);
+
+    context.leave_game(&player, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        treasury_before + BURN_RATE_DECIMAL_TOKENS
+    );
+    assert_eq!(context.mint_supply(&context.mint), supply_before);
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(events[0].penalty_decimal_tokens, BURN_RATE_DECIMAL_TOKENS);
+    assert_eq!(events[0].burned_decimal_tokens, 0);
+    assert_pool_holds_game(&context, game_id);
+}
+
+#[test]
+fn leave_game_all_good_account_not_needed_without_penalty() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let (_, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        BURN_RATE_DECIMAL_TOKENS,
+        PenaltyDestination::Account {
+            owner: creator.wallet,
+        },
+    ));
+
+    leave_game_passing(&mut context, &player, game_id, vec![]).unwrap();
+}
+
+#[test]
+fn leave_game_penalty_destination_missing_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let (_, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        BURN_RATE_DECIMAL_TOKENS,
+        PenaltyDestination::Account {
+            owner: creator.wallet,
+        },
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+
+    assert_error(
+        leave_game_passing(&mut context, &player, game_id, vec![]),
+        EquilibrateError::MissingPenaltyDestination,
+    );
+}
+
+#[test]
+fn leave_game_penalty_destination_of_someone_else_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let (_, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        BURN_RATE_DECIMAL_TOKENS,
+        PenaltyDestination::Account {
+            owner: creator.wallet,
+        },
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+
+    assert_error(
+        leave_game_passing(
+            &mut context,
+            &player,
+            game_id,
+            vec![AccountMeta::new(player.token_account, false)],
+        ),
+        EquilibrateError::InvalidPenaltyDestination,
+    );
+}
+
+#[test]
+fn withdraw_all_good_account_gets_share_of_penalty() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let (_, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        BURN_RATE_DECIMAL_TOKENS,
+        PenaltyDestination::Account {
+            owner: creator.wallet,
+        },
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+    let treasury_before = context.token_balance(&creator.token_account);
+
+    context.withdraw(&player, game_id, 5_000).unwrap();
+
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        treasury_before + BURN_RATE_DECIMAL_TOKENS / 2
+    );
+    let events = context.bank.events::<PlayerWithdrew>();
+    assert_eq!(
+        events[0].penalty_decimal_tokens,
+        BURN_RATE_DECIMAL_TOKENS / 2
+    );
+    assert_eq!(events[0].burned_decimal_tokens, 0);
+    assert_pool_holds_game(&context, game_id);
+}
+
+#[test]
+fn move_penalties_all_good_with_native_mint_when_not_burned() {
+    let mut context = TestContext::new();
+    context.mint = context.create_mint(native_mint());
+    context.create_pool(context.mint).unwrap();
+    let (_, player, game_id) = context.two_player_game_with(penalty_config(
+        context.mint,
+        BURN_RATE_DECIMAL_TOKENS,
+        PenaltyDestination::HoldingBucket,
+    ));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+
+    context.leave_game(&player, game_id).unwrap();
+
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(events[0].penalty_decimal_tokens, BURN_RATE_DECIMAL_TOKENS);
+    assert_pool_holds_game(&context, game_id);
+}
+
+#[test]
+fn new_game_burning_penalties_with_native_mint_fails() {
+    let mut context = TestContext::new();
+    context.mint = context.create_mint(native_mint());
+    context.create_pool(context.mint).unwrap();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        burn_rate_decimal_tokens_per_move: BURN_RATE_DECIMAL_TOKENS,
+        penalty_destination: PenaltyDestination::Burn,
+        ..default_game_config(context.mint)
+    };
+
+    assert_error(
+        context.new_game_with(&creator, game_id, config),
+        EquilibrateError::CannotBurnNativeMint,
+    );
+}
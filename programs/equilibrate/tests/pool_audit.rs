@@ -0,0 +1,239 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode, solana_program::pubkey::Pubkey, AnchorSerialize, Discriminator,
+};
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, PoolAudited},
+    state::{migration::PoolManagerV1, GameConfig, PoolManager},
+};
+
+fn game_balance(context: &TestContext, game_id: u64) -> u64 {
+    context.game(game_id).map_or(0, |game| {
+        game.state
+            .buckets
+            .iter()
+            .map(|b| b.decimal_tokens)
+            .sum::<u64>()
+            + game.dust_decimal_tokens
+    })
+}
+
+fn liabilities(context: &TestContext) -> u64 {
+    context
+        .pool_manager(&context.mint)
+        .unwrap()
+        .liabilities_decimal_tokens
+}
+
+fn set_liabilities(context: &mut TestContext, liabilities_decimal_tokens: u64) {
+    let pool_manager = PoolManager {
+        liabilities_decimal_tokens,
+        ..context.pool_manager(&context.mint).unwrap()
+    };
+    context.bank.set_anchor_account(
+        pool_manager_address(&context.mint),
+        &pool_manager,
+        PoolManager::get_space(),
+    );
+}
+
+/// Overwrites the token pool, keeping its owner but holding `amount`
+fn set_pool_balance(context: &mut TestContext, amount: u64) {
+    let mint = context.mint;
+    context.create_token_account(
+        token_pool_address(&mint),
+        mint,
+        pool_manager_address(&mint),
+        amount,
+    );
+}
+
+/// Writes the pool manager with the layout from before it recorded liabilities
+fn set_old_pool_manager(context: &mut TestContext) {
+    let mint = context.mint;
+    let mut data = PoolManager::discriminator().to_vec();
+    PoolManagerV1 {
+        bump: PoolManager::get_address(mint).1,
+    }
+    .serialize(&mut data)
+    .unwrap();
+    context.bank.set_account(
+        pool_manager_address(&mint),
+        StoredAccount {
+            lamports: context.bank.minimum_balance(data.len()),
+            data,
+            owner: equilibrate::ID,
+            executable: false,
+        },
+    );
+}
+
+#[test]
+fn audit_pool_all_good_new_pool_owes_nothing() {
+    let mut context = TestContext::new();
+
+    context.audit_pool(context.mint).unwrap();
+
+    let event = &context.bank.events::<PoolAudited>()[0];
+    assert_eq!(event.mint, context.mint);
+    assert_eq!(event.pool_decimal_tokens, 0);
+    assert_eq!(event.liabilities_decimal_tokens, 0);
+    assert_eq!(event.surplus_decimal_tokens, 0);
+}
+
+#[test]
+fn audit_pool_all_good_liabilities_are_the_bucket_balances_of_all_games() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let (_, _, other_game_id) = context.two_player_game();
+    context.add_deposit(&player, game_id, 5_000_000).unwrap();
+    context.bank.warp_seconds(30);
+    context.withdraw(&player, game_id, 5_000).unwrap();
+
+    context.audit_pool(context.mint).unwrap();
+
+    let owed = game_balance(&context, game_id) + game_balance(&context, other_game_id);
+    assert_eq!(liabilities(&context), owed);
+    let event = &context.bank.events::<PoolAudited>()[0];
+    assert_eq!(event.liabilities_decimal_tokens, owed);
+    assert_eq!(event.pool_decimal_tokens, owed);
+    assert_eq!(event.surplus_decimal_tokens, 0);
+}
+
+#[test]
+fn audit_pool_all_good_burned_penalty_leaves_liabilities() {
+    let burn_rate = 1_000_000;
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        burn_rate_decimal_tokens_per_move: burn_rate,
+        ..default_game_config(context.mint)
+    });
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(30);
+
+    context.leave_game(&player, game_id).unwrap();
+
+    assert_eq!(liabilities(&context), game_balance(&context, game_id));
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        liabilities(&context)
+    );
+}
+
+#[test]
+fn audit_pool_all_good_ended_game_owes_nothing() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    let (_, _, other_game_id) = context.two_player_game();
+    context.bank.warp_seconds(30);
+    context.leave_game(&player, game_id).unwrap();
+
+    context.leave_game(&creator, game_id).unwrap();
+
+    assert!(context.game(game_id).is_none());
+    assert_eq!(liabilities(&context), game_balance(&context, other_game_id));
+}
+
+#[test]
+fn audit_pool_all_good_donation_is_surplus() {
+    let mut context = TestContext::new();
+    context.two_player_game();
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+    set_pool_balance(&mut context, pool_balance + 42);
+
+    context.audit_pool(context.mint).unwrap();
+
+    let event = &context.bank.events::<PoolAudited>()[0];
+    assert_eq!(event.pool_decimal_tokens, pool_balance + 42);
+    assert_eq!(event.liabilities_decimal_tokens, pool_balance);
+    assert_eq!(event.surplus_decimal_tokens, 42);
+}
+
+#[test]
+fn audit_pool_underfunded_fails() {
+    let mut context = TestContext::new();
+    context.two_player_game();
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+    set_pool_balance(&mut context, pool_balance - 1);
+
+    assert_error(
+        context.audit_pool(context.mint),
+        EquilibrateError::PoolUnderfunded,
+    );
+}
+
+#[test]
+fn audit_pool_token_pool_of_other_mint_fails() {
+    let mut context = TestContext::new();
+    let other_mint = context.create_mint(Pubkey::new_unique());
+    context.create_pool(other_mint).unwrap();
+    let mut accounts = context.audit_pool_accounts(context.mint);
+    accounts.token_pool = token_pool_address(&other_mint);
+    let ix = instruction(accounts, equilibrate::instruction::AuditPool {});
+
+    assert_error(
+        context.bank.process(&ix, &[]),
+        EquilibrateError::InvalidTokenPool,
+    );
+}
+
+#[test]
+fn leave_game_paying_out_more_than_liabilities_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(30);
+    // the pool never pays out more than it owes all of its games
+    set_liabilities(&mut context, 1);
+
+    assert_error(
+        context.leave_game(&player, game_id),
+        EquilibrateError::PoolLiabilitiesExceeded,
+    );
+}
+
+#[test]
+fn migrate_pool_manager_all_good_owes_whole_pool() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let pool_balance = context.token_balance(&token_pool_address(&context.mint));
+    set_pool_balance(&mut context, pool_balance + 42);
+    set_old_pool_manager(&mut context);
+
+    context.migrate_pool_manager(context.mint).unwrap();
+
+    assert_eq!(
+        context.pool_manager(&context.mint).unwrap(),
+        PoolManager {
+            bump: PoolManager::get_address(context.mint).1,
+            liabilities_decimal_tokens: pool_balance + 42,
+        }
+    );
+    // games play on as usual once the manager is migrated
+    context.leave_game(&player, game_id).unwrap();
+}
+
+#[test]
+fn migrate_pool_manager_already_current_fails() {
+    let mut context = TestContext::new();
+
+    assert_error(
+        context.migrate_pool_manager(context.mint),
+        EquilibrateError::AccountAlreadyMigrated,
+    );
+}
+
+#[test]
+fn enter_game_pool_manager_not_migrated_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    set_old_pool_manager(&mut context);
+    let player = context.new_player();
+
+    assert_error(
+        context.enter_game(&player, game_id, 2),
+        ErrorCode::AccountDidNotDeserialize,
+    );
+}
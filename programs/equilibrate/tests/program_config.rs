@@ -0,0 +1,204 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        pubkey::Pubkey,
+    },
+};
+use common::*;
+use equilibrate::{
+    constants::{
+        GAME_MAX_BUCKETS, GAME_MAX_CREATOR_FEE_BPS, GAME_MAX_PLAYERS, PROGRAM_CONFIG_VERSION,
+    },
+    model::EquilibrateError,
+};
+
+#[test]
+fn initialize_config_all_good_config_is_stored() {
+    let mut context = TestContext::without_config();
+    let settings = default_program_settings(context.program_fee_destination);
+    context.initialize_config(settings.clone()).unwrap();
+
+    let config = context.program_config().unwrap();
+    assert_eq!(config.version, PROGRAM_CONFIG_VERSION);
+    assert_eq!(config.admin, context.admin);
+    assert_eq!(config.settings, settings);
+}
+
+#[test]
+fn initialize_config_twice_fails() {
+    let mut context = TestContext::new();
+    let settings = default_program_settings(context.program_fee_destination);
+    assert!(context.initialize_config(settings).is_err());
+}
+
+#[test]
+fn initialize_config_not_upgrade_authority_fails() {
+    let mut context = TestContext::without_config();
+    let payer = context.new_wallet();
+    let ix = instruction(
+        context.initialize_config_accounts(payer),
+        equilibrate::instruction::InitializeConfig {
+            admin: payer,
+            settings: default_program_settings(context.program_fee_destination),
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[payer]),
+        EquilibrateError::NotUpgradeAuthority,
+    );
+}
+
+#[test]
+fn initialize_config_program_data_of_another_program_fails() {
+    let mut context = TestContext::without_config();
+    let payer = context.new_wallet();
+    let other_program_data = Pubkey::new_unique();
+    context.bank.set_account(
+        other_program_data,
+        StoredAccount {
+            lamports: 1,
+            data: bincode::serialize(&UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(payer),
+            })
+            .unwrap(),
+            owner: bpf_loader_upgradeable::ID,
+            executable: false,
+        },
+    );
+    let mut accounts = context.initialize_config_accounts(payer);
+    accounts.program_data = other_program_data;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::InitializeConfig {
+            admin: payer,
+            settings: default_program_settings(context.program_fee_destination),
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[payer]),
+        EquilibrateError::InvalidProgramData,
+    );
+}
+
+#[test]
+fn initialize_config_bad_seed_fails() {
+    let mut context = TestContext::without_config();
+    let mut accounts = context.initialize_config_accounts(context.upgrade_authority);
+    accounts.program_config = Pubkey::find_program_address(&[b"wrong"], &equilibrate::ID).0;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::InitializeConfig {
+            admin: context.admin,
+            settings: default_program_settings(context.program_fee_destination),
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[context.upgrade_authority]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn initialize_config_invalid_settings_fail() {
+    let mut context = TestContext::without_config();
+    let defaults = default_program_settings(context.program_fee_destination);
+    let cases = [
+        (1, GAME_MAX_PLAYERS, EquilibrateError::TooFewBuckets),
+        (
+            GAME_MAX_BUCKETS + 1,
+            GAME_MAX_PLAYERS,
+            EquilibrateError::TooManyBuckets,
+        ),
+        (GAME_MAX_BUCKETS, 1, EquilibrateError::MaxPlayersTooSmall),
+        (
+            GAME_MAX_BUCKETS,
+            GAME_MAX_PLAYERS + 1,
+            EquilibrateError::MaxPlayersTooLarge,
+        ),
+    ];
+    for (game_max_buckets, game_max_players, error) in cases {
+        let mut settings = defaults.clone();
+        settings.game_max_buckets = game_max_buckets;
+        settings.game_max_players = game_max_players;
+        assert_error(context.initialize_config(settings), error);
+    }
+    assert!(context.program_config().is_none());
+}
+
+#[test]
+fn initialize_config_creator_fee_too_large_fails() {
+    let mut context = TestContext::without_config();
+    let mut settings = default_program_settings(context.program_fee_destination);
+    settings.game_max_creator_fee_bps = GAME_MAX_CREATOR_FEE_BPS + 1;
+    assert_error(
+        context.initialize_config(settings),
+        EquilibrateError::CreatorFeeTooLarge,
+    );
+    assert!(context.program_config().is_none());
+}
+
+#[test]
+fn update_config_all_good_config_is_replaced() {
+    let mut context = TestContext::new();
+    let new_admin = Pubkey::new_unique();
+    let mut settings = default_program_settings(Pubkey::new_unique());
+    settings.program_fee_lamports = 1;
+    settings.game_max_buckets = 4;
+    settings.game_max_players = 20;
+    context
+        .update_config(context.admin, new_admin, settings.clone())
+        .unwrap();
+
+    let config = context.program_config().unwrap();
+    assert_eq!(config.admin, new_admin);
+    assert_eq!(config.settings, settings);
+}
+
+#[test]
+fn update_config_new_admin_takes_over() {
+    let mut context = TestContext::new();
+    let new_admin = Pubkey::new_unique();
+    let settings = default_program_settings(context.program_fee_destination);
+    context
+        .update_config(context.admin, new_admin, settings.clone())
+        .unwrap();
+
+    assert_error(
+        context.update_config(context.admin, context.admin, settings.clone()),
+        EquilibrateError::ProgramConfigAdminMismatch,
+    );
+    context
+        .update_config(new_admin, context.admin, settings)
+        .unwrap();
+    assert_eq!(context.program_config().unwrap().admin, context.admin);
+}
+
+#[test]
+fn update_config_not_admin_fails() {
+    let mut context = TestContext::new();
+    let imposter = context.new_wallet();
+    let settings = default_program_settings(imposter);
+    assert_error(
+        context.update_config(imposter, imposter, settings),
+        EquilibrateError::ProgramConfigAdminMismatch,
+    );
+}
+
+#[test]
+fn update_config_invalid_settings_fail() {
+    let mut context = TestContext::new();
+    let mut settings = default_program_settings(context.program_fee_destination);
+    settings.game_max_buckets = 0;
+    assert_error(
+        context.update_config(context.admin, context.admin, settings),
+        EquilibrateError::TooFewBuckets,
+    );
+    assert_eq!(
+        context.program_config().unwrap().settings,
+        default_program_settings(context.program_fee_destination)
+    );
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use anchor_lang::{prelude::AccountMeta, solana_program::instruction::Instruction};
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, PlayerReferred},
+    state::ReferralStats,
+};
+
+const REFERRAL_FEE_LAMPORTS: u64 = PROGRAM_FEE_LAMPORTS / 5;
+
+fn enter_game_ix(
+    context: &TestContext,
+    player: &Player,
+    game_id: u64,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    let mut ix = instruction(
+        context.enter_game_accounts(player, game_id),
+        equilibrate::instruction::EnterGame {
+            bucket: 2,
+            stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+            allowlist_proof: vec![],
+        },
+    );
+    ix.accounts.extend_from_slice(remaining_accounts);
+    ix
+}
+
+#[test]
+fn enter_game_all_good_referrer_gets_share_of_program_fee() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let referrer = context.new_wallet();
+    let referrer_lamports = context.bank.lamports(&referrer);
+    let fee_destination_lamports = context.bank.lamports(&context.program_fee_destination);
+    let player = context.new_player();
+
+    context
+        .enter_game_referred(&player, game_id, 2, referrer)
+        .unwrap();
+
+    assert_eq!(
+        context.bank.lamports(&referrer),
+        referrer_lamports + REFERRAL_FEE_LAMPORTS
+    );
+    assert_eq!(
+        context.bank.lamports(&context.program_fee_destination),
+        fee_destination_lamports + PROGRAM_FEE_LAMPORTS - REFERRAL_FEE_LAMPORTS
+    );
+    assert_eq!(
+        context.referral_stats(&referrer).unwrap(),
+        ReferralStats {
+            version: equilibrate::constants::REFERRAL_STATS_VERSION,
+            referrer,
+            referrals: 1,
+            volume_lamports: PROGRAM_FEE_LAMPORTS,
+            rewards_lamports: REFERRAL_FEE_LAMPORTS,
+        }
+    );
+    let events = context.bank.events::<PlayerReferred>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].referrer, referrer);
+    assert_eq!(events[0].reward_lamports, REFERRAL_FEE_LAMPORTS);
+}
+
+#[test]
+fn enter_game_all_good_referrals_add_up_across_games() {
+    let mut context = TestContext::new();
+    let referrer = context.new_wallet();
+    for _ in 0..3 {
+        let creator = context.new_player();
+        let game_id = context.new_game(&creator);
+        let player = context.new_player();
+        context
+            .enter_game_referred(&player, game_id, 3, referrer)
+            .unwrap();
+    }
+
+    let stats = context.referral_stats(&referrer).unwrap();
+    assert_eq!(stats.referrals, 3);
+    assert_eq!(stats.volume_lamports, 3 * PROGRAM_FEE_LAMPORTS);
+    assert_eq!(stats.rewards_lamports, 3 * REFERRAL_FEE_LAMPORTS);
+}
+
+#[test]
+fn enter_game_all_good_no_referral_fee_still_counts_referral() {
+    let mut context = TestContext::new();
+    let mut settings = default_program_settings(context.program_fee_destination);
+    settings.referral_fee_bps = 0;
+    context
+        .update_config(context.admin, context.admin, settings)
+        .unwrap();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let referrer = context.new_wallet();
+    let referrer_lamports = context.bank.lamports(&referrer);
+    let player = context.new_player();
+
+    context
+        .enter_game_referred(&player, game_id, 2, referrer)
+        .unwrap();
+
+    assert_eq!(context.bank.lamports(&referrer), referrer_lamports);
+    let stats = context.referral_stats(&referrer).unwrap();
+    assert_eq!(stats.referrals, 1);
+    assert_eq!(stats.rewards_lamports, 0);
+}
+
+#[test]
+fn enter_game_all_good_referral_stats_address_funded_ahead_of_time() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let referrer = context.new_wallet();
+    context.bank.airdrop(referral_stats_address(&referrer), 1);
+    let player = context.new_player();
+
+    context
+        .enter_game_referred(&player, game_id, 2, referrer)
+        .unwrap();
+
+    assert_eq!(context.referral_stats(&referrer).unwrap().referrals, 1);
+}
+
+#[test]
+fn enter_game_self_referral_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+
+    assert_error(
+        context.enter_game_referred(&player, game_id, 2, player.wallet),
+        EquilibrateError::SelfReferral,
+    );
+    assert!(context.referral_stats(&player.wallet).is_none());
+}
+
+#[test]
+fn enter_game_referral_stats_of_someone_else_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let referrer = context.new_wallet();
+    let player = context.new_player();
+    let ix = enter_game_ix(
+        &context,
+        &player,
+        game_id,
+        &[
+            AccountMeta::new(referrer, false),
+            AccountMeta::new(referral_stats_address(&creator.wallet), false),
+        ],
+    );
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidReferralStats,
+    );
+}
+
+#[test]
+fn enter_game_referrer_without_stats_account_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let referrer = context.new_wallet();
+    let player = context.new_player();
+    let ix = enter_game_ix(
+        &context,
+        &player,
+        game_id,
+        &[AccountMeta::new(referrer, false)],
+    );
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::MissingReferralAccounts,
+    );
+}
+
+#[test]
+fn update_config_referral_fee_above_whole_program_fee_fails() {
+    let mut context = TestContext::new();
+    let mut settings = default_program_settings(context.program_fee_destination);
+    settings.referral_fee_bps = 10_001;
+    assert_error(
+        context.update_config(context.admin, context.admin, settings),
+        EquilibrateError::ReferralFeeTooLarge,
+    );
+}
@@ -0,0 +1,319 @@
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::Pubkey};
+use common::*;
+use equilibrate::{
+    constants::{SESSION_MAX_SECONDS, SESSION_VERSION},
+    model::{EquilibrateError, PlayerLeft, PlayerMoved, SessionCreated},
+    state::{GameConfig, PlayerState, SessionAuthority},
+};
+
+const SESSION_SECONDS: i64 = 60 * 60;
+
+/// Creator in bucket 1 and a player in bucket 2 who has a session key
+fn set_up_session(context: &mut TestContext, can_leave: bool) -> (Player, Player, u64, Pubkey) {
+    let (creator, player, game_id) = context.two_player_game();
+    let session_key = context.new_wallet();
+    context
+        .create_session(&player, game_id, session_key, SESSION_SECONDS, can_leave)
+        .unwrap();
+    (creator, player, game_id, session_key)
+}
+
+#[test]
+fn create_session_all_good_session_is_recorded() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, true);
+
+    let expires_epoch_seconds = context.bank.now_epoch_seconds() + SESSION_SECONDS;
+    assert_eq!(
+        context.session(game_id, &player).unwrap(),
+        SessionAuthority {
+            version: SESSION_VERSION,
+            owner: player.wallet,
+            game_id,
+            session_key,
+            expires_epoch_seconds,
+            can_leave: true,
+        }
+    );
+    assert!(context.player_state(game_id, &player).unwrap().has_session);
+    let events = context.bank.events::<SessionCreated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].session_key, session_key);
+    assert_eq!(events[0].expires_epoch_seconds, expires_epoch_seconds);
+}
+
+#[test]
+fn session_move_buckets_all_good_player_is_moved() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, false);
+    let player_lamports = context.bank.lamports(&player.wallet);
+
+    context
+        .session_move_buckets(session_key, &player, game_id, 3)
+        .unwrap();
+
+    assert_eq!(context.player_state(game_id, &player).unwrap().bucket, 3);
+    // the session key pays the transaction fee
+    assert_eq!(context.bank.lamports(&player.wallet), player_lamports);
+    let events = context.bank.events::<PlayerMoved>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].to_bucket, 3);
+}
+
+#[test]
+fn session_leave_game_all_good_winnings_and_rent_go_to_player() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, true);
+    context.bank.warp_seconds(10);
+    let player_lamports = context.bank.lamports(&player.wallet);
+
+    context
+        .session_leave_game(session_key, &player, game_id)
+        .unwrap();
+
+    let winnings = 2 * 10 * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER / 3;
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + winnings
+    );
+    assert!(context.player_state(game_id, &player).is_none());
+    assert!(context.session(game_id, &player).is_none());
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        player_lamports
+            + context.bank.minimum_balance(PlayerState::get_space())
+            + context.bank.minimum_balance(SessionAuthority::get_space())
+    );
+    let events = context.bank.events::<PlayerLeft>();
+    assert_eq!(events[0].player, player.wallet);
+}
+
+#[test]
+fn create_session_again_replaces_session_key() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, old_session_key) = set_up_session(&mut context, false);
+    let new_session_key = context.new_wallet();
+    context
+        .create_session(&player, game_id, new_session_key, SESSION_SECONDS, false)
+        .unwrap();
+
+    assert_error(
+        context.session_move_buckets(old_session_key, &player, game_id, 3),
+        EquilibrateError::InvalidSessionKey,
+    );
+    context
+        .session_move_buckets(new_session_key, &player, game_id, 3)
+        .unwrap();
+}
+
+#[test]
+fn revoke_session_all_good_key_can_no_longer_move() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, false);
+    let player_lamports = context.bank.lamports(&player.wallet);
+
+    context.revoke_session(&player, game_id).unwrap();
+
+    assert!(context.session(game_id, &player).is_none());
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        player_lamports + context.bank.minimum_balance(SessionAuthority::get_space())
+    );
+    assert_error(
+        context.session_move_buckets(session_key, &player, game_id, 3),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn leave_game_all_good_after_revoking_session() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, false);
+    context.revoke_session(&player, game_id).unwrap();
+
+    context.leave_game(&player, game_id).unwrap();
+
+    assert!(context.player_state(game_id, &player).is_none());
+    context.enter_game(&player, game_id, 2).unwrap();
+    assert!(!context.player_state(game_id, &player).unwrap().has_session);
+    assert_error(
+        context.session_move_buckets(session_key, &player, game_id, 3),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn leave_game_all_good_session_is_closed_and_cant_act_after_entering_again() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, true);
+    let player_lamports = context.bank.lamports(&player.wallet);
+
+    context.leave_game(&player, game_id).unwrap();
+
+    assert!(context.session(game_id, &player).is_none());
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        player_lamports
+            + context.bank.minimum_balance(PlayerState::get_space())
+            + context.bank.minimum_balance(SessionAuthority::get_space())
+    );
+    context.enter_game(&player, game_id, 2).unwrap();
+    assert_error(
+        context.session_move_buckets(session_key, &player, game_id, 3),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn leave_lamport_game_all_good_session_is_closed() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) =
+        context.two_player_lamport_game_with(default_game_config(Pubkey::default()));
+    let session_key = context.new_wallet();
+    context
+        .create_session(&player, game_id, session_key, SESSION_SECONDS, false)
+        .unwrap();
+
+    context.leave_lamport_game(&player, game_id).unwrap();
+
+    assert!(context.session(game_id, &player).is_none());
+}
+
+#[test]
+fn settle_player_all_good_session_is_closed() {
+    let mut context = TestContext::new();
+    let end_epoch_seconds = context.bank.now_epoch_seconds() + 10;
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        end_epoch_seconds: Some(end_epoch_seconds),
+        ..default_game_config(context.mint)
+    });
+    let session_key = context.new_wallet();
+    context
+        .create_session(&player, game_id, session_key, SESSION_SECONDS, false)
+        .unwrap();
+    context.bank.warp_seconds(10);
+    let player_lamports = context.bank.lamports(&player.wallet);
+
+    let settler = context.new_wallet();
+    context.settle_player(settler, &player, game_id).unwrap();
+
+    assert!(context.session(game_id, &player).is_none());
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        player_lamports
+            + context.bank.minimum_balance(PlayerState::get_space())
+            + context.bank.minimum_balance(SessionAuthority::get_space())
+    );
+}
+
+#[test]
+fn leave_game_without_session_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, _) = set_up_session(&mut context, false);
+    let ix = instruction(
+        context.leave_game_accounts(&player, game_id),
+        equilibrate::instruction::LeaveGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::MissingSession,
+    );
+    assert!(context.session(game_id, &player).is_some());
+}
+
+#[test]
+fn create_session_expiry_outside_range_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let session_key = context.new_wallet();
+
+    for seconds in [0, SESSION_MAX_SECONDS + 1] {
+        assert_error(
+            context.create_session(&creator, game_id, session_key, seconds, false),
+            EquilibrateError::InvalidSessionExpiry,
+        );
+    }
+}
+
+#[test]
+fn create_session_not_in_game_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let outsider = context.new_player();
+    let session_key = context.new_wallet();
+
+    assert_error(
+        context.create_session(&outsider, game_id, session_key, SESSION_SECONDS, false),
+        ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[test]
+fn session_move_buckets_expired_session_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, false);
+    context.bank.warp_seconds(SESSION_SECONDS);
+
+    assert_error(
+        context.session_move_buckets(session_key, &player, game_id, 3),
+        EquilibrateError::SessionExpired,
+    );
+}
+
+#[test]
+fn session_move_buckets_other_key_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, _) = set_up_session(&mut context, false);
+    let other_key = context.new_wallet();
+
+    assert_error(
+        context.session_move_buckets(other_key, &player, game_id, 3),
+        EquilibrateError::InvalidSessionKey,
+    );
+}
+
+#[test]
+fn session_leave_game_session_without_leave_permission_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, false);
+
+    assert_error(
+        context.session_leave_game(session_key, &player, game_id),
+        EquilibrateError::SessionCannotLeave,
+    );
+    assert!(context.player_state(game_id, &player).is_some());
+}
+
+#[test]
+fn session_leave_game_winnings_to_session_key_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id, session_key) = set_up_session(&mut context, true);
+    let session_key_token_account = context.new_wallet();
+    context.create_token_account(session_key_token_account, context.mint, session_key, 0);
+    let mut accounts = context.session_leave_game_accounts(session_key, &player, game_id);
+    accounts.winnings_destination_account = session_key_token_account;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::SessionLeaveGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+
+    assert_error(
+        context.bank.process(&ix, &[session_key]),
+        EquilibrateError::InvalidWinningsDestinationOwner,
+    );
+}
@@ -0,0 +1,288 @@
+mod common;
+
+use anchor_lang::{error::ErrorCode, solana_program::pubkey::Pubkey};
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, GameEnded, PlayerSettled},
+    state::{Game, GameConfig, PlayerState},
+};
+
+const GAME_SECONDS: i64 = 100;
+
+/// Config of a game that ends after `GAME_SECONDS`, with `burn_rate` per move
+fn ending_game_config(context: &TestContext, burn_rate: u64) -> GameConfig {
+    GameConfig {
+        burn_rate_decimal_tokens_per_move: burn_rate,
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + GAME_SECONDS),
+        ..default_game_config(context.mint)
+    }
+}
+
+/// Game as it stood at its end time
+fn game_at_end(context: &TestContext, game_id: u64) -> Game {
+    let mut game = context.game(game_id).unwrap();
+    let end_epoch_seconds = game.config.end_epoch_seconds.unwrap();
+    game.update_bucket_balances(end_epoch_seconds as u64);
+    game
+}
+
+/// Share of bucket `i_bucket` and of the holding bucket for one of its players
+fn share_at_end(game: &Game, i_bucket: usize) -> u64 {
+    let bucket = &game.state.buckets[i_bucket];
+    let holding = &game.state.buckets[0];
+    bucket.decimal_tokens / bucket.players as u64 + holding.decimal_tokens / holding.players as u64
+}
+
+#[test]
+fn settle_player_all_good_anyone_can_settle_after_end() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS + 50);
+    let expected_winnings = share_at_end(&game_at_end(&context, game_id), 2);
+    let player_lamports = context.bank.lamports(&player.wallet);
+    let settler = context.new_wallet();
+    context.settle_player(settler, &player, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + expected_winnings
+    );
+    assert!(context.player_state(game_id, &player).is_none());
+    // rent of the player state goes to the player, not the settler
+    assert_eq!(
+        context.bank.lamports(&player.wallet),
+        player_lamports + context.bank.minimum_balance(PlayerState::get_space())
+    );
+
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.get_player_count(), 1);
+    assert_eq!(game.state.buckets[2].players, 0);
+
+    let events = context.bank.events::<PlayerSettled>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].settled_by, settler);
+    assert_eq!(events[0].winnings_decimal_tokens, expected_winnings);
+    assert!(context.bank.events::<GameEnded>().is_empty());
+}
+
+#[test]
+fn settle_player_all_good_holding_bucket_remainder_is_shared() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS);
+    let game = game_at_end(&context, game_id);
+    let holding_decimal_tokens = game.state.buckets[0].decimal_tokens;
+    // the holding bucket hasnt emptied by the time the game ends
+    assert!(holding_decimal_tokens > 0);
+    let settler = context.new_wallet();
+    context.settle_player(settler, &player, game_id).unwrap();
+
+    let game = context.game(game_id).unwrap();
+    assert_eq!(
+        game.state.buckets[0].decimal_tokens,
+        holding_decimal_tokens - holding_decimal_tokens / 2
+    );
+}
+
+#[test]
+fn settle_player_all_good_balances_stop_at_end_time() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS);
+    let expected_game = game_at_end(&context, game_id);
+    let expected_winnings = share_at_end(&expected_game, 2);
+
+    context.bank.warp_seconds(10_000);
+    let settler = context.new_wallet();
+    context.settle_player(settler, &player, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + expected_winnings
+    );
+    // the remaining player can still be settled later for the same balances
+    let game = context.game(game_id).unwrap();
+    assert_eq!(
+        game.state.last_update_epoch_seconds,
+        expected_game.config.end_epoch_seconds.unwrap()
+    );
+    context.bank.warp_seconds(10_000);
+    let settler = context.new_wallet();
+    context.settle_player(settler, &creator, game_id).unwrap();
+    assert_eq!(
+        context.token_balance(&creator.token_account)
+            + context.token_balance(&player.token_account),
+        2 * WALLET_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn settle_player_all_good_final_settlement_closes_game() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS);
+    let settler = context.new_wallet();
+    context.settle_player(settler, &player, game_id).unwrap();
+    let creator_lamports = context.bank.lamports(&creator.wallet);
+    let game_rent = context.bank.lamports(&game_address(game_id));
+    let player_rent = context.bank.minimum_balance(PlayerState::get_space());
+    context.settle_player(settler, &creator, game_id).unwrap();
+
+    assert!(context.game(game_id).is_none());
+    assert!(context.player_state(game_id, &creator).is_none());
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+    assert_eq!(
+        context.bank.lamports(&creator.wallet),
+        creator_lamports + game_rent + player_rent
+    );
+
+    let events = context.bank.events::<GameEnded>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].player, creator.wallet);
+}
+
+#[test]
+fn settle_player_all_good_burn_penalty_is_burned() {
+    let mut context = TestContext::new();
+    let burn_rate = 1_000;
+    let (_, player, game_id) =
+        context.two_player_game_with(ending_game_config(&context, burn_rate));
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(GAME_SECONDS);
+    let share = share_at_end(&game_at_end(&context, game_id), 3);
+    let supply = context.mint_supply(&context.mint);
+    let settler = context.new_wallet();
+    context.settle_player(settler, &player, game_id).unwrap();
+
+    assert_eq!(context.mint_supply(&context.mint), supply - burn_rate);
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + share - burn_rate
+    );
+}
+
+#[test]
+fn settle_player_before_end_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS - 1);
+    let settler = context.new_wallet();
+    assert_error(
+        context.settle_player(settler, &player, game_id),
+        EquilibrateError::GameHasNotEnded,
+    );
+}
+
+#[test]
+fn settle_player_game_without_end_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    context.bank.warp_seconds(1_000_000);
+    let settler = context.new_wallet();
+    assert_error(
+        context.settle_player(settler, &creator, game_id),
+        EquilibrateError::GameHasNotEnded,
+    );
+}
+
+#[test]
+fn settle_player_winnings_destination_not_owned_by_player_fails() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS);
+    let settler = context.new_wallet();
+    let mut accounts = context.settle_player_accounts(settler, &player, game_id);
+    accounts.winnings_destination_account = creator.token_account;
+    let ix = instruction(accounts, equilibrate::instruction::SettlePlayer {});
+    assert_error(
+        context.bank.process(&ix, &[settler]),
+        EquilibrateError::InvalidWinningsDestinationOwner,
+    );
+}
+
+#[test]
+fn settle_player_player_wallet_doesnt_match_player_state_fails() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS);
+    let settler = context.new_wallet();
+    let mut accounts = context.settle_player_accounts(settler, &player, game_id);
+    accounts.player_wallet = creator.wallet;
+    let ix = instruction(accounts, equilibrate::instruction::SettlePlayer {});
+    assert_error(
+        context.bank.process(&ix, &[settler]),
+        ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn settle_player_game_creator_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS);
+    let settler = context.new_wallet();
+    let mut accounts = context.settle_player_accounts(settler, &player, game_id);
+    accounts.game_creator = Pubkey::new_unique();
+    let ix = instruction(accounts, equilibrate::instruction::SettlePlayer {});
+    assert_error(
+        context.bank.process(&ix, &[settler]),
+        EquilibrateError::GameCreatorMismatch,
+    );
+}
+
+#[test]
+fn leave_game_all_good_after_end_includes_holding_share() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS + 50);
+    let expected_winnings = share_at_end(&game_at_end(&context, game_id), 2);
+    context.leave_game(&player, game_id).unwrap();
+
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + expected_winnings
+    );
+}
+
+#[test]
+fn enter_game_after_end_fails() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS);
+    let late_player = context.new_player();
+    assert_error(
+        context.enter_game(&late_player, game_id, 3),
+        EquilibrateError::GameHasEnded,
+    );
+}
+
+#[test]
+fn move_buckets_after_end_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(ending_game_config(&context, 0));
+    context.bank.warp_seconds(GAME_SECONDS);
+    assert_error(
+        context.move_buckets(&player, game_id, 3),
+        EquilibrateError::GameHasEnded,
+    );
+}
+
+#[test]
+fn new_game_end_time_not_in_future_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    for end_epoch_seconds in [
+        context.bank.now_epoch_seconds(),
+        context.bank.now_epoch_seconds() - 1,
+    ] {
+        let game_id = context.next_game_id();
+        let mut config = default_game_config(context.mint);
+        config.end_epoch_seconds = Some(end_epoch_seconds);
+        assert_error(
+            context.new_game_with(&creator, game_id, config),
+            EquilibrateError::InvalidEndTime,
+        );
+    }
+}
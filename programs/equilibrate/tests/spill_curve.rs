@@ -0,0 +1,222 @@
+mod common;
+
+use common::*;
+use equilibrate::{
+    constants::SPILL_CURVE_MAX_STEPS,
+    model::EquilibrateError,
+    state::{GameConfig, SpillCurve, SpillStep},
+};
+
+const RATE: u64 = SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER;
+
+fn new_game_with_curve(
+    context: &mut TestContext,
+    creator: &Player,
+    spill_curve: SpillCurve,
+) -> u64 {
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        spill_curve,
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(creator, game_id, config).unwrap();
+    game_id
+}
+
+fn holding_decimal_tokens(context: &TestContext, game_id: u64) -> u64 {
+    context.game(game_id).unwrap().state.buckets[0].decimal_tokens
+}
+
+#[test]
+fn spill_curve_all_good_proportional_to_balance_decays_exponentially() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_game_with_curve(
+        &mut context,
+        &creator,
+        SpillCurve::ProportionalToBalance {
+            decay_bps_per_second: 100,
+        },
+    );
+    context.bank.warp_seconds(10);
+    context.update_game(game_id).unwrap();
+
+    // the holding bucket keeps 99% of its balance each second, spilling
+    // evenly into the two empty buckets
+    let expected = ENTRY_FEE_DECIMAL_TOKENS as f64 * 0.99f64.powi(10);
+    let holding = holding_decimal_tokens(&context, game_id);
+    assert!((holding as f64 - expected).abs() <= 2.0);
+    let game = context.game(game_id).unwrap();
+    assert_eq!(
+        game.state.buckets[2].decimal_tokens,
+        game.state.buckets[3].decimal_tokens
+    );
+}
+
+#[test]
+fn spill_curve_all_good_proportional_to_balance_never_empties() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_game_with_curve(
+        &mut context,
+        &creator,
+        SpillCurve::ProportionalToBalance {
+            decay_bps_per_second: 1,
+        },
+    );
+    let mut previous = holding_decimal_tokens(&context, game_id);
+    for _ in 0..5 {
+        context.bank.warp_seconds(1_000);
+        context.update_game(game_id).unwrap();
+        let holding = holding_decimal_tokens(&context, game_id);
+        assert!(holding < previous);
+        assert!(holding > 0);
+        previous = holding;
+    }
+}
+
+#[test]
+fn spill_curve_all_good_sqrt_of_players_slows_crowded_buckets() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_game_with_curve(&mut context, &creator, SpillCurve::SqrtOfPlayers);
+    for _ in 0..3 {
+        let player = context.new_player();
+        context.enter_game(&player, game_id, 1).unwrap();
+    }
+    context.bank.warp_seconds(10);
+    context.update_game(game_id).unwrap();
+
+    // 4 players in the holding bucket spill at twice the rate, not four times
+    assert_eq!(
+        holding_decimal_tokens(&context, game_id),
+        4 * ENTRY_FEE_DECIMAL_TOKENS - 2 * RATE * 10
+    );
+}
+
+#[test]
+fn spill_curve_all_good_piecewise_follows_schedule() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let now_epoch_seconds = context.bank.now_epoch_seconds();
+    let game_id = new_game_with_curve(
+        &mut context,
+        &creator,
+        SpillCurve::Piecewise {
+            steps: vec![
+                SpillStep {
+                    start_epoch_seconds: now_epoch_seconds + 10,
+                    spill_rate_decimal_tokens_per_second_per_player: 0,
+                },
+                SpillStep {
+                    start_epoch_seconds: now_epoch_seconds + 20,
+                    spill_rate_decimal_tokens_per_second_per_player: 3 * RATE,
+                },
+            ],
+        },
+    );
+    context.bank.warp_seconds(15);
+    context.update_game(game_id).unwrap();
+    assert_eq!(
+        holding_decimal_tokens(&context, game_id),
+        ENTRY_FEE_DECIMAL_TOKENS - RATE * 10
+    );
+
+    context.bank.warp_seconds(15);
+    context.update_game(game_id).unwrap();
+    assert_eq!(
+        holding_decimal_tokens(&context, game_id),
+        ENTRY_FEE_DECIMAL_TOKENS - RATE * 10 - 3 * RATE * 10
+    );
+}
+
+#[test]
+fn spill_curve_all_good_proportional_to_balance_ignores_spill_rate() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        spill_rate_decimal_tokens_per_second_per_player: 0,
+        spill_curve: SpillCurve::ProportionalToBalance {
+            decay_bps_per_second: 10,
+        },
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(&creator, game_id, config).unwrap();
+}
+
+#[test]
+fn new_game_proportional_to_balance_decay_out_of_range_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    for decay_bps_per_second in [0, 10_001] {
+        let game_id = context.next_game_id();
+        let config = GameConfig {
+            spill_curve: SpillCurve::ProportionalToBalance {
+                decay_bps_per_second,
+            },
+            ..default_game_config(context.mint)
+        };
+        assert_error(
+            context.new_game_with(&creator, game_id, config),
+            EquilibrateError::InvalidSpillCurve,
+        );
+    }
+}
+
+#[test]
+fn new_game_piecewise_steps_invalid_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let step = |start_epoch_seconds: i64| SpillStep {
+        start_epoch_seconds,
+        spill_rate_decimal_tokens_per_second_per_player: RATE,
+    };
+    let too_many = (0..=SPILL_CURVE_MAX_STEPS as i64).map(step).collect();
+    for steps in [
+        vec![],
+        too_many,
+        vec![step(20), step(10)],
+        vec![step(10), step(10)],
+    ] {
+        let game_id = context.next_game_id();
+        let config = GameConfig {
+            spill_curve: SpillCurve::Piecewise { steps },
+            ..default_game_config(context.mint)
+        };
+        assert_error(
+            context.new_game_with(&creator, game_id, config),
+            EquilibrateError::InvalidSpillCurve,
+        );
+    }
+}
+
+#[test]
+fn new_game_sqrt_of_players_spill_rate_is_zero_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        spill_rate_decimal_tokens_per_second_per_player: 0,
+        spill_curve: SpillCurve::SqrtOfPlayers,
+        ..default_game_config(context.mint)
+    };
+    assert_error(
+        context.new_game_with(&creator, game_id, config),
+        EquilibrateError::InvalidSpillRate,
+    );
+}
+
+#[test]
+fn new_game_piecewise_with_max_steps_fits_game_account() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let steps = (0..SPILL_CURVE_MAX_STEPS as i64)
+        .map(|i| SpillStep {
+            start_epoch_seconds: context.bank.now_epoch_seconds() + i,
+            spill_rate_decimal_tokens_per_second_per_player: u64::MAX,
+        })
+        .collect();
+    let game_id = new_game_with_curve(&mut context, &creator, SpillCurve::Piecewise { steps });
+    assert!(context.game(game_id).is_some());
+}
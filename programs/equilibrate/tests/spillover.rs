@@ -0,0 +1,232 @@
+//! Property tests for `Game::update_bucket_balances` over random games. Failing
+//! cases are shrunk by proptest and saved under `proptest-regressions/`.
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use equilibrate::{
+    constants::{GAME_MAX_BUCKETS, GAME_VERSION, SPILL_CURVE_MAX_STEPS},
+    state::{
+        Bucket, BurnSchedule, DustDestination, Game, GameConfig, GameState, PenaltyDestination,
+        SpillCurve, SpillStep, TopUpDestination,
+    },
+};
+use proptest::prelude::*;
+
+/// Keeps the total player count within `GAME_MAX_PLAYERS` for the largest games
+const MAX_PLAYERS_PER_BUCKET: u16 = 150;
+const MAX_DECIMAL_TOKENS_PER_BUCKET: u64 = 1_000_000_000_000_000;
+const MAX_STAKE_PER_PLAYER: u64 = 1_000_000_000_000;
+const MAX_SPILL_RATE: u64 = 1_000_000_000;
+const MAX_ELAPSED_SECONDS: u64 = 1_000_000;
+
+/// Any spill curve `new_game` accepts
+fn arb_spill_curve() -> impl Strategy<Value = SpillCurve> {
+    prop_oneof![
+        Just(SpillCurve::Linear),
+        (1..=10_000u16).prop_map(|decay_bps_per_second| SpillCurve::ProportionalToBalance {
+            decay_bps_per_second
+        }),
+        Just(SpillCurve::SqrtOfPlayers),
+        prop::collection::btree_map(
+            0..=MAX_ELAPSED_SECONDS as i64,
+            0..=MAX_SPILL_RATE,
+            1..=SPILL_CURVE_MAX_STEPS as usize,
+        )
+        .prop_map(|steps| SpillCurve::Piecewise {
+            steps: steps
+                .into_iter()
+                .map(|(start_epoch_seconds, rate)| SpillStep {
+                    start_epoch_seconds,
+                    spill_rate_decimal_tokens_per_second_per_player: rate,
+                })
+                .collect(),
+        }),
+    ]
+}
+
+/// A game as it could exist on chain, with any spill curve
+fn arb_game() -> impl Strategy<Value = Game> {
+    arb_game_with(arb_spill_curve())
+}
+
+/// A game as it could exist on chain: the holding bucket counts every player and
+/// all of their stake
+fn arb_game_with(spill_curve: impl Strategy<Value = SpillCurve>) -> impl Strategy<Value = Game> {
+    (2..=GAME_MAX_BUCKETS, spill_curve)
+        .prop_flat_map(|(n_buckets, spill_curve)| {
+            (
+                prop::collection::vec(
+                    (
+                        0..=MAX_DECIMAL_TOKENS_PER_BUCKET,
+                        0..=MAX_PLAYERS_PER_BUCKET,
+                        1..=MAX_STAKE_PER_PLAYER,
+                    ),
+                    n_buckets as usize,
+                ),
+                0..=MAX_DECIMAL_TOKENS_PER_BUCKET,
+                1..=MAX_SPILL_RATE,
+                Just(spill_curve),
+            )
+        })
+        .prop_map(
+            |(buckets, holding_decimal_tokens, spill_rate, spill_curve)| {
+                let n_buckets = buckets.len() as u8;
+                let buckets: Vec<Bucket> = buckets
+                    .into_iter()
+                    .map(|(decimal_tokens, players, stake_per_player)| Bucket {
+                        decimal_tokens,
+                        players,
+                        stake_decimal_tokens: (players as u64) * stake_per_player,
+                    })
+                    .collect();
+                let holding = Bucket {
+                    decimal_tokens: holding_decimal_tokens,
+                    players: buckets.iter().map(|b| b.players).sum(),
+                    stake_decimal_tokens: buckets.iter().map(|b| b.stake_decimal_tokens).sum(),
+                };
+                let buckets = std::iter::once(holding).chain(buckets).collect();
+                Game {
+                    version: GAME_VERSION,
+                    id: 0,
+                    creator: Pubkey::default(),
+                    config: GameConfig {
+                        mint: Pubkey::default(),
+                        entry_fee_decimal_tokens: 1,
+                        spill_rate_decimal_tokens_per_second_per_player: spill_rate,
+                        n_buckets,
+                        max_players: u16::MAX,
+                        burn_rate_decimal_tokens_per_move: 0,
+                        end_epoch_seconds: None,
+                        spill_curve,
+                        allowlist_root: None,
+                        creator_fee_bps: 0,
+                        max_stake_decimal_tokens: MAX_STAKE_PER_PLAYER,
+                        top_up_destination: TopUpDestination::HoldingBucket,
+                        min_seconds_between_moves: 0,
+                        burn_schedule: BurnSchedule::Flat,
+                        burn_penalty_half_life_seconds: None,
+                        penalty_destination: PenaltyDestination::Burn,
+                        dust_destination: DustDestination::LastLeaver,
+                    },
+                    state: GameState {
+                        buckets,
+                        last_update_epoch_seconds: 0,
+                    },
+                    creator_fees_decimal_tokens: 0,
+                    dust_decimal_tokens: 0,
+                }
+            },
+        )
+}
+
+/// Everything the game owns: its bucket balances and its dust
+fn total_decimal_tokens(game: &Game) -> u128 {
+    game.state
+        .buckets
+        .iter()
+        .map(|b| b.decimal_tokens as u128)
+        .sum::<u128>()
+        + game.dust_decimal_tokens as u128
+}
+
+fn updated(game: &Game, elapsed_seconds: u64) -> Game {
+    let mut game = game.clone();
+    game.update_bucket_balances(elapsed_seconds);
+    game
+}
+
+proptest! {
+    #[test]
+    fn tokens_are_conserved(game in arb_game(), elapsed in 0..=MAX_ELAPSED_SECONDS) {
+        let after = updated(&game, elapsed);
+        prop_assert_eq!(total_decimal_tokens(&after), total_decimal_tokens(&game));
+    }
+
+    #[test]
+    fn spills_make_no_dust(game in arb_game(), elapsed in 0..=MAX_ELAPSED_SECONDS) {
+        // the remainder of splitting a spill stays in the bucket it came from
+        let after = updated(&game, elapsed);
+        prop_assert_eq!(after.dust_decimal_tokens, game.dust_decimal_tokens);
+    }
+
+    #[test]
+    fn players_and_stakes_are_unchanged(game in arb_game(), elapsed in 0..=MAX_ELAPSED_SECONDS) {
+        let after = updated(&game, elapsed);
+        for (before, after) in game.state.buckets.iter().zip(after.state.buckets.iter()) {
+            prop_assert_eq!(before.players, after.players);
+            prop_assert_eq!(before.stake_decimal_tokens, after.stake_decimal_tokens);
+        }
+    }
+
+    #[test]
+    fn no_bucket_spills_more_than_it_holds(
+        game in arb_game_with(Just(SpillCurve::Linear)),
+        elapsed in 0..=MAX_ELAPSED_SECONDS,
+    ) {
+        // a bucket can only lose what it spills, and never more than it holds or
+        // its players' share of the spill rate
+        let after = updated(&game, elapsed);
+        let rate = game.config.spill_rate_decimal_tokens_per_second_per_player;
+        for (before, after) in game.state.buckets.iter().zip(after.state.buckets.iter()) {
+            if after.decimal_tokens < before.decimal_tokens {
+                let lost = before.decimal_tokens - after.decimal_tokens;
+                let max_spill = (before.players as u64) * rate * elapsed;
+                prop_assert!(lost <= max_spill);
+            }
+        }
+    }
+
+    #[test]
+    fn holding_bucket_only_drains(game in arb_game(), elapsed in 0..=MAX_ELAPSED_SECONDS) {
+        let after = updated(&game, elapsed);
+        prop_assert!(after.state.buckets[0].decimal_tokens <= game.state.buckets[0].decimal_tokens);
+    }
+
+    #[test]
+    fn holding_bucket_drains_monotonically_over_time(
+        game in arb_game(),
+        earlier in 0..=MAX_ELAPSED_SECONDS,
+        later in 0..=MAX_ELAPSED_SECONDS,
+    ) {
+        let (earlier, later) = (earlier.min(later), earlier.max(later));
+        let at_earlier = updated(&game, earlier).state.buckets[0].decimal_tokens;
+        let at_later = updated(&game, later).state.buckets[0].decimal_tokens;
+        prop_assert!(at_later <= at_earlier);
+    }
+
+    #[test]
+    fn buckets_only_gain_from_buckets_with_more_at_stake(
+        game in arb_game(),
+        elapsed in 0..=MAX_ELAPSED_SECONDS,
+    ) {
+        let after = updated(&game, elapsed);
+        let buckets = &game.state.buckets;
+        for (j, (before, after)) in buckets.iter().zip(after.state.buckets.iter()).enumerate() {
+            if after.decimal_tokens > before.decimal_tokens {
+                prop_assert!(j != 0);
+                let has_source = buckets.iter().any(|source| {
+                    (source.stake_decimal_tokens > before.stake_decimal_tokens)
+                        && (source.decimal_tokens > 0)
+                });
+                prop_assert!(has_source);
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_updates_conserve_tokens(
+        game in arb_game(),
+        steps in prop::collection::vec(0..=MAX_ELAPSED_SECONDS / 100, 1..20),
+    ) {
+        let total = total_decimal_tokens(&game);
+        let mut game = game;
+        let mut now = 0u64;
+        for step in steps {
+            now += step;
+            let holding_before = game.state.buckets[0].decimal_tokens;
+            game.update_bucket_balances(now);
+            game.state.last_update_epoch_seconds = now as i64;
+            prop_assert_eq!(total_decimal_tokens(&game), total);
+            prop_assert!(game.state.buckets[0].decimal_tokens <= holding_before);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, PlayerEntered},
+    state::GameConfig,
+};
+
+const MAX_STAKE_DECIMAL_TOKENS: u64 = 5 * ENTRY_FEE_DECIMAL_TOKENS;
+const WHALE_STAKE_DECIMAL_TOKENS: u64 = 3 * ENTRY_FEE_DECIMAL_TOKENS;
+
+/// Creator in bucket 1 of a game where players can stake up to five entry fees
+fn new_variable_stake_game(context: &mut TestContext, creator: &Player) -> u64 {
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        max_stake_decimal_tokens: MAX_STAKE_DECIMAL_TOKENS,
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(creator, game_id, config).unwrap();
+    game_id
+}
+
+#[test]
+fn enter_game_all_good_stake_is_deposited_and_recorded() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_variable_stake_game(&mut context, &creator);
+    let whale = context.new_player();
+
+    context
+        .enter_game_with_stake(&whale, game_id, 2, WHALE_STAKE_DECIMAL_TOKENS)
+        .unwrap();
+
+    assert_eq!(
+        context.token_balance(&whale.token_account),
+        WALLET_DECIMAL_TOKENS - WHALE_STAKE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context
+            .player_state(game_id, &whale)
+            .unwrap()
+            .stake_decimal_tokens,
+        WHALE_STAKE_DECIMAL_TOKENS
+    );
+    let buckets = context.game(game_id).unwrap().state.buckets;
+    assert_eq!(
+        buckets[0].decimal_tokens,
+        ENTRY_FEE_DECIMAL_TOKENS + WHALE_STAKE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        buckets[0].stake_decimal_tokens,
+        ENTRY_FEE_DECIMAL_TOKENS + WHALE_STAKE_DECIMAL_TOKENS
+    );
+    assert_eq!(buckets[1].stake_decimal_tokens, ENTRY_FEE_DECIMAL_TOKENS);
+    assert_eq!(buckets[2].stake_decimal_tokens, WHALE_STAKE_DECIMAL_TOKENS);
+    let events = context.bank.events::<PlayerEntered>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].stake_decimal_tokens, WHALE_STAKE_DECIMAL_TOKENS);
+}
+
+#[test]
+fn leave_game_all_good_shares_are_stake_weighted() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_variable_stake_game(&mut context, &creator);
+    let minnow = context.new_player();
+    let whale = context.new_player();
+    context.enter_game(&minnow, game_id, 2).unwrap();
+    context
+        .enter_game_with_stake(&whale, game_id, 2, WHALE_STAKE_DECIMAL_TOKENS)
+        .unwrap();
+    context.bank.warp_seconds(1_000);
+    let mut game = context.game(game_id).unwrap();
+    game.update(context.bank.now_epoch_seconds());
+    let bucket_decimal_tokens = game.state.buckets[2].decimal_tokens;
+    assert!(bucket_decimal_tokens > 0);
+
+    context.leave_game(&minnow, game_id).unwrap();
+    context.leave_game(&whale, game_id).unwrap();
+
+    // the minnow has a quarter of the bucket's stake, and the whale is left
+    // with the rest of the bucket
+    let minnow_winnings = bucket_decimal_tokens / 4;
+    assert_eq!(
+        context.token_balance(&minnow.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + minnow_winnings
+    );
+    assert_eq!(
+        context.token_balance(&whale.token_account),
+        WALLET_DECIMAL_TOKENS - WHALE_STAKE_DECIMAL_TOKENS + bucket_decimal_tokens
+            - minnow_winnings
+    );
+}
+
+#[test]
+fn update_game_all_good_spills_toward_less_stake_not_fewer_players() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_variable_stake_game(&mut context, &creator);
+    let whale = context.new_player();
+    context
+        .enter_game_with_stake(&whale, game_id, 2, WHALE_STAKE_DECIMAL_TOKENS)
+        .unwrap();
+
+    // the holding bucket spills evenly into all three buckets...
+    context.bank.warp_seconds(10);
+    context.update_game(game_id).unwrap();
+    let spilled_to_each = 2 * 10 * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER / 3;
+    let buckets = context.game(game_id).unwrap().state.buckets;
+    assert_eq!(buckets[1].decimal_tokens, spilled_to_each);
+    assert_eq!(buckets[2].decimal_tokens, spilled_to_each);
+
+    // ...then both buckets have one player, but only the whale's bucket has
+    // more at stake, so it spills half of everything into the creator's bucket
+    context.bank.warp_seconds(10);
+    context.update_game(game_id).unwrap();
+    let buckets = context.game(game_id).unwrap().state.buckets;
+    assert_eq!(
+        buckets[1].decimal_tokens,
+        buckets[2].decimal_tokens + spilled_to_each / 2
+    );
+}
+
+#[test]
+fn leave_game_cancel_on_loss_compares_winnings_to_stake() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_variable_stake_game(&mut context, &creator);
+    let whale = context.new_player();
+    context
+        .enter_game_with_stake(&whale, game_id, 2, WHALE_STAKE_DECIMAL_TOKENS)
+        .unwrap();
+    context.bank.warp_seconds(2_000);
+    context.leave_game(&creator, game_id).unwrap();
+    let creator_winnings = context.token_balance(&creator.token_account)
+        - (WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS);
+
+    // the whale gets back more than the entry fee, but less than they staked
+    let whale_winnings = ENTRY_FEE_DECIMAL_TOKENS + WHALE_STAKE_DECIMAL_TOKENS - creator_winnings;
+    assert!(whale_winnings > ENTRY_FEE_DECIMAL_TOKENS);
+    assert!(whale_winnings < WHALE_STAKE_DECIMAL_TOKENS);
+    assert_error(
+        context.leave_game_with(&whale, game_id, true),
+        EquilibrateError::AbortLeaveOnLoss,
+    );
+
+    context.leave_game(&whale, game_id).unwrap();
+    assert_eq!(
+        context.token_balance(&whale.token_account),
+        WALLET_DECIMAL_TOKENS - WHALE_STAKE_DECIMAL_TOKENS + whale_winnings
+    );
+}
+
+#[test]
+fn enter_game_stake_outside_range_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = new_variable_stake_game(&mut context, &creator);
+    let player = context.new_player();
+
+    for stake_decimal_tokens in [ENTRY_FEE_DECIMAL_TOKENS - 1, MAX_STAKE_DECIMAL_TOKENS + 1] {
+        assert_error(
+            context.enter_game_with_stake(&player, game_id, 2, stake_decimal_tokens),
+            EquilibrateError::InvalidStake,
+        );
+    }
+    assert!(context.player_state(game_id, &player).is_none());
+}
+
+#[test]
+fn enter_game_fixed_stake_game_other_stake_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+
+    assert_error(
+        context.enter_game_with_stake(&player, game_id, 2, WHALE_STAKE_DECIMAL_TOKENS),
+        EquilibrateError::InvalidStake,
+    );
+}
+
+#[test]
+fn new_game_max_stake_below_entry_fee_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        max_stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS - 1,
+        ..default_game_config(context.mint)
+    };
+
+    assert_error(
+        context.new_game_with(&creator, game_id, config),
+        EquilibrateError::InvalidMaxStake,
+    );
+    assert!(context.game(game_id).is_none());
+}
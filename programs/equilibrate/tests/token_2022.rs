@@ -0,0 +1,263 @@
+mod common;
+
+use anchor_lang::solana_program::{pubkey::Pubkey, system_program};
+use common::*;
+use equilibrate::{model::EquilibrateError, state::PoolManager};
+
+const TRANSFER_FEE_BASIS_POINTS: u16 = 100;
+
+fn transfer_fee() -> MintExtension {
+    MintExtension::TransferFee {
+        basis_points: TRANSFER_FEE_BASIS_POINTS,
+        maximum_fee: u64::MAX,
+    }
+}
+
+/// Fee Token-2022 withholds on a transfer of `amount`, rounded up
+fn fee_on(amount: u64) -> u64 {
+    (amount * TRANSFER_FEE_BASIS_POINTS as u64).div_ceil(10_000)
+}
+
+fn game_decimal_tokens(context: &TestContext, game_id: u64) -> u64 {
+    let game = context.game(game_id).unwrap();
+    game.state
+        .buckets
+        .iter()
+        .map(|b| b.decimal_tokens)
+        .sum::<u64>()
+        + game.dust_decimal_tokens
+}
+
+#[test]
+fn token_2022_all_good_pool_is_owned_by_token_2022() {
+    let context = TestContext::with_token_2022(&[]);
+    let pool = token_pool_address(&context.mint);
+    assert_eq!(
+        context.bank.get_account(&pool).unwrap().owner,
+        spl_token_2022::ID
+    );
+
+    let pool = context.token_account(&pool);
+    assert_eq!(pool.mint, context.mint);
+    assert_eq!(pool.owner, pool_manager_address(&context.mint));
+}
+
+#[test]
+fn token_2022_all_good_game_plays_out_like_spl_token() {
+    let mut context = TestContext::with_token_2022(&[]);
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+    context.bank.warp_seconds(10);
+    context.leave_game(&player, game_id).unwrap();
+    context.leave_game(&creator, game_id).unwrap();
+
+    assert!(context.game(game_id).is_none());
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+    assert_eq!(
+        context.token_balance(&creator.token_account)
+            + context.token_balance(&player.token_account),
+        2 * WALLET_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn token_2022_all_good_burn_penalty_is_burned() {
+    let mut context = TestContext::with_token_2022(&[]);
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let mut config = default_game_config(context.mint);
+    config.burn_rate_decimal_tokens_per_move = 1000;
+    context.new_game_with(&creator, game_id, config).unwrap();
+    context.bank.warp_seconds(100);
+    context.move_buckets(&creator, game_id, 2).unwrap();
+    let supply_before = context.mint_supply(&context.mint);
+    context.leave_game(&creator, game_id).unwrap();
+
+    assert_eq!(context.mint_supply(&context.mint), supply_before - 1000);
+}
+
+#[test]
+fn transfer_fee_new_game_holding_bucket_is_credited_with_amount_received() {
+    let mut context = TestContext::with_token_2022(&[transfer_fee()]);
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+
+    let received = ENTRY_FEE_DECIMAL_TOKENS - fee_on(ENTRY_FEE_DECIMAL_TOKENS);
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.state.buckets[0].decimal_tokens, received);
+    assert_eq!(
+        context.token_balance(&creator.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS
+    );
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        received
+    );
+}
+
+#[test]
+fn transfer_fee_enter_game_game_is_credited_with_amount_received() {
+    let mut context = TestContext::with_token_2022(&[transfer_fee()]);
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    context.bank.warp_seconds(10);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+
+    let received = ENTRY_FEE_DECIMAL_TOKENS - fee_on(ENTRY_FEE_DECIMAL_TOKENS);
+    assert_eq!(game_decimal_tokens(&context, game_id), 2 * received);
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        2 * received
+    );
+}
+
+#[test]
+fn transfer_fee_leave_game_player_pays_fee_on_winnings() {
+    let mut context = TestContext::with_token_2022(&[transfer_fee()]);
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+    context.bank.warp_seconds(10);
+    context.leave_game(&player, game_id).unwrap();
+
+    let winnings = 2 * 10 * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER / 3;
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + winnings - fee_on(winnings)
+    );
+    // the pool only ever holds what the game accounts for
+    assert_eq!(
+        context.token_balance(&token_pool_address(&context.mint)),
+        game_decimal_tokens(&context, game_id)
+    );
+}
+
+#[test]
+fn transfer_fee_last_player_leaving_empties_pool() {
+    let mut context = TestContext::with_token_2022(&[transfer_fee()]);
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(1_000);
+    context.leave_game(&creator, game_id).unwrap();
+    context.leave_game(&player, game_id).unwrap();
+
+    assert!(context.game(game_id).is_none());
+    assert_eq!(context.token_balance(&token_pool_address(&context.mint)), 0);
+}
+
+#[test]
+fn create_pool_mint_with_supported_extensions_succeeds() {
+    let mut context = TestContext::new();
+    let mint = context.create_mint_2022(
+        Pubkey::new_unique(),
+        &[transfer_fee(), MintExtension::MintCloseAuthority],
+    );
+    context.create_pool(mint).unwrap();
+}
+
+#[test]
+fn create_pool_mint_with_unsupported_extension_fails() {
+    let mut context = TestContext::new();
+    for extension in [
+        MintExtension::NonTransferable,
+        MintExtension::PermanentDelegate,
+    ] {
+        let mint = context.create_mint_2022(Pubkey::new_unique(), &[extension]);
+        assert_error(
+            context.create_pool(mint),
+            EquilibrateError::UnsupportedMintExtension,
+        );
+        assert!(context
+            .bank
+            .get_account(&token_pool_address(&mint))
+            .is_none());
+    }
+}
+
+#[test]
+fn create_pool_token_program_doesnt_own_mint_fails() {
+    let mut context = TestContext::new();
+    let mint = context.create_mint(Pubkey::new_unique());
+    let payer = context.new_wallet();
+    let mut accounts = context.create_pool_accounts(payer, mint);
+    accounts.token_program = spl_token_2022::ID;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::CreatePool {
+            pool_manager_bump: PoolManager::get_address(mint).1,
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[payer]),
+        EquilibrateError::InvalidTokenProgram,
+    );
+}
+
+#[test]
+fn new_game_token_program_isnt_a_token_program_fails() {
+    let mut context = TestContext::new();
+    let player = context.new_player();
+    let game_id = context.next_game_id();
+    let mut accounts = context.new_game_accounts(&player, game_id, context.mint);
+    accounts.token_program = system_program::ID;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::NewGame {
+            config: default_game_config(context.mint),
+            game_id,
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidTokenProgram,
+    );
+}
+
+#[test]
+fn enter_game_game_mint_is_wrong_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let player = context.new_player();
+    let mut accounts = context.enter_game_accounts(&player, game_id);
+    accounts.game_mint = context.create_mint(Pubkey::new_unique());
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::EnterGame {
+            bucket: 2,
+            stake_decimal_tokens: ENTRY_FEE_DECIMAL_TOKENS,
+            allowlist_proof: vec![],
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidGameMint,
+    );
+}
+
+#[test]
+fn leave_game_token_program_doesnt_own_mint_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.new_game(&creator);
+    let mut accounts = context.leave_game_accounts(&creator, game_id);
+    accounts.token_program = spl_token_2022::ID;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::LeaveGame {
+            cancel_on_loss: false,
+            min_winnings_decimal_tokens: None,
+            max_burn_decimal_tokens: None,
+        },
+    );
+    assert_error(
+        context.bank.process(&ix, &[creator.wallet]),
+        EquilibrateError::InvalidTokenProgram,
+    );
+}
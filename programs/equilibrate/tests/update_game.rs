@@ -0,0 +1,124 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use equilibrate::{model::EquilibrateError, model::GameUpdated};
+
+fn game_decimal_tokens(context: &TestContext, game_id: u64) -> u64 {
+    let game = context.game(game_id).unwrap();
+    game.state
+        .buckets
+        .iter()
+        .map(|b| b.decimal_tokens)
+        .sum::<u64>()
+        + game.dust_decimal_tokens
+}
+
+#[test]
+fn update_game_all_good_balances_are_brought_up_to_date() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    context.bank.warp_seconds(100);
+    let mut expected = context.game(game_id).unwrap();
+    let now_epoch_seconds = context.bank.now_epoch_seconds();
+    expected.update_bucket_balances(now_epoch_seconds as u64);
+    expected.state.last_update_epoch_seconds = now_epoch_seconds;
+    let decimal_tokens = game_decimal_tokens(&context, game_id);
+
+    context.update_game(game_id).unwrap();
+
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game, expected);
+    assert_eq!(game_decimal_tokens(&context, game_id), decimal_tokens);
+    // nobody moved
+    let players: Vec<u16> = game.state.buckets.iter().map(|b| b.players).collect();
+    assert_eq!(players, vec![2, 1, 1, 0]);
+}
+
+#[test]
+fn update_game_all_good_event_is_emitted() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    context.bank.warp_seconds(100);
+    context.update_game(game_id).unwrap();
+
+    let game = context.game(game_id).unwrap();
+    let events = context.bank.events::<GameUpdated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].game_id, game_id);
+    assert_eq!(events[0].buckets, game.state.buckets);
+    assert_eq!(
+        events[0].last_update_epoch_seconds,
+        context.bank.now_epoch_seconds()
+    );
+}
+
+#[test]
+fn update_game_all_good_twice_at_same_time_changes_nothing() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    context.bank.warp_seconds(100);
+    context.update_game(game_id).unwrap();
+    let game = context.game(game_id).unwrap();
+    context.update_game(game_id).unwrap();
+
+    assert_eq!(context.game(game_id).unwrap(), game);
+}
+
+#[test]
+fn update_game_all_good_players_can_still_leave() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    for _ in 0..5 {
+        context.bank.warp_seconds(100);
+        context.update_game(game_id).unwrap();
+    }
+    context.leave_game(&player, game_id).unwrap();
+    context.leave_game(&creator, game_id).unwrap();
+
+    assert!(context.game(game_id).is_none());
+    assert_eq!(
+        context.token_balance(&creator.token_account)
+            + context.token_balance(&player.token_account),
+        2 * WALLET_DECIMAL_TOKENS
+    );
+}
+
+#[test]
+fn update_game_all_good_stops_at_end_time() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let mut config = default_game_config(context.mint);
+    let end_epoch_seconds = context.bank.now_epoch_seconds() + 100;
+    config.end_epoch_seconds = Some(end_epoch_seconds);
+    context.new_game_with(&creator, game_id, config).unwrap();
+    context.bank.warp_seconds(1_000);
+    context.update_game(game_id).unwrap();
+
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.state.last_update_epoch_seconds, end_epoch_seconds);
+}
+
+#[test]
+fn update_game_stale_game_fails() {
+    let mut context = TestContext::new();
+    let (_, _, game_id) = context.two_player_game();
+    let mut game = context.game(game_id).unwrap();
+    game.version -= 1;
+    context.set_game(&game);
+    assert_error(
+        context.update_game(game_id),
+        EquilibrateError::StaleAccountVersion,
+    );
+}
+
+#[test]
+fn update_game_game_doesnt_exist_fails() {
+    let mut context = TestContext::new();
+    let game_id = context.next_game_id();
+    assert_error(
+        context.update_game(game_id),
+        ErrorCode::AccountNotInitialized,
+    );
+}
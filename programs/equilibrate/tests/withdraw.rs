@@ -0,0 +1,186 @@
+mod common;
+
+use common::*;
+use equilibrate::{
+    model::{EquilibrateError, PlayerWithdrew},
+    state::GameConfig,
+};
+
+/// Share of each bucket after `seconds` in a two player game nobody has moved in.
+/// The holding bucket spills into all three buckets since each has fewer players.
+fn two_player_share(seconds: u64) -> u64 {
+    2 * seconds * SPILL_RATE_DECIMAL_TOKENS_PER_SECOND_PER_PLAYER / 3
+}
+
+#[test]
+fn withdraw_all_good_player_takes_fraction_of_share_and_stays() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    context.bank.warp_seconds(10);
+
+    context.withdraw(&player, game_id, 2_500).unwrap();
+
+    let winnings = two_player_share(10) / 4;
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + winnings
+    );
+    let state = context.player_state(game_id, &player).unwrap();
+    assert_eq!(state.bucket, 2);
+    assert_eq!(state.stake_decimal_tokens, 3 * ENTRY_FEE_DECIMAL_TOKENS / 4);
+    let game = context.game(game_id).unwrap();
+    assert_eq!(game.get_player_count(), 2);
+    let buckets = game.state.buckets;
+    assert_eq!(buckets[2].players, 1);
+    // what the rest of the bucket's stake is owed, rounded down
+    assert_eq!(buckets[2].decimal_tokens, 3 * two_player_share(10) / 4);
+    assert_eq!(
+        buckets[2].stake_decimal_tokens,
+        3 * ENTRY_FEE_DECIMAL_TOKENS / 4
+    );
+    assert_eq!(
+        buckets[0].stake_decimal_tokens,
+        ENTRY_FEE_DECIMAL_TOKENS + 3 * ENTRY_FEE_DECIMAL_TOKENS / 4
+    );
+    let events = context.bank.events::<PlayerWithdrew>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].player, player.wallet);
+    assert_eq!(events[0].bucket, 2);
+    assert_eq!(events[0].winnings_decimal_tokens, winnings);
+    assert_eq!(events[0].burned_decimal_tokens, 0);
+    assert_eq!(
+        events[0].stake_decimal_tokens,
+        3 * ENTRY_FEE_DECIMAL_TOKENS / 4
+    );
+}
+
+#[test]
+fn withdraw_all_good_bucket_mates_keep_their_share() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+    let mate = context.new_player();
+    context.enter_game(&mate, game_id, 2).unwrap();
+    context.bank.warp_seconds(1_000);
+    let mut game = context.game(game_id).unwrap();
+    game.update(context.bank.now_epoch_seconds());
+    let bucket_decimal_tokens = game.state.buckets[2].decimal_tokens;
+    assert!(bucket_decimal_tokens > 0);
+
+    context.withdraw(&player, game_id, 5_000).unwrap();
+    context.leave_game(&mate, game_id).unwrap();
+
+    // the player gave up half of their half of the bucket, and their mate
+    // still gets the other half
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + bucket_decimal_tokens / 4
+    );
+    assert_eq!(
+        context.token_balance(&mate.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + bucket_decimal_tokens / 2
+    );
+}
+
+#[test]
+fn withdraw_all_good_burn_penalty_applies_proportionally() {
+    let burn_rate = 1_000_000;
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game_with(GameConfig {
+        burn_rate_decimal_tokens_per_move: burn_rate,
+        ..default_game_config(context.mint)
+    });
+    // moving without time passing keeps every bucket's share the same
+    context.move_buckets(&player, game_id, 3).unwrap();
+    context.bank.warp_seconds(10);
+    let supply_before = context.mint_supply(&context.mint);
+
+    context.withdraw(&player, game_id, 5_000).unwrap();
+
+    assert_eq!(
+        context.token_balance(&player.token_account),
+        WALLET_DECIMAL_TOKENS - ENTRY_FEE_DECIMAL_TOKENS + two_player_share(10) / 2 - burn_rate / 2
+    );
+    assert_eq!(
+        context.mint_supply(&context.mint),
+        supply_before - burn_rate / 2
+    );
+    assert_eq!(
+        context
+            .player_state(game_id, &player)
+            .unwrap()
+            .burn_penalty_decimal_tokens,
+        burn_rate / 2
+    );
+    let events = context.bank.events::<PlayerWithdrew>();
+    assert_eq!(events[0].burned_decimal_tokens, burn_rate / 2);
+}
+
+#[test]
+fn withdraw_invalid_fraction_fails() {
+    let mut context = TestContext::new();
+    let (_, player, game_id) = context.two_player_game();
+
+    for fraction_bps in [0, 10_000] {
+        assert_error(
+            context.withdraw(&player, game_id, fraction_bps),
+            EquilibrateError::InvalidWithdrawalFraction,
+        );
+    }
+}
+
+#[test]
+fn withdraw_less_than_one_decimal_token_of_stake_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        entry_fee_decimal_tokens: 5_000,
+        max_stake_decimal_tokens: 5_000,
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(&creator, game_id, config).unwrap();
+    let player = context.new_player();
+    context.enter_game(&player, game_id, 2).unwrap();
+
+    assert_error(
+        context.withdraw(&player, game_id, 1),
+        EquilibrateError::WithdrawalTooSmall,
+    );
+}
+
+#[test]
+fn withdraw_after_end_fails() {
+    let mut context = TestContext::new();
+    let creator = context.new_player();
+    let game_id = context.next_game_id();
+    let config = GameConfig {
+        end_epoch_seconds: Some(context.bank.now_epoch_seconds() + 100),
+        ..default_game_config(context.mint)
+    };
+    context.new_game_with(&creator, game_id, config).unwrap();
+    context.bank.warp_seconds(100);
+
+    assert_error(
+        context.withdraw(&creator, game_id, 5_000),
+        EquilibrateError::GameHasEnded,
+    );
+}
+
+#[test]
+fn withdraw_winnings_destination_account_not_owned_by_player_fails() {
+    let mut context = TestContext::new();
+    let (creator, player, game_id) = context.two_player_game();
+    let mut accounts = context.withdraw_accounts(&player, game_id);
+    accounts.winnings_destination_account = creator.token_account;
+    let ix = instruction(
+        accounts,
+        equilibrate::instruction::Withdraw {
+            fraction_bps: 5_000,
+        },
+    );
+
+    assert_error(
+        context.bank.process(&ix, &[player.wallet]),
+        EquilibrateError::InvalidWinningsDestinationOwner,
+    );
+}
@@ -0,0 +1,15 @@
+//! Off-chain simulator for equilibrate games.
+//!
+//! A [`Scenario`] describes a game config, an optional starting state, and a
+//! timeline of players entering, moving and leaving. [`simulate`] plays the
+//! timeline through the same `Game` state transitions the program uses, and
+//! reports the bucket balances at every second along with each player's payout.
+
+pub mod output;
+pub use output::*;
+
+pub mod scenario;
+pub use scenario::*;
+
+pub mod simulation;
+pub use simulation::*;
@@ -0,0 +1,50 @@
+use std::{env, fs, io, process};
+
+use equilibrate_simulator::{
+    simulate, write_json, write_payouts_csv, write_trajectory_csv, Scenario,
+};
+
+const USAGE: &str =
+    "usage: equilibrate-sim <scenario.json> [--format json|csv] [--table trajectory|payouts]";
+
+fn main() {
+    if let Err(message) = run(env::args().skip(1).collect()) {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut scenario_path: Option<String> = None;
+    let mut format = String::from("json");
+    let mut table = String::from("trajectory");
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next().ok_or(USAGE)?,
+            "--table" => table = args.next().ok_or(USAGE)?,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ if scenario_path.is_none() => scenario_path = Some(arg),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let scenario_path = scenario_path.ok_or(USAGE)?;
+
+    let scenario_json = fs::read_to_string(&scenario_path)
+        .map_err(|e| format!("failed to read {}: {}", scenario_path, e))?;
+    let scenario: Scenario = serde_json::from_str(&scenario_json)
+        .map_err(|e| format!("failed to parse {}: {}", scenario_path, e))?;
+    let result = simulate(&scenario).map_err(|e| e.to_string())?;
+
+    let stdout = io::stdout().lock();
+    match (format.as_str(), table.as_str()) {
+        ("json", _) => write_json(&result, stdout),
+        ("csv", "trajectory") => write_trajectory_csv(&result, stdout),
+        ("csv", "payouts") => write_payouts_csv(&result, stdout),
+        _ => return Err(USAGE.to_string()),
+    }
+    .map_err(|e| format!("failed to write output: {}", e))
+}
@@ -0,0 +1,56 @@
+use std::io::{self, Write};
+
+use crate::simulation::SimulationResult;
+
+/// Writes the whole result as a single pretty-printed JSON object.
+pub fn write_json<W: Write>(result: &SimulationResult, writer: W) -> io::Result<()> {
+    serde_json::to_writer_pretty(writer, result).map_err(io::Error::from)
+}
+
+/// Writes the trajectory as CSV with one row per bucket per second. Bucket 0
+/// is the holding bucket.
+pub fn write_trajectory_csv<W: Write>(result: &SimulationResult, mut writer: W) -> io::Result<()> {
+    writeln!(
+        writer,
+        "epoch_seconds,bucket,players,decimal_tokens,stake_decimal_tokens"
+    )?;
+    for snapshot in &result.trajectory {
+        for (i, bucket) in snapshot.buckets.iter().enumerate() {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                snapshot.epoch_seconds,
+                i,
+                bucket.players,
+                bucket.decimal_tokens,
+                bucket.stake_decimal_tokens
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the payouts as CSV with one row per player that left the game.
+pub fn write_payouts_csv<W: Write>(result: &SimulationResult, mut writer: W) -> io::Result<()> {
+    writeln!(
+        writer,
+        "player,bucket,entered_epoch_seconds,left_epoch_seconds,moves,deposit_decimal_tokens,withdrawn_decimal_tokens,winnings_decimal_tokens,penalty_decimal_tokens,burned_decimal_tokens"
+    )?;
+    for payout in &result.payouts {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{}",
+            payout.player,
+            payout.bucket,
+            payout.entered_epoch_seconds,
+            payout.left_epoch_seconds,
+            payout.moves,
+            payout.deposit_decimal_tokens,
+            payout.withdrawn_decimal_tokens,
+            payout.winnings_decimal_tokens,
+            payout.penalty_decimal_tokens,
+            payout.burned_decimal_tokens
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,302 @@
+use anchor_lang::prelude::Pubkey;
+use equilibrate::state::{
+    Bucket, BurnSchedule, DustDestination, GameConfig, GameState, PenaltyDestination, SpillCurve,
+    SpillStep, TopUpDestination,
+};
+use serde::{Deserialize, Serialize};
+
+/// Everything needed to run a simulation, usually read from a JSON file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub config: ScenarioConfig,
+    /// Bucket balances to start from. Players already in these buckets are
+    /// anonymous and never leave. When omitted the game starts empty and the
+    /// first `enter` event creates it, like `new_game`.
+    #[serde(default)]
+    pub initial_state: Option<ScenarioState>,
+    /// Events in the order they happen. Events at the same second are applied
+    /// in the order listed.
+    pub events: Vec<ScenarioEvent>,
+    /// Keep recording balances until this time, even if the timeline ends earlier.
+    #[serde(default)]
+    pub end_epoch_seconds: Option<i64>,
+}
+
+/// Serializable counterpart of [`GameConfig`]. The mint doesnt matter to the
+/// simulation, so it is left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioConfig {
+    pub entry_fee_decimal_tokens: u64,
+    pub spill_rate_decimal_tokens_per_second_per_player: u64,
+    pub n_buckets: u8,
+    pub max_players: u16,
+    #[serde(default)]
+    pub burn_rate_decimal_tokens_per_move: u64,
+    /// Game end time, after which players can only leave
+    #[serde(default)]
+    pub end_epoch_seconds: Option<i64>,
+    #[serde(default)]
+    pub spill_curve: ScenarioSpillCurve,
+    /// Share of each entry deposit paid to the creator instead of the holding bucket
+    #[serde(default)]
+    pub creator_fee_bps: u16,
+    /// Most a player can stake when entering. Defaults to the entry fee, so
+    /// everyone stakes the same.
+    #[serde(default)]
+    pub max_stake_decimal_tokens: Option<u64>,
+    #[serde(default)]
+    pub top_up_destination: ScenarioTopUpDestination,
+    /// Least time a player has to wait after moving before they can move again
+    #[serde(default)]
+    pub min_seconds_between_moves: u32,
+    #[serde(default)]
+    pub burn_schedule: ScenarioBurnSchedule,
+    /// Time over which a player's burn penalty halves while they stay put
+    #[serde(default)]
+    pub burn_penalty_half_life_seconds: Option<u32>,
+    #[serde(default)]
+    pub penalty_destination: ScenarioPenaltyDestination,
+    #[serde(default)]
+    pub dust_destination: ScenarioDustDestination,
+}
+
+impl From<&ScenarioConfig> for GameConfig {
+    fn from(config: &ScenarioConfig) -> Self {
+        GameConfig {
+            mint: Pubkey::default(),
+            entry_fee_decimal_tokens: config.entry_fee_decimal_tokens,
+            spill_rate_decimal_tokens_per_second_per_player: config
+                .spill_rate_decimal_tokens_per_second_per_player,
+            n_buckets: config.n_buckets,
+            max_players: config.max_players,
+            burn_rate_decimal_tokens_per_move: config.burn_rate_decimal_tokens_per_move,
+            end_epoch_seconds: config.end_epoch_seconds,
+            spill_curve: (&config.spill_curve).into(),
+            allowlist_root: None,
+            creator_fee_bps: config.creator_fee_bps,
+            max_stake_decimal_tokens: config
+                .max_stake_decimal_tokens
+                .unwrap_or(config.entry_fee_decimal_tokens),
+            top_up_destination: config.top_up_destination.into(),
+            min_seconds_between_moves: config.min_seconds_between_moves,
+            burn_schedule: config.burn_schedule.into(),
+            burn_penalty_half_life_seconds: config.burn_penalty_half_life_seconds,
+            penalty_destination: config.penalty_destination.into(),
+            dust_destination: config.dust_destination.into(),
+        }
+    }
+}
+
+/// Serializable counterpart of [`TopUpDestination`]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioTopUpDestination {
+    #[default]
+    HoldingBucket,
+    PlayerBucket,
+}
+
+impl From<ScenarioTopUpDestination> for TopUpDestination {
+    fn from(destination: ScenarioTopUpDestination) -> Self {
+        match destination {
+            ScenarioTopUpDestination::HoldingBucket => TopUpDestination::HoldingBucket,
+            ScenarioTopUpDestination::PlayerBucket => TopUpDestination::PlayerBucket,
+        }
+    }
+}
+
+/// Serializable counterpart of [`PenaltyDestination`]. Who receives penalties
+/// paid to an account doesnt matter to the simulation, only that they leave
+/// the game.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioPenaltyDestination {
+    #[default]
+    Burn,
+    HoldingBucket,
+    OtherBuckets,
+    Account,
+}
+
+impl From<ScenarioPenaltyDestination> for PenaltyDestination {
+    fn from(destination: ScenarioPenaltyDestination) -> Self {
+        match destination {
+            ScenarioPenaltyDestination::Burn => PenaltyDestination::Burn,
+            ScenarioPenaltyDestination::HoldingBucket => PenaltyDestination::HoldingBucket,
+            ScenarioPenaltyDestination::OtherBuckets => PenaltyDestination::OtherBuckets,
+            ScenarioPenaltyDestination::Account => PenaltyDestination::Account {
+                owner: Pubkey::default(),
+            },
+        }
+    }
+}
+
+/// Serializable counterpart of [`DustDestination`]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioDustDestination {
+    #[default]
+    LastLeaver,
+    Creator,
+    Burn,
+}
+
+impl From<ScenarioDustDestination> for DustDestination {
+    fn from(destination: ScenarioDustDestination) -> Self {
+        match destination {
+            ScenarioDustDestination::LastLeaver => DustDestination::LastLeaver,
+            ScenarioDustDestination::Creator => DustDestination::Creator,
+            ScenarioDustDestination::Burn => DustDestination::Burn,
+        }
+    }
+}
+
+/// Serializable counterpart of [`BurnSchedule`]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ScenarioBurnSchedule {
+    #[default]
+    Flat,
+    Linear {
+        increment_decimal_tokens_per_move: u64,
+    },
+    Geometric {
+        growth_bps: u32,
+    },
+    ShareOfBucket {
+        share_bps: u16,
+    },
+}
+
+impl From<ScenarioBurnSchedule> for BurnSchedule {
+    fn from(schedule: ScenarioBurnSchedule) -> Self {
+        match schedule {
+            ScenarioBurnSchedule::Flat => BurnSchedule::Flat,
+            ScenarioBurnSchedule::Linear {
+                increment_decimal_tokens_per_move,
+            } => BurnSchedule::Linear {
+                increment_decimal_tokens_per_move,
+            },
+            ScenarioBurnSchedule::Geometric { growth_bps } => {
+                BurnSchedule::Geometric { growth_bps }
+            }
+            ScenarioBurnSchedule::ShareOfBucket { share_bps } => {
+                BurnSchedule::ShareOfBucket { share_bps }
+            }
+        }
+    }
+}
+
+/// Serializable counterpart of [`SpillCurve`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ScenarioSpillCurve {
+    #[default]
+    Linear,
+    ProportionalToBalance {
+        decay_bps_per_second: u16,
+    },
+    SqrtOfPlayers,
+    Piecewise {
+        steps: Vec<ScenarioSpillStep>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioSpillStep {
+    pub start_epoch_seconds: i64,
+    pub spill_rate_decimal_tokens_per_second_per_player: u64,
+}
+
+impl From<&ScenarioSpillCurve> for SpillCurve {
+    fn from(curve: &ScenarioSpillCurve) -> Self {
+        match curve {
+            ScenarioSpillCurve::Linear => SpillCurve::Linear,
+            ScenarioSpillCurve::ProportionalToBalance {
+                decay_bps_per_second,
+            } => SpillCurve::ProportionalToBalance {
+                decay_bps_per_second: *decay_bps_per_second,
+            },
+            ScenarioSpillCurve::SqrtOfPlayers => SpillCurve::SqrtOfPlayers,
+            ScenarioSpillCurve::Piecewise { steps } => SpillCurve::Piecewise {
+                steps: steps
+                    .iter()
+                    .map(|s| SpillStep {
+                        start_epoch_seconds: s.start_epoch_seconds,
+                        spill_rate_decimal_tokens_per_second_per_player: s
+                            .spill_rate_decimal_tokens_per_second_per_player,
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Serializable counterpart of [`GameState`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioState {
+    /// Holding bucket first, then the `n_buckets` configured buckets.
+    pub buckets: Vec<ScenarioBucket>,
+    pub last_update_epoch_seconds: i64,
+}
+
+impl ScenarioState {
+    /// Counterpart [`GameState`], where players whose buckets dont say what
+    /// they staked staked `entry_fee_decimal_tokens` each
+    pub fn game_state(&self, entry_fee_decimal_tokens: u64) -> GameState {
+        GameState {
+            buckets: self
+                .buckets
+                .iter()
+                .map(|b| Bucket {
+                    decimal_tokens: b.decimal_tokens,
+                    players: b.players,
+                    stake_decimal_tokens: b
+                        .stake_decimal_tokens
+                        .unwrap_or((b.players as u64) * entry_fee_decimal_tokens),
+                })
+                .collect(),
+            last_update_epoch_seconds: self.last_update_epoch_seconds,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioBucket {
+    pub decimal_tokens: u64,
+    pub players: u16,
+    /// Sum of the stakes of the players in the bucket
+    #[serde(default)]
+    pub stake_decimal_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioEvent {
+    pub epoch_seconds: i64,
+    /// any name that identifies the player within the scenario
+    pub player: String,
+    #[serde(flatten)]
+    pub action: ScenarioAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScenarioAction {
+    Enter {
+        bucket: u8,
+        /// What the player deposits, defaulting to the entry fee. The first
+        /// player creates the game and always stakes the entry fee.
+        #[serde(default)]
+        stake_decimal_tokens: Option<u64>,
+    },
+    AddDeposit {
+        deposit_decimal_tokens: u64,
+    },
+    Withdraw {
+        fraction_bps: u16,
+    },
+    Move {
+        bucket: u8,
+    },
+    Leave,
+}
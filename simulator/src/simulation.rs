@@ -0,0 +1,444 @@
+use std::{collections::BTreeMap, fmt};
+
+use equilibrate::{
+    constants::{GAME_MAX_CREATOR_FEE_BPS, GAME_VERSION, PLAYER_STATE_VERSION},
+    model::EquilibrateError,
+    state::{Bucket, Game, GameConfig, GameState, PenaltyDestination, PlayerState},
+};
+use serde::Serialize;
+
+use crate::scenario::{Scenario, ScenarioAction};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationResult {
+    /// bucket balances at every second of the simulation
+    pub trajectory: Vec<Snapshot>,
+    /// one entry per player that left the game, in the order they left
+    pub payouts: Vec<Payout>,
+    /// total taken from entry deposits for the creator
+    pub creator_fees_decimal_tokens: u64,
+    /// dust paid to the creator or burned when the game ended, rather than
+    /// taken by the last player
+    pub dust_decimal_tokens: u64,
+}
+
+/// Bucket balances as they would be if any player acted at `epoch_seconds`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snapshot {
+    pub epoch_seconds: i64,
+    /// holding bucket first
+    pub buckets: Vec<SnapshotBucket>,
+    /// rounding remainders that no bucket holds
+    pub dust_decimal_tokens: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotBucket {
+    pub decimal_tokens: u64,
+    pub players: u16,
+    pub stake_decimal_tokens: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Payout {
+    pub player: String,
+    /// bucket the player left from
+    pub bucket: u8,
+    pub entered_epoch_seconds: i64,
+    pub left_epoch_seconds: i64,
+    pub moves: u32,
+    /// stake plus anything the player added after entering
+    pub deposit_decimal_tokens: u64,
+    /// taken out by withdrawing before the player left
+    pub withdrawn_decimal_tokens: u64,
+    pub winnings_decimal_tokens: u64,
+    /// burn penalty charged when withdrawing and leaving, wherever the game sent it
+    pub penalty_decimal_tokens: u64,
+    /// part of the penalty that was burned
+    pub burned_decimal_tokens: u64,
+}
+
+#[derive(Debug)]
+pub enum SimulationError {
+    /// the scenario's config or starting state is unusable
+    InvalidScenario(String),
+    /// event `index` happens before the event preceding it
+    EventOutOfOrder { index: usize },
+    /// event `index` would have been rejected by the program
+    Rejected {
+        index: usize,
+        error: EquilibrateError,
+    },
+    /// event `index` is for a player who entered and hasnt left yet
+    AlreadyInGame { index: usize },
+    /// event `index` is for a player who isnt in the game
+    NotInGame { index: usize },
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationError::InvalidScenario(reason) => write!(f, "invalid scenario: {}", reason),
+            SimulationError::EventOutOfOrder { index } => {
+                write!(f, "event {} happens before the previous event", index)
+            }
+            SimulationError::Rejected { index, error } => {
+                write!(f, "event {} rejected: {}", index, error)
+            }
+            SimulationError::AlreadyInGame { index } => {
+                write!(f, "event {}: player is already in the game", index)
+            }
+            SimulationError::NotInGame { index } => {
+                write!(f, "event {}: player is not in the game", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimulationError {}
+
+struct SimulatedPlayer {
+    bucket: u8,
+    stake_decimal_tokens: u64,
+    added_decimal_tokens: u64,
+    /// everything deposited, which withdrawing doesnt reduce
+    deposit_decimal_tokens: u64,
+    withdrawn_decimal_tokens: u64,
+    penalty_decimal_tokens: u64,
+    burned_decimal_tokens: u64,
+    entered_epoch_seconds: i64,
+    moves: u32,
+    last_move_epoch_seconds: i64,
+    burn_penalty_decimal_tokens: u64,
+}
+
+impl SimulatedPlayer {
+    /// The player's state account, so the program's own bookkeeping can be reused
+    fn state(&self) -> PlayerState {
+        PlayerState {
+            version: PLAYER_STATE_VERSION,
+            bucket: self.bucket,
+            burn_penalty_decimal_tokens: self.burn_penalty_decimal_tokens,
+            stake_decimal_tokens: self.stake_decimal_tokens,
+            added_decimal_tokens: self.added_decimal_tokens,
+            last_move_epoch_seconds: self.last_move_epoch_seconds,
+            moves: self.moves,
+            has_session: false,
+        }
+    }
+
+    fn update_from(&mut self, state: &PlayerState) {
+        self.bucket = state.bucket;
+        self.burn_penalty_decimal_tokens = state.burn_penalty_decimal_tokens;
+        self.stake_decimal_tokens = state.stake_decimal_tokens;
+        self.added_decimal_tokens = state.added_decimal_tokens;
+        self.last_move_epoch_seconds = state.last_move_epoch_seconds;
+        self.moves = state.moves;
+    }
+}
+
+/// Plays the scenario's timeline and records balances each second and each player's payout.
+pub fn simulate(scenario: &Scenario) -> Result<SimulationResult, SimulationError> {
+    let config: GameConfig = (&scenario.config).into();
+    let start_epoch_seconds = scenario
+        .initial_state
+        .as_ref()
+        .map(|s| s.last_update_epoch_seconds)
+        .or_else(|| scenario.events.first().map(|e| e.epoch_seconds))
+        .unwrap_or(0);
+    let state = match &scenario.initial_state {
+        Some(state) => state.game_state(config.entry_fee_decimal_tokens),
+        None => GameState {
+            buckets: vec![
+                Bucket {
+                    decimal_tokens: 0,
+                    players: 0,
+                    stake_decimal_tokens: 0,
+                };
+                (config.n_buckets as usize) + 1
+            ],
+            last_update_epoch_seconds: start_epoch_seconds,
+        },
+    };
+    if config.n_buckets < 2 {
+        return Err(SimulationError::InvalidScenario(
+            "game needs at least 2 buckets".to_string(),
+        ));
+    }
+    if config
+        .spill_curve
+        .validate(config.spill_rate_decimal_tokens_per_second_per_player)
+        .is_err()
+    {
+        return Err(SimulationError::InvalidScenario(
+            "spill rate or spill curve is invalid".to_string(),
+        ));
+    }
+    if config.burn_schedule.validate().is_err() || config.burn_penalty_half_life_seconds == Some(0)
+    {
+        return Err(SimulationError::InvalidScenario(
+            "burn schedule or burn penalty half-life is invalid".to_string(),
+        ));
+    }
+    if config.creator_fee_bps > GAME_MAX_CREATOR_FEE_BPS {
+        return Err(SimulationError::InvalidScenario(format!(
+            "creator fee can be at most {} bps",
+            GAME_MAX_CREATOR_FEE_BPS
+        )));
+    }
+    if config.max_stake_decimal_tokens < config.entry_fee_decimal_tokens {
+        return Err(SimulationError::InvalidScenario(
+            "max stake must be at least the entry fee".to_string(),
+        ));
+    }
+    if state.buckets.len() != (config.n_buckets as usize) + 1 {
+        return Err(SimulationError::InvalidScenario(format!(
+            "initial state needs {} buckets, including the holding bucket",
+            (config.n_buckets as usize) + 1
+        )));
+    }
+
+    let mut game = Game {
+        version: GAME_VERSION,
+        id: 0,
+        creator: Default::default(),
+        config,
+        state,
+        creator_fees_decimal_tokens: 0,
+        dust_decimal_tokens: 0,
+    };
+    let mut ended_dust_decimal_tokens = 0;
+    // a game with nobody in it has either not been created yet or has ended
+    let mut started = game.get_player_count() > 0;
+    let mut players: BTreeMap<&str, SimulatedPlayer> = BTreeMap::new();
+    let mut trajectory: Vec<Snapshot> = Vec::new();
+    let mut payouts: Vec<Payout> = Vec::new();
+
+    let end_epoch_seconds = scenario
+        .events
+        .iter()
+        .map(|e| e.epoch_seconds)
+        .chain(scenario.end_epoch_seconds)
+        .max()
+        .unwrap_or(start_epoch_seconds);
+
+    let mut events = scenario.events.iter().enumerate().peekable();
+    let mut previous_epoch_seconds = start_epoch_seconds;
+    for now in start_epoch_seconds..=end_epoch_seconds {
+        while let Some((index, event)) = events.next_if(|(_, e)| e.epoch_seconds <= now) {
+            if event.epoch_seconds < previous_epoch_seconds {
+                return Err(SimulationError::EventOutOfOrder { index });
+            }
+            previous_epoch_seconds = event.epoch_seconds;
+            let reject = |error: EquilibrateError| SimulationError::Rejected { index, error };
+
+            match event.action {
+                ScenarioAction::Enter {
+                    bucket,
+                    stake_decimal_tokens,
+                } => {
+                    if players.contains_key(event.player.as_str()) {
+                        return Err(SimulationError::AlreadyInGame { index });
+                    }
+                    let player_count = game.get_player_count();
+                    if started && player_count == 0 {
+                        return Err(reject(EquilibrateError::GameIsOver));
+                    }
+                    if game.is_past_end(now) {
+                        return Err(reject(EquilibrateError::GameHasEnded));
+                    }
+                    if !started && bucket != 1 {
+                        // new_game always puts the creator in the first bucket
+                        return Err(reject(EquilibrateError::BucketDoesNotExist));
+                    }
+                    validate_bucket(&game, bucket).map_err(reject)?;
+                    let stake_decimal_tokens =
+                        stake_decimal_tokens.unwrap_or(game.config.entry_fee_decimal_tokens);
+                    if !game.config.is_valid_stake(stake_decimal_tokens)
+                        || (!started
+                            && stake_decimal_tokens != game.config.entry_fee_decimal_tokens)
+                    {
+                        return Err(reject(EquilibrateError::InvalidStake));
+                    }
+                    if player_count >= game.config.max_players {
+                        return Err(reject(EquilibrateError::GameAtCapacity));
+                    }
+                    let creator_fee_decimal_tokens =
+                        game.config.creator_fee_decimal_tokens(stake_decimal_tokens);
+                    game.creator_fees_decimal_tokens += creator_fee_decimal_tokens;
+                    game.add_player(
+                        bucket as usize,
+                        stake_decimal_tokens,
+                        stake_decimal_tokens - creator_fee_decimal_tokens,
+                        now,
+                    );
+                    started = true;
+                    players.insert(
+                        &event.player,
+                        SimulatedPlayer {
+                            bucket,
+                            stake_decimal_tokens,
+                            added_decimal_tokens: 0,
+                            deposit_decimal_tokens: stake_decimal_tokens,
+                            withdrawn_decimal_tokens: 0,
+                            penalty_decimal_tokens: 0,
+                            burned_decimal_tokens: 0,
+                            entered_epoch_seconds: now,
+                            moves: 0,
+                            last_move_epoch_seconds: 0,
+                            burn_penalty_decimal_tokens: 0,
+                        },
+                    );
+                }
+                ScenarioAction::AddDeposit {
+                    deposit_decimal_tokens,
+                } => {
+                    let player = players
+                        .get_mut(event.player.as_str())
+                        .ok_or(SimulationError::NotInGame { index })?;
+                    if deposit_decimal_tokens == 0 {
+                        return Err(reject(EquilibrateError::EmptyTopUp));
+                    }
+                    if game.is_past_end(now) {
+                        return Err(reject(EquilibrateError::GameHasEnded));
+                    }
+                    game.top_up(player.bucket as usize, deposit_decimal_tokens, now);
+                    player.added_decimal_tokens += deposit_decimal_tokens;
+                    player.deposit_decimal_tokens += deposit_decimal_tokens;
+                }
+                ScenarioAction::Withdraw { fraction_bps } => {
+                    let player = players
+                        .get_mut(event.player.as_str())
+                        .ok_or(SimulationError::NotInGame { index })?;
+                    if !(1..10_000).contains(&fraction_bps) {
+                        return Err(reject(EquilibrateError::InvalidWithdrawalFraction));
+                    }
+                    let mut state = player.state();
+                    let stake_decimal_tokens = state.stake_fraction(fraction_bps);
+                    if stake_decimal_tokens == 0 {
+                        return Err(reject(EquilibrateError::WithdrawalTooSmall));
+                    }
+                    if game.is_past_end(now) {
+                        return Err(reject(EquilibrateError::GameHasEnded));
+                    }
+                    let share = game.withdraw(player.bucket as usize, stake_decimal_tokens, now);
+                    let (winnings, penalty) =
+                        state.withdraw(&game.config, stake_decimal_tokens, share, now);
+                    let (winnings, burned) =
+                        route_penalty(&mut game, player.bucket, winnings, penalty);
+                    player.update_from(&state);
+                    player.withdrawn_decimal_tokens += winnings;
+                    player.penalty_decimal_tokens += share - winnings;
+                    player.burned_decimal_tokens += burned;
+                }
+                ScenarioAction::Move { bucket } => {
+                    let player = players
+                        .get_mut(event.player.as_str())
+                        .ok_or(SimulationError::NotInGame { index })?;
+                    if player.bucket == bucket {
+                        return Err(reject(EquilibrateError::AlreadyInBucket));
+                    }
+                    if game.is_past_end(now) {
+                        return Err(reject(EquilibrateError::GameHasEnded));
+                    }
+                    validate_bucket(&game, bucket).map_err(reject)?;
+                    if !game
+                        .config
+                        .is_move_cooldown_over(player.last_move_epoch_seconds, now)
+                    {
+                        return Err(reject(EquilibrateError::MoveCooldownActive));
+                    }
+                    let share = game.move_player(
+                        player.bucket as usize,
+                        bucket as usize,
+                        player.stake_decimal_tokens,
+                        now,
+                    );
+                    let mut state = player.state();
+                    state.record_move(&game.config, bucket, share, now);
+                    player.update_from(&state);
+                }
+                ScenarioAction::Leave => {
+                    let player = players
+                        .remove(event.player.as_str())
+                        .ok_or(SimulationError::NotInGame { index })?;
+                    let share = game.remove_player(
+                        player.bucket as usize,
+                        player.stake_decimal_tokens,
+                        now,
+                    );
+                    let (winnings, penalty) =
+                        player.state().apply_burn_penalty(&game.config, share, now);
+                    let (winnings, burned) =
+                        route_penalty(&mut game, player.bucket, winnings, penalty);
+                    if game.get_player_count() == 0 {
+                        ended_dust_decimal_tokens += game.take_dust();
+                    }
+                    payouts.push(Payout {
+                        player: event.player.clone(),
+                        bucket: player.bucket,
+                        entered_epoch_seconds: player.entered_epoch_seconds,
+                        left_epoch_seconds: now,
+                        moves: player.moves,
+                        deposit_decimal_tokens: player.deposit_decimal_tokens,
+                        withdrawn_decimal_tokens: player.withdrawn_decimal_tokens,
+                        winnings_decimal_tokens: winnings,
+                        penalty_decimal_tokens: player.penalty_decimal_tokens + share - winnings,
+                        burned_decimal_tokens: player.burned_decimal_tokens + burned,
+                    });
+                }
+            }
+        }
+
+        trajectory.push(snapshot(&game, now));
+    }
+
+    Ok(SimulationResult {
+        trajectory,
+        payouts,
+        creator_fees_decimal_tokens: game.creator_fees_decimal_tokens,
+        dust_decimal_tokens: ended_dust_decimal_tokens,
+    })
+}
+
+/// Sends a player's penalty wherever the game sends penalties, like the program
+/// does. Returns their winnings and the part of the penalty that was burned.
+fn route_penalty(game: &mut Game, bucket: u8, winnings: u64, penalty: u64) -> (u64, u64) {
+    let (winnings, out_of_pool) = game.route_penalty(bucket as usize, winnings, penalty);
+    match game.config.penalty_destination {
+        PenaltyDestination::Burn => (winnings, out_of_pool),
+        _ => (winnings, 0),
+    }
+}
+
+fn validate_bucket(game: &Game, bucket: u8) -> Result<(), EquilibrateError> {
+    if (bucket as usize) >= game.state.buckets.len() {
+        return Err(EquilibrateError::BucketDoesNotExist);
+    }
+    if bucket == 0 {
+        return Err(EquilibrateError::CannotEnterHoldingBucket);
+    }
+    Ok(())
+}
+
+/// Balances as they would be settled by an action at `now`, without changing the game.
+fn snapshot(game: &Game, now: i64) -> Snapshot {
+    let mut settled = game.clone();
+    if settled.get_player_count() > 0 {
+        settled.update_bucket_balances(now.try_into().unwrap());
+    }
+    Snapshot {
+        epoch_seconds: now,
+        buckets: settled
+            .state
+            .buckets
+            .iter()
+            .map(|b| SnapshotBucket {
+                decimal_tokens: b.decimal_tokens,
+                players: b.players,
+                stake_decimal_tokens: b.stake_decimal_tokens,
+            })
+            .collect(),
+        dust_decimal_tokens: settled.dust_decimal_tokens,
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use equilibrate::{
+    constants::{GAME_VERSION, PLAYER_STATE_VERSION},
+    state::{Bucket, Game, GameConfig, GameState, PlayerState},
+};
+use equilibrate_simulator::{simulate, Scenario, ScenarioAction, SimulationResult};
+use serde_json::json;
+
+const ENTRY_FEE_DECIMAL_TOKENS: u64 = 1_000_000;
+
+/// Three players enter, two of them move, and everyone listed in `leavers`
+/// leaves, in that order
+fn scenario(config: serde_json::Value, leavers: &[&str]) -> Scenario {
+    let mut events = vec![
+        json!({ "epoch_seconds": 1000, "player": "alice", "action": "enter", "bucket": 1 }),
+        json!({ "epoch_seconds": 1010, "player": "bob", "action": "enter", "bucket": 2 }),
+        json!({ "epoch_seconds": 1017, "player": "carol", "action": "enter", "bucket": 3 }),
+        json!({ "epoch_seconds": 1031, "player": "bob", "action": "move", "bucket": 3 }),
+        json!({ "epoch_seconds": 1050, "player": "carol", "action": "move", "bucket": 1 }),
+    ];
+    for (i, player) in leavers.iter().enumerate() {
+        events.push(json!({
+            "epoch_seconds": 1100 + 100 * i as i64,
+            "player": player,
+            "action": "leave",
+        }));
+    }
+    serde_json::from_value(json!({
+        "config": config,
+        "events": events,
+        "end_epoch_seconds": 1400,
+    }))
+    .unwrap()
+}
+
+/// Plays the scenario's timeline straight through the `Game` and `PlayerState`
+/// methods the program's instructions call, returning each leaver's winnings
+/// in the order they left
+fn on_chain_winnings(scenario: &Scenario) -> Vec<(String, u64)> {
+    let config: GameConfig = (&scenario.config).into();
+    let mut game = Game {
+        version: GAME_VERSION,
+        id: 0,
+        creator: Default::default(),
+        state: GameState {
+            buckets: vec![
+                Bucket {
+                    decimal_tokens: 0,
+                    players: 0,
+                    stake_decimal_tokens: 0,
+                };
+                (config.n_buckets as usize) + 1
+            ],
+            last_update_epoch_seconds: 0,
+        },
+        config,
+        creator_fees_decimal_tokens: 0,
+        dust_decimal_tokens: 0,
+    };
+    let mut players: BTreeMap<&str, PlayerState> = BTreeMap::new();
+    let mut winnings = Vec::new();
+    for event in &scenario.events {
+        let now = event.epoch_seconds;
+        match event.action {
+            ScenarioAction::Enter { bucket, .. } => {
+                let stake = game.config.entry_fee_decimal_tokens;
+                let creator_fee = game.config.creator_fee_decimal_tokens(stake);
+                game.creator_fees_decimal_tokens += creator_fee;
+                game.add_player(bucket as usize, stake, stake - creator_fee, now);
+                players.insert(
+                    &event.player,
+                    PlayerState {
+                        version: PLAYER_STATE_VERSION,
+                        bucket,
+                        burn_penalty_decimal_tokens: 0,
+                        stake_decimal_tokens: stake,
+                        added_decimal_tokens: 0,
+                        last_move_epoch_seconds: 0,
+                        moves: 0,
+                        has_session: false,
+                    },
+                );
+            }
+            ScenarioAction::Move { bucket } => {
+                let player = players.get_mut(event.player.as_str()).unwrap();
+                let share = game.move_player(
+                    player.bucket as usize,
+                    bucket as usize,
+                    player.stake_decimal_tokens,
+                    now,
+                );
+                player.record_move(&game.config, bucket, share, now);
+            }
+            ScenarioAction::Leave => {
+                let player = players.remove(event.player.as_str()).unwrap();
+                let share =
+                    game.remove_player(player.bucket as usize, player.stake_decimal_tokens, now);
+                let (player_winnings, penalty) =
+                    player.apply_burn_penalty(&game.config, share, now);
+                let (player_winnings, _) =
+                    game.route_penalty(player.bucket as usize, player_winnings, penalty);
+                winnings.push((event.player.clone(), player_winnings));
+            }
+            _ => unreachable!("the timeline only enters, moves and leaves"),
+        }
+    }
+    winnings
+}
+
+fn simulated_winnings(result: &SimulationResult) -> Vec<(String, u64)> {
+    result
+        .payouts
+        .iter()
+        .map(|p| (p.player.clone(), p.winnings_decimal_tokens))
+        .collect()
+}
+
+/// Everything deposited is still in the buckets or the game's dust, or was
+/// paid out, charged as a penalty, taken as a creator fee or swept up as dust
+/// when the game ended
+fn assert_tokens_conserved(scenario: &Scenario, result: &SimulationResult) {
+    let entries = scenario
+        .events
+        .iter()
+        .filter(|e| matches!(e.action, ScenarioAction::Enter { .. }))
+        .count() as u64;
+    let deposits = entries * scenario.config.entry_fee_decimal_tokens;
+    let last = result.trajectory.last().unwrap();
+    let buckets: u64 = last.buckets.iter().map(|b| b.decimal_tokens).sum();
+    let payouts: u64 = result
+        .payouts
+        .iter()
+        .map(|p| p.winnings_decimal_tokens + p.penalty_decimal_tokens)
+        .sum();
+    assert_eq!(
+        buckets
+            + last.dust_decimal_tokens
+            + result.dust_decimal_tokens
+            + payouts
+            + result.creator_fees_decimal_tokens,
+        deposits
+    );
+}
+
+#[test]
+fn simulate_all_good_payouts_match_game_and_tokens_are_conserved() {
+    let scenario = scenario(
+        json!({
+            "entry_fee_decimal_tokens": ENTRY_FEE_DECIMAL_TOKENS,
+            "spill_rate_decimal_tokens_per_second_per_player": 1001,
+            "n_buckets": 3,
+            "max_players": 10,
+        }),
+        &["bob", "carol"],
+    );
+
+    let result = simulate(&scenario).unwrap();
+
+    assert_eq!(simulated_winnings(&result), on_chain_winnings(&scenario));
+    assert_tokens_conserved(&scenario, &result);
+    // alice is still in the game, so nothing was swept up as dust yet
+    assert_eq!(result.dust_decimal_tokens, 0);
+}
+
+#[test]
+fn simulate_all_good_game_ending_with_fees_and_burns_conserves_tokens() {
+    let scenario = scenario(
+        json!({
+            "entry_fee_decimal_tokens": ENTRY_FEE_DECIMAL_TOKENS,
+            "spill_rate_decimal_tokens_per_second_per_player": 1001,
+            "n_buckets": 3,
+            "max_players": 10,
+            // penalties are burned, so they leave the game
+            "burn_rate_decimal_tokens_per_move": 1000,
+            "creator_fee_bps": 250,
+            "dust_destination": "creator",
+        }),
+        &["bob", "carol", "alice"],
+    );
+
+    let result = simulate(&scenario).unwrap();
+
+    assert_eq!(simulated_winnings(&result), on_chain_winnings(&scenario));
+    assert_tokens_conserved(&scenario, &result);
+    assert!(result.creator_fees_decimal_tokens > 0);
+    assert!(result.payouts.iter().any(|p| p.burned_decimal_tokens > 0));
+    let last = result.trajectory.last().unwrap();
+    assert!(last.buckets.iter().all(|b| b.decimal_tokens == 0));
+}
@@ -21,16 +21,17 @@ import {
     MINT_DECIMALS,
     withoutDecimals,
 } from "./helpers/token";
-import { Game, GameState, PlayerState } from "./helpers/types";
+import { Game, GameState, PlayerEnteredEvent, PlayerState } from "./helpers/types";
 import { Keypair, PublicKey, Connection } from "@solana/web3.js";
 import {
     GAME_SEED,
     getGameAddress,
     getPlayerStateAddress,
+    getProgramConfigAddress,
     PLAYER_SEED,
 } from "./helpers/address";
 import { assert } from "chai";
-import { assertAsyncThrows, repeat } from "./helpers/test";
+import { assertAsyncThrows, collectEvents, repeat } from "./helpers/test";
 import {
     NewGameContext,
     NewGameEtcContext,
@@ -191,6 +192,33 @@ describe("EnterGame Instruction Tests", () => {
         );
     });
 
+    it("enter game > all good > chosen stake is recorded", async () => {
+        const entryFee: number = 1 * Math.pow(10, MINT_DECIMALS);
+        const stake: number = 3 * entryFee;
+        const { newGame: newGameContext, playerStateAddress } = await setUpEnterGameEtc(program, {
+            otherPlayers: 1,
+            playerBucketIndex: 2,
+            playerStakeDecimalTokens: new anchor.BN(stake),
+            playerStartingTokens: 4,
+            newGame: {
+                gameConfig: {
+                    nBuckets: 2,
+                    entryFeeDecimalTokens: new anchor.BN(entryFee),
+                    maxStakeDecimalTokens: new anchor.BN(5 * entryFee),
+                },
+            },
+        });
+
+        const playerState: PlayerState = await getPlayerState(playerStateAddress, program);
+        const gameState: GameState = (
+            await getGame(newGameContext.gameAddress, program)
+        ).state;
+
+        assert.strictEqual(playerState.stakeDecimalTokens.toNumber(), stake);
+        assert.strictEqual(gameState.buckets[0].stakeDecimalTokens.toNumber(), entryFee + stake);
+        assert.strictEqual(gameState.buckets[2].stakeDecimalTokens.toNumber(), stake);
+    });
+
     it("enter game > all good > prize pool balance remains consistent", repeat(10, async () => {
         const nOtherPlayers: number = Math.ceil(Math.random() * 10) + 1;
         const context = await setUpEnterGameEtc(program, {
@@ -424,7 +452,7 @@ describe("EnterGame Instruction Tests", () => {
                     playerWallet: wallet,
                     playerTokenAccount: tokenAccount,
                 }),
-            "ConstraintTokenMint"
+            "InvalidTokenSourceMint"
         );
     });
 
@@ -446,7 +474,7 @@ describe("EnterGame Instruction Tests", () => {
                 setUpEnterGameEtc(program, {
                     tokenPoolAddress: wrongTokenPool,
                 }),
-            "ConstraintTokenMint"
+            "InvalidTokenPool"
         );
     });
 
@@ -503,6 +531,28 @@ describe("EnterGame Instruction Tests", () => {
             })
         );
     });
+
+    it("enter game > all good > PlayerEntered event is emitted", async () => {
+        const newGameContext: NewGameEtcContext = await setUpNewGameEtc(program);
+        let context: EnterGameContext;
+        const events: PlayerEnteredEvent[] = await collectEvents(
+            program,
+            "PlayerEntered",
+            async () => {
+                context = await setUpEnterGame(program, newGameContext.createPool, newGameContext);
+            }
+        );
+
+        assert.strictEqual(events.length, 1);
+        const game: Game = await getGame(newGameContext.gameAddress, program);
+        assert.strictEqual(events[0].gameId.toNumber(), newGameContext.gameId);
+        assert.strictEqual(events[0].player.toBase58(), context.playerWallet.publicKey.toBase58());
+        assert.strictEqual(events[0].bucket, context.playerBucketIndex);
+        assert.deepEqual(
+            events[0].buckets.map(b => b.decimalTokens.toNumber()),
+            game.state.buckets.map(b => b.decimalTokens.toNumber())
+        );
+    });
 });
 
 export interface EnterGameEtcSetupArgs extends EnterGameSetupArgs {
@@ -513,6 +563,7 @@ export interface EnterGameEtcSetupArgs extends EnterGameSetupArgs {
 export interface EnterGameSetupArgs {
   otherPlayers?: number;
   playerBucketIndex?: number;
+  playerStakeDecimalTokens?: anchor.BN;
   playerStartingSol?: number;
   playerStartingTokens?: number;
   playerWallet?: Keypair;
@@ -653,13 +704,22 @@ export async function setUpEnterGame(
 
         try {
             await program.methods
-                .enterGame(bucketIndex, createPoolContext.poolManagerAddress)
+                .enterGame(
+                    bucketIndex,
+                    customSetup?.playerStakeDecimalTokens ??
+                        newGameContext.gameConfig.entryFeeDecimalTokens,
+                    []
+                )
                 .accountsStrict({
                     game: customSetup?.gameAddress ?? newGameContext.gameAddress,
                     player: playerStateAddress,
+                    programConfig: await getProgramConfigAddress(program.programId),
                     programFeeDestination:
             customSetup?.programFeeDestination ?? PROGRAM_FEE_DESTINATION,
+                    gameMint: createPoolContext.mint.publicKey,
                     depositSourceAccount: playerTokenAccount,
+                    creatorFeeDestination: newGameContext.playerTokenAccount,
+                    poolManager: createPoolContext.poolManagerAddress,
                     tokenPool:
             customSetup?.tokenPoolAddress ?? createPoolContext.tokenPoolAddress,
                     payer: player.publicKey,
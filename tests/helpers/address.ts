@@ -6,6 +6,7 @@ export const GAME_SEED: string = "equilibrate-game";
 export const PLAYER_SEED: string = "equilibrate-player";
 export const POOL_SEED: string = "equilibrate-pool";
 export const POOL_MANAGER_SEED: string = "equilibrate-pool-manager";
+export const PROGRAM_CONFIG_SEED: string = "equilibrate-program-config";
 
 export async function getGameAddress(
     gameId: number,
@@ -66,6 +67,30 @@ export async function getPoolManagerAddress(
 }
 
 
+export async function getProgramConfigAddress(
+    programId: PublicKey
+): Promise<PublicKey> {
+    return (await PublicKey.findProgramAddress(
+        [
+            anchor.utils.bytes.utf8.encode(PROGRAM_CONFIG_SEED),
+        ],
+        programId
+    ))[0];
+}
+
+
+export async function getProgramDataAddress(
+    programId: PublicKey
+): Promise<PublicKey> {
+    return (await PublicKey.findProgramAddress(
+        [
+            programId.toBuffer(),
+        ],
+        anchor.web3.BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    ))[0];
+}
+
+
 export async function getAssociatedTokenAddress(mint: PublicKey, owner: PublicKey): Promise<PublicKey> {
     return await spl.getAssociatedTokenAddress(mint, owner);
 }
@@ -1,4 +1,4 @@
-import { Game, GameConfig, PlayerState } from "./types";
+import { Game, GameConfig, PlayerState, ProgramConfig, ProgramSettings } from "./types";
 import { PublicKey } from "@solana/web3.js";
 import * as anchor from "@project-serum/anchor";
 import { MINT_DECIMALS } from "./token";
@@ -7,6 +7,18 @@ import { Equilibrate } from "../../target/types/equilibrate";
 export const PROGRAM_FEE_DESTINATION: PublicKey = new PublicKey("EQui1fSdC2HetpETDAoaLaPYvYR7xVuXmLyUiiEvfA2h");
 export const PROGRAM_FEE_LAMPORTS: number = 25000000;
 export const MAX_GAME_BUCKETS: number = 64;
+export const MAX_GAME_PLAYERS: number = 10000;
+export const MAX_GAME_CREATOR_FEE_BPS: number = 5000;
+
+// settings the program config is initialized with before any tests run
+export const DEFAULT_PROGRAM_SETTINGS: ProgramSettings = {
+    programFeeDestination: PROGRAM_FEE_DESTINATION,
+    programFeeLamports: new anchor.BN(PROGRAM_FEE_LAMPORTS),
+    gameMaxBuckets: MAX_GAME_BUCKETS,
+    gameMaxPlayers: MAX_GAME_PLAYERS,
+    gameMaxCreatorFeeBps: MAX_GAME_CREATOR_FEE_BPS,
+    referralFeeBps: 0,
+};
 
 export function generateGameId(): number {
     // All we need is to ensure that each game is unique,
@@ -35,7 +47,18 @@ export function generateGameConfig(mint: PublicKey): GameConfig {
         spillRateDecimalTokensPerSecondPerPlayer: new anchor.BN(spillRate),
         nBuckets: nBuckets,
         maxPlayers: maxPlayers,
-        burnRateDecimalTokensPerMove: new anchor.BN(0)
+        burnRateDecimalTokensPerMove: new anchor.BN(0),
+        endEpochSeconds: null,
+        spillCurve: { linear: {} },
+        allowlistRoot: null,
+        creatorFeeBps: 0,
+        maxStakeDecimalTokens: new anchor.BN(entryFeeDecimalTokens),
+        topUpDestination: { holdingBucket: {} },
+        minSecondsBetweenMoves: 0,
+        burnSchedule: { flat: {} },
+        burnPenaltyHalfLifeSeconds: null,
+        penaltyDestination: { burn: {} },
+        dustDestination: { lastLeaver: {} }
     };
 }
 
@@ -44,6 +67,14 @@ export async function getGame(gameAddress: PublicKey, program: anchor.Program<Eq
     return (await program.account.game.fetch(gameAddress)) as Game;
 }
 
+export async function getProgramConfig(
+    programConfigAddress: PublicKey,
+    program: anchor.Program<Equilibrate>
+): Promise<ProgramConfig> {
+    return await program.account.programConfig.fetch(programConfigAddress);
+}
+
+
 export async function getPlayerState(
     playerStateAddress: PublicKey,
     program: anchor.Program<Equilibrate>
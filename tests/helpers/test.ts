@@ -1,3 +1,4 @@
+import * as anchor from "@project-serum/anchor";
 import { AnchorError } from "@project-serum/anchor";
 import { assert, AssertionError } from "chai";
 
@@ -47,3 +48,30 @@ export function repeat<T>(
 export async function sleep(durationMs: number): Promise<void> {
     return await new Promise((r) => setTimeout(r, durationMs));
 }
+
+
+/**
+ * Collects all program events with the given name that are emitted while `method` runs.
+ *
+ * @param program program emitting the events
+ * @param eventName name of the event as it appears in the IDL, e.g. `"PlayerEntered"`
+ * @param method actions which should cause the events
+ * @param waitMs time to wait for event logs to arrive after `method` finishes
+ * @returns events in the order they were received
+ */
+export async function collectEvents<E, P extends anchor.Idl>(
+    program: anchor.Program<P>,
+    eventName: string,
+    method: () => Promise<unknown>,
+    waitMs: number = 1000
+): Promise<E[]> {
+    const events: E[] = [];
+    const listenerId: number = program.addEventListener(eventName, (event) => events.push(event as E));
+    try {
+        await method();
+        await sleep(waitMs);
+    } finally {
+        await program.removeEventListener(listenerId);
+    }
+    return events;
+}
@@ -7,6 +7,8 @@ export interface Game {
     creator: PublicKey;
     config: GameConfig;
     state: GameState;
+    creatorFeesDecimalTokens: anchor.BN;
+    dustDecimalTokens: anchor.BN;
 }
 
 export interface GameConfig {
@@ -16,6 +18,49 @@ export interface GameConfig {
     nBuckets: number;
     maxPlayers: number;
     burnRateDecimalTokensPerMove: anchor.BN;
+    endEpochSeconds: anchor.BN | null;
+    spillCurve: SpillCurve;
+    allowlistRoot: number[] | null;
+    creatorFeeBps: number;
+    maxStakeDecimalTokens: anchor.BN;
+    topUpDestination: TopUpDestination;
+    minSecondsBetweenMoves: number;
+    burnSchedule: BurnSchedule;
+    burnPenaltyHalfLifeSeconds: number | null;
+    penaltyDestination: PenaltyDestination;
+    dustDestination: DustDestination;
+}
+
+export type DustDestination =
+    | { lastLeaver: Record<string, never> }
+    | { creator: Record<string, never> }
+    | { burn: Record<string, never> };
+
+export type PenaltyDestination =
+    | { burn: Record<string, never> }
+    | { holdingBucket: Record<string, never> }
+    | { otherBuckets: Record<string, never> }
+    | { account: { owner: PublicKey } };
+
+export type BurnSchedule =
+    | { flat: Record<string, never> }
+    | { linear: { incrementDecimalTokensPerMove: anchor.BN } }
+    | { geometric: { growthBps: number } }
+    | { shareOfBucket: { shareBps: number } };
+
+export type TopUpDestination =
+    | { holdingBucket: Record<string, never> }
+    | { playerBucket: Record<string, never> };
+
+export type SpillCurve =
+    | { linear: Record<string, never> }
+    | { proportionalToBalance: { decayBpsPerSecond: number } }
+    | { sqrtOfPlayers: Record<string, never> }
+    | { piecewise: { steps: SpillStep[] } };
+
+export interface SpillStep {
+    startEpochSeconds: anchor.BN;
+    spillRateDecimalTokensPerSecondPerPlayer: anchor.BN;
 }
 
 export interface GameState {
@@ -26,6 +71,7 @@ export interface GameState {
 export interface Bucket {
     decimalTokens: anchor.BN;
     players: number;
+    stakeDecimalTokens: anchor.BN;
 }
 
 
@@ -33,10 +79,90 @@ export interface PlayerState {
     version: number;
     bucket: number;
     burnPenaltyDecimalTokens: anchor.BN;
+    stakeDecimalTokens: anchor.BN;
+    addedDecimalTokens: anchor.BN;
+    lastMoveEpochSeconds: anchor.BN;
+    moves: number;
+    hasSession: boolean;
 }
 
 
 export interface PoolManager {
     version: number;
     bump: number;
+    liabilitiesDecimalTokens: anchor.BN;
+}
+
+
+export interface GameCreatedEvent {
+    gameId: anchor.BN;
+    player: PublicKey;
+    bucket: number;
+    creatorFeeDecimalTokens: anchor.BN;
+    buckets: Bucket[];
+}
+
+
+export interface PlayerEnteredEvent {
+    gameId: anchor.BN;
+    player: PublicKey;
+    bucket: number;
+    stakeDecimalTokens: anchor.BN;
+    creatorFeeDecimalTokens: anchor.BN;
+    buckets: Bucket[];
+}
+
+
+export interface PlayerReferredEvent {
+    gameId: anchor.BN;
+    player: PublicKey;
+    referrer: PublicKey;
+    rewardLamports: anchor.BN;
+    referrals: anchor.BN;
+}
+
+
+export interface PlayerMovedEvent {
+    gameId: anchor.BN;
+    player: PublicKey;
+    fromBucket: number;
+    toBucket: number;
+    burnPenaltyDecimalTokens: anchor.BN;
+    buckets: Bucket[];
+}
+
+
+export interface PlayerLeftEvent {
+    gameId: anchor.BN;
+    player: PublicKey;
+    bucket: number;
+    winningsDecimalTokens: anchor.BN;
+    penaltyDecimalTokens: anchor.BN;
+    burnedDecimalTokens: anchor.BN;
+    buckets: Bucket[];
+}
+
+
+export interface GameEndedEvent {
+    gameId: anchor.BN;
+    player: PublicKey;
+    bucket: number;
+    buckets: Bucket[];
+}
+
+
+export interface ProgramConfig {
+    version: number;
+    admin: PublicKey;
+    settings: ProgramSettings;
+}
+
+
+export interface ProgramSettings {
+    programFeeDestination: PublicKey;
+    programFeeLamports: anchor.BN;
+    gameMaxBuckets: number;
+    gameMaxPlayers: number;
+    gameMaxCreatorFeeBps: number;
+    referralFeeBps: number;
 }
@@ -11,7 +11,7 @@ import {
     makeAssociatedTokenAccountWithPayer,
     MINT_DECIMALS,
 } from "./helpers/token";
-import { GameState } from "./helpers/types";
+import { GameEndedEvent, GameState, PlayerLeftEvent } from "./helpers/types";
 import { Keypair, PublicKey, Connection } from "@solana/web3.js";
 import {
     GAME_SEED,
@@ -36,7 +36,7 @@ import {
     setUpEnterGameEtc,
     EnterGameEtcContext,
 } from "./enterGame";
-import { assertAsyncThrows, repeat, sleep } from "./helpers/test";
+import { assertAsyncThrows, collectEvents, repeat, sleep } from "./helpers/test";
 import {
     CreatePoolContext,
     CreatePoolSetupArgs,
@@ -281,7 +281,7 @@ describe("LeaveGame Instruction Tests", () => {
                 setUpLeaveGameEtc(program, {
                     playerTokenAccount: wrongTokenAccount,
                 }),
-            "ConstraintTokenOwner"
+            "InvalidWinningsDestinationMint"
         );
     });
 
@@ -308,7 +308,7 @@ describe("LeaveGame Instruction Tests", () => {
                 setUpLeaveGame(program, context, newGameContext, enterGameContext, {
                     tokenPoolAddress: context2.tokenPoolAddress,
                 }),
-            "ConstraintTokenMint"
+            "InvalidTokenPool"
         );
     });
 
@@ -1048,6 +1048,72 @@ describe("LeaveGame Instruction Tests", () => {
             newGameContext.gameConfig.entryFeeDecimalTokens.toNumber()
         );
     });
+
+    it("leave game > winnings below minimum > fails", async () => {
+        const enterEtcContext: EnterGameEtcContext = await setUpEnterGameEtc(program);
+
+        await assertAsyncThrows(
+            () =>
+                setUpLeaveGame(
+                    program,
+                    enterEtcContext.createPool,
+                    enterEtcContext.newGame,
+                    enterEtcContext,
+                    {
+                        minWinningsDecimalTokens: new anchor.BN(Number.MAX_SAFE_INTEGER)
+                    }
+                ),
+            "WinningsBelowMinimum"
+        );
+    });
+
+    it("leave game > all good > PlayerLeft and GameEnded events are emitted", async () => {
+        const enterEtcContext: EnterGameEtcContext = await setUpEnterGameEtc(program);
+        const leftEvents: PlayerLeftEvent[] = await collectEvents(
+            program,
+            "PlayerLeft",
+            () => setUpLeaveGame(
+                program,
+                enterEtcContext.createPool,
+                enterEtcContext.newGame,
+                enterEtcContext
+            )
+        );
+
+        assert.strictEqual(leftEvents.length, 1);
+        assert.strictEqual(leftEvents[0].gameId.toNumber(), enterEtcContext.newGame.gameId);
+        assert.strictEqual(
+            leftEvents[0].player.toBase58(),
+            enterEtcContext.playerWallet.publicKey.toBase58()
+        );
+        assert.strictEqual(leftEvents[0].bucket, enterEtcContext.playerBucketIndex);
+        const gameState: GameState = (await getGame(enterEtcContext.newGame.gameAddress, program)).state;
+        assert.deepEqual(
+            leftEvents[0].buckets.map(b => b.decimalTokens.toNumber()),
+            gameState.buckets.map(b => b.decimalTokens.toNumber())
+        );
+
+        // the game creator is the last one in the game
+        const endedEvents: GameEndedEvent[] = await collectEvents(
+            program,
+            "GameEnded",
+            () => setUpLeaveGame(
+                program,
+                enterEtcContext.createPool,
+                enterEtcContext.newGame,
+                enterEtcContext,
+                { playerWallet: enterEtcContext.newGame.playerWallet }
+            )
+        );
+
+        assert.strictEqual(endedEvents.length, 1);
+        assert.strictEqual(endedEvents[0].gameId.toNumber(), enterEtcContext.newGame.gameId);
+        assert.strictEqual(
+            endedEvents[0].player.toBase58(),
+            enterEtcContext.newGame.playerWallet.publicKey.toBase58()
+        );
+        assert.isTrue(endedEvents[0].buckets.every(b => b.decimalTokens.toNumber() === 0));
+    });
 });
 
 export interface LeaveGameEtcSetupArgs extends LeaveGameSetupArgs {
@@ -1064,6 +1130,8 @@ export interface LeaveGameSetupArgs {
   playerTokenAccount?: PublicKey;
   tokenPoolAddress?: PublicKey;
   cancelOnLoss?: boolean
+  minWinningsDecimalTokens?: anchor.BN;
+  maxBurnDecimalTokens?: anchor.BN;
 }
 
 // eslint-disable-next-line @typescript-eslint/no-empty-interface
@@ -1155,7 +1223,11 @@ export async function setUpLeaveGame(
 
     try {
         await program.methods
-            .leaveGame(customSetup?.cancelOnLoss === true)
+            .leaveGame(
+                customSetup?.cancelOnLoss === true,
+                customSetup?.minWinningsDecimalTokens ?? null,
+                customSetup?.maxBurnDecimalTokens ?? null
+            )
             .accountsStrict({
                 game: customSetup?.gameAddress ?? newGameContext.gameAddress,
                 gameCreator: customSetup?.gameCreator ?? newGameContext.playerWallet.publicKey,
@@ -2,7 +2,7 @@ import * as anchor from "@project-serum/anchor";
 import { Equilibrate } from "../target/types/equilibrate";
 import { generateGameId, getGame, getPlayerState } from "./helpers/game";
 import { MINT_DECIMALS } from "./helpers/token";
-import { GameState, PlayerState } from "./helpers/types";
+import { Game, GameState, PlayerMovedEvent, PlayerState } from "./helpers/types";
 import { Keypair, PublicKey } from "@solana/web3.js";
 import {
     GAME_SEED,
@@ -25,7 +25,7 @@ import {
     setUpEnterGameEtc,
     EnterGameEtcContext,
 } from "./enterGame";
-import { assertAsyncThrows, sleep } from "./helpers/test";
+import { assertAsyncThrows, collectEvents, sleep } from "./helpers/test";
 import { CreatePoolContext, CreatePoolSetupArgs } from "./createPool";
 
 describe("MoveBuckets Instruction Tests", () => {
@@ -287,6 +287,38 @@ describe("MoveBuckets Instruction Tests", () => {
         );
     });
 
+    it("move buckets > bucket doesnt have expected players > fails", async () => {
+        const enterEtcContext: EnterGameEtcContext = await setUpEnterGameEtc(program);
+
+        await assertAsyncThrows(
+            () =>
+                setUpMoveBuckets(
+                    program,
+                    enterEtcContext.createPool,
+                    enterEtcContext.newGame,
+                    enterEtcContext,
+                    {
+                        expectedBucketPlayers: 1000,
+                    }
+                ),
+            "UnexpectedBucketPlayers"
+        );
+    });
+
+    it("move buckets > again before cooldown is over > fails", async () => {
+        const newGameContext: NewGameEtcContext = await setUpNewGameEtc(program, {
+            gameConfig: {
+                minSecondsBetweenMoves: 60 * 60,
+            },
+        });
+        await setUpMoveBuckets(program, newGameContext.createPool, newGameContext);
+
+        await assertAsyncThrows(
+            () => setUpMoveBuckets(program, newGameContext.createPool, newGameContext),
+            "MoveCooldownActive"
+        );
+    });
+
     it("move buckets > player tries to move to holding bucket > fails", async () => {
         const enterEtcContext: EnterGameEtcContext = await setUpEnterGameEtc(
             program,
@@ -401,6 +433,35 @@ describe("MoveBuckets Instruction Tests", () => {
         assert.strictEqual(gameState.buckets[1].decimalTokens.toNumber(), entryFee / 2);
         assert.strictEqual(gameState.buckets[2].decimalTokens.toNumber(), (3/2) * entryFee);
     });
+
+    it("move buckets > all good > PlayerMoved event is emitted", async () => {
+        const enterEtcContext: EnterGameEtcContext = await setUpEnterGameEtc(program, {
+            playerBucketIndex: 1,
+            newGame: { gameConfig: { nBuckets: 3 } },
+        });
+        const events: PlayerMovedEvent[] = await collectEvents(
+            program,
+            "PlayerMoved",
+            () => setUpMoveBuckets(
+                program,
+                enterEtcContext.createPool,
+                enterEtcContext.newGame,
+                enterEtcContext,
+                { newBucketIndex: 2 }
+            )
+        );
+
+        assert.strictEqual(events.length, 1);
+        const game: Game = await getGame(enterEtcContext.newGame.gameAddress, program);
+        assert.strictEqual(events[0].gameId.toNumber(), enterEtcContext.newGame.gameId);
+        assert.strictEqual(events[0].player.toBase58(), enterEtcContext.playerWallet.publicKey.toBase58());
+        assert.strictEqual(events[0].fromBucket, 1);
+        assert.strictEqual(events[0].toBucket, 2);
+        assert.deepEqual(
+            events[0].buckets.map(b => b.players),
+            game.state.buckets.map(b => b.players)
+        );
+    });
 });
 
 export interface MoveBucketsEtcSetupArgs extends MoveBucketsSetupArgs {
@@ -414,6 +475,7 @@ export interface MoveBucketsSetupArgs {
   playerStateAddress?: PublicKey;
   playerWallet?: Keypair;
   newBucketIndex?: number;
+  expectedBucketPlayers?: number;
 }
 
 export interface MoveBucketsContext {
@@ -479,7 +541,7 @@ export async function setUpMoveBuckets(
 
     try {
         await program.methods
-            .moveBuckets(newBucketIndex)
+            .moveBuckets(newBucketIndex, customSetup?.expectedBucketPlayers ?? null)
             .accountsStrict({
                 game: customSetup?.gameAddress ?? newGameContext.gameAddress,
                 player: playerStateAddress,
@@ -19,16 +19,27 @@ import {
     MINT_DECIMALS,
     withoutDecimals,
 } from "./helpers/token";
-import { Game, GameConfig, PlayerState } from "./helpers/types";
+import {
+    BurnSchedule,
+    DustDestination,
+    Game,
+    GameConfig,
+    GameCreatedEvent,
+    PenaltyDestination,
+    PlayerState,
+    SpillCurve,
+    TopUpDestination,
+} from "./helpers/types";
 import { Keypair, PublicKey, Connection} from "@solana/web3.js";
 import {
     GAME_SEED,
     getGameAddress,
     getPlayerStateAddress,
+    getProgramConfigAddress,
     PLAYER_SEED,
 } from "./helpers/address";
 import { assert } from "chai";
-import { assertAsyncThrows } from "./helpers/test";
+import { assertAsyncThrows, collectEvents } from "./helpers/test";
 import { getAssociatedTokenAddress, NATIVE_MINT } from "@solana/spl-token";
 import { testIsReady } from "./setup";
 import {
@@ -404,6 +415,66 @@ describe("NewGame Instruction Tests", () => {
         );
     });
 
+    it("create a new game > end time is in the past > fails", async () => {
+        await assertAsyncThrows(
+            () =>
+                setUpNewGameEtc(program, {
+                    gameConfig: {
+                        endEpochSeconds: new anchor.BN(Math.floor(Date.now() / 1000) - 60),
+                    },
+                }),
+            "InvalidEndTime"
+        );
+    });
+
+    it("create a new game > spill curve decay is out of range > fails", async () => {
+        await assertAsyncThrows(
+            () =>
+                setUpNewGameEtc(program, {
+                    gameConfig: {
+                        spillCurve: { proportionalToBalance: { decayBpsPerSecond: 0 } },
+                    },
+                }),
+            "InvalidSpillCurve"
+        );
+    });
+
+    it("create a new game > burn schedule shrinks each move > fails", async () => {
+        await assertAsyncThrows(
+            () =>
+                setUpNewGameEtc(program, {
+                    gameConfig: {
+                        burnSchedule: { geometric: { growthBps: 5_000 } },
+                    },
+                }),
+            "InvalidBurnSchedule"
+        );
+    });
+
+    it("create a new game > penalties go back into the pot > game keeps the destination", async () => {
+        const { gameAddress } = await setUpNewGameEtc(program, {
+            gameConfig: {
+                burnRateDecimalTokensPerMove: new anchor.BN(1_000),
+                penaltyDestination: { otherBuckets: {} },
+            },
+        });
+
+        const game: Game = await getGame(gameAddress, program);
+        assert.deepStrictEqual(game.config.penaltyDestination, { otherBuckets: {} });
+    });
+
+    it("create a new game > dust goes to the creator > game starts without dust", async () => {
+        const { gameAddress } = await setUpNewGameEtc(program, {
+            gameConfig: {
+                dustDestination: { creator: {} },
+            },
+        });
+
+        const game: Game = await getGame(gameAddress, program);
+        assert.deepStrictEqual(game.config.dustDestination, { creator: {} });
+        assert.strictEqual(game.dustDecimalTokens.toNumber(), 0);
+    });
+
     it("create a new game > wrong pool manager address > fails", async () => {
         const createPoolContext1: CreatePoolContext = await setUpCreatePool(
             program
@@ -416,7 +487,7 @@ describe("NewGame Instruction Tests", () => {
                 setUpNewGame(program, createPoolContext1, {
                     poolManager: createPoolContext2.poolManagerAddress,
                 }),
-            "ConstraintTokenOwner"
+            "InvalidPoolManager"
         );
     });
 
@@ -432,7 +503,7 @@ describe("NewGame Instruction Tests", () => {
                 setUpNewGame(program, createPoolContext1, {
                     tokenPoolAddress: createPoolContext2.tokenPoolAddress,
                 }),
-            "ConstraintTokenOwner"
+            "InvalidTokenPool"
         );
     });
 
@@ -441,6 +512,25 @@ describe("NewGame Instruction Tests", () => {
         //  wrapped mint account, blah blah and then set up config. This will mean changing
         //  the way that the create game args are handled.
     });
+
+    it("create a new game > all good > GameCreated event is emitted", async () => {
+        let context: NewGameEtcContext;
+        const events: GameCreatedEvent[] = await collectEvents(
+            program,
+            "GameCreated",
+            async () => { context = await setUpNewGameEtc(program); }
+        );
+
+        assert.strictEqual(events.length, 1);
+        const game: Game = await getGame(context.gameAddress, program);
+        assert.strictEqual(events[0].gameId.toNumber(), context.gameId);
+        assert.strictEqual(events[0].player.toBase58(), context.playerWallet.publicKey.toBase58());
+        assert.strictEqual(events[0].bucket, 1);
+        assert.deepEqual(
+            events[0].buckets.map(b => b.decimalTokens.toNumber()),
+            game.state.buckets.map(b => b.decimalTokens.toNumber())
+        );
+    });
 });
 
 export interface NewGameEtcSetupArgs extends NewGameSetupArgs {
@@ -456,6 +546,17 @@ export interface NewGameSetupArgs {
     nBuckets?: number;
     maxPlayers?: number;
     burnRateDecimalTokensPerMove?: anchor.BN;
+    endEpochSeconds?: anchor.BN;
+    spillCurve?: SpillCurve;
+    allowlistRoot?: number[];
+    creatorFeeBps?: number;
+    maxStakeDecimalTokens?: anchor.BN;
+    topUpDestination?: TopUpDestination;
+    minSecondsBetweenMoves?: number;
+    burnSchedule?: BurnSchedule;
+    burnPenaltyHalfLifeSeconds?: number;
+    penaltyDestination?: PenaltyDestination;
+    dustDestination?: DustDestination;
   };
   gameId?: number;
   gameAddress?: PublicKey;
@@ -523,6 +624,7 @@ export async function setUpNewGame(
     if (customSetup?.gameConfig?.entryFeeDecimalTokens != null) {
         config.entryFeeDecimalTokens =
       customSetup?.gameConfig?.entryFeeDecimalTokens;
+        config.maxStakeDecimalTokens = config.entryFeeDecimalTokens;
     }
 
     if (customSetup?.gameConfig?.nBuckets != null) {
@@ -542,6 +644,50 @@ export async function setUpNewGame(
         config.burnRateDecimalTokensPerMove = customSetup?.gameConfig?.burnRateDecimalTokensPerMove;
     }
 
+    if (customSetup?.gameConfig?.endEpochSeconds != null) {
+        config.endEpochSeconds = customSetup?.gameConfig?.endEpochSeconds;
+    }
+
+    if (customSetup?.gameConfig?.spillCurve != null) {
+        config.spillCurve = customSetup?.gameConfig?.spillCurve;
+    }
+
+    if (customSetup?.gameConfig?.allowlistRoot != null) {
+        config.allowlistRoot = customSetup?.gameConfig?.allowlistRoot;
+    }
+
+    if (customSetup?.gameConfig?.creatorFeeBps != null) {
+        config.creatorFeeBps = customSetup?.gameConfig?.creatorFeeBps;
+    }
+
+    if (customSetup?.gameConfig?.maxStakeDecimalTokens != null) {
+        config.maxStakeDecimalTokens = customSetup?.gameConfig?.maxStakeDecimalTokens;
+    }
+
+    if (customSetup?.gameConfig?.topUpDestination != null) {
+        config.topUpDestination = customSetup?.gameConfig?.topUpDestination;
+    }
+
+    if (customSetup?.gameConfig?.minSecondsBetweenMoves != null) {
+        config.minSecondsBetweenMoves = customSetup?.gameConfig?.minSecondsBetweenMoves;
+    }
+
+    if (customSetup?.gameConfig?.burnSchedule != null) {
+        config.burnSchedule = customSetup?.gameConfig?.burnSchedule;
+    }
+
+    if (customSetup?.gameConfig?.burnPenaltyHalfLifeSeconds != null) {
+        config.burnPenaltyHalfLifeSeconds = customSetup?.gameConfig?.burnPenaltyHalfLifeSeconds;
+    }
+
+    if (customSetup?.gameConfig?.penaltyDestination != null) {
+        config.penaltyDestination = customSetup?.gameConfig?.penaltyDestination;
+    }
+
+    if (customSetup?.gameConfig?.dustDestination != null) {
+        config.dustDestination = customSetup?.gameConfig?.dustDestination;
+    }
+
     const gameId: number = customSetup?.gameId ?? generateGameId();
     const gameAddress: PublicKey =
     customSetup?.gameAddress ??
@@ -585,14 +731,16 @@ export async function setUpNewGame(
         await program.methods
             .newGame(
                 config,
-                new anchor.BN(gameId),
-                customSetup?.poolManager ?? createPoolContext.poolManagerAddress
+                new anchor.BN(gameId)
             )
             .accountsStrict({
                 game: gameAddress,
                 firstPlayer: playerStateAddress,
+                programConfig: await getProgramConfigAddress(program.programId),
                 programFeeDestination: customSetup?.programFeeDestination ?? PROGRAM_FEE_DESTINATION,
+                gameMint: createPoolContext.mint.publicKey,
                 depositSourceAccount: playerTokenAccount,
+                poolManager: customSetup?.poolManager ?? createPoolContext.poolManagerAddress,
                 tokenPool: customSetup?.tokenPoolAddress ?? createPoolContext.tokenPoolAddress,
                 payer: player.publicKey,
                 associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
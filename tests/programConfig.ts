@@ -0,0 +1,156 @@
+import * as anchor from "@project-serum/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { assert } from "chai";
+import { Equilibrate } from "../target/types/equilibrate";
+import { getProgramConfigAddress, getProgramDataAddress } from "./helpers/address";
+import { DEFAULT_PROGRAM_SETTINGS, getProgramConfig, MAX_GAME_BUCKETS } from "./helpers/game";
+import { makeAndFundWallet } from "./helpers/token";
+import { assertAsyncThrows } from "./helpers/test";
+import { ProgramConfig, ProgramSettings } from "./helpers/types";
+import { setUpNewGameEtc } from "./newGame";
+
+describe("ProgramConfig Instruction Tests", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    // eslint-disable-next-line import/namespace
+    const program = anchor.workspace.Equilibrate as anchor.Program<Equilibrate>;
+
+    // the program config is a singleton shared by every test, so put it
+    // back the way it was after each test that changes it
+    afterEach(async () => {
+        await updateProgramConfig(program, program.provider.publicKey, DEFAULT_PROGRAM_SETTINGS);
+    });
+
+    it("initialize config > all good > config holds default settings", async () => {
+        const programConfig: ProgramConfig = await getProgramConfig(
+            await getProgramConfigAddress(program.programId),
+            program
+        );
+        assert.strictEqual(programConfig.admin.toBase58(), program.provider.publicKey.toBase58());
+        assert.strictEqual(
+            programConfig.settings.programFeeDestination.toBase58(),
+            DEFAULT_PROGRAM_SETTINGS.programFeeDestination.toBase58()
+        );
+        assert.strictEqual(
+            programConfig.settings.programFeeLamports.toNumber(),
+            DEFAULT_PROGRAM_SETTINGS.programFeeLamports.toNumber()
+        );
+        assert.strictEqual(programConfig.settings.gameMaxBuckets, DEFAULT_PROGRAM_SETTINGS.gameMaxBuckets);
+        assert.strictEqual(programConfig.settings.gameMaxPlayers, DEFAULT_PROGRAM_SETTINGS.gameMaxPlayers);
+    });
+
+    it("initialize config > already initialized > fails", async () => {
+        await assertAsyncThrows(() => initializeProgramConfig(program));
+    });
+
+    it("update config > all good > settings are replaced", async () => {
+        const newSettings: ProgramSettings = {
+            ...DEFAULT_PROGRAM_SETTINGS,
+            programFeeLamports: DEFAULT_PROGRAM_SETTINGS.programFeeLamports.muln(2),
+            gameMaxBuckets: 10,
+        };
+        await updateProgramConfig(program, program.provider.publicKey, newSettings);
+
+        const programConfig: ProgramConfig = await getProgramConfig(
+            await getProgramConfigAddress(program.programId),
+            program
+        );
+        assert.strictEqual(
+            programConfig.settings.programFeeLamports.toNumber(),
+            newSettings.programFeeLamports.toNumber()
+        );
+        assert.strictEqual(programConfig.settings.gameMaxBuckets, 10);
+    });
+
+    it("update config > signer isnt admin > fails", async () => {
+        const notAdmin: Keypair = await makeAndFundWallet(1, program.provider.connection);
+        const programConfigAddress: PublicKey = await getProgramConfigAddress(program.programId);
+        await assertAsyncThrows(
+            () => program.methods
+                .updateConfig(notAdmin.publicKey, DEFAULT_PROGRAM_SETTINGS)
+                .accountsStrict({
+                    programConfig: programConfigAddress,
+                    admin: notAdmin.publicKey,
+                })
+                .signers([notAdmin])
+                .rpc(),
+            "ProgramConfigAdminMismatch"
+        );
+    });
+
+    it("update config > max buckets above hard limit > fails", async () => {
+        await assertAsyncThrows(
+            () => updateProgramConfig(program, program.provider.publicKey, {
+                ...DEFAULT_PROGRAM_SETTINGS,
+                gameMaxBuckets: MAX_GAME_BUCKETS + 1,
+            }),
+            "TooManyBuckets"
+        );
+    });
+
+    it("new game > more buckets than config allows > fails", async () => {
+        await updateProgramConfig(program, program.provider.publicKey, {
+            ...DEFAULT_PROGRAM_SETTINGS,
+            gameMaxBuckets: 2,
+        });
+        await assertAsyncThrows(
+            () => setUpNewGameEtc(program, { gameConfig: { nBuckets: 3 } }),
+            "TooManyBuckets"
+        );
+    });
+
+    it("new game > program fee destination changed > old destination fails", async () => {
+        const newDestination: PublicKey = Keypair.generate().publicKey;
+        await updateProgramConfig(program, program.provider.publicKey, {
+            ...DEFAULT_PROGRAM_SETTINGS,
+            programFeeDestination: newDestination,
+        });
+        await assertAsyncThrows(
+            () => setUpNewGameEtc(program),
+            "InvalidProgramFeeDestination"
+        );
+        await setUpNewGameEtc(program, { programFeeDestination: newDestination });
+    });
+});
+
+
+/**
+ * Initializes the program config with the default test settings if it
+ * hasn't been initialized yet. The provider wallet is the program's upgrade
+ * authority on the local validator, so it becomes the admin.
+ */
+export async function setUpProgramConfig(program: anchor.Program<Equilibrate>): Promise<void> {
+    const programConfigAddress: PublicKey = await getProgramConfigAddress(program.programId);
+    const exists: boolean = (await program.provider.connection.getAccountInfo(programConfigAddress)) != null;
+    if (!exists) {
+        await initializeProgramConfig(program);
+    }
+}
+
+
+async function initializeProgramConfig(program: anchor.Program<Equilibrate>): Promise<void> {
+    await program.methods
+        .initializeConfig(program.provider.publicKey, DEFAULT_PROGRAM_SETTINGS)
+        .accountsStrict({
+            programConfig: await getProgramConfigAddress(program.programId),
+            program: program.programId,
+            programData: await getProgramDataAddress(program.programId),
+            payer: program.provider.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+}
+
+
+export async function updateProgramConfig(
+    program: anchor.Program<Equilibrate>,
+    newAdmin: PublicKey,
+    settings: ProgramSettings
+): Promise<void> {
+    await program.methods
+        .updateConfig(newAdmin, settings)
+        .accountsStrict({
+            programConfig: await getProgramConfigAddress(program.programId),
+            admin: program.provider.publicKey,
+        })
+        .rpc();
+}
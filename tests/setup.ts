@@ -1,3 +1,15 @@
+import * as anchor from "@project-serum/anchor";
+import { Equilibrate } from "../target/types/equilibrate";
+import { setUpProgramConfig } from "./programConfig";
+
+before(async () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    // eslint-disable-next-line import/namespace
+    const program = anchor.workspace.Equilibrate as anchor.Program<Equilibrate>;
+    // every game instruction needs the program config to exist
+    await setUpProgramConfig(program);
+});
+
 beforeEach(async () => {
     setTestIsReady(true);
 });